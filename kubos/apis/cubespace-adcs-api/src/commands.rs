@@ -1,5 +1,6 @@
 //! Generated typed CubeSpace ADCS telecommands.
 
+use crate::enums::{ControlMode, EstimatorMode, OperationalState, OrbitMode, PowerState, RunMode};
 use crate::{codec, AdcsResult, CommandSpec, DataType, FieldSpec, Telecommand};
 use async_graphql::InputObject;
 
@@ -177,11 +178,11 @@ const CONTROL_AND_ESTIMATION_MODE_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct ControlAndEstimationModeCommand {
     /// Control mode. Control mode. Possible values are in Table 9
-    pub control_mode: u8,
+    pub control_mode: ControlMode,
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
-    pub main_estimator_mode: u8,
+    pub main_estimator_mode: EstimatorMode,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
-    pub backup_estimator_mode: u8,
+    pub backup_estimator_mode: EstimatorMode,
     /// Control timeout. Control timeout. (Unit of measure is [s])
     pub control_timeout: u16,
 }
@@ -198,21 +199,21 @@ impl Telecommand for ControlAndEstimationModeCommand {
             0,
             8,
             "Control mode",
-            u128::from(self.control_mode),
+            u128::from(self.control_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             8,
             8,
             "Main estimator mode",
-            u128::from(self.main_estimator_mode),
+            u128::from(self.main_estimator_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             16,
             8,
             "Backup estimator mode",
-            u128::from(self.backup_estimator_mode),
+            u128::from(self.backup_estimator_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
@@ -436,7 +437,7 @@ const ORBIT_MODE_COMMAND_FIELDS: &[FieldSpec] = &[FieldSpec {
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct OrbitModeCommand {
     /// Orbit mode. Orbit calculation mode. Possible values are in Table 19
-    pub orbit_mode: u8,
+    pub orbit_mode: OrbitMode,
 }
 
 impl Telecommand for OrbitModeCommand {
@@ -451,7 +452,7 @@ impl Telecommand for OrbitModeCommand {
             0,
             8,
             "Orbit mode",
-            u128::from(self.orbit_mode),
+            u128::from(self.orbit_mode.to_raw()),
         )?;
         Ok(payload)
     }
@@ -873,45 +874,45 @@ const POWERSTATE_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct PowerstateCommand {
     /// RWL0 power state. RWL0 power state. Possible values are in Table 24
-    pub rwl0_power_state: u8,
+    pub rwl0_power_state: PowerState,
     /// RWL1 power state. RWL1 power state. Possible values are in Table 24
-    pub rwl1_power_state: u8,
+    pub rwl1_power_state: PowerState,
     /// RWL2 power state. RWL2 power state. Possible values are in Table 24
-    pub rwl2_power_state: u8,
+    pub rwl2_power_state: PowerState,
     /// RWL3 power state. RWL3 power state. Possible values are in Table 24
-    pub rwl3_power_state: u8,
+    pub rwl3_power_state: PowerState,
     /// MAG0 power state. MAG0 power state. Possible values are in Table 24
-    pub mag0_power_state: u8,
+    pub mag0_power_state: PowerState,
     /// MAG1 power state. MAG1 power state. Possible values are in Table 24
-    pub mag1_power_state: u8,
+    pub mag1_power_state: PowerState,
     /// GYR0 power state. GYR0 power state. Possible values are in Table 24
-    pub gyr0_power_state: u8,
+    pub gyr0_power_state: PowerState,
     /// GYR1 power state. GYR1 power state. Possible values are in Table 24
-    pub gyr1_power_state: u8,
+    pub gyr1_power_state: PowerState,
     /// FSS0 power state. FSS0 power state. Possible values are in Table 24
-    pub fss0_power_state: u8,
+    pub fss0_power_state: PowerState,
     /// FSS1 power state. FSS1 power state. Possible values are in Table 24
-    pub fss1_power_state: u8,
+    pub fss1_power_state: PowerState,
     /// FSS2 power state. FSS2 power state. Possible values are in Table 24
-    pub fss2_power_state: u8,
+    pub fss2_power_state: PowerState,
     /// FSS3 power state. FSS3 power state. Possible values are in Table 24
-    pub fss3_power_state: u8,
+    pub fss3_power_state: PowerState,
     /// HSS0 power state. HSS0 power state. Possible values are in Table 24
-    pub hss0_power_state: u8,
+    pub hss0_power_state: PowerState,
     /// HSS1 power state. HSS1 power state. Possible values are in Table 24
-    pub hss1_power_state: u8,
+    pub hss1_power_state: PowerState,
     /// STR0 power state. STR0 power state. Possible values are in Table 24
-    pub str0_power_state: u8,
+    pub str0_power_state: PowerState,
     /// STR1 power state. STR1 power state. Possible values are in Table 24
-    pub str1_power_state: u8,
+    pub str1_power_state: PowerState,
     /// ExtSensor0 power state. ExtSensor0 power state. Possible values are in Table 24
-    pub extsensor0_power_state: u8,
+    pub extsensor0_power_state: PowerState,
     /// ExtSensor1 power state. ExtSensor1 power state. Possible values are in Table 24
-    pub extsensor1_power_state: u8,
+    pub extsensor1_power_state: PowerState,
     /// EXTGYR0 power state. EXTGYR0 power state. Possible values are in Table 24
-    pub extgyr0_power_state: u8,
+    pub extgyr0_power_state: PowerState,
     /// EXTGYR1 power state. EXTGYR1 power state. Possible values are in Table 24
-    pub extgyr1_power_state: u8,
+    pub extgyr1_power_state: PowerState,
 }
 
 impl Telecommand for PowerstateCommand {
@@ -926,140 +927,140 @@ impl Telecommand for PowerstateCommand {
             0,
             8,
             "RWL0 power state",
-            u128::from(self.rwl0_power_state),
+            u128::from(self.rwl0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             8,
             8,
             "RWL1 power state",
-            u128::from(self.rwl1_power_state),
+            u128::from(self.rwl1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             16,
             8,
             "RWL2 power state",
-            u128::from(self.rwl2_power_state),
+            u128::from(self.rwl2_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             24,
             8,
             "RWL3 power state",
-            u128::from(self.rwl3_power_state),
+            u128::from(self.rwl3_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             32,
             8,
             "MAG0 power state",
-            u128::from(self.mag0_power_state),
+            u128::from(self.mag0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             40,
             8,
             "MAG1 power state",
-            u128::from(self.mag1_power_state),
+            u128::from(self.mag1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             48,
             8,
             "GYR0 power state",
-            u128::from(self.gyr0_power_state),
+            u128::from(self.gyr0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             56,
             8,
             "GYR1 power state",
-            u128::from(self.gyr1_power_state),
+            u128::from(self.gyr1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             64,
             8,
             "FSS0 power state",
-            u128::from(self.fss0_power_state),
+            u128::from(self.fss0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             72,
             8,
             "FSS1 power state",
-            u128::from(self.fss1_power_state),
+            u128::from(self.fss1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             80,
             8,
             "FSS2 power state",
-            u128::from(self.fss2_power_state),
+            u128::from(self.fss2_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             88,
             8,
             "FSS3 power state",
-            u128::from(self.fss3_power_state),
+            u128::from(self.fss3_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             96,
             8,
             "HSS0 power state",
-            u128::from(self.hss0_power_state),
+            u128::from(self.hss0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             104,
             8,
             "HSS1 power state",
-            u128::from(self.hss1_power_state),
+            u128::from(self.hss1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             112,
             8,
             "STR0 power state",
-            u128::from(self.str0_power_state),
+            u128::from(self.str0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             120,
             8,
             "STR1 power state",
-            u128::from(self.str1_power_state),
+            u128::from(self.str1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             128,
             8,
             "ExtSensor0 power state",
-            u128::from(self.extsensor0_power_state),
+            u128::from(self.extsensor0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             136,
             8,
             "ExtSensor1 power state",
-            u128::from(self.extsensor1_power_state),
+            u128::from(self.extsensor1_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             144,
             8,
             "EXTGYR0 power state",
-            u128::from(self.extgyr0_power_state),
+            u128::from(self.extgyr0_power_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             152,
             8,
             "EXTGYR1 power state",
-            u128::from(self.extgyr1_power_state),
+            u128::from(self.extgyr1_power_state.to_raw()),
         )?;
         Ok(payload)
     }
@@ -1080,7 +1081,7 @@ const ADCS_RUN_MODE_COMMAND_FIELDS: &[FieldSpec] = &[FieldSpec {
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct AdcsRunModeCommand {
     /// ADCS run mode. ADCS run mode. Possible values are in Table 26
-    pub adcs_run_mode: u8,
+    pub adcs_run_mode: RunMode,
 }
 
 impl Telecommand for AdcsRunModeCommand {
@@ -1095,7 +1096,7 @@ impl Telecommand for AdcsRunModeCommand {
             0,
             8,
             "ADCS run mode",
-            u128::from(self.adcs_run_mode),
+            u128::from(self.adcs_run_mode.to_raw()),
         )?;
         Ok(payload)
     }
@@ -1128,7 +1129,7 @@ const CONTROL_MODE_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct ControlModeCommand {
     /// Control mode. Control mode. Possible values are in Table 9
-    pub control_mode: u8,
+    pub control_mode: ControlMode,
     /// Control timeout. Control timeout. (Unit of measure is [s])
     pub control_timeout: u16,
 }
@@ -1145,7 +1146,7 @@ impl Telecommand for ControlModeCommand {
             0,
             8,
             "Control mode",
-            u128::from(self.control_mode),
+            u128::from(self.control_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
@@ -2098,13 +2099,13 @@ const DEFAULT_MODE_CONFIGURATION_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct DefaultModeConfigurationCommand {
     /// Default ADCS run mode. Default ADCS run mode. Possible values are in Table 26
-    pub default_adcs_run_mode: u8,
+    pub default_adcs_run_mode: RunMode,
     /// Default ADCS operational state. Default ADCS operational state. Possible values are in Table 36
-    pub default_adcs_operational_state: u8,
+    pub default_adcs_operational_state: OperationalState,
     /// Default control mode in OpStateSafe. Default control mode in OpStateSafe. Possible values are in Table 9
-    pub default_control_mode_in_opstatesafe: u8,
+    pub default_control_mode_in_opstatesafe: ControlMode,
     /// Default control mode in OpStateAuto. Default control mode in OpStateAuto. Possible values are in Table 9
-    pub default_control_mode_in_opstateauto: u8,
+    pub default_control_mode_in_opstateauto: ControlMode,
 }
 
 impl Telecommand for DefaultModeConfigurationCommand {
@@ -2119,28 +2120,28 @@ impl Telecommand for DefaultModeConfigurationCommand {
             0,
             8,
             "Default ADCS run mode",
-            u128::from(self.default_adcs_run_mode),
+            u128::from(self.default_adcs_run_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             8,
             8,
             "Default ADCS operational state",
-            u128::from(self.default_adcs_operational_state),
+            u128::from(self.default_adcs_operational_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             16,
             8,
             "Default control mode in OpStateSafe",
-            u128::from(self.default_control_mode_in_opstatesafe),
+            u128::from(self.default_control_mode_in_opstatesafe.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             24,
             8,
             "Default control mode in OpStateAuto",
-            u128::from(self.default_control_mode_in_opstateauto),
+            u128::from(self.default_control_mode_in_opstateauto.to_raw()),
         )?;
         Ok(payload)
     }
@@ -3824,9 +3825,9 @@ const ADCS_ESTIMATOR_CONFIG_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct AdcsEstimatorConfigCommand {
     /// Default main estimator mode. Default main estimator mode. Possible values are in Table 10
-    pub default_main_estimator_mode: u8,
+    pub default_main_estimator_mode: EstimatorMode,
     /// Default backup estimator mode. Default backup estimator mode. Possible values are in Table 10
-    pub default_backup_estimator_mode: u8,
+    pub default_backup_estimator_mode: EstimatorMode,
     /// MAG measurement noise. Magnetometer measurement noise
     pub mag_measurement_noise: f32,
     /// CSS measurement noise. Coarse sun sensor measurement noise
@@ -3871,14 +3872,14 @@ impl Telecommand for AdcsEstimatorConfigCommand {
             0,
             8,
             "Default main estimator mode",
-            u128::from(self.default_main_estimator_mode),
+            u128::from(self.default_main_estimator_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             8,
             8,
             "Default backup estimator mode",
-            u128::from(self.default_backup_estimator_mode),
+            u128::from(self.default_backup_estimator_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
@@ -4491,9 +4492,9 @@ const ESTIMATION_MODE_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct EstimationModeCommand {
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
-    pub main_estimator_mode: u8,
+    pub main_estimator_mode: EstimatorMode,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
-    pub backup_estimator_mode: u8,
+    pub backup_estimator_mode: EstimatorMode,
 }
 
 impl Telecommand for EstimationModeCommand {
@@ -4508,14 +4509,14 @@ impl Telecommand for EstimationModeCommand {
             0,
             8,
             "Main estimator mode",
-            u128::from(self.main_estimator_mode),
+            u128::from(self.main_estimator_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
             8,
             8,
             "Backup estimator mode",
-            u128::from(self.backup_estimator_mode),
+            u128::from(self.backup_estimator_mode.to_raw()),
         )?;
         Ok(payload)
     }
@@ -5965,7 +5966,7 @@ const ADCS_CONTROLLER_CONFIGURATION_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct AdcsControllerConfigurationCommand {
     /// Default control mode. Default control mode. Possible values are in Table 9
-    pub default_control_mode: u8,
+    pub default_control_mode: ControlMode,
     /// Detumbling damping gain. Detumbling damping gain (Kd)
     pub detumbling_damping_gain: f32,
     /// Sun-spin control gain - sunlit part. Sun-spin control gain (KDsun)
@@ -6034,7 +6035,7 @@ impl Telecommand for AdcsControllerConfigurationCommand {
             0,
             8,
             "Default control mode",
-            u128::from(self.default_control_mode),
+            u128::from(self.default_control_mode.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
@@ -6746,7 +6747,7 @@ const ADCS_OPERATIONAL_STATE_COMMAND_FIELDS: &[FieldSpec] = &[
 #[derive(Clone, Debug, Default, PartialEq, InputObject)]
 pub struct AdcsOperationalStateCommand {
     /// ADCS operational state. ADCS operational state. Possible values are in Table 36
-    pub adcs_operational_state: u8,
+    pub adcs_operational_state: OperationalState,
     /// Node Type: Sensor 1. Sensor 1 port - Node type identifier. Possible values are in Table 57
     pub node_type_sensor_1: u8,
     /// Abstract Node Type: Sensor 1. Sensor 1 port - Abstract Node type identifier. Possible values are in Table 61
@@ -6857,7 +6858,7 @@ impl Telecommand for AdcsOperationalStateCommand {
             0,
             8,
             "ADCS operational state",
-            u128::from(self.adcs_operational_state),
+            u128::from(self.adcs_operational_state.to_raw()),
         )?;
         codec::write_unsigned(
             &mut payload,
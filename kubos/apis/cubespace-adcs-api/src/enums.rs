@@ -0,0 +1,177 @@
+//! CubeSpace ADCS enumeration tables.
+//!
+//! Coverage is partial: only tables 9, 10, 19, 24, 26, 36, 119, 128 and 129 are
+//! defined here. Fields referencing any other table keep their raw value only and
+//! are not range-checked by [`enum_value_valid`]. The tables referenced by the
+//! command and telemetry specs but not yet covered are:
+//!
+//! 3, 13, 29, 30, 38, 41, 51, 55, 56, 57, 58, 59, 61, 64, 72, 78, 80, 84, 85, 88,
+//! 90, 91, 93, 99, 101, 107, 109, 110, 111, 113, 114, 117, 120, 126, 139, 140,
+//! 175, 176, 177, 178, 179, 180, 181, 182, 183, 200, 203, 205, 208, 214, 215.
+
+use async_graphql::Enum;
+
+macro_rules! adcs_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident($table:literal) {
+            #[default]
+            $default:ident = $default_raw:literal,
+            $($variant:ident = $raw:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Enum)]
+        pub enum $name {
+            #[doc = stringify!($default)]
+            #[default]
+            $default,
+            $(
+                #[doc = stringify!($variant)]
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// Enum table key referenced by `FieldSpec::enum_table`.
+            pub const TABLE: &'static str = $table;
+
+            /// Looks up a raw value, returning `None` if it is not in the table.
+            pub fn from_raw(raw: u8) -> Option<Self> {
+                match raw {
+                    $default_raw => Some($name::$default),
+                    $($raw => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Raw value sent over the bus.
+            pub fn to_raw(self) -> u8 {
+                match self {
+                    $name::$default => $default_raw,
+                    $($name::$variant => $raw,)*
+                }
+            }
+
+            /// Name of the variant, e.g. `"ConBdot3"`.
+            pub fn label(self) -> &'static str {
+                match self {
+                    $name::$default => stringify!($default),
+                    $($name::$variant => stringify!($variant),)*
+                }
+            }
+        }
+    };
+}
+
+adcs_enum! {
+    /// Control mode (Table 9).
+    ControlMode("table_9") {
+        #[default]
+        ConNone = 0,
+        ConBdot = 1,
+        ConBdot3 = 3,
+        ConXYZWheel = 12,
+        ConSunTrack = 13,
+        ConStopRW = 50,
+        ConHxyzRW = 51,
+    }
+}
+
+adcs_enum! {
+    /// Estimator mode (Table 10).
+    EstimatorMode("table_10") {
+        #[default]
+        EstNone = 0,
+        EstGyro = 1,
+        EstMagRkf = 2,
+        EstFullEkf = 5,
+        EstGyroEkf = 6,
+    }
+}
+
+adcs_enum! {
+    /// Orbit propagation mode (Table 19).
+    OrbitMode("table_19") {
+        #[default]
+        OrbSgp4 = 0,
+        OrbGnssSgp4 = 1,
+        OrbAsgp4 = 2,
+        OrbGnssAsgp4 = 3,
+    }
+}
+
+adcs_enum! {
+    /// Power state of a switchable node (Table 24).
+    PowerState("table_24") {
+        #[default]
+        PowerOff = 0,
+        PowerOn = 1,
+    }
+}
+
+adcs_enum! {
+    /// ADCS run mode (Table 26).
+    RunMode("table_26") {
+        #[default]
+        RunModeNormal = 0,
+        RunModeConfig = 1,
+    }
+}
+
+adcs_enum! {
+    /// ADCS operational state (Table 36).
+    OperationalState("table_36") {
+        #[default]
+        OpStateManual = 0,
+        OpStateSafe = 1,
+        OpStateAuto = 2,
+    }
+}
+
+adcs_enum! {
+    /// Source of the current orbit position and velocity (Table 119).
+    NavigationSource("table_119") {
+        #[default]
+        NavNone = 0,
+        NavGnss = 1,
+        NavSgp4Tle = 2,
+        NavAsgp4Tle = 3,
+    }
+}
+
+adcs_enum! {
+    /// Fine sun sensor capture result (Table 128).
+    CaptureResult("table_128") {
+        #[default]
+        NoCapture = 0,
+        Captured = 1,
+    }
+}
+
+adcs_enum! {
+    /// Fine sun sensor detection result (Table 129).
+    DetectionResult("table_129") {
+        #[default]
+        NoDetection = 0,
+        Detected = 1,
+    }
+}
+
+/// Checks a raw value against one of the enum tables above.
+///
+/// Tables which are not generated here cannot be checked, so any value is accepted.
+pub fn enum_value_valid(table: &str, raw: u8) -> bool {
+    match table {
+        ControlMode::TABLE => ControlMode::from_raw(raw).is_some(),
+        EstimatorMode::TABLE => EstimatorMode::from_raw(raw).is_some(),
+        OrbitMode::TABLE => OrbitMode::from_raw(raw).is_some(),
+        PowerState::TABLE => PowerState::from_raw(raw).is_some(),
+        RunMode::TABLE => RunMode::from_raw(raw).is_some(),
+        OperationalState::TABLE => OperationalState::from_raw(raw).is_some(),
+        NavigationSource::TABLE => NavigationSource::from_raw(raw).is_some(),
+        CaptureResult::TABLE => CaptureResult::from_raw(raw).is_some(),
+        DetectionResult::TABLE => DetectionResult::from_raw(raw).is_some(),
+        _ => true,
+    }
+}
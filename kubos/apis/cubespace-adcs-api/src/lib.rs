@@ -20,6 +20,7 @@
 
 pub mod codec;
 pub mod commands;
+pub mod enums;
 mod packet;
 pub mod telemetry;
 #[cfg(test)]
@@ -27,6 +28,7 @@ mod tests;
 pub mod types;
 
 pub use crate::commands::*;
+pub use crate::enums::*;
 pub use crate::packet::*;
 pub use crate::telemetry::*;
 pub use crate::types::*;
@@ -10,6 +10,10 @@ pub const MSG_TYPE_TC_NACK: u8 = 3;
 pub const MSG_TYPE_TLM_REQ: u8 = 4;
 /// CubeSpace CAN message type for extended telemetry responses.
 pub const MSG_TYPE_TLM_RESP_EXT: u8 = 8;
+/// CubeSpace CAN message type for unsolicited telemetry.
+pub const MSG_TYPE_UNSOLICITED_TLM: u8 = 9;
+/// CubeSpace CAN message type for unsolicited events.
+pub const MSG_TYPE_UNSOLICITED_EVENT: u8 = 10;
 
 /// Decoded CubeSpace CAN identifier fields.
 #[derive(Clone, Debug, Eq, PartialEq)]
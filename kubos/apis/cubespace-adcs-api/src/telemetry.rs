@@ -1,5 +1,9 @@
 //! Generated typed CubeSpace ADCS telemetry responses.
 
+use crate::enums::{
+    CaptureResult, ControlMode, DetectionResult, EstimatorMode, NavigationSource, OperationalState,
+    OrbitMode, PowerState, RunMode,
+};
 use crate::{codec, AdcsError, AdcsResult, DataType, FieldSpec, Telemetry, TelemetrySpec};
 use async_graphql::SimpleObject;
 
@@ -2429,11 +2433,11 @@ pub struct FssCubesenseSunRawTelemetry {
     /// FSS0 capture result. FSS0 capture result. Possible values are in Table 128
     pub fss0_capture_result_raw: u8,
     /// FSS0 capture result. FSS0 capture result. Possible values are in Table 128
-    pub fss0_capture_result: Option<String>,
+    pub fss0_capture_result: Option<CaptureResult>,
     /// FSS0 detection result. FSS0 detection result. Possible values are in Table 129
     pub fss0_detection_result_raw: u8,
     /// FSS0 detection result. FSS0 detection result. Possible values are in Table 129
-    pub fss0_detection_result: Option<String>,
+    pub fss0_detection_result: Option<DetectionResult>,
     /// FSS1 alpha angle. FSS1 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
     pub fss1_alpha_angle_raw: i16,
     /// FSS1 alpha angle. FSS1 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
@@ -2445,11 +2449,11 @@ pub struct FssCubesenseSunRawTelemetry {
     /// FSS1 capture result. FSS1 capture result. Possible values are in Table 128
     pub fss1_capture_result_raw: u8,
     /// FSS1 capture result. FSS1 capture result. Possible values are in Table 128
-    pub fss1_capture_result: Option<String>,
+    pub fss1_capture_result: Option<CaptureResult>,
     /// FSS1 detection result. FSS1 detection result. Possible values are in Table 129
     pub fss1_detection_result_raw: u8,
     /// FSS1 detection result. FSS1 detection result. Possible values are in Table 129
-    pub fss1_detection_result: Option<String>,
+    pub fss1_detection_result: Option<DetectionResult>,
     /// FSS2 alpha angle. FSS2 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
     pub fss2_alpha_angle_raw: i16,
     /// FSS2 alpha angle. FSS2 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
@@ -2461,11 +2465,11 @@ pub struct FssCubesenseSunRawTelemetry {
     /// FSS2 capture result. FSS2 capture result. Possible values are in Table 128
     pub fss2_capture_result_raw: u8,
     /// FSS2 capture result. FSS2 capture result. Possible values are in Table 128
-    pub fss2_capture_result: Option<String>,
+    pub fss2_capture_result: Option<CaptureResult>,
     /// FSS2 detection result. FSS2 detection result. Possible values are in Table 129
     pub fss2_detection_result_raw: u8,
     /// FSS2 detection result. FSS2 detection result. Possible values are in Table 129
-    pub fss2_detection_result: Option<String>,
+    pub fss2_detection_result: Option<DetectionResult>,
     /// FSS3 alpha angle. FSS3 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
     pub fss3_alpha_angle_raw: i16,
     /// FSS3 alpha angle. FSS3 alpha Angle. Formatted value is obtained using the formula: (formatted value) [deg] = RAWVAL*0.01f
@@ -2477,11 +2481,11 @@ pub struct FssCubesenseSunRawTelemetry {
     /// FSS3 capture result. FSS3 capture result. Possible values are in Table 128
     pub fss3_capture_result_raw: u8,
     /// FSS3 capture result. FSS3 capture result. Possible values are in Table 128
-    pub fss3_capture_result: Option<String>,
+    pub fss3_capture_result: Option<CaptureResult>,
     /// FSS3 detection result. FSS3 detection result. Possible values are in Table 129
     pub fss3_detection_result_raw: u8,
     /// FSS3 detection result. FSS3 detection result. Possible values are in Table 129
-    pub fss3_detection_result: Option<String>,
+    pub fss3_detection_result: Option<DetectionResult>,
     /// FSS0 valid flag. FSS0 valid flag
     pub fss0_valid_flag: bool,
     /// FSS1 valid flag. FSS1 valid flag
@@ -2539,65 +2543,33 @@ impl Telemetry for FssCubesenseSunRawTelemetry {
             fss0_beta_angle_raw: raw_fss0_beta_angle,
             fss0_beta_angle: (raw_fss0_beta_angle as f64) * 0.01,
             fss0_capture_result_raw: raw_fss0_capture_result,
-            fss0_capture_result: match raw_fss0_capture_result {
-                0 => Some("NoCapture".to_string()),
-                1 => Some("Captured".to_string()),
-                _ => None,
-            },
+            fss0_capture_result: CaptureResult::from_raw(raw_fss0_capture_result),
             fss0_detection_result_raw: raw_fss0_detection_result,
-            fss0_detection_result: match raw_fss0_detection_result {
-                0 => Some("NoDetection".to_string()),
-                1 => Some("Detected".to_string()),
-                _ => None,
-            },
+            fss0_detection_result: DetectionResult::from_raw(raw_fss0_detection_result),
             fss1_alpha_angle_raw: raw_fss1_alpha_angle,
             fss1_alpha_angle: (raw_fss1_alpha_angle as f64) * 0.01,
             fss1_beta_angle_raw: raw_fss1_beta_angle,
             fss1_beta_angle: (raw_fss1_beta_angle as f64) * 0.01,
             fss1_capture_result_raw: raw_fss1_capture_result,
-            fss1_capture_result: match raw_fss1_capture_result {
-                0 => Some("NoCapture".to_string()),
-                1 => Some("Captured".to_string()),
-                _ => None,
-            },
+            fss1_capture_result: CaptureResult::from_raw(raw_fss1_capture_result),
             fss1_detection_result_raw: raw_fss1_detection_result,
-            fss1_detection_result: match raw_fss1_detection_result {
-                0 => Some("NoDetection".to_string()),
-                1 => Some("Detected".to_string()),
-                _ => None,
-            },
+            fss1_detection_result: DetectionResult::from_raw(raw_fss1_detection_result),
             fss2_alpha_angle_raw: raw_fss2_alpha_angle,
             fss2_alpha_angle: (raw_fss2_alpha_angle as f64) * 0.01,
             fss2_beta_angle_raw: raw_fss2_beta_angle,
             fss2_beta_angle: (raw_fss2_beta_angle as f64) * 0.01,
             fss2_capture_result_raw: raw_fss2_capture_result,
-            fss2_capture_result: match raw_fss2_capture_result {
-                0 => Some("NoCapture".to_string()),
-                1 => Some("Captured".to_string()),
-                _ => None,
-            },
+            fss2_capture_result: CaptureResult::from_raw(raw_fss2_capture_result),
             fss2_detection_result_raw: raw_fss2_detection_result,
-            fss2_detection_result: match raw_fss2_detection_result {
-                0 => Some("NoDetection".to_string()),
-                1 => Some("Detected".to_string()),
-                _ => None,
-            },
+            fss2_detection_result: DetectionResult::from_raw(raw_fss2_detection_result),
             fss3_alpha_angle_raw: raw_fss3_alpha_angle,
             fss3_alpha_angle: (raw_fss3_alpha_angle as f64) * 0.01,
             fss3_beta_angle_raw: raw_fss3_beta_angle,
             fss3_beta_angle: (raw_fss3_beta_angle as f64) * 0.01,
             fss3_capture_result_raw: raw_fss3_capture_result,
-            fss3_capture_result: match raw_fss3_capture_result {
-                0 => Some("NoCapture".to_string()),
-                1 => Some("Captured".to_string()),
-                _ => None,
-            },
+            fss3_capture_result: CaptureResult::from_raw(raw_fss3_capture_result),
             fss3_detection_result_raw: raw_fss3_detection_result,
-            fss3_detection_result: match raw_fss3_detection_result {
-                0 => Some("NoDetection".to_string()),
-                1 => Some("Detected".to_string()),
-                _ => None,
-            },
+            fss3_detection_result: DetectionResult::from_raw(raw_fss3_detection_result),
             fss0_valid_flag: raw_fss0_valid_flag,
             fss1_valid_flag: raw_fss1_valid_flag,
             fss2_valid_flag: raw_fss2_valid_flag,
@@ -3171,7 +3143,7 @@ pub struct ControllerTelemetry {
     /// Active control mode. Active control mode. Possible values are in Table 9
     pub active_control_mode_raw: u8,
     /// Active control mode. Active control mode. Possible values are in Table 9
-    pub active_control_mode: Option<String>,
+    pub active_control_mode: Option<ControlMode>,
     /// RWL0 error flag. RWL0 error flag
     pub rwl0_error_flag: bool,
     /// RWL1 error flag. RWL1 error flag
@@ -3321,16 +3293,7 @@ impl Telemetry for ControllerTelemetry {
             mtq2_on_time_command: raw_mtq2_on_time_command,
             control_timeout: raw_control_timeout,
             active_control_mode_raw: raw_active_control_mode,
-            active_control_mode: match raw_active_control_mode {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            active_control_mode: ControlMode::from_raw(raw_active_control_mode),
             rwl0_error_flag: raw_rwl0_error_flag,
             rwl1_error_flag: raw_rwl1_error_flag,
             rwl2_error_flag: raw_rwl2_error_flag,
@@ -3774,7 +3737,7 @@ pub struct EstimatorBackupTelemetry {
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
     pub active_estimator_mode_raw: u8,
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
-    pub active_estimator_mode: Option<String>,
+    pub active_estimator_mode: Option<EstimatorMode>,
 }
 
 impl Telemetry for EstimatorBackupTelemetry {
@@ -3930,14 +3893,7 @@ impl Telemetry for EstimatorBackupTelemetry {
             stddev_of_estimated_quaternion_q2_component:
                 (raw_stddev_of_estimated_quaternion_q2_component as f64) * 0.001,
             active_estimator_mode_raw: raw_active_estimator_mode,
-            active_estimator_mode: match raw_active_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            active_estimator_mode: EstimatorMode::from_raw(raw_active_estimator_mode),
         })
     }
 }
@@ -4379,7 +4335,7 @@ pub struct ModelsTelemetry {
     /// Active orbit mode. Active orbit mode. Possible values are in Table 19
     pub active_orbit_mode_raw: u8,
     /// Active orbit mode. Active orbit mode. Possible values are in Table 19
-    pub active_orbit_mode: Option<String>,
+    pub active_orbit_mode: Option<OrbitMode>,
     /// ASGP4 position error. GNSS and ASGP4 position error differs too much
     pub asgp4_position_error: bool,
     /// Eclipse flag. Eclipse flag
@@ -4387,7 +4343,7 @@ pub struct ModelsTelemetry {
     /// Source of current orbit position and velocity. Source of current orbit position and velocity. Possible values are in Table 119
     pub source_of_current_orbit_position_and_velocity_raw: u8,
     /// Source of current orbit position and velocity. Source of current orbit position and velocity. Possible values are in Table 119
-    pub source_of_current_orbit_position_and_velocity: Option<String>,
+    pub source_of_current_orbit_position_and_velocity: Option<NavigationSource>,
 }
 
 impl Telemetry for ModelsTelemetry {
@@ -4570,25 +4526,14 @@ impl Telemetry for ModelsTelemetry {
             asgp4_batch_counter: raw_asgp4_batch_counter,
             asgp4_position_delta: raw_asgp4_position_delta,
             active_orbit_mode_raw: raw_active_orbit_mode,
-            active_orbit_mode: match raw_active_orbit_mode {
-                0 => Some("OrbSgp4".to_string()),
-                1 => Some("OrbGnssSgp4".to_string()),
-                2 => Some("OrbAsgp4".to_string()),
-                3 => Some("OrbGnssAsgp4".to_string()),
-                _ => None,
-            },
+            active_orbit_mode: OrbitMode::from_raw(raw_active_orbit_mode),
             asgp4_position_error: raw_asgp4_position_error,
             eclipse_flag: raw_eclipse_flag,
             source_of_current_orbit_position_and_velocity_raw:
                 raw_source_of_current_orbit_position_and_velocity,
-            source_of_current_orbit_position_and_velocity:
-                match raw_source_of_current_orbit_position_and_velocity {
-                    0 => Some("NavNone".to_string()),
-                    1 => Some("NavGnss".to_string()),
-                    2 => Some("NavSgp4Tle".to_string()),
-                    3 => Some("NavAsgp4Tle".to_string()),
-                    _ => None,
-                },
+            source_of_current_orbit_position_and_velocity: NavigationSource::from_raw(
+                raw_source_of_current_orbit_position_and_velocity,
+            ),
         })
     }
 }
@@ -6396,83 +6341,83 @@ pub struct PowerstateTelemetry {
     /// RWL0 power state. RWL0 power state. Possible values are in Table 24
     pub rwl0_power_state_raw: u8,
     /// RWL0 power state. RWL0 power state. Possible values are in Table 24
-    pub rwl0_power_state: Option<String>,
+    pub rwl0_power_state: Option<PowerState>,
     /// RWL1 power state. RWL1 power state. Possible values are in Table 24
     pub rwl1_power_state_raw: u8,
     /// RWL1 power state. RWL1 power state. Possible values are in Table 24
-    pub rwl1_power_state: Option<String>,
+    pub rwl1_power_state: Option<PowerState>,
     /// RWL2 power state. RWL2 power state. Possible values are in Table 24
     pub rwl2_power_state_raw: u8,
     /// RWL2 power state. RWL2 power state. Possible values are in Table 24
-    pub rwl2_power_state: Option<String>,
+    pub rwl2_power_state: Option<PowerState>,
     /// RWL3 power state. RWL3 power state. Possible values are in Table 24
     pub rwl3_power_state_raw: u8,
     /// RWL3 power state. RWL3 power state. Possible values are in Table 24
-    pub rwl3_power_state: Option<String>,
+    pub rwl3_power_state: Option<PowerState>,
     /// MAG0 power state. MAG0 power state. Possible values are in Table 24
     pub mag0_power_state_raw: u8,
     /// MAG0 power state. MAG0 power state. Possible values are in Table 24
-    pub mag0_power_state: Option<String>,
+    pub mag0_power_state: Option<PowerState>,
     /// MAG1 power state. MAG1 power state. Possible values are in Table 24
     pub mag1_power_state_raw: u8,
     /// MAG1 power state. MAG1 power state. Possible values are in Table 24
-    pub mag1_power_state: Option<String>,
+    pub mag1_power_state: Option<PowerState>,
     /// GYR0 power state. GYR0 power state. Possible values are in Table 24
     pub gyr0_power_state_raw: u8,
     /// GYR0 power state. GYR0 power state. Possible values are in Table 24
-    pub gyr0_power_state: Option<String>,
+    pub gyr0_power_state: Option<PowerState>,
     /// GYR1 power state. GYR1 power state. Possible values are in Table 24
     pub gyr1_power_state_raw: u8,
     /// GYR1 power state. GYR1 power state. Possible values are in Table 24
-    pub gyr1_power_state: Option<String>,
+    pub gyr1_power_state: Option<PowerState>,
     /// FSS0 power state. FSS0 power state. Possible values are in Table 24
     pub fss0_power_state_raw: u8,
     /// FSS0 power state. FSS0 power state. Possible values are in Table 24
-    pub fss0_power_state: Option<String>,
+    pub fss0_power_state: Option<PowerState>,
     /// FSS1 power state. FSS1 power state. Possible values are in Table 24
     pub fss1_power_state_raw: u8,
     /// FSS1 power state. FSS1 power state. Possible values are in Table 24
-    pub fss1_power_state: Option<String>,
+    pub fss1_power_state: Option<PowerState>,
     /// FSS2 power state. FSS2 power state. Possible values are in Table 24
     pub fss2_power_state_raw: u8,
     /// FSS2 power state. FSS2 power state. Possible values are in Table 24
-    pub fss2_power_state: Option<String>,
+    pub fss2_power_state: Option<PowerState>,
     /// FSS3 power state. FSS3 power state. Possible values are in Table 24
     pub fss3_power_state_raw: u8,
     /// FSS3 power state. FSS3 power state. Possible values are in Table 24
-    pub fss3_power_state: Option<String>,
+    pub fss3_power_state: Option<PowerState>,
     /// HSS0 power state. HSS0 power state. Possible values are in Table 24
     pub hss0_power_state_raw: u8,
     /// HSS0 power state. HSS0 power state. Possible values are in Table 24
-    pub hss0_power_state: Option<String>,
+    pub hss0_power_state: Option<PowerState>,
     /// HSS1 power state. HSS1 power state. Possible values are in Table 24
     pub hss1_power_state_raw: u8,
     /// HSS1 power state. HSS1 power state. Possible values are in Table 24
-    pub hss1_power_state: Option<String>,
+    pub hss1_power_state: Option<PowerState>,
     /// STR0 power state. STR0 power state. Possible values are in Table 24
     pub str0_power_state_raw: u8,
     /// STR0 power state. STR0 power state. Possible values are in Table 24
-    pub str0_power_state: Option<String>,
+    pub str0_power_state: Option<PowerState>,
     /// STR1 power state. STR1 power state. Possible values are in Table 24
     pub str1_power_state_raw: u8,
     /// STR1 power state. STR1 power state. Possible values are in Table 24
-    pub str1_power_state: Option<String>,
+    pub str1_power_state: Option<PowerState>,
     /// ExtSensor0 power state. ExtSensor0 power state. Possible values are in Table 24
     pub extsensor0_power_state_raw: u8,
     /// ExtSensor0 power state. ExtSensor0 power state. Possible values are in Table 24
-    pub extsensor0_power_state: Option<String>,
+    pub extsensor0_power_state: Option<PowerState>,
     /// ExtSensor1 power state. ExtSensor1 power state. Possible values are in Table 24
     pub extsensor1_power_state_raw: u8,
     /// ExtSensor1 power state. ExtSensor1 power state. Possible values are in Table 24
-    pub extsensor1_power_state: Option<String>,
+    pub extsensor1_power_state: Option<PowerState>,
     /// EXTGYR0 power state. EXTGYR0 power state. Possible values are in Table 24
     pub extgyr0_power_state_raw: u8,
     /// EXTGYR0 power state. EXTGYR0 power state. Possible values are in Table 24
-    pub extgyr0_power_state: Option<String>,
+    pub extgyr0_power_state: Option<PowerState>,
     /// EXTGYR1 power state. EXTGYR1 power state. Possible values are in Table 24
     pub extgyr1_power_state_raw: u8,
     /// EXTGYR1 power state. EXTGYR1 power state. Possible values are in Table 24
-    pub extgyr1_power_state: Option<String>,
+    pub extgyr1_power_state: Option<PowerState>,
 }
 
 impl Telemetry for PowerstateTelemetry {
@@ -6508,125 +6453,45 @@ impl Telemetry for PowerstateTelemetry {
             codec::read_unsigned(payload, 152, 8, "EXTGYR1 power state")? as u8;
         Ok(Self {
             rwl0_power_state_raw: raw_rwl0_power_state,
-            rwl0_power_state: match raw_rwl0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            rwl0_power_state: PowerState::from_raw(raw_rwl0_power_state),
             rwl1_power_state_raw: raw_rwl1_power_state,
-            rwl1_power_state: match raw_rwl1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            rwl1_power_state: PowerState::from_raw(raw_rwl1_power_state),
             rwl2_power_state_raw: raw_rwl2_power_state,
-            rwl2_power_state: match raw_rwl2_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            rwl2_power_state: PowerState::from_raw(raw_rwl2_power_state),
             rwl3_power_state_raw: raw_rwl3_power_state,
-            rwl3_power_state: match raw_rwl3_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            rwl3_power_state: PowerState::from_raw(raw_rwl3_power_state),
             mag0_power_state_raw: raw_mag0_power_state,
-            mag0_power_state: match raw_mag0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            mag0_power_state: PowerState::from_raw(raw_mag0_power_state),
             mag1_power_state_raw: raw_mag1_power_state,
-            mag1_power_state: match raw_mag1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            mag1_power_state: PowerState::from_raw(raw_mag1_power_state),
             gyr0_power_state_raw: raw_gyr0_power_state,
-            gyr0_power_state: match raw_gyr0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            gyr0_power_state: PowerState::from_raw(raw_gyr0_power_state),
             gyr1_power_state_raw: raw_gyr1_power_state,
-            gyr1_power_state: match raw_gyr1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            gyr1_power_state: PowerState::from_raw(raw_gyr1_power_state),
             fss0_power_state_raw: raw_fss0_power_state,
-            fss0_power_state: match raw_fss0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            fss0_power_state: PowerState::from_raw(raw_fss0_power_state),
             fss1_power_state_raw: raw_fss1_power_state,
-            fss1_power_state: match raw_fss1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            fss1_power_state: PowerState::from_raw(raw_fss1_power_state),
             fss2_power_state_raw: raw_fss2_power_state,
-            fss2_power_state: match raw_fss2_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            fss2_power_state: PowerState::from_raw(raw_fss2_power_state),
             fss3_power_state_raw: raw_fss3_power_state,
-            fss3_power_state: match raw_fss3_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            fss3_power_state: PowerState::from_raw(raw_fss3_power_state),
             hss0_power_state_raw: raw_hss0_power_state,
-            hss0_power_state: match raw_hss0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            hss0_power_state: PowerState::from_raw(raw_hss0_power_state),
             hss1_power_state_raw: raw_hss1_power_state,
-            hss1_power_state: match raw_hss1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            hss1_power_state: PowerState::from_raw(raw_hss1_power_state),
             str0_power_state_raw: raw_str0_power_state,
-            str0_power_state: match raw_str0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            str0_power_state: PowerState::from_raw(raw_str0_power_state),
             str1_power_state_raw: raw_str1_power_state,
-            str1_power_state: match raw_str1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            str1_power_state: PowerState::from_raw(raw_str1_power_state),
             extsensor0_power_state_raw: raw_extsensor0_power_state,
-            extsensor0_power_state: match raw_extsensor0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            extsensor0_power_state: PowerState::from_raw(raw_extsensor0_power_state),
             extsensor1_power_state_raw: raw_extsensor1_power_state,
-            extsensor1_power_state: match raw_extsensor1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            extsensor1_power_state: PowerState::from_raw(raw_extsensor1_power_state),
             extgyr0_power_state_raw: raw_extgyr0_power_state,
-            extgyr0_power_state: match raw_extgyr0_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            extgyr0_power_state: PowerState::from_raw(raw_extgyr0_power_state),
             extgyr1_power_state_raw: raw_extgyr1_power_state,
-            extgyr1_power_state: match raw_extgyr1_power_state {
-                0 => Some("PowerOff".to_string()),
-                1 => Some("PowerOn".to_string()),
-                _ => None,
-            },
+            extgyr1_power_state: PowerState::from_raw(raw_extgyr1_power_state),
         })
     }
 }
@@ -6648,7 +6513,7 @@ pub struct AdcsRunModeTelemetry {
     /// ADCS run mode. ADCS run mode. Possible values are in Table 26
     pub adcs_run_mode_raw: u8,
     /// ADCS run mode. ADCS run mode. Possible values are in Table 26
-    pub adcs_run_mode: Option<String>,
+    pub adcs_run_mode: Option<RunMode>,
 }
 
 impl Telemetry for AdcsRunModeTelemetry {
@@ -6661,11 +6526,7 @@ impl Telemetry for AdcsRunModeTelemetry {
         let raw_adcs_run_mode = codec::read_unsigned(payload, 0, 8, "ADCS run mode")? as u8;
         Ok(Self {
             adcs_run_mode_raw: raw_adcs_run_mode,
-            adcs_run_mode: match raw_adcs_run_mode {
-                0 => Some("RunModeNormal".to_string()),
-                1 => Some("RunModeConfig".to_string()),
-                _ => None,
-            },
+            adcs_run_mode: RunMode::from_raw(raw_adcs_run_mode),
         })
     }
 }
@@ -6699,7 +6560,7 @@ pub struct ControlModeTelemetry {
     /// Control mode. Control mode. Possible values are in Table 9
     pub control_mode_raw: u8,
     /// Control mode. Control mode. Possible values are in Table 9
-    pub control_mode: Option<String>,
+    pub control_mode: Option<ControlMode>,
     /// Control timeout. Control timeout. (Unit of measure is [s])
     pub control_timeout: u16,
 }
@@ -6715,16 +6576,7 @@ impl Telemetry for ControlModeTelemetry {
         let raw_control_timeout = codec::read_unsigned(payload, 8, 16, "Control timeout")? as u16;
         Ok(Self {
             control_mode_raw: raw_control_mode,
-            control_mode: match raw_control_mode {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            control_mode: ControlMode::from_raw(raw_control_mode),
             control_timeout: raw_control_timeout,
         })
     }
@@ -7593,7 +7445,7 @@ pub struct AdcsControllerConfigurationTelemetry {
     /// Default control mode. Default control mode. Possible values are in Table 9
     pub default_control_mode_raw: u8,
     /// Default control mode. Default control mode. Possible values are in Table 9
-    pub default_control_mode: Option<String>,
+    pub default_control_mode: Option<ControlMode>,
     /// Detumbling damping gain. Detumbling damping gain (Kd)
     pub detumbling_damping_gain: f32,
     /// Sun-spin control gain - sunlit part. Sun-spin control gain (KDsun)
@@ -7727,16 +7579,7 @@ impl Telemetry for AdcsControllerConfigurationTelemetry {
             codec::read_unsigned(payload, 778, 1, "Enable sun avoidance")? != 0;
         Ok(Self {
             default_control_mode_raw: raw_default_control_mode,
-            default_control_mode: match raw_default_control_mode {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            default_control_mode: ControlMode::from_raw(raw_default_control_mode),
             detumbling_damping_gain: raw_detumbling_damping_gain,
             sun_spin_control_gain_sunlit_part: raw_sun_spin_control_gain_sunlit_part,
             sun_spin_control_gain_eclipse_part: raw_sun_spin_control_gain_eclipse_part,
@@ -8066,19 +7909,19 @@ pub struct DefaultModeConfigurationTelemetry {
     /// Default ADCS run mode. Default ADCS run mode. Possible values are in Table 26
     pub default_adcs_run_mode_raw: u8,
     /// Default ADCS run mode. Default ADCS run mode. Possible values are in Table 26
-    pub default_adcs_run_mode: Option<String>,
+    pub default_adcs_run_mode: Option<RunMode>,
     /// Default ADCS operational state. Default ADCS operational state. Possible values are in Table 36
     pub default_adcs_operational_state_raw: u8,
     /// Default ADCS operational state. Default ADCS operational state. Possible values are in Table 36
-    pub default_adcs_operational_state: Option<String>,
+    pub default_adcs_operational_state: Option<OperationalState>,
     /// Default control mode in OpStateSafe. Default control mode in OpStateSafe. Possible values are in Table 9
     pub default_control_mode_in_opstatesafe_raw: u8,
     /// Default control mode in OpStateSafe. Default control mode in OpStateSafe. Possible values are in Table 9
-    pub default_control_mode_in_opstatesafe: Option<String>,
+    pub default_control_mode_in_opstatesafe: Option<ControlMode>,
     /// Default control mode in OpStateAuto. Default control mode in OpStateAuto. Possible values are in Table 9
     pub default_control_mode_in_opstateauto_raw: u8,
     /// Default control mode in OpStateAuto. Default control mode in OpStateAuto. Possible values are in Table 9
-    pub default_control_mode_in_opstateauto: Option<String>,
+    pub default_control_mode_in_opstateauto: Option<ControlMode>,
 }
 
 impl Telemetry for DefaultModeConfigurationTelemetry {
@@ -8098,40 +7941,19 @@ impl Telemetry for DefaultModeConfigurationTelemetry {
             codec::read_unsigned(payload, 24, 8, "Default control mode in OpStateAuto")? as u8;
         Ok(Self {
             default_adcs_run_mode_raw: raw_default_adcs_run_mode,
-            default_adcs_run_mode: match raw_default_adcs_run_mode {
-                0 => Some("RunModeNormal".to_string()),
-                1 => Some("RunModeConfig".to_string()),
-                _ => None,
-            },
+            default_adcs_run_mode: RunMode::from_raw(raw_default_adcs_run_mode),
             default_adcs_operational_state_raw: raw_default_adcs_operational_state,
-            default_adcs_operational_state: match raw_default_adcs_operational_state {
-                0 => Some("OpStateManual".to_string()),
-                1 => Some("OpStateSafe".to_string()),
-                2 => Some("OpStateAuto".to_string()),
-                _ => None,
-            },
+            default_adcs_operational_state: OperationalState::from_raw(
+                raw_default_adcs_operational_state,
+            ),
             default_control_mode_in_opstatesafe_raw: raw_default_control_mode_in_opstatesafe,
-            default_control_mode_in_opstatesafe: match raw_default_control_mode_in_opstatesafe {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            default_control_mode_in_opstatesafe: ControlMode::from_raw(
+                raw_default_control_mode_in_opstatesafe,
+            ),
             default_control_mode_in_opstateauto_raw: raw_default_control_mode_in_opstateauto,
-            default_control_mode_in_opstateauto: match raw_default_control_mode_in_opstateauto {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            default_control_mode_in_opstateauto: ControlMode::from_raw(
+                raw_default_control_mode_in_opstateauto,
+            ),
         })
     }
 }
@@ -9730,11 +9552,11 @@ pub struct AdcsEstimatorConfigurationTelemetry {
     /// Default main estimator mode. Default main estimator mode. Possible values are in Table 10
     pub default_main_estimator_mode_raw: u8,
     /// Default main estimator mode. Default main estimator mode. Possible values are in Table 10
-    pub default_main_estimator_mode: Option<String>,
+    pub default_main_estimator_mode: Option<EstimatorMode>,
     /// Default backup estimator mode. Default backup estimator mode. Possible values are in Table 10
     pub default_backup_estimator_mode_raw: u8,
     /// Default backup estimator mode. Default backup estimator mode. Possible values are in Table 10
-    pub default_backup_estimator_mode: Option<String>,
+    pub default_backup_estimator_mode: Option<EstimatorMode>,
     /// MAG measurement noise. Magnetometer measurement noise
     pub mag_measurement_noise: f32,
     /// CSS measurement noise. Coarse sun sensor measurement noise
@@ -9802,23 +9624,11 @@ impl Telemetry for AdcsEstimatorConfigurationTelemetry {
         let raw_triad_vector_2 = codec::read_unsigned(payload, 312, 4, "Triad Vector 2")? as u8;
         Ok(Self {
             default_main_estimator_mode_raw: raw_default_main_estimator_mode,
-            default_main_estimator_mode: match raw_default_main_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            default_main_estimator_mode: EstimatorMode::from_raw(raw_default_main_estimator_mode),
             default_backup_estimator_mode_raw: raw_default_backup_estimator_mode,
-            default_backup_estimator_mode: match raw_default_backup_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            default_backup_estimator_mode: EstimatorMode::from_raw(
+                raw_default_backup_estimator_mode,
+            ),
             mag_measurement_noise: raw_mag_measurement_noise,
             css_measurement_noise: raw_css_measurement_noise,
             fss_measurement_noise: raw_fss_measurement_noise,
@@ -10253,11 +10063,11 @@ pub struct EstimationModeTelemetry {
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
     pub main_estimator_mode_raw: u8,
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
-    pub main_estimator_mode: Option<String>,
+    pub main_estimator_mode: Option<EstimatorMode>,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
     pub backup_estimator_mode_raw: u8,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
-    pub backup_estimator_mode: Option<String>,
+    pub backup_estimator_mode: Option<EstimatorMode>,
 }
 
 impl Telemetry for EstimationModeTelemetry {
@@ -10273,23 +10083,9 @@ impl Telemetry for EstimationModeTelemetry {
             codec::read_unsigned(payload, 8, 8, "Backup estimator mode")? as u8;
         Ok(Self {
             main_estimator_mode_raw: raw_main_estimator_mode,
-            main_estimator_mode: match raw_main_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            main_estimator_mode: EstimatorMode::from_raw(raw_main_estimator_mode),
             backup_estimator_mode_raw: raw_backup_estimator_mode,
-            backup_estimator_mode: match raw_backup_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            backup_estimator_mode: EstimatorMode::from_raw(raw_backup_estimator_mode),
         })
     }
 }
@@ -11913,7 +11709,7 @@ pub struct MainEstimatorTelemetry {
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
     pub active_estimator_mode_raw: u8,
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
-    pub active_estimator_mode: Option<String>,
+    pub active_estimator_mode: Option<EstimatorMode>,
 }
 
 impl Telemetry for MainEstimatorTelemetry {
@@ -12069,14 +11865,7 @@ impl Telemetry for MainEstimatorTelemetry {
             stddev_of_estimated_quaternion_q2_component:
                 (raw_stddev_of_estimated_quaternion_q2_component as f64) * 0.001,
             active_estimator_mode_raw: raw_active_estimator_mode,
-            active_estimator_mode: match raw_active_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            active_estimator_mode: EstimatorMode::from_raw(raw_active_estimator_mode),
         })
     }
 }
@@ -19291,15 +19080,15 @@ pub struct ControlAndEstimationModeTelemetry {
     /// Control mode. Control mode. Possible values are in Table 9
     pub control_mode_raw: u8,
     /// Control mode. Control mode. Possible values are in Table 9
-    pub control_mode: Option<String>,
+    pub control_mode: Option<ControlMode>,
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
     pub main_estimator_mode_raw: u8,
     /// Main estimator mode. Main estimator mode. Possible values are in Table 10
-    pub main_estimator_mode: Option<String>,
+    pub main_estimator_mode: Option<EstimatorMode>,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
     pub backup_estimator_mode_raw: u8,
     /// Backup estimator mode. Backup estimator mode. Possible values are in Table 10
-    pub backup_estimator_mode: Option<String>,
+    pub backup_estimator_mode: Option<EstimatorMode>,
     /// Control timeout. Control timeout. (Unit of measure is [s])
     pub control_timeout: u16,
 }
@@ -19319,34 +19108,11 @@ impl Telemetry for ControlAndEstimationModeTelemetry {
         let raw_control_timeout = codec::read_unsigned(payload, 24, 16, "Control timeout")? as u16;
         Ok(Self {
             control_mode_raw: raw_control_mode,
-            control_mode: match raw_control_mode {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            control_mode: ControlMode::from_raw(raw_control_mode),
             main_estimator_mode_raw: raw_main_estimator_mode,
-            main_estimator_mode: match raw_main_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            main_estimator_mode: EstimatorMode::from_raw(raw_main_estimator_mode),
             backup_estimator_mode_raw: raw_backup_estimator_mode,
-            backup_estimator_mode: match raw_backup_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            backup_estimator_mode: EstimatorMode::from_raw(raw_backup_estimator_mode),
             control_timeout: raw_control_timeout,
         })
     }
@@ -22205,7 +21971,7 @@ pub struct OrbitModeTelemetry {
     /// Orbit mode. Orbit calculation mode. Possible values are in Table 19
     pub orbit_mode_raw: u8,
     /// Orbit mode. Orbit calculation mode. Possible values are in Table 19
-    pub orbit_mode: Option<String>,
+    pub orbit_mode: Option<OrbitMode>,
 }
 
 impl Telemetry for OrbitModeTelemetry {
@@ -22218,13 +21984,7 @@ impl Telemetry for OrbitModeTelemetry {
         let raw_orbit_mode = codec::read_unsigned(payload, 0, 8, "Orbit mode")? as u8;
         Ok(Self {
             orbit_mode_raw: raw_orbit_mode,
-            orbit_mode: match raw_orbit_mode {
-                0 => Some("OrbSgp4".to_string()),
-                1 => Some("OrbGnssSgp4".to_string()),
-                2 => Some("OrbAsgp4".to_string()),
-                3 => Some("OrbGnssAsgp4".to_string()),
-                _ => None,
-            },
+            orbit_mode: OrbitMode::from_raw(raw_orbit_mode),
         })
     }
 }
@@ -22776,23 +22536,23 @@ pub struct HilTelemetry {
     /// Active control mode. Active control mode. Possible values are in Table 9
     pub active_control_mode_raw: u8,
     /// Active control mode. Active control mode. Possible values are in Table 9
-    pub active_control_mode: Option<String>,
+    pub active_control_mode: Option<ControlMode>,
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
     pub active_estimator_mode_raw: u8,
     /// Active estimator mode. Active estimator mode. Possible values are in Table 10
-    pub active_estimator_mode: Option<String>,
+    pub active_estimator_mode: Option<EstimatorMode>,
     /// Active orbit mode. Active orbit mode. Possible values are in Table 19
     pub active_orbit_mode_raw: u8,
     /// Active orbit mode. Active orbit mode. Possible values are in Table 19
-    pub active_orbit_mode: Option<String>,
+    pub active_orbit_mode: Option<OrbitMode>,
     /// Source of current orbit pos and vel. Source of current orbit position and velocity. Possible values are in Table 119
     pub source_of_current_orbit_pos_and_vel_raw: u8,
     /// Source of current orbit pos and vel. Source of current orbit position and velocity. Possible values are in Table 119
-    pub source_of_current_orbit_pos_and_vel: Option<String>,
+    pub source_of_current_orbit_pos_and_vel: Option<NavigationSource>,
     /// Active operational state. Active operational state. Possible values are in Table 36
     pub active_operational_state_raw: u8,
     /// Active operational state. Active operational state. Possible values are in Table 36
-    pub active_operational_state: Option<String>,
+    pub active_operational_state: Option<OperationalState>,
     /// ASGP4 position error. GNSS and ASGP4 position error differs too much
     pub asgp4_position_error: bool,
     /// HIL synchronised. HIL is currently synchronised
@@ -23002,48 +22762,17 @@ impl Telemetry for HilTelemetry {
         let raw_fmc_stage = codec::read_unsigned(payload, 852, 4, "FMC Stage")? as u8;
         Ok(Self {
             active_control_mode_raw: raw_active_control_mode,
-            active_control_mode: match raw_active_control_mode {
-                0 => Some("ConNone".to_string()),
-                1 => Some("ConBdot".to_string()),
-                3 => Some("ConBdot3".to_string()),
-                12 => Some("ConXYZWheel".to_string()),
-                13 => Some("ConSunTrack".to_string()),
-                50 => Some("ConStopRW".to_string()),
-                51 => Some("ConHxyzRW".to_string()),
-                _ => None,
-            },
+            active_control_mode: ControlMode::from_raw(raw_active_control_mode),
             active_estimator_mode_raw: raw_active_estimator_mode,
-            active_estimator_mode: match raw_active_estimator_mode {
-                0 => Some("EstNone".to_string()),
-                1 => Some("EstGyro".to_string()),
-                2 => Some("EstMagRkf".to_string()),
-                5 => Some("EstFullEkf".to_string()),
-                6 => Some("EstGyroEkf".to_string()),
-                _ => None,
-            },
+            active_estimator_mode: EstimatorMode::from_raw(raw_active_estimator_mode),
             active_orbit_mode_raw: raw_active_orbit_mode,
-            active_orbit_mode: match raw_active_orbit_mode {
-                0 => Some("OrbSgp4".to_string()),
-                1 => Some("OrbGnssSgp4".to_string()),
-                2 => Some("OrbAsgp4".to_string()),
-                3 => Some("OrbGnssAsgp4".to_string()),
-                _ => None,
-            },
+            active_orbit_mode: OrbitMode::from_raw(raw_active_orbit_mode),
             source_of_current_orbit_pos_and_vel_raw: raw_source_of_current_orbit_pos_and_vel,
-            source_of_current_orbit_pos_and_vel: match raw_source_of_current_orbit_pos_and_vel {
-                0 => Some("NavNone".to_string()),
-                1 => Some("NavGnss".to_string()),
-                2 => Some("NavSgp4Tle".to_string()),
-                3 => Some("NavAsgp4Tle".to_string()),
-                _ => None,
-            },
+            source_of_current_orbit_pos_and_vel: NavigationSource::from_raw(
+                raw_source_of_current_orbit_pos_and_vel,
+            ),
             active_operational_state_raw: raw_active_operational_state,
-            active_operational_state: match raw_active_operational_state {
-                0 => Some("OpStateManual".to_string()),
-                1 => Some("OpStateSafe".to_string()),
-                2 => Some("OpStateAuto".to_string()),
-                _ => None,
-            },
+            active_operational_state: OperationalState::from_raw(raw_active_operational_state),
             asgp4_position_error: raw_asgp4_position_error,
             hil_synchronised: raw_hil_synchronised,
             rwl0_speed_command: raw_rwl0_speed_command,
@@ -24190,7 +23919,7 @@ pub struct AdcsOperationalStateTelemetry {
     /// ADCS operational state. ADCS operational state. Possible values are in Table 36
     pub adcs_operational_state_raw: u8,
     /// ADCS operational state. ADCS operational state. Possible values are in Table 36
-    pub adcs_operational_state: Option<String>,
+    pub adcs_operational_state: Option<OperationalState>,
 }
 
 impl Telemetry for AdcsOperationalStateTelemetry {
@@ -24204,12 +23933,7 @@ impl Telemetry for AdcsOperationalStateTelemetry {
             codec::read_unsigned(payload, 0, 8, "ADCS operational state")? as u8;
         Ok(Self {
             adcs_operational_state_raw: raw_adcs_operational_state,
-            adcs_operational_state: match raw_adcs_operational_state {
-                0 => Some("OpStateManual".to_string()),
-                1 => Some("OpStateSafe".to_string()),
-                2 => Some("OpStateAuto".to_string()),
-                _ => None,
-            },
+            adcs_operational_state: OperationalState::from_raw(raw_adcs_operational_state),
         })
     }
 }
@@ -27,7 +27,7 @@ fn can_id_round_trip_matches_adcs_scripts() {
 #[test]
 fn encode_control_mode_payload() {
     let command = ControlModeCommand {
-        control_mode: 0,
+        control_mode: ControlMode::ConNone,
         control_timeout: 0,
     };
 
@@ -65,10 +65,10 @@ fn decode_raw_cubesense_sun_payload() {
     assert_eq!(decoded.fss0_alpha_angle, 1.0);
     assert_eq!(decoded.fss0_beta_angle_raw, -200);
     assert_eq!(decoded.fss0_beta_angle, -2.0);
-    assert_eq!(decoded.fss0_capture_result, Some("Captured".to_string()));
+    assert_eq!(decoded.fss0_capture_result, Some(CaptureResult::Captured));
     assert_eq!(
         decoded.fss0_detection_result,
-        Some("NoDetection".to_string())
+        Some(DetectionResult::NoDetection)
     );
     assert!(!decoded.fss0_valid_flag);
     assert!(decoded.fss1_valid_flag);
@@ -85,18 +85,80 @@ fn decode_hil_enum_labels() {
 
     let decoded = HilTelemetry::decode(&payload).unwrap();
 
-    assert_eq!(decoded.active_control_mode, Some("ConXYZWheel".to_string()));
+    assert_eq!(decoded.active_control_mode, Some(ControlMode::ConXYZWheel));
     assert_eq!(
         decoded.active_estimator_mode,
-        Some("EstFullEkf".to_string())
+        Some(EstimatorMode::EstFullEkf)
     );
-    assert_eq!(decoded.active_orbit_mode, Some("OrbAsgp4".to_string()));
+    assert_eq!(decoded.active_orbit_mode, Some(OrbitMode::OrbAsgp4));
     assert_eq!(
         decoded.source_of_current_orbit_pos_and_vel,
-        Some("NavAsgp4Tle".to_string())
+        Some(NavigationSource::NavAsgp4Tle)
     );
     assert_eq!(
         decoded.active_operational_state,
-        Some("OpStateManual".to_string())
+        Some(OperationalState::OpStateManual)
+    );
+}
+
+#[test]
+fn encode_control_mode_uses_table_value() {
+    let command = ControlModeCommand {
+        control_mode: ControlMode::ConXYZWheel,
+        control_timeout: 600,
+    };
+
+    assert_eq!(command.encode().unwrap(), vec![12, 0x58, 0x02]);
+}
+
+#[test]
+fn decode_unknown_enum_value_keeps_raw() {
+    let payload = vec![7, 0, 0];
+
+    let decoded = ControlModeTelemetry::decode(&payload).unwrap();
+
+    assert_eq!(decoded.control_mode_raw, 7);
+    assert_eq!(decoded.control_mode, None);
+}
+
+#[test]
+fn enum_raw_round_trip() {
+    for raw in 0..=u8::MAX {
+        if let Some(mode) = ControlMode::from_raw(raw) {
+            assert_eq!(mode.to_raw(), raw);
+        }
+    }
+    assert_eq!(ControlMode::default(), ControlMode::ConNone);
+    assert_eq!(ControlMode::ConBdot3.label(), "ConBdot3");
+    assert_eq!(ControlMode::TABLE, "table_9");
+}
+
+#[test]
+fn enum_value_valid_checks_known_tables() {
+    assert!(enum_value_valid("table_9", 13));
+    assert!(!enum_value_valid("table_9", 2));
+    assert!(enum_value_valid("table_999", 2));
+}
+
+#[test]
+fn decode_values_from_spec() {
+    let spec = telemetry_spec(ControlModeTelemetry::ID).unwrap();
+    let values = spec.decode_values(&[12, 0x58, 0x02]).unwrap();
+
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0].name, "control_mode");
+    assert_eq!(values[0].value, 12.0);
+    assert_eq!(values[1].name, "control_timeout");
+    assert_eq!(values[1].value, 600.0);
+    assert_eq!(values[1].unit.as_deref(), Some("s"));
+}
+
+#[test]
+fn field_keys_are_snake_case() {
+    assert_eq!(field_key("RWL0 inertia"), "rwl0_inertia");
+    assert_eq!(field_key("Read-Queue state"), "read_queue_state");
+    assert_eq!(
+        field_key("Firmware Version (Major)"),
+        "firmware_version_major"
     );
 }
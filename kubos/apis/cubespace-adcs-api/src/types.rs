@@ -2,7 +2,7 @@
 
 use async_graphql::SimpleObject;
 
-use crate::AdcsResult;
+use crate::{codec, AdcsResult};
 
 /// Data type used by a command or telemetry field in the CubeSpace matrix.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -80,6 +80,67 @@ pub struct TelemetrySpec {
     pub fields: &'static [FieldSpec],
 }
 
+impl TelemetrySpec {
+    /// Decodes the numeric fields of a response payload using the field metadata.
+    ///
+    /// Integer, enum and boolean fields are returned as numbers with any scale
+    /// applied. Byte arrays, strings and padding are skipped.
+    pub fn decode_values(&self, payload: &[u8]) -> AdcsResult<Vec<FieldValue>> {
+        let mut values = Vec::new();
+
+        for field in self.fields {
+            let (offset, length, name) = (field.offset_bits, field.length_bits, field.name);
+            let raw = match field.data_type {
+                DataType::Uint | DataType::Enum | DataType::Bool => {
+                    codec::read_unsigned(payload, offset, length, name)? as f64
+                }
+                DataType::Int => codec::read_signed(payload, offset, length, name)? as f64,
+                DataType::Float => f64::from(codec::read_f32(payload, offset, length, name)?),
+                DataType::Double => codec::read_f64(payload, offset, length, name)?,
+                DataType::Array | DataType::String | DataType::Padding | DataType::Unknown => {
+                    continue
+                }
+            };
+
+            values.push(FieldValue {
+                name: field_key(name),
+                value: raw * field.scale.unwrap_or(1.0),
+                unit: field.unit.map(ToOwned::to_owned),
+            });
+        }
+
+        Ok(values)
+    }
+}
+
+/// Converts a field name from the CubeSpace matrix into a snake_case key,
+/// e.g. `RWL0 inertia` becomes `rwl0_inertia`.
+pub fn field_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    for character in name.chars() {
+        if character.is_ascii_alphanumeric() {
+            key.push(character.to_ascii_lowercase());
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
+        }
+    }
+
+    let len = key.trim_end_matches('_').len();
+    key.truncate(len);
+    key
+}
+
+/// One numeric value decoded from a telemetry payload.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct FieldValue {
+    /// Field key, see [`field_key`].
+    pub name: String,
+    /// Engineering value.
+    pub value: f64,
+    /// Engineering unit, if known.
+    pub unit: Option<String>,
+}
+
 /// A typed CubeSpace ADCS telecommand.
 pub trait Telecommand {
     /// Telecommand ID.
@@ -119,6 +180,8 @@ pub struct HealthInfo {
     pub destination_address: i32,
     /// Request timeout in milliseconds.
     pub timeout_ms: i64,
+    /// CAN bus error statistics, if error frames are monitored.
+    pub bus: Option<BusHealth>,
 }
 
 impl HealthInfo {
@@ -136,10 +199,42 @@ impl HealthInfo {
             source_address,
             destination_address,
             timeout_ms,
+            bus: None,
         }
     }
 }
 
+/// CAN bus error statistics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, SimpleObject)]
+pub struct BusHealth {
+    /// Controller state: `ERROR_ACTIVE`, `ERROR_WARNING`, `ERROR_PASSIVE` or `BUS_OFF`.
+    pub state: String,
+    /// Error frames received.
+    pub error_frames: i64,
+    /// Bus-off events.
+    pub bus_off: i64,
+    /// Transitions to error-passive.
+    pub error_passive: i64,
+    /// Transitions to error-warning.
+    pub error_warning: i64,
+    /// Frames not acknowledged by any node.
+    pub no_ack: i64,
+    /// Protocol violations.
+    pub protocol: i64,
+    /// Controller buffer overflows.
+    pub overflow: i64,
+    /// Interface restarts attempted after bus-off.
+    pub recovery_attempts: i64,
+    /// Interface restarts which failed.
+    pub recovery_failures: i64,
+    /// Most recent transmit error counter.
+    pub tx_errors: Option<i32>,
+    /// Most recent receive error counter.
+    pub rx_errors: Option<i32>,
+    /// Description of the most recent error frame.
+    pub last_error: Option<String>,
+}
+
 /// One ADCS command or telemetry field definition.
 #[derive(Clone, Debug, PartialEq, SimpleObject)]
 pub struct FieldInfo {
@@ -0,0 +1,75 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::models::{Alarm, NewAlarm};
+use crate::{telemetry_alarm, Database};
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::update;
+
+impl Database {
+    /// Record a newly raised alarm, returning its id
+    pub fn raise_alarm(&mut self, alarm: &NewAlarm) -> QueryResult<i32> {
+        self.connection.transaction(|conn| {
+            insert_into(telemetry_alarm::table)
+                .values(alarm)
+                .execute(conn)?;
+            telemetry_alarm::table
+                .select(telemetry_alarm::id)
+                .order(telemetry_alarm::id.desc())
+                .first(conn)
+        })
+    }
+
+    /// Mark the active alarms of a parameter as cleared, returning how many
+    /// there were
+    pub fn clear_alarms(
+        &mut self,
+        subsystem: &str,
+        parameter: &str,
+        timestamp: f64,
+    ) -> QueryResult<usize> {
+        update(
+            telemetry_alarm::table
+                .filter(telemetry_alarm::subsystem.eq(subsystem))
+                .filter(telemetry_alarm::parameter.eq(parameter))
+                .filter(telemetry_alarm::cleared.is_null()),
+        )
+        .set(telemetry_alarm::cleared.eq(Some(timestamp)))
+        .execute(&mut self.connection)
+    }
+
+    /// Acknowledge an alarm. Returns false if there is no unacknowledged
+    /// alarm with the id.
+    pub fn acknowledge_alarm(&mut self, id: i32, timestamp: f64) -> QueryResult<bool> {
+        update(
+            telemetry_alarm::table
+                .find(id)
+                .filter(telemetry_alarm::acknowledged.is_null()),
+        )
+        .set(telemetry_alarm::acknowledged.eq(Some(timestamp)))
+        .execute(&mut self.connection)
+        .map(|count| count > 0)
+    }
+
+    /// Alarms which have not been cleared, oldest first
+    pub fn active_alarms(&mut self) -> QueryResult<Vec<Alarm>> {
+        telemetry_alarm::table
+            .filter(telemetry_alarm::cleared.is_null())
+            .order(telemetry_alarm::id.asc())
+            .load(&mut self.connection)
+    }
+}
@@ -0,0 +1,175 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Type of the values of a telemetry parameter
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    /// Signed 64-bit integer
+    Integer,
+    /// Finite 64-bit float
+    Float,
+    /// `true` or `false`
+    Bool,
+    /// Any text
+    String,
+}
+
+/// A telemetry value checked against its parameter's definition
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Integer value
+    Integer(i64),
+    /// Float value
+    Float(f64),
+    /// Boolean value
+    Bool(bool),
+    /// Text value
+    String(String),
+}
+
+/// Definition of one telemetry parameter
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ParameterDefinition {
+    /// Subsystem name
+    pub subsystem: String,
+    /// Parameter name
+    pub parameter: String,
+    /// Number identifying the parameter in binary telemetry
+    pub id: Option<u32>,
+    /// Type of the parameter's values
+    #[serde(rename = "type")]
+    pub value_type: ValueType,
+    /// Unit of the parameter's values, e.g. "V"
+    pub unit: Option<String>,
+    /// Smallest valid value of a numeric parameter
+    pub min: Option<f64>,
+    /// Largest valid value of a numeric parameter
+    pub max: Option<f64>,
+    /// Human-readable description
+    pub description: Option<String>,
+}
+
+impl ParameterDefinition {
+    /// Parse a raw telemetry value as this parameter's type, checking it is
+    /// within the valid range
+    pub fn parse(&self, raw: &str) -> Result<Value, String> {
+        let value = match self.value_type {
+            ValueType::Integer => raw
+                .trim()
+                .parse::<i64>()
+                .map(Value::Integer)
+                .map_err(|_| format!("'{}' is not an integer", raw))?,
+            ValueType::Float => match raw.trim().parse::<f64>() {
+                Ok(value) if value.is_finite() => Value::Float(value),
+                _ => return Err(format!("'{}' is not a number", raw)),
+            },
+            ValueType::Bool => match raw.trim().to_lowercase().as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err(format!("'{}' is not a boolean", raw)),
+            },
+            ValueType::String => Value::String(raw.to_owned()),
+        };
+
+        let number = match value {
+            Value::Integer(value) => value as f64,
+            Value::Float(value) => value,
+            _ => return Ok(value),
+        };
+        if let Some(min) = self.min.filter(|min| number < *min) {
+            return Err(format!("{} is below the minimum of {}", raw, min));
+        }
+        if let Some(max) = self.max.filter(|max| number > *max) {
+            return Err(format!("{} is above the maximum of {}", raw, max));
+        }
+
+        Ok(value)
+    }
+}
+
+/// Known telemetry parameters, keyed by subsystem and parameter name
+#[derive(Clone, Debug, Default)]
+pub struct Dictionary {
+    definitions: BTreeMap<(String, String), ParameterDefinition>,
+    ids: BTreeMap<u32, (String, String)>,
+}
+
+impl Dictionary {
+    /// Build a dictionary, checking that each parameter is defined once with
+    /// a sensible range and its own ID
+    pub fn new(definitions: Vec<ParameterDefinition>) -> Result<Self, String> {
+        let mut dictionary = Dictionary::default();
+
+        for definition in definitions {
+            let name = format!("{}/{}", definition.subsystem, definition.parameter);
+            let numeric = match definition.value_type {
+                ValueType::Integer | ValueType::Float => true,
+                ValueType::Bool | ValueType::String => false,
+            };
+            if !numeric && (definition.min.is_some() || definition.max.is_some()) {
+                return Err(format!(
+                    "{}: only numeric parameters may have a range",
+                    name
+                ));
+            }
+            if let (Some(min), Some(max)) = (definition.min, definition.max) {
+                if min > max {
+                    return Err(format!("{}: min is greater than max", name));
+                }
+            }
+
+            let key = (definition.subsystem.clone(), definition.parameter.clone());
+            let id = definition.id;
+            if dictionary
+                .definitions
+                .insert(key.clone(), definition)
+                .is_some()
+            {
+                return Err(format!("{}: defined more than once", name));
+            }
+            if let Some(id) = id {
+                if let Some((subsystem, parameter)) = dictionary.ids.insert(id, key) {
+                    return Err(format!(
+                        "{}: id {} is already used by {}/{}",
+                        name, id, subsystem, parameter
+                    ));
+                }
+            }
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Definition of a parameter, if it has one
+    pub fn get(&self, subsystem: &str, parameter: &str) -> Option<&ParameterDefinition> {
+        self.definitions
+            .get(&(subsystem.to_owned(), parameter.to_owned()))
+    }
+
+    /// Definition of the parameter with an ID, if there is one
+    pub fn get_by_id(&self, id: u32) -> Option<&ParameterDefinition> {
+        self.ids.get(&id).and_then(|key| self.definitions.get(key))
+    }
+
+    /// All definitions, ordered by subsystem and parameter
+    pub fn definitions(&self) -> impl Iterator<Item = &ParameterDefinition> {
+        self.definitions.values()
+    }
+}
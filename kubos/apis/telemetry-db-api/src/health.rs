@@ -0,0 +1,367 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::{systime, Database};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Text};
+use diesel::sqlite::SqliteConnection;
+use log::{error, info, warn};
+use std::fs;
+use std::path::Path;
+
+// Tables created by `Database::setup` and their columns, in the order they
+// are salvaged. Databases which predate a column or table are missing it.
+const TABLES: &[(&str, &[&str])] = &[
+    (
+        "telemetry",
+        &[
+            "timestamp",
+            "subsystem",
+            "parameter",
+            "value",
+            "int_value",
+            "float_value",
+            "bool_value",
+        ],
+    ),
+    (
+        "telemetry_rollup",
+        &[
+            "resolution",
+            "start",
+            "subsystem",
+            "parameter",
+            "minimum",
+            "maximum",
+            "mean",
+            "samples",
+        ],
+    ),
+    ("telemetry_rollup_state", &["resolution", "through"]),
+    (
+        "telemetry_alarm",
+        &[
+            "id",
+            "timestamp",
+            "subsystem",
+            "parameter",
+            "severity",
+            "value",
+            "message",
+            "cleared",
+            "acknowledged",
+        ],
+    ),
+];
+
+// Messages of SQLITE_CORRUPT and SQLITE_NOTADB errors. Diesel doesn't expose
+// SQLite's result codes
+const DAMAGED_MESSAGES: &[&str] = &["database disk image is malformed", "file is not a database"];
+
+// Files SQLite may keep alongside a database, which belong to it
+const COMPANION_SUFFIXES: &[&str] = &["-journal", "-wal", "-shm"];
+
+// Oldest entries removed per eviction pass
+const EVICT_BATCH: usize = 100;
+
+/// Outcome of checking a database's integrity
+#[derive(Clone, Debug)]
+pub struct IntegrityCheck {
+    /// System time of the check
+    pub timestamp: f64,
+    /// Problems found by SQLite. Empty if the database is intact
+    pub problems: Vec<String>,
+    /// Where a damaged database file was moved before a fresh one was created
+    pub quarantined: Option<String>,
+    /// Rows copied from the damaged database into the fresh one
+    pub recovered_rows: usize,
+}
+
+impl IntegrityCheck {
+    fn new(problems: Vec<String>) -> Self {
+        IntegrityCheck {
+            timestamp: systime(),
+            problems,
+            quarantined: None,
+            recovered_rows: 0,
+        }
+    }
+
+    /// Whether the database was found to be intact
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Space used by a database, in bytes
+#[derive(Clone, Copy, Debug)]
+pub struct DatabaseSize {
+    /// Size of the database file
+    pub total: u64,
+    /// Space within the file freed by deleted rows, which is reused before
+    /// the file grows
+    pub free: u64,
+}
+
+impl DatabaseSize {
+    /// Space holding data
+    pub fn used(&self) -> u64 {
+        self.total - self.free
+    }
+}
+
+#[derive(QueryableByName)]
+struct CheckRow {
+    #[diesel(sql_type = Text)]
+    quick_check: String,
+}
+
+#[derive(QueryableByName)]
+struct SizeRow {
+    #[diesel(sql_type = BigInt)]
+    total: i64,
+    #[diesel(sql_type = BigInt)]
+    free: i64,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct ColumnRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+// Problems found by SQLite's quick check, which covers everything but
+// index contents
+fn quick_check(connection: &mut SqliteConnection) -> QueryResult<Vec<String>> {
+    Ok(sql_query("PRAGMA quick_check")
+        .load::<CheckRow>(connection)?
+        .into_iter()
+        .map(|row| row.quick_check)
+        .filter(|result| result != "ok")
+        .collect())
+}
+
+// Whether an error means the database file itself is damaged, rather than
+// e.g. locked or unreadable
+fn is_damaged(err: &DieselError) -> bool {
+    match err {
+        DieselError::DatabaseError(_, info) => DAMAGED_MESSAGES
+            .iter()
+            .any(|message| info.message().contains(message)),
+        _ => false,
+    }
+}
+
+// Problems found in an existing database file. Panics if the check can't be
+// run for a reason other than damage to the file.
+fn check_existing(path: &str) -> Vec<String> {
+    let mut connection = SqliteConnection::establish(path).unwrap_or_else(|err| {
+        error!(
+            "Could not create SQLite database connection to {}: {}",
+            path, err
+        );
+        panic!(
+            "Could not create SQLite database connection to {}: {}",
+            path, err
+        )
+    });
+
+    match quick_check(&mut connection) {
+        Ok(problems) => problems,
+        Err(err) if is_damaged(&err) => vec![format!("{}", err)],
+        Err(err) => {
+            error!("Failed to check integrity of database {}: {}", path, err);
+            panic!("Failed to check integrity of database {}: {}", path, err)
+        }
+    }
+}
+
+impl Database {
+    /// Open a database, check its integrity and set up its tables.
+    ///
+    /// A database which fails the check, or which SQLite reports as malformed
+    /// or not a database, is moved, along with its journal, to
+    /// `<path>.corrupt-<time>` and replaced with a fresh one. Tables which can
+    /// still be read in full are copied into the fresh database; the rest of
+    /// the damaged file is left for download.
+    ///
+    /// # Arguments
+    /// `path` - Path to database file
+    ///
+    /// # Panics
+    ///
+    /// Will `panic!` if the database can't be opened or checked for any other
+    /// reason, if a damaged database can't be moved aside, or if the fresh
+    /// database can't be created.
+    pub fn open(path: &str) -> (Self, IntegrityCheck) {
+        let mut check = if Path::new(path).exists() {
+            IntegrityCheck::new(check_existing(path))
+        } else {
+            IntegrityCheck::new(vec![])
+        };
+
+        if !check.ok() {
+            error!(
+                "Database {} is damaged: {}",
+                path,
+                check.problems.join("; ")
+            );
+
+            let damaged = format!("{}.corrupt-{}", path, check.timestamp as u64);
+            for suffix in [""].iter().chain(COMPANION_SUFFIXES) {
+                let from = format!("{}{}", path, suffix);
+                if !Path::new(&from).exists() {
+                    continue;
+                }
+                if let Err(err) = fs::rename(&from, format!("{}{}", damaged, suffix)) {
+                    error!("Failed to move damaged database file {}: {}", from, err);
+                    panic!("Failed to move damaged database file {}: {}", from, err)
+                }
+            }
+            info!("Moved damaged database to {}", damaged);
+            check.quarantined = Some(damaged);
+        }
+
+        let mut database = Database::new(path);
+        database.setup();
+
+        if let Some(damaged) = &check.quarantined {
+            check.recovered_rows = database.salvage(damaged);
+        }
+
+        (database, check)
+    }
+
+    /// Run SQLite's quick integrity check against the open database
+    pub fn check_integrity(&mut self) -> IntegrityCheck {
+        IntegrityCheck::new(
+            quick_check(&mut self.connection).unwrap_or_else(|err| vec![format!("{}", err)]),
+        )
+    }
+
+    /// Size of the database file and the free space within it
+    pub fn size(&mut self) -> QueryResult<DatabaseSize> {
+        let row = sql_query(
+            "SELECT page_count * page_size AS total, freelist_count * page_size AS free \
+             FROM pragma_page_count(), pragma_page_size(), pragma_freelist_count()",
+        )
+        .get_result::<SizeRow>(&mut self.connection)?;
+
+        Ok(DatabaseSize {
+            total: row.total as u64,
+            free: row.free as u64,
+        })
+    }
+
+    /// Number of rows in each of the service's tables
+    pub fn row_counts(&mut self) -> QueryResult<Vec<(&'static str, i64)>> {
+        TABLES
+            .iter()
+            .map(|(table, _)| {
+                sql_query(format!("SELECT COUNT(*) AS count FROM {}", table))
+                    .get_result::<CountRow>(&mut self.connection)
+                    .map(|row| (*table, row.count))
+            })
+            .collect()
+    }
+
+    /// Remove a batch of the oldest telemetry entries if the space used by
+    /// the database is over `max_size` bytes. Rollups and alarms are kept.
+    ///
+    /// Returns the number of entries removed, which is zero once the database
+    /// is within its budget or there is no telemetry left to remove.
+    pub fn evict_oldest(&mut self, max_size: u64) -> QueryResult<usize> {
+        if self.size()?.used() <= max_size {
+            return Ok(0);
+        }
+
+        sql_query(format!(
+            "DELETE FROM telemetry WHERE rowid IN \
+             (SELECT rowid FROM telemetry ORDER BY timestamp LIMIT {})",
+            EVICT_BATCH
+        ))
+        .execute(&mut self.connection)
+    }
+
+    // Copy what can be read from a damaged database, one table at a time.
+    // Reading stops at the first damaged page, so a table is either copied
+    // in full or not at all. Only the columns the damaged table has are
+    // copied, as it may predate some of them.
+    fn salvage(&mut self, damaged: &str) -> usize {
+        if let Err(err) = sql_query(format!(
+            "ATTACH DATABASE '{}' AS damaged",
+            damaged.replace('\'', "''")
+        ))
+        .execute(&mut self.connection)
+        {
+            warn!("Failed to open damaged database {}: {}", damaged, err);
+            return 0;
+        }
+
+        let mut recovered = 0;
+        for (table, columns) in TABLES {
+            let existing = match sql_query(format!(
+                "SELECT name FROM pragma_table_info('{}', 'damaged')",
+                table
+            ))
+            .load::<ColumnRow>(&mut self.connection)
+            {
+                Ok(rows) => rows.into_iter().map(|row| row.name).collect::<Vec<_>>(),
+                Err(err) => {
+                    warn!("Failed to read columns of {}: {}", table, err);
+                    continue;
+                }
+            };
+            let columns = columns
+                .iter()
+                .filter(|column| existing.iter().any(|name| name == *column))
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ");
+            if columns.is_empty() {
+                info!("Damaged database has no {} table", table);
+                continue;
+            }
+
+            match sql_query(format!(
+                "INSERT OR IGNORE INTO main.{0} ({1}) SELECT {1} FROM damaged.{0}",
+                table, columns
+            ))
+            .execute(&mut self.connection)
+            {
+                Ok(rows) => {
+                    info!("Recovered {} rows of {}", rows, table);
+                    recovered += rows;
+                }
+                Err(err) => warn!("Failed to recover {}: {}", table, err),
+            }
+        }
+
+        if let Err(err) = sql_query("DETACH DATABASE damaged").execute(&mut self.connection) {
+            warn!("Failed to close damaged database {}: {}", damaged, err);
+        }
+
+        recovered
+    }
+}
@@ -17,8 +17,15 @@
 // In Diesel 2.x, we don't need extern crate declarations with Rust 2018 edition
 // and can directly use the imports
 
+mod alarm;
+pub mod dictionary;
+mod health;
 pub mod models;
+mod rollup;
+pub use crate::dictionary::{Dictionary, ParameterDefinition, Value, ValueType};
+pub use crate::health::{DatabaseSize, IntegrityCheck};
 pub use crate::models::*;
+pub use crate::rollup::{RESOLUTION_HOUR, RESOLUTION_MINUTE};
 
 use diesel::dsl::sql;
 use diesel::insert_into;
@@ -29,6 +36,9 @@ use diesel::sqlite::SqliteConnection;
 use diesel::*;
 use log::{error, info};
 
+// Rows written per insert statement, keeping under SQLite's variable limit
+const INSERT_BATCH: usize = 100;
+
 pub struct Database {
     pub connection: SqliteConnection,
 }
@@ -93,6 +103,74 @@ impl Database {
                 }
             }
         };
+
+        // Typed value columns were added after the telemetry table, so are
+        // added to databases which predate them
+        for (column, sql_type) in &[
+            ("int_value", "BIGINT"),
+            ("float_value", "DOUBLE"),
+            ("bool_value", "BOOLEAN"),
+        ] {
+            let exists = select(sql::<Bool>(&format!(
+                "EXISTS (SELECT 1 FROM pragma_table_info('telemetry') WHERE name = '{}')",
+                column
+            )))
+            .get_result::<bool>(&mut self.connection);
+            let result = match exists {
+                Ok(true) => continue,
+                Ok(false) => sql_query(format!(
+                    "ALTER TABLE telemetry ADD COLUMN {} {}",
+                    column, sql_type
+                ))
+                .execute(&mut self.connection),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!("Error adding typed value columns: {:?}", err);
+                panic!("Error adding typed value columns: {:?}", err)
+            }
+        }
+
+        // Summary tables were added after the telemetry table, so are
+        // created separately for databases which predate them
+        for statement in &[
+            "CREATE TABLE IF NOT EXISTS telemetry_rollup (
+                resolution INTEGER NOT NULL,
+                start DOUBLE NOT NULL,
+                subsystem VARCHAR(255) NOT NULL,
+                parameter VARCHAR(255) NOT NULL,
+                minimum DOUBLE NOT NULL,
+                maximum DOUBLE NOT NULL,
+                mean DOUBLE NOT NULL,
+                samples INTEGER NOT NULL,
+                PRIMARY KEY (resolution, start, subsystem, parameter))",
+            "CREATE TABLE IF NOT EXISTS telemetry_rollup_state (
+                resolution INTEGER NOT NULL PRIMARY KEY,
+                through DOUBLE NOT NULL)",
+        ] {
+            if let Err(err) = sql_query(*statement).execute(&mut self.connection) {
+                error!("Error creating rollup tables: {:?}", err);
+                panic!("Error creating rollup tables: {:?}", err)
+            }
+        }
+
+        if let Err(err) = sql_query(
+            "CREATE TABLE IF NOT EXISTS telemetry_alarm (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp DOUBLE NOT NULL,
+                subsystem VARCHAR(255) NOT NULL,
+                parameter VARCHAR(255) NOT NULL,
+                severity VARCHAR(16) NOT NULL,
+                value VARCHAR(255) NOT NULL,
+                message TEXT NOT NULL,
+                cleared DOUBLE,
+                acknowledged DOUBLE)",
+        )
+        .execute(&mut self.connection)
+        {
+            error!("Error creating alarm table: {:?}", err);
+            panic!("Error creating alarm table: {:?}", err)
+        }
     }
 
     pub fn insert<'a>(
@@ -120,9 +198,7 @@ impl Database {
         parameter: &'a str,
         value: &'a str,
     ) -> QueryResult<usize> {
-        let time = time::now_utc().to_timespec();
-        let timestamp = time.sec as f64 + (f64::from(time.nsec) / 1_000_000_000.0);
-        self.insert(timestamp, subsystem, parameter, value)
+        self.insert(systime(), subsystem, parameter, value)
     }
 
     pub fn insert_bulk(&mut self, entries: Vec<Entry>) -> QueryResult<usize> {
@@ -130,6 +206,32 @@ impl Database {
             .values(&entries)
             .execute(&mut self.connection)
     }
+
+    /// Insert an entry whose value has been checked against the parameter dictionary
+    pub fn insert_typed(&mut self, entry: &TypedEntry) -> QueryResult<usize> {
+        insert_into(telemetry::table)
+            .values(entry)
+            .execute(&mut self.connection)
+    }
+
+    /// Insert entries whose values have been checked against the parameter dictionary.
+    /// Either every entry is inserted or none are.
+    pub fn insert_bulk_typed(&mut self, entries: &[TypedEntry]) -> QueryResult<usize> {
+        self.connection.transaction(|conn| {
+            let mut count = 0;
+            for batch in entries.chunks(INSERT_BATCH) {
+                count += insert_into(telemetry::table).values(batch).execute(conn)?;
+            }
+            Ok(count)
+        })
+    }
+}
+
+/// Current system time in fractional seconds, as used for entries inserted
+/// without a timestamp
+pub fn systime() -> f64 {
+    let time = time::now_utc().to_timespec();
+    time.sec as f64 + (f64::from(time.nsec) / 1_000_000_000.0)
 }
 
 // Use diesel's table! macro
@@ -141,5 +243,42 @@ table! {
         subsystem -> Text,
         parameter -> Text,
         value -> Text,
+        int_value -> Nullable<BigInt>,
+        float_value -> Nullable<Double>,
+        bool_value -> Nullable<Bool>,
+    }
+}
+
+table! {
+    telemetry_rollup (resolution, start, subsystem, parameter) {
+        resolution -> Integer,
+        start -> Double,
+        subsystem -> Text,
+        parameter -> Text,
+        minimum -> Double,
+        maximum -> Double,
+        mean -> Double,
+        samples -> BigInt,
+    }
+}
+
+table! {
+    telemetry_rollup_state (resolution) {
+        resolution -> Integer,
+        through -> Double,
+    }
+}
+
+table! {
+    telemetry_alarm (id) {
+        id -> Integer,
+        timestamp -> Double,
+        subsystem -> Text,
+        parameter -> Text,
+        severity -> Text,
+        value -> Text,
+        message -> Text,
+        cleared -> Nullable<Double>,
+        acknowledged -> Nullable<Double>,
     }
 }
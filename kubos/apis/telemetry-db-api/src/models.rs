@@ -14,6 +14,8 @@
 // limitations under the License.
 //
 
+use crate::dictionary::Value;
+
 // Import Diesel traits
 use diesel::Insertable;
 use diesel::Queryable;
@@ -27,3 +29,90 @@ pub struct Entry {
     pub parameter: String,
     pub value: String,
 }
+
+/// Telemetry entry whose value is also stored in the column for its type
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::telemetry)]
+#[diesel(treat_none_as_default_value = false)]
+pub struct TypedEntry {
+    pub timestamp: f64,
+    pub subsystem: String,
+    pub parameter: String,
+    /// Value as it was received
+    pub value: String,
+    pub int_value: Option<i64>,
+    pub float_value: Option<f64>,
+    pub bool_value: Option<bool>,
+}
+
+impl TypedEntry {
+    /// Create an entry from a received value and its typed form
+    pub fn new(timestamp: f64, subsystem: &str, parameter: &str, raw: &str, value: &Value) -> Self {
+        let mut entry = TypedEntry {
+            timestamp,
+            subsystem: subsystem.to_owned(),
+            parameter: parameter.to_owned(),
+            value: raw.to_owned(),
+            int_value: None,
+            float_value: None,
+            bool_value: None,
+        };
+        match *value {
+            Value::Integer(value) => entry.int_value = Some(value),
+            Value::Float(value) => entry.float_value = Some(value),
+            Value::Bool(value) => entry.bool_value = Some(value),
+            Value::String(_) => (),
+        }
+        entry
+    }
+}
+
+/// Summary of a parameter's numeric values over one time bucket
+#[derive(Debug, Queryable, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::telemetry_rollup)]
+pub struct Rollup {
+    /// Length of the bucket in seconds
+    pub resolution: i32,
+    /// Start of the bucket
+    pub start: f64,
+    pub subsystem: String,
+    pub parameter: String,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub mean: f64,
+    /// Number of values summarised
+    pub samples: i64,
+}
+
+/// Alarm raised when a parameter's value enters its yellow or red limits
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::telemetry_alarm)]
+pub struct NewAlarm {
+    /// Timestamp of the value which raised the alarm
+    pub timestamp: f64,
+    pub subsystem: String,
+    pub parameter: String,
+    /// "yellow" or "red"
+    pub severity: String,
+    /// Value which raised the alarm
+    pub value: String,
+    /// Description of the limit which was violated
+    pub message: String,
+}
+
+/// Stored alarm
+#[derive(Debug, Queryable, Serialize, Deserialize)]
+pub struct Alarm {
+    pub id: i32,
+    pub timestamp: f64,
+    pub subsystem: String,
+    pub parameter: String,
+    pub severity: String,
+    pub value: String,
+    pub message: String,
+    /// Timestamp of the value which brought the parameter back within its
+    /// limits, or which raised a newer alarm
+    pub cleared: Option<f64>,
+    /// System time at which the alarm was acknowledged
+    pub acknowledged: Option<f64>,
+}
@@ -0,0 +1,158 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::models::{Entry, Rollup};
+use crate::{telemetry, telemetry_rollup, telemetry_rollup_state, Database, INSERT_BATCH};
+use diesel::dsl::min;
+use diesel::prelude::*;
+use diesel::replace_into;
+use std::collections::BTreeMap;
+
+/// Bucket length of per-minute rollups, in seconds
+pub const RESOLUTION_MINUTE: i32 = 60;
+/// Bucket length of per-hour rollups, in seconds
+pub const RESOLUTION_HOUR: i32 = 3600;
+
+// Telemetry summarised in one pass, in seconds. Must be a multiple of every
+// resolution
+const CHUNK: f64 = 3600.0;
+
+// Running totals of one bucket
+struct Totals {
+    minimum: f64,
+    maximum: f64,
+    sum: f64,
+    samples: i64,
+}
+
+impl Database {
+    /// Time up to which telemetry has been summarised at a resolution
+    pub fn rollup_through(&mut self, resolution: i32) -> QueryResult<Option<f64>> {
+        telemetry_rollup_state::table
+            .find(resolution)
+            .select(telemetry_rollup_state::through)
+            .first(&mut self.connection)
+            .optional()
+    }
+
+    /// Summarise the next hour of telemetry which has not been rolled up at
+    /// a resolution, stopping at the start of the bucket containing `until`.
+    /// Values which are not numbers are skipped, as is telemetry inserted
+    /// with a timestamp before the time already rolled up.
+    ///
+    /// Returns true once there is nothing left to summarise.
+    pub fn rollup_chunk(&mut self, resolution: i32, until: f64) -> QueryResult<bool> {
+        let width = f64::from(resolution);
+        let end = (until / width).floor() * width;
+
+        self.connection.transaction(|conn| {
+            let start = match telemetry_rollup_state::table
+                .find(resolution)
+                .select(telemetry_rollup_state::through)
+                .first::<f64>(conn)
+                .optional()?
+            {
+                Some(through) => through,
+                None => match telemetry::table
+                    .select(min(telemetry::timestamp))
+                    .first::<Option<f64>>(conn)?
+                {
+                    Some(first) => (first / width).floor() * width,
+                    None => return Ok(true),
+                },
+            };
+            if start >= end {
+                return Ok(true);
+            }
+
+            let chunk_end = (start + CHUNK).min(end);
+            let entries: Vec<Entry> = telemetry::table
+                .select((
+                    telemetry::timestamp,
+                    telemetry::subsystem,
+                    telemetry::parameter,
+                    telemetry::value,
+                ))
+                .filter(telemetry::timestamp.ge(start))
+                .filter(telemetry::timestamp.lt(chunk_end))
+                .load(conn)?;
+            for batch in summarise(resolution, &entries).chunks(INSERT_BATCH) {
+                replace_into(telemetry_rollup::table)
+                    .values(batch)
+                    .execute(conn)?;
+            }
+
+            // Skip over gaps without telemetry
+            let through = match telemetry::table
+                .select(min(telemetry::timestamp))
+                .filter(telemetry::timestamp.ge(chunk_end))
+                .first::<Option<f64>>(conn)?
+            {
+                Some(next) => ((next / width).floor() * width).min(end),
+                None => end,
+            };
+            replace_into(telemetry_rollup_state::table)
+                .values((
+                    telemetry_rollup_state::resolution.eq(resolution),
+                    telemetry_rollup_state::through.eq(through),
+                ))
+                .execute(conn)?;
+
+            Ok(through >= end)
+        })
+    }
+}
+
+// Minimum, maximum, mean and count of each parameter's numeric values in
+// each bucket
+fn summarise(resolution: i32, entries: &[Entry]) -> Vec<Rollup> {
+    let width = f64::from(resolution);
+    let mut buckets: BTreeMap<(i64, &str, &str), Totals> = BTreeMap::new();
+
+    for entry in entries {
+        let value = match entry.value.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            _ => continue,
+        };
+        let bucket = (entry.timestamp / width).floor() as i64;
+        let totals = buckets
+            .entry((bucket, &entry.subsystem, &entry.parameter))
+            .or_insert(Totals {
+                minimum: value,
+                maximum: value,
+                sum: 0.0,
+                samples: 0,
+            });
+        totals.minimum = totals.minimum.min(value);
+        totals.maximum = totals.maximum.max(value);
+        totals.sum += value;
+        totals.samples += 1;
+    }
+
+    buckets
+        .into_iter()
+        .map(|((bucket, subsystem, parameter), totals)| Rollup {
+            resolution,
+            start: bucket as f64 * width,
+            subsystem: subsystem.to_owned(),
+            parameter: parameter.to_owned(),
+            minimum: totals.minimum,
+            maximum: totals.maximum,
+            mean: totals.sum / totals.samples as f64,
+            samples: totals.samples,
+        })
+        .collect()
+}
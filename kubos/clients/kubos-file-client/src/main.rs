@@ -16,7 +16,7 @@
 
 use clap::{App, AppSettings, Arg, SubCommand};
 use failure::bail;
-use file_protocol::{FileProtocol, FileProtocolConfig, State};
+use file_protocol::{Compression, FileOp, FileOpReply, FileProtocol, FileProtocolConfig, State};
 use log::{error, info};
 use simplelog::*;
 use std::path::Path;
@@ -54,10 +54,51 @@ fn upload(
     Ok(())
 }
 
+fn patch(
+    protocol_instance: FileProtocol,
+    base_path: &str,
+    source_path: &str,
+    target_path: &str,
+    remote_base_path: &str,
+) -> Result<(), failure::Error> {
+    info!(
+        "Patching remote:{} into remote:{} using local:{} -> local:{}",
+        &remote_base_path, &target_path, &base_path, &source_path
+    );
+
+    // Work out what has changed since the base file, and store the patch in temp storage
+    let (patch, num_chunks, mode) =
+        protocol_instance.initialize_patch(base_path, source_path, remote_base_path)?;
+
+    info!(
+        "Patch is {} chunks, replacing a {} byte file",
+        num_chunks,
+        std::fs::metadata(source_path)?.len()
+    );
+
+    // Generate channel id for transaction
+    let channel = protocol_instance.generate_channel()?;
+
+    // Tell our destination the hash and number of chunks of the patch to expect
+    protocol_instance.send_metadata(channel, &patch.hash, num_chunks)?;
+
+    // Send patch command, saying which file to apply it to
+    protocol_instance.send_patch(channel, &patch, target_path, mode)?;
+
+    // Start the engine to send the patch data chunks
+    protocol_instance.message_engine(
+        |d| protocol_instance.recv(Some(d)),
+        Duration::from_secs(2),
+        &State::Transmitting,
+    )?;
+    Ok(())
+}
+
 fn download(
     protocol_instance: FileProtocol,
     source_path: &str,
     target_path: &str,
+    resume_hash: Option<&str>,
 ) -> Result<(), failure::Error> {
     info!(
         "Downloading remote: {} to local: {}",
@@ -69,7 +110,10 @@ fn download(
 
     // Send our file request to the remote addr and verify that it's
     // going to be able to send it
-    protocol_instance.send_import(channel, source_path)?;
+    match resume_hash {
+        Some(hash) => protocol_instance.send_resume_import(channel, source_path, hash)?,
+        None => protocol_instance.send_import(channel, source_path)?,
+    }
 
     // Wait for the request reply.
     // Note/TODO: We don't use a timeout here because we don't know how long it will
@@ -111,6 +155,89 @@ fn cleanup(protocol_instance: FileProtocol, hash: Option<String>) -> Result<(),
     Ok(())
 }
 
+fn status(protocol_instance: FileProtocol, hash: Option<String>) -> Result<(), failure::Error> {
+    // Generate channel ID for transaction
+    let channel = protocol_instance.generate_channel()?;
+
+    let transfers = match hash {
+        Some(hash) => {
+            vec![protocol_instance.request_status(channel, &hash, Duration::from_secs(2))?]
+        }
+        None => protocol_instance.request_transfers(channel, Duration::from_secs(2))?,
+    };
+
+    if transfers.is_empty() {
+        info!("No transfers in remote temp storage");
+    }
+
+    for transfer in transfers {
+        match transfer.num_chunks {
+            Some(num_chunks) => info!(
+                "{}: {} of {} chunks present",
+                transfer.hash, transfer.chunks_present, num_chunks
+            ),
+            None => info!(
+                "{}: {} chunks present, total unknown",
+                transfer.hash, transfer.chunks_present
+            ),
+        }
+        if !transfer.missing.is_empty() {
+            info!("Missing chunk ranges: {:?}", transfer.missing);
+        }
+    }
+
+    Ok(())
+}
+
+fn file_op(protocol_instance: FileProtocol, op: FileOp) -> Result<(), failure::Error> {
+    // Generate channel ID for transaction
+    let channel = protocol_instance.generate_channel()?;
+
+    match protocol_instance.request_file_op(channel, &op, Duration::from_secs(2))? {
+        FileOpReply::List {
+            path,
+            total,
+            entries,
+        } => {
+            info!("{} contains {} entries", path, total);
+            for entry in &entries {
+                if entry.is_dir {
+                    info!("{}/", entry.name);
+                } else {
+                    info!("{} ({} bytes)", entry.name, entry.size);
+                }
+            }
+            if (entries.len() as u32) < total {
+                info!("{} entries not shown", total - entries.len() as u32);
+            }
+        }
+        FileOpReply::Stat {
+            path,
+            is_dir,
+            size,
+            mode,
+            modified,
+        } => info!(
+            "{}: {}, {} bytes, mode {:o}, modified {}",
+            path,
+            if is_dir { "directory" } else { "file" },
+            size,
+            mode,
+            modified
+        ),
+        FileOpReply::Delete { path } => info!("Deleted {}", path),
+        FileOpReply::Mkdir { path } => info!("Created {}", path),
+        FileOpReply::Move { from, to } => info!("Moved {} to {}", from, to),
+        FileOpReply::DiskUsage {
+            path,
+            total,
+            available,
+        } => info!("{}: {} of {} bytes available", path, available, total),
+    }
+
+    Ok(())
+}
+
 fn main() {
     CombinedLogger::init(vec![
         TermLogger::new(LevelFilter::Info, Config::default()).unwrap()
@@ -148,6 +275,40 @@ fn main() {
                     Arg::with_name("target_path")
                         .help("Local destination path")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .help("Hash of an interrupted download of the file to continue")
+                        .long("resume")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("patch")
+                .about("Updates a remote file by uploading only what has changed")
+                .arg(
+                    Arg::with_name("base_path")
+                        .help("Local copy of the remote file the patch applies to")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("source_path")
+                        .help("Local file path of the new version")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("target_path")
+                        .help("Destination path on remote target")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("base")
+                        .help("Remote path of the file to patch, if not the destination")
+                        .long("base")
+                        .takes_value(true),
                 ),
         )
         .subcommand(
@@ -159,6 +320,81 @@ fn main() {
                         .takes_value(true),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("status")
+                .about("Requests the status of transfers in remote temporary storage")
+                .arg(
+                    Arg::with_name("hash")
+                        .help("Specific file to report on. Lists all transfers if omitted")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("Lists the contents of a remote directory")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Remote directory to list")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stat")
+                .about("Shows details of a remote file or directory")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Remote path to examine")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("delete")
+                .about("Deletes a remote file or empty directory")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Remote path to delete")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("mkdir")
+                .about("Creates a remote directory")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Remote directory to create")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("move")
+                .about("Moves or renames a remote file or directory")
+                .arg(
+                    Arg::with_name("from")
+                        .help("Remote path to move")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .help("New remote path")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("df")
+                .about("Shows the space on the remote filesystem holding a path")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Remote path on the filesystem to examine")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
         .arg(
             Arg::with_name("host_ip")
                 .help("IP address of the local host to use")
@@ -237,6 +473,20 @@ fn main() {
                 .short("-m")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("compression")
+                .help("Compression to use for file chunks in transit")
+                .long("compression")
+                .takes_value(true)
+                .possible_values(&["none", "deflate"])
+                .default_value("none"),
+        )
+        .arg(
+            Arg::with_name("max_bandwidth")
+                .help("Maximum average rate, in bytes per second, at which to transmit chunks")
+                .long("max-bandwidth")
+                .takes_value(true),
+        )
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .setting(AppSettings::DeriveDisplayOrder)
         .get_matches();
@@ -267,6 +517,10 @@ fn main() {
     } else {
         None
     };
+    let compression: Compression = args.value_of("compression").unwrap().parse().unwrap();
+    let max_bandwidth: Option<u32> = args
+        .value_of("max_bandwidth")
+        .map(|rate| rate.parse().unwrap());
 
     let protocol_config = FileProtocolConfig::new(
         Some(storage_prefix),
@@ -275,7 +529,9 @@ fn main() {
         inter_chunk_delay,
         max_chunks_transmit,
         hash_chunk_size,
-    );
+    )
+    .with_compression(compression)
+    .with_max_bandwidth(max_bandwidth);
     let protocol_instance = FileProtocol::new(
         &format!("{}:{}", host_ip, host_port),
         &remote_addr,
@@ -309,7 +565,24 @@ fn main() {
                     .into_owned(),
             };
 
-            download(protocol_instance, source_path, &target_path)
+            download(
+                protocol_instance,
+                source_path,
+                &target_path,
+                download_args.value_of("resume"),
+            )
+        }
+        Some("patch") => {
+            let patch_args = args.subcommand_matches("patch").unwrap();
+            let target_path = patch_args.value_of("target_path").unwrap();
+
+            patch(
+                protocol_instance,
+                patch_args.value_of("base_path").unwrap(),
+                patch_args.value_of("source_path").unwrap(),
+                target_path,
+                patch_args.value_of("base").unwrap_or(target_path),
+            )
         }
         Some("cleanup") => {
             let hash = args
@@ -320,6 +593,39 @@ fn main() {
                 .map(|v| v.to_owned());
             cleanup(protocol_instance, hash)
         }
+        Some("status") => {
+            let hash = args
+                .subcommand_matches("status")
+                .unwrap()
+                .value_of("hash")
+                .map(|v| v.to_owned());
+            status(protocol_instance, hash)
+        }
+        Some("list") | Some("stat") | Some("delete") | Some("mkdir") | Some("df") => {
+            let name = args.subcommand_name().unwrap();
+            let path = args
+                .subcommand_matches(name)
+                .unwrap()
+                .value_of("path")
+                .unwrap()
+                .to_owned();
+            let op = match name {
+                "list" => FileOp::List { path },
+                "stat" => FileOp::Stat { path },
+                "delete" => FileOp::Delete { path },
+                "mkdir" => FileOp::Mkdir { path },
+                _ => FileOp::DiskUsage { path },
+            };
+            file_op(protocol_instance, op)
+        }
+        Some("move") => {
+            let move_args = args.subcommand_matches("move").unwrap();
+            let op = FileOp::Move {
+                from: move_args.value_of("from").unwrap().to_owned(),
+                to: move_args.value_of("to").unwrap().to_owned(),
+            };
+            file_op(protocol_instance, op)
+        }
         _ => panic!("Invalid command"),
     };
 
@@ -0,0 +1,111 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Ground side decoder for binary telemetry exports
+//
+// Reads an export written by the telemetry service's `routedTelemetry` query with
+// `format: BINARY`, unpacking it first if it was compressed, and writes its entries to stdout
+// as JSON or CSV.
+
+use clap::{App, Arg};
+use failure::{bail, format_err, Error};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use telemetry_export::{decode, Point};
+
+// Return type for this client.
+type ClientResult<T> = Result<T, Error>;
+
+fn read_export(path: &str) -> ClientResult<Vec<u8>> {
+    let mut data = vec![];
+    let mut file =
+        File::open(path).map_err(|err| format_err!("Failed to open {}: {}", path, err))?;
+
+    // Compressed exports are tarballs holding the single export file
+    if path.ends_with(".tar.gz") {
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let mut entry = match archive.entries()?.next() {
+            Some(entry) => entry?,
+            None => bail!("{} is an empty archive", path),
+        };
+        entry.read_to_end(&mut data)?;
+    } else {
+        file.read_to_end(&mut data)?;
+    }
+
+    Ok(data)
+}
+
+// Quotes a CSV field if it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn print_csv(points: &[Point]) {
+    println!("timestamp,subsystem,parameter,value,type");
+    for point in points {
+        let value_type = if point.int_value.is_some() {
+            "integer"
+        } else if point.float_value.is_some() {
+            "float"
+        } else if point.bool_value.is_some() {
+            "bool"
+        } else {
+            "string"
+        };
+        println!(
+            "{},{},{},{},{}",
+            point.timestamp,
+            csv_field(&point.subsystem),
+            csv_field(&point.parameter),
+            csv_field(&point.value),
+            value_type
+        );
+    }
+}
+
+fn main() -> ClientResult<()> {
+    let args = App::new("Telemetry Export Client")
+        .arg(
+            Arg::with_name("file")
+                .help("Binary telemetry export to decode")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["json", "csv"])
+                .default_value("json"),
+        )
+        .get_matches();
+
+    let path = args.value_of("file").unwrap();
+    let points = decode(&read_export(path)?)
+        .map_err(|err| format_err!("Failed to decode {}: {}", path, err))?;
+
+    match args.value_of("format") {
+        Some("csv") => print_csv(&points),
+        _ => println!("{}", serde_json::to_string_pretty(&points)?),
+    }
+
+    Ok(())
+}
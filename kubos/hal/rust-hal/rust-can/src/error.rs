@@ -25,6 +25,26 @@ pub enum CanError {
         /// Error description.
         description: String,
     },
+    /// A multi-frame transfer received a frame out of sequence.
+    #[error("Out-of-sequence frame: expected {expected}, received {received}")]
+    Sequence {
+        /// Expected sequence number.
+        expected: u8,
+        /// Received sequence number.
+        received: u8,
+    },
+    /// A multi-frame transfer did not follow the transport protocol.
+    #[error("Transport protocol error: {description}")]
+    Protocol {
+        /// Error description.
+        description: String,
+    },
+    /// A payload was too long for the transport or the receiving node.
+    #[error("Payload of {length} bytes is too long")]
+    Overflow {
+        /// Payload length in bytes.
+        length: usize,
+    },
     /// Interface setup command failed.
     #[error("Interface command failed: {description}")]
     InterfaceError {
@@ -20,17 +20,24 @@
 
 mod error;
 pub mod mock;
+pub mod monitor;
 #[cfg(test)]
 mod tests;
+pub mod transport;
 
 pub use crate::error::*;
+pub use crate::monitor::{BusMonitor, BusState, BusStats, MonitorOptions};
+pub use crate::transport::{Framing, IsoTpOptions, Transport};
 
+use crate::monitor::ErrorFrame;
 use socketcan::{
-    CanFrame as SocketCanFrame, CanSocket, EmbeddedFrame, ExtendedId, Id, Socket, StandardId,
+    CanFrame as SocketCanFrame, CanSocket, EmbeddedFrame, ExtendedId, Id, Socket, SocketOptions,
+    StandardId,
 };
 use std::cell::RefCell;
 use std::convert::TryFrom;
 use std::process::Command;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// CAN frame data used by the HAL.
@@ -164,6 +171,11 @@ pub trait Stream: Send {
         timeout: Duration,
         filter: Option<FrameFilter>,
     ) -> CanResult<Vec<u8>>;
+
+    /// Bus error monitor, if the stream reports bus errors.
+    fn monitor(&self) -> Option<Arc<BusMonitor>> {
+        None
+    }
 }
 
 /// Wrapper for CAN streams.
@@ -185,10 +197,31 @@ impl Connection {
     /// `interface` - CAN network interface name, for example `can0`
     pub fn from_interface(interface: &str) -> CanResult<Connection> {
         Ok(Connection {
-            stream: Box::new(SocketCanStream::new(interface)?),
+            stream: Box::new(SocketCanStream::new(interface, None)?),
         })
     }
 
+    /// Creates a SocketCAN connection which receives error frames, keeps bus
+    /// error statistics and optionally restarts the interface after bus-off.
+    ///
+    /// # Arguments
+    ///
+    /// `interface` - CAN network interface name, for example `can0`
+    /// `options` - Bus-off recovery settings
+    pub fn from_interface_monitored(
+        interface: &str,
+        options: MonitorOptions,
+    ) -> CanResult<Connection> {
+        Ok(Connection {
+            stream: Box::new(SocketCanStream::new(interface, Some(options))?),
+        })
+    }
+
+    /// Returns current bus error statistics, if the stream is monitored.
+    pub fn bus_stats(&self) -> Option<BusStats> {
+        self.stream.monitor().map(|monitor| monitor.stats())
+    }
+
     /// Writes one CAN frame to the stream.
     pub fn write(&self, frame: CanFrame) -> CanResult<()> {
         self.stream.write(frame)
@@ -263,14 +296,30 @@ fn run_ip_link(args: &[&str]) -> CanResult<()> {
 
 struct SocketCanStream {
     socket: RefCell<CanSocket>,
+    interface: String,
+    monitor: Option<Arc<BusMonitor>>,
 }
 
 impl SocketCanStream {
-    fn new(interface: &str) -> CanResult<Self> {
+    fn new(interface: &str, monitor: Option<MonitorOptions>) -> CanResult<Self> {
+        let socket = CanSocket::open(interface)?;
+        if monitor.is_some() {
+            socket.set_error_filter_accept_all()?;
+        }
+
         Ok(Self {
-            socket: RefCell::new(CanSocket::open(interface)?),
+            socket: RefCell::new(socket),
+            interface: interface.to_owned(),
+            monitor: monitor.map(|options| Arc::new(BusMonitor::new(options))),
         })
     }
+
+    // Restarts the interface if it is bus-off and the recovery backoff has elapsed
+    fn recover(&self, monitor: &BusMonitor) {
+        if let Some(bitrate) = monitor.recovery_due() {
+            monitor.record_recovery(Connection::reset_interface(&self.interface, bitrate).is_ok());
+        }
+    }
 }
 
 impl Stream for SocketCanStream {
@@ -281,8 +330,32 @@ impl Stream for SocketCanStream {
     }
 
     fn read(&self, timeout: Duration) -> CanResult<CanFrame> {
-        let socket = self.socket.try_borrow().map_err(|_| CanError::PortBusy)?;
-        Ok(socket.read_frame_timeout(timeout)?.into())
+        let start = Instant::now();
+
+        loop {
+            let remaining = timeout
+                .checked_sub(Instant::now() - start)
+                .ok_or(CanError::Timeout)?;
+            if let Some(monitor) = &self.monitor {
+                self.recover(monitor);
+            }
+
+            let frame = {
+                let socket = self.socket.try_borrow().map_err(|_| CanError::PortBusy)?;
+                socket.read_frame_timeout(remaining)?
+            };
+
+            match (&self.monitor, &frame) {
+                (Some(monitor), SocketCanFrame::Error(error)) => {
+                    monitor.record(&ErrorFrame::classify(error.error_bits(), error.data()));
+                    continue;
+                }
+                (Some(monitor), _) => monitor.record_traffic(),
+                (None, _) => {}
+            }
+
+            return Ok(frame.into());
+        }
     }
 
     fn read_frames(&self, count: usize, timeout: Duration) -> CanResult<Vec<CanFrame>> {
@@ -326,4 +399,8 @@ impl Stream for SocketCanStream {
         payload.truncate(expected_len);
         Ok(payload)
     }
+
+    fn monitor(&self) -> Option<Arc<BusMonitor>> {
+        self.monitor.clone()
+    }
 }
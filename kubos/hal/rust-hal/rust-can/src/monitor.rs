@@ -0,0 +1,336 @@
+//! CAN bus error monitoring and bus-off recovery.
+//!
+//! When enabled with [`MonitorOptions`], the SocketCAN stream asks the kernel
+//! for error frames, classifies them as described in `linux/can/error.h` and
+//! keeps running counters in a shared [`BusMonitor`]. Error frames are never
+//! returned to readers.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Error class bits in the error frame ID
+const ERR_TX_TIMEOUT: u32 = 0x0001;
+const ERR_LOST_ARBITRATION: u32 = 0x0002;
+const ERR_CONTROLLER: u32 = 0x0004;
+const ERR_PROTOCOL: u32 = 0x0008;
+const ERR_TRANSCEIVER: u32 = 0x0010;
+const ERR_NO_ACK: u32 = 0x0020;
+const ERR_BUS_OFF: u32 = 0x0040;
+const ERR_BUS_ERROR: u32 = 0x0080;
+const ERR_RESTARTED: u32 = 0x0100;
+const ERR_COUNTERS: u32 = 0x0200;
+
+// Controller status bits in data[1]
+const CTRL_RX_OVERFLOW: u8 = 0x01;
+const CTRL_TX_OVERFLOW: u8 = 0x02;
+const CTRL_RX_WARNING: u8 = 0x04;
+const CTRL_TX_WARNING: u8 = 0x08;
+const CTRL_RX_PASSIVE: u8 = 0x10;
+const CTRL_TX_PASSIVE: u8 = 0x20;
+const CTRL_ACTIVE: u8 = 0x40;
+
+/// Error reporting and recovery settings for a SocketCAN stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MonitorOptions {
+    /// Bitrate used when the interface is restarted, or `None` to never
+    /// restart it automatically.
+    pub recovery_bitrate: Option<u32>,
+    /// Wait before the first restart attempt after a bus-off.
+    pub initial_backoff: Duration,
+    /// Longest wait between restart attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for MonitorOptions {
+    fn default() -> Self {
+        MonitorOptions {
+            recovery_bitrate: None,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Fault confinement state of the CAN controller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BusState {
+    /// Normal operation.
+    ErrorActive,
+    /// An error counter passed the warning level.
+    ErrorWarning,
+    /// An error counter passed the error-passive level.
+    ErrorPassive,
+    /// The controller has disconnected from the bus.
+    BusOff,
+}
+
+/// One classified error frame.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ErrorFrame {
+    /// Transmit timed out.
+    pub tx_timeout: bool,
+    /// Arbitration was lost.
+    pub lost_arbitration: bool,
+    /// Receive or transmit buffer overflowed in the controller.
+    pub overflow: bool,
+    /// Controller passed the warning level.
+    pub warning: bool,
+    /// Controller became error-passive.
+    pub passive: bool,
+    /// Controller returned to error-active.
+    pub active: bool,
+    /// Protocol violation, e.g. bit stuffing or form error.
+    pub protocol: bool,
+    /// Transceiver fault.
+    pub transceiver: bool,
+    /// Transmitted frame was not acknowledged by any node.
+    pub no_ack: bool,
+    /// Controller went bus-off.
+    pub bus_off: bool,
+    /// Bus error reported by the controller.
+    pub bus_error: bool,
+    /// Controller restarted after bus-off.
+    pub restarted: bool,
+    /// Transmit error counter, if reported.
+    pub tx_errors: Option<u8>,
+    /// Receive error counter, if reported.
+    pub rx_errors: Option<u8>,
+}
+
+impl ErrorFrame {
+    /// Classifies an error frame from its ID and payload.
+    pub fn classify(id: u32, data: &[u8]) -> Self {
+        let byte = |index: usize| data.get(index).copied().unwrap_or(0);
+        let status = if id & ERR_CONTROLLER != 0 { byte(1) } else { 0 };
+        let counters = id & ERR_COUNTERS != 0 && data.len() >= 8;
+
+        ErrorFrame {
+            tx_timeout: id & ERR_TX_TIMEOUT != 0,
+            lost_arbitration: id & ERR_LOST_ARBITRATION != 0,
+            overflow: status & (CTRL_RX_OVERFLOW | CTRL_TX_OVERFLOW) != 0,
+            warning: status & (CTRL_RX_WARNING | CTRL_TX_WARNING) != 0,
+            passive: status & (CTRL_RX_PASSIVE | CTRL_TX_PASSIVE) != 0,
+            active: status & CTRL_ACTIVE != 0,
+            protocol: id & ERR_PROTOCOL != 0,
+            transceiver: id & ERR_TRANSCEIVER != 0,
+            no_ack: id & ERR_NO_ACK != 0,
+            bus_off: id & ERR_BUS_OFF != 0,
+            bus_error: id & ERR_BUS_ERROR != 0,
+            restarted: id & ERR_RESTARTED != 0,
+            tx_errors: if counters { Some(byte(6)) } else { None },
+            rx_errors: if counters { Some(byte(7)) } else { None },
+        }
+    }
+
+    fn describe(&self) -> String {
+        let names = [
+            (self.bus_off, "bus-off"),
+            (self.passive, "error-passive"),
+            (self.warning, "error-warning"),
+            (self.no_ack, "no ACK"),
+            (self.protocol, "protocol violation"),
+            (self.transceiver, "transceiver fault"),
+            (self.overflow, "controller overflow"),
+            (self.lost_arbitration, "lost arbitration"),
+            (self.tx_timeout, "transmit timeout"),
+            (self.bus_error, "bus error"),
+            (self.restarted, "restarted"),
+        ];
+        let found: Vec<&str> = names
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| *name)
+            .collect();
+
+        if found.is_empty() {
+            "unclassified error".to_owned()
+        } else {
+            found.join(", ")
+        }
+    }
+}
+
+/// Running error statistics for one CAN interface.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BusStats {
+    /// Current controller state.
+    pub state: BusState,
+    /// Error frames received.
+    pub error_frames: u64,
+    /// Bus-off events.
+    pub bus_off: u64,
+    /// Transitions to error-passive.
+    pub error_passive: u64,
+    /// Transitions to error-warning.
+    pub error_warning: u64,
+    /// Frames not acknowledged by any node.
+    pub no_ack: u64,
+    /// Protocol violations.
+    pub protocol: u64,
+    /// Controller buffer overflows.
+    pub overflow: u64,
+    /// Lost arbitration events.
+    pub lost_arbitration: u64,
+    /// Transmit timeouts.
+    pub tx_timeout: u64,
+    /// Interface restarts attempted after bus-off.
+    pub recovery_attempts: u64,
+    /// Interface restarts which failed.
+    pub recovery_failures: u64,
+    /// Most recent transmit error counter.
+    pub tx_errors: Option<u8>,
+    /// Most recent receive error counter.
+    pub rx_errors: Option<u8>,
+    /// Description of the most recent error frame.
+    pub last_error: Option<String>,
+}
+
+impl Default for BusStats {
+    fn default() -> Self {
+        BusStats {
+            state: BusState::ErrorActive,
+            error_frames: 0,
+            bus_off: 0,
+            error_passive: 0,
+            error_warning: 0,
+            no_ack: 0,
+            protocol: 0,
+            overflow: 0,
+            lost_arbitration: 0,
+            tx_timeout: 0,
+            recovery_attempts: 0,
+            recovery_failures: 0,
+            tx_errors: None,
+            rx_errors: None,
+            last_error: None,
+        }
+    }
+}
+
+struct Recovery {
+    backoff: Duration,
+    next_attempt: Option<Instant>,
+}
+
+/// Shared error statistics and bus-off recovery schedule.
+pub struct BusMonitor {
+    options: MonitorOptions,
+    stats: Mutex<BusStats>,
+    recovery: Mutex<Recovery>,
+}
+
+impl BusMonitor {
+    /// Creates a monitor.
+    pub fn new(options: MonitorOptions) -> Self {
+        BusMonitor {
+            recovery: Mutex::new(Recovery {
+                backoff: options.initial_backoff,
+                next_attempt: None,
+            }),
+            options,
+            stats: Mutex::new(BusStats::default()),
+        }
+    }
+
+    /// Returns a copy of the current statistics.
+    pub fn stats(&self) -> BusStats {
+        self.stats
+            .lock()
+            .map(|stats| stats.clone())
+            .unwrap_or_default()
+    }
+
+    /// Records one error frame.
+    pub fn record(&self, frame: &ErrorFrame) {
+        let mut stats = match self.stats.lock() {
+            Ok(stats) => stats,
+            Err(_) => return,
+        };
+
+        stats.error_frames += 1;
+        stats.last_error = Some(frame.describe());
+        let count = |counter: &mut u64, set: bool| {
+            if set {
+                *counter += 1;
+            }
+        };
+        count(&mut stats.no_ack, frame.no_ack);
+        count(&mut stats.protocol, frame.protocol);
+        count(&mut stats.overflow, frame.overflow);
+        count(&mut stats.lost_arbitration, frame.lost_arbitration);
+        count(&mut stats.tx_timeout, frame.tx_timeout);
+        if frame.tx_errors.is_some() {
+            stats.tx_errors = frame.tx_errors;
+            stats.rx_errors = frame.rx_errors;
+        }
+
+        if frame.bus_off {
+            if stats.state != BusState::BusOff {
+                stats.bus_off += 1;
+                self.schedule_recovery();
+            }
+            stats.state = BusState::BusOff;
+        } else if frame.restarted || frame.active {
+            stats.state = BusState::ErrorActive;
+        } else if frame.passive {
+            if stats.state != BusState::ErrorPassive {
+                stats.error_passive += 1;
+            }
+            stats.state = BusState::ErrorPassive;
+        } else if frame.warning {
+            if stats.state != BusState::ErrorWarning {
+                stats.error_warning += 1;
+            }
+            stats.state = BusState::ErrorWarning;
+        }
+    }
+
+    /// Records a successfully received data frame, which means the bus is usable.
+    pub fn record_traffic(&self) {
+        if let Ok(mut stats) = self.stats.lock() {
+            if stats.state == BusState::BusOff {
+                stats.state = BusState::ErrorActive;
+            }
+        }
+        if let Ok(mut recovery) = self.recovery.lock() {
+            recovery.backoff = self.options.initial_backoff;
+            recovery.next_attempt = None;
+        }
+    }
+
+    /// Returns the bitrate to restart the interface with if the bus is off and
+    /// the backoff has elapsed, and schedules the following attempt.
+    pub fn recovery_due(&self) -> Option<u32> {
+        let bitrate = self.options.recovery_bitrate?;
+        if self.stats().state != BusState::BusOff {
+            return None;
+        }
+
+        let mut recovery = self.recovery.lock().ok()?;
+        let now = Instant::now();
+        if recovery.next_attempt.is_none_or(|next| now < next) {
+            return None;
+        }
+        recovery.backoff = (recovery.backoff * 2).min(self.options.max_backoff);
+        recovery.next_attempt = Some(now + recovery.backoff);
+        Some(bitrate)
+    }
+
+    /// Records the outcome of a restart attempt.
+    pub fn record_recovery(&self, succeeded: bool) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.recovery_attempts += 1;
+            if !succeeded {
+                stats.recovery_failures += 1;
+            }
+        }
+    }
+
+    fn schedule_recovery(&self) {
+        if let Ok(mut recovery) = self.recovery.lock() {
+            if recovery.next_attempt.is_none() {
+                recovery.next_attempt = Some(Instant::now() + recovery.backoff);
+            }
+        }
+    }
+}
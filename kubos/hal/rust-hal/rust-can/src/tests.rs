@@ -116,3 +116,250 @@ fn test_read_payload_trims_excess() {
         (0_u8..10).collect::<Vec<_>>()
     );
 }
+
+fn transport(connection: &Connection, framing: Framing) -> Transport<'_> {
+    Transport::new(
+        connection,
+        FrameFilter::standard(0x7E0),
+        FrameFilter::standard(0x7E8),
+        framing,
+        Duration::from_millis(10),
+    )
+}
+
+#[test]
+fn test_iso_tp_send_single_frame() {
+    let mut mock = MockStream::default();
+
+    mock.write.set_input(CanFrame::standard(
+        0x7E0,
+        &[3, 1, 2, 3, 0xAA, 0xAA, 0xAA, 0xAA],
+    ));
+    let connection = Connection::new(Box::new(mock));
+    let options = IsoTpOptions {
+        padding: Some(0xAA),
+        ..IsoTpOptions::default()
+    };
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(options)).send(&[1, 2, 3]),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_iso_tp_send_waits_for_flow_control() {
+    let payload: Vec<u8> = (0..20).collect();
+    let mut mock = MockStream::default();
+
+    mock.write
+        .set_input(CanFrame::standard(0x7E0, &[0x10, 20, 0, 1, 2, 3, 4, 5]));
+    mock.write
+        .set_input(CanFrame::standard(0x7E0, &[0x21, 6, 7, 8, 9, 10, 11, 12]));
+    mock.write.set_input(CanFrame::standard(
+        0x7E0,
+        &[0x22, 13, 14, 15, 16, 17, 18, 19],
+    ));
+    mock.read.set_output(vec![
+        CanFrame::standard(0x7E8, &[0x31, 0, 0]),
+        CanFrame::standard(0x7E8, &[0x30, 1, 0]),
+        CanFrame::standard(0x7E8, &[0x30, 1, 0]),
+    ]);
+    let connection = Connection::new(Box::new(mock));
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(IsoTpOptions::default())).send(&payload),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_iso_tp_send_overflow() {
+    let mut mock = MockStream::default();
+
+    mock.write.set_result(Ok(()));
+    mock.read
+        .set_output(vec![CanFrame::standard(0x7E8, &[0x32, 0, 0])]);
+    let connection = Connection::new(Box::new(mock));
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(IsoTpOptions::default())).send(&[0; 20]),
+        Err(CanError::Overflow { length: 20 })
+    );
+}
+
+#[test]
+fn test_iso_tp_receive_multi_frame() {
+    let mut mock = MockStream::default();
+
+    mock.write
+        .set_input(CanFrame::standard(0x7E0, &[0x30, 0, 0]));
+    mock.read.set_output(vec![
+        CanFrame::standard(0x7E8, &[0x10, 10, 0, 1, 2, 3, 4, 5]),
+        CanFrame::standard(0x123, &[9]),
+        CanFrame::standard(0x7E8, &[0x21, 6, 7, 8, 9, 0, 0, 0]),
+    ]);
+    let connection = Connection::new(Box::new(mock));
+    let mut unmatched = Vec::new();
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(IsoTpOptions::default()))
+            .receive_with(Some(10), |frame| unmatched.push(frame)),
+        Ok((0_u8..10).collect::<Vec<_>>())
+    );
+    assert_eq!(unmatched, vec![CanFrame::standard(0x123, &[9])]);
+}
+
+#[test]
+fn test_iso_tp_receive_bad_sequence() {
+    let mut mock = MockStream::default();
+
+    mock.write.set_result(Ok(()));
+    mock.read.set_output(vec![
+        CanFrame::standard(0x7E8, &[0x10, 20, 0, 1, 2, 3, 4, 5]),
+        CanFrame::standard(0x7E8, &[0x22, 6, 7, 8, 9, 10, 11, 12]),
+    ]);
+    let connection = Connection::new(Box::new(mock));
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(IsoTpOptions::default())).receive(None),
+        Err(CanError::Sequence {
+            expected: 1,
+            received: 2
+        })
+    );
+}
+
+#[test]
+fn test_iso_tp_receive_rejects_short_first_frame_length() {
+    let mut mock = MockStream::default();
+
+    mock.read.set_output(vec![CanFrame::standard(
+        0x7E8,
+        &[0x10, 4, 0, 1, 2, 3, 4, 5],
+    )]);
+    let connection = Connection::new(Box::new(mock));
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(IsoTpOptions::default())).receive(None),
+        Err(CanError::Protocol {
+            description: "first frame announces 4 bytes, which fit in a single frame".to_owned()
+        })
+    );
+}
+
+#[test]
+fn test_iso_tp_receive_refuses_long_payload() {
+    let mut mock = MockStream::default();
+
+    mock.write
+        .set_input(CanFrame::standard(0x7E0, &[0x32, 0, 0]));
+    mock.read.set_output(vec![CanFrame::standard(
+        0x7E8,
+        &[0x10, 100, 0, 1, 2, 3, 4, 5],
+    )]);
+    let connection = Connection::new(Box::new(mock));
+    let options = IsoTpOptions {
+        max_payload: 64,
+        ..IsoTpOptions::default()
+    };
+
+    assert_eq!(
+        transport(&connection, Framing::IsoTp(options)).receive(None),
+        Err(CanError::Overflow { length: 100 })
+    );
+}
+
+#[test]
+fn test_extended_round_trip() {
+    let mut mock = MockStream::default();
+
+    mock.write
+        .set_input(CanFrame::standard(0x7E0, &[0, 1, 2, 3, 4, 5, 6, 7]));
+    mock.write.set_input(CanFrame::standard(0x7E0, &[8, 9]));
+    mock.read.set_output(vec![
+        CanFrame::standard(0x7E8, &[0, 1, 2, 3, 4, 5, 6, 7]),
+        CanFrame::standard(0x7E8, &[8, 9]),
+    ]);
+    let connection = Connection::new(Box::new(mock));
+    let transport = transport(&connection, Framing::Extended);
+
+    assert_eq!(transport.send(&(0_u8..10).collect::<Vec<_>>()), Ok(()));
+    assert_eq!(
+        transport.receive(Some(10)),
+        Ok((0_u8..10).collect::<Vec<_>>())
+    );
+}
+
+#[test]
+fn test_extended_rejects_short_frame() {
+    let mut mock = MockStream::default();
+
+    mock.read.set_output(vec![
+        CanFrame::standard(0x7E8, &[0, 1, 2]),
+        CanFrame::standard(0x7E8, &[3, 4, 5, 6, 7, 8, 9, 10]),
+    ]);
+    let connection = Connection::new(Box::new(mock));
+
+    assert!(matches!(
+        transport(&connection, Framing::Extended).receive(Some(10)),
+        Err(CanError::Protocol { .. })
+    ));
+}
+
+#[test]
+fn test_classify_error_frame() {
+    let frame = monitor::ErrorFrame::classify(0x0224, &[0, 0x20, 0, 0, 0, 0, 130, 5]);
+
+    assert!(frame.passive);
+    assert!(frame.no_ack);
+    assert!(!frame.bus_off);
+    assert_eq!(frame.tx_errors, Some(130));
+    assert_eq!(frame.rx_errors, Some(5));
+}
+
+#[test]
+fn test_monitor_tracks_bus_state() {
+    let monitor = BusMonitor::new(MonitorOptions::default());
+
+    monitor.record(&monitor::ErrorFrame::classify(0x0004, &[0, 0x20]));
+    monitor.record(&monitor::ErrorFrame::classify(0x0004, &[0, 0x20]));
+    monitor.record(&monitor::ErrorFrame::classify(0x0040, &[]));
+
+    let stats = monitor.stats();
+    assert_eq!(stats.state, BusState::BusOff);
+    assert_eq!(stats.error_frames, 3);
+    assert_eq!(stats.error_passive, 1);
+    assert_eq!(stats.bus_off, 1);
+    assert_eq!(stats.last_error.as_deref(), Some("bus-off"));
+
+    monitor.record_traffic();
+    assert_eq!(monitor.stats().state, BusState::ErrorActive);
+}
+
+#[test]
+fn test_monitor_backs_off_recovery() {
+    let monitor = BusMonitor::new(MonitorOptions {
+        recovery_bitrate: Some(1_000_000),
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_secs(60),
+    });
+
+    assert_eq!(monitor.recovery_due(), None);
+    monitor.record(&monitor::ErrorFrame::classify(0x0040, &[]));
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(monitor.recovery_due(), Some(1_000_000));
+    // The following attempt waits for the doubled backoff
+    assert_eq!(monitor.recovery_due(), None);
+
+    monitor.record_recovery(false);
+    assert_eq!(monitor.stats().recovery_attempts, 1);
+    assert_eq!(monitor.stats().recovery_failures, 1);
+}
+
+#[test]
+fn test_unmonitored_connection_has_no_stats() {
+    let connection = Connection::new(Box::new(MockStream::default()));
+
+    assert_eq!(connection.bus_stats(), None);
+}
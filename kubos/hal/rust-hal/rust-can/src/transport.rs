@@ -0,0 +1,427 @@
+//! Multi-frame transport for payloads longer than one classic CAN frame.
+//!
+//! Two framings are supported:
+//!
+//! - [`Framing::IsoTp`]: ISO 15765-2 segmentation with single, first,
+//!   consecutive and flow control frames (normal addressing, 12-bit lengths).
+//! - [`Framing::Extended`]: back-to-back full frames sharing one CAN ID with no
+//!   protocol header, as used by CubeSpace extended telemetry responses. The
+//!   receiver must know the payload length in advance.
+
+use super::*;
+use std::thread;
+
+/// Bytes in a classic CAN frame.
+pub const CAN_FRAME_BYTES: usize = 8;
+
+/// Longest payload which fits the 12-bit ISO-TP first frame length.
+pub const ISO_TP_MAX_PAYLOAD: usize = 4095;
+
+const PCI_SINGLE: u8 = 0x0;
+const PCI_FIRST: u8 = 0x1;
+const PCI_CONSECUTIVE: u8 = 0x2;
+const PCI_FLOW_CONTROL: u8 = 0x3;
+
+const FLOW_CONTINUE: u8 = 0x0;
+const FLOW_WAIT: u8 = 0x1;
+const FLOW_OVERFLOW: u8 = 0x2;
+
+/// ISO-TP flow control settings.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IsoTpOptions {
+    /// Consecutive frames the peer may send before waiting for the next flow
+    /// control frame. 0 sends the whole payload in one block.
+    pub block_size: u8,
+    /// Minimum gap the peer must leave between consecutive frames.
+    pub separation_time: Duration,
+    /// Number of flow control WAIT frames accepted before giving up.
+    pub max_wait_frames: u32,
+    /// Longest payload accepted; longer ones are refused with an overflow flow
+    /// control frame.
+    pub max_payload: usize,
+    /// Byte used to pad frames to 8 bytes, or `None` to send short frames.
+    pub padding: Option<u8>,
+}
+
+impl Default for IsoTpOptions {
+    fn default() -> Self {
+        IsoTpOptions {
+            block_size: 0,
+            separation_time: Duration::from_millis(0),
+            max_wait_frames: 10,
+            max_payload: ISO_TP_MAX_PAYLOAD,
+            padding: None,
+        }
+    }
+}
+
+/// How payloads are split into CAN frames.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Framing {
+    /// ISO 15765-2 segmentation with flow control.
+    IsoTp(IsoTpOptions),
+    /// Full frames with a shared ID and no header; the last frame may be short.
+    Extended,
+}
+
+/// Sends and receives payloads of any length between two CAN IDs.
+pub struct Transport<'a> {
+    connection: &'a Connection,
+    tx: FrameFilter,
+    rx: FrameFilter,
+    framing: Framing,
+    timeout: Duration,
+}
+
+impl<'a> Transport<'a> {
+    /// Creates a transport.
+    ///
+    /// # Arguments
+    ///
+    /// `connection` - CAN connection to use
+    /// `tx` - ID of frames sent by this node
+    /// `rx` - ID of frames sent by the peer
+    /// `framing` - Segmentation scheme
+    /// `timeout` - Maximum wait for each expected frame
+    pub fn new(
+        connection: &'a Connection,
+        tx: FrameFilter,
+        rx: FrameFilter,
+        framing: Framing,
+        timeout: Duration,
+    ) -> Self {
+        Transport {
+            connection,
+            tx,
+            rx,
+            framing,
+            timeout,
+        }
+    }
+
+    /// Sends a payload, segmenting it as needed.
+    pub fn send(&self, payload: &[u8]) -> CanResult<()> {
+        self.send_with(payload, |_| {})
+    }
+
+    /// Sends a payload, passing any frames received from other IDs while
+    /// waiting for flow control to `unmatched`.
+    pub fn send_with<F: FnMut(CanFrame)>(&self, payload: &[u8], unmatched: F) -> CanResult<()> {
+        match &self.framing {
+            Framing::Extended => {
+                if payload.is_empty() {
+                    return self.write(&[]);
+                }
+                for chunk in payload.chunks(CAN_FRAME_BYTES) {
+                    self.write(chunk)?;
+                }
+                Ok(())
+            }
+            Framing::IsoTp(options) => self.send_iso_tp(payload, options, unmatched),
+        }
+    }
+
+    /// Receives one payload.
+    ///
+    /// `expected_len` is required for [`Framing::Extended`]. For ISO-TP it is
+    /// optional and, when given, checked against the length in the first frame.
+    pub fn receive(&self, expected_len: Option<usize>) -> CanResult<Vec<u8>> {
+        self.receive_with(expected_len, |_| {})
+    }
+
+    /// Receives one payload, passing frames from other IDs to `unmatched`.
+    pub fn receive_with<F: FnMut(CanFrame)>(
+        &self,
+        expected_len: Option<usize>,
+        mut unmatched: F,
+    ) -> CanResult<Vec<u8>> {
+        match &self.framing {
+            Framing::Extended => {
+                let expected_len = expected_len.ok_or_else(|| CanError::Protocol {
+                    description: "extended framing needs the expected payload length".to_owned(),
+                })?;
+                self.receive_extended(expected_len, &mut unmatched)
+            }
+            Framing::IsoTp(options) => {
+                let payload = self.receive_iso_tp(options, &mut unmatched)?;
+                match expected_len {
+                    Some(len) if len != payload.len() => Err(CanError::Protocol {
+                        description: format!(
+                            "expected {} payload bytes, peer sent {}",
+                            len,
+                            payload.len()
+                        ),
+                    }),
+                    _ => Ok(payload),
+                }
+            }
+        }
+    }
+
+    fn receive_extended(
+        &self,
+        expected_len: usize,
+        unmatched: &mut dyn FnMut(CanFrame),
+    ) -> CanResult<Vec<u8>> {
+        let mut payload = Vec::with_capacity(expected_len);
+
+        while payload.len() < expected_len {
+            let frame = self.next_frame(unmatched)?;
+            let remaining = expected_len - payload.len();
+            if frame.data.len() < CAN_FRAME_BYTES && frame.data.len() < remaining {
+                return Err(CanError::Protocol {
+                    description: format!(
+                        "short frame of {} bytes with {} bytes still expected",
+                        frame.data.len(),
+                        remaining
+                    ),
+                });
+            }
+            payload.extend_from_slice(&frame.data);
+        }
+
+        payload.truncate(expected_len);
+        Ok(payload)
+    }
+
+    fn send_iso_tp(
+        &self,
+        payload: &[u8],
+        options: &IsoTpOptions,
+        mut unmatched: impl FnMut(CanFrame),
+    ) -> CanResult<()> {
+        if payload.len() > ISO_TP_MAX_PAYLOAD {
+            return Err(CanError::Overflow {
+                length: payload.len(),
+            });
+        }
+
+        if payload.len() < CAN_FRAME_BYTES {
+            // The single frame PCI type is 0, leaving just the length
+            let mut data = vec![payload.len() as u8];
+            data.extend_from_slice(payload);
+            return self.write_padded(data, options);
+        }
+
+        let length = payload.len();
+        let mut data = vec![(PCI_FIRST << 4) | (length >> 8) as u8, length as u8];
+        data.extend_from_slice(&payload[..6]);
+        self.write(&data)?;
+
+        let mut sequence = 1_u8;
+        let mut chunks = payload[6..].chunks(CAN_FRAME_BYTES - 1).peekable();
+        while chunks.peek().is_some() {
+            let (block_size, separation_time) =
+                self.await_flow_control(length, options, &mut unmatched)?;
+
+            let mut sent = 0;
+            while let Some(chunk) = chunks.next() {
+                let mut data = vec![(PCI_CONSECUTIVE << 4) | sequence];
+                data.extend_from_slice(chunk);
+                self.write_padded(data, options)?;
+                sequence = (sequence + 1) & 0x0F;
+                sent += 1;
+
+                if block_size != 0 && sent == block_size {
+                    break;
+                }
+                if chunks.peek().is_some() && separation_time > Duration::from_millis(0) {
+                    thread::sleep(separation_time);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn await_flow_control(
+        &self,
+        length: usize,
+        options: &IsoTpOptions,
+        unmatched: &mut dyn FnMut(CanFrame),
+    ) -> CanResult<(u8, Duration)> {
+        let mut waits = 0;
+
+        loop {
+            let frame = self.next_frame(unmatched)?;
+            let pci = frame.data.first().copied().unwrap_or(0);
+            if pci >> 4 != PCI_FLOW_CONTROL || frame.data.len() < 3 {
+                return Err(CanError::Protocol {
+                    description: format!(
+                        "expected flow control frame, received {:02X?}",
+                        frame.data
+                    ),
+                });
+            }
+
+            match pci & 0x0F {
+                FLOW_CONTINUE => return Ok((frame.data[1], decode_separation_time(frame.data[2]))),
+                FLOW_WAIT => {
+                    waits += 1;
+                    if waits > options.max_wait_frames {
+                        return Err(CanError::Protocol {
+                            description: format!(
+                                "peer sent more than {} flow control WAIT frames",
+                                options.max_wait_frames
+                            ),
+                        });
+                    }
+                }
+                FLOW_OVERFLOW => return Err(CanError::Overflow { length }),
+                status => {
+                    return Err(CanError::Protocol {
+                        description: format!("invalid flow status {}", status),
+                    })
+                }
+            }
+        }
+    }
+
+    fn receive_iso_tp(
+        &self,
+        options: &IsoTpOptions,
+        unmatched: &mut dyn FnMut(CanFrame),
+    ) -> CanResult<Vec<u8>> {
+        let frame = self.next_frame(unmatched)?;
+        let pci = frame.data.first().copied().unwrap_or(0);
+
+        match pci >> 4 {
+            PCI_SINGLE => {
+                let length = usize::from(pci & 0x0F);
+                if length == 0 || frame.data.len() < length + 1 {
+                    return Err(CanError::Protocol {
+                        description: format!("invalid single frame {:02X?}", frame.data),
+                    });
+                }
+                Ok(frame.data[1..=length].to_vec())
+            }
+            PCI_FIRST => {
+                if frame.data.len() < CAN_FRAME_BYTES {
+                    return Err(CanError::Protocol {
+                        description: format!("short first frame {:02X?}", frame.data),
+                    });
+                }
+                let length = (usize::from(pci & 0x0F) << 8) | usize::from(frame.data[1]);
+                if length < CAN_FRAME_BYTES {
+                    return Err(CanError::Protocol {
+                        description: format!(
+                            "first frame announces {} bytes, which fit in a single frame",
+                            length
+                        ),
+                    });
+                }
+                if length > options.max_payload {
+                    self.write_flow_control(FLOW_OVERFLOW, options)?;
+                    return Err(CanError::Overflow { length });
+                }
+
+                let mut payload = Vec::with_capacity(length);
+                payload.extend_from_slice(&frame.data[2..]);
+                self.write_flow_control(FLOW_CONTINUE, options)?;
+
+                let mut sequence = 1_u8;
+                let mut in_block = 0;
+                while payload.len() < length {
+                    let frame = self.next_frame(unmatched)?;
+                    let pci = frame.data.first().copied().unwrap_or(0);
+                    if pci >> 4 != PCI_CONSECUTIVE {
+                        return Err(CanError::Protocol {
+                            description: format!(
+                                "expected consecutive frame, received {:02X?}",
+                                frame.data
+                            ),
+                        });
+                    }
+                    if pci & 0x0F != sequence {
+                        return Err(CanError::Sequence {
+                            expected: sequence,
+                            received: pci & 0x0F,
+                        });
+                    }
+
+                    payload.extend_from_slice(&frame.data[1..]);
+                    sequence = (sequence + 1) & 0x0F;
+                    in_block += 1;
+
+                    if options.block_size != 0
+                        && in_block == options.block_size
+                        && payload.len() < length
+                    {
+                        self.write_flow_control(FLOW_CONTINUE, options)?;
+                        in_block = 0;
+                    }
+                }
+
+                payload.truncate(length);
+                Ok(payload)
+            }
+            _ => Err(CanError::Protocol {
+                description: format!(
+                    "expected single or first frame, received {:02X?}",
+                    frame.data
+                ),
+            }),
+        }
+    }
+
+    // Waits for the next frame from the peer, handing other frames to `unmatched`
+    fn next_frame(&self, unmatched: &mut dyn FnMut(CanFrame)) -> CanResult<CanFrame> {
+        let start = Instant::now();
+
+        loop {
+            let remaining = self
+                .timeout
+                .checked_sub(Instant::now() - start)
+                .ok_or(CanError::Timeout)?;
+            let frame = self.connection.read(remaining)?;
+
+            if self.rx.matches(&frame) {
+                return Ok(frame);
+            }
+            unmatched(frame);
+        }
+    }
+
+    fn write_flow_control(&self, status: u8, options: &IsoTpOptions) -> CanResult<()> {
+        let data = vec![
+            (PCI_FLOW_CONTROL << 4) | status,
+            options.block_size,
+            encode_separation_time(options.separation_time),
+        ];
+        self.write_padded(data, options)
+    }
+
+    fn write_padded(&self, mut data: Vec<u8>, options: &IsoTpOptions) -> CanResult<()> {
+        if let Some(padding) = options.padding {
+            data.resize(CAN_FRAME_BYTES, padding);
+        }
+        self.write(&data)
+    }
+
+    fn write(&self, data: &[u8]) -> CanResult<()> {
+        self.connection.write(CanFrame {
+            id: self.tx.id,
+            extended: self.tx.extended,
+            data: data.to_vec(),
+        })
+    }
+}
+
+// 0x00-0x7F are milliseconds, 0xF1-0xF9 are 100-900 microseconds, the rest are reserved
+// and treated as the maximum of 127 ms
+fn decode_separation_time(value: u8) -> Duration {
+    match value {
+        0x00..=0x7F => Duration::from_millis(u64::from(value)),
+        0xF1..=0xF9 => Duration::from_micros(u64::from(value - 0xF0) * 100),
+        _ => Duration::from_millis(0x7F),
+    }
+}
+
+fn encode_separation_time(time: Duration) -> u8 {
+    let micros = time.as_micros();
+    if micros > 0 && micros < 1000 {
+        0xF0 + ((micros + 99) / 100).min(9) as u8
+    } else {
+        time.as_millis().min(0x7F) as u8
+    }
+}
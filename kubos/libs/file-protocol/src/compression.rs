@@ -0,0 +1,110 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::error::ProtocolError;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+// Largest chunk which can be sent in a single UDP packet. Compressed chunks which
+// expand beyond this are rejected
+const MAX_CHUNK_SIZE: usize = 65_507;
+
+/// Compression applied to file chunks while they're in transit
+///
+/// Chunks are always kept uncompressed in temporary storage, so the file hash and any
+/// resumed transfers are unaffected by the compression used.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Compression {
+    /// Chunks are sent as they are
+    #[default]
+    None,
+    /// Chunks are compressed with DEFLATE
+    Deflate,
+}
+
+impl Compression {
+    /// Name of the compression scheme in protocol messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Deflate => "deflate",
+        }
+    }
+
+    // Compress a chunk for transmission
+    pub(crate) fn compress(self, data: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let map_err = |err| ProtocolError::CompressionError {
+            action: "compress chunk".to_owned(),
+            err,
+        };
+
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Deflate => {
+                let mut encoder = DeflateEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(data).map_err(map_err)?;
+                encoder.finish().map_err(map_err)
+            }
+        }
+    }
+
+    // Restore a chunk which was compressed for transmission
+    pub(crate) fn decompress(self, data: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let map_err = |err| ProtocolError::CompressionError {
+            action: "decompress chunk".to_owned(),
+            err,
+        };
+
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Deflate => {
+                let mut chunk = vec![];
+                DeflateDecoder::new(data)
+                    .take(MAX_CHUNK_SIZE as u64 + 1)
+                    .read_to_end(&mut chunk)
+                    .map_err(map_err)?;
+                if chunk.len() > MAX_CHUNK_SIZE {
+                    return Err(map_err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "chunk is too large",
+                    )));
+                }
+                Ok(chunk)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = ProtocolError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "none" => Ok(Compression::None),
+            "deflate" => Ok(Compression::Deflate),
+            other => Err(ProtocolError::UnknownCompression(other.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Binary patches which turn one version of a file into another.
+//
+// A patch is a CBOR array of instructions which build the new file in order. Each is
+// either a `[offset, length]` pair, copying part of the base file, or a byte string
+// to insert as it is.
+
+use crate::error::ProtocolError;
+use serde_cbor::{de, ser, Value};
+use std::collections::HashMap;
+
+// Size of the blocks of the base file which are looked for in the new file
+const BLOCK_SIZE: usize = 64;
+
+/// A patch uploaded to the remote target, to be applied to one of its files
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Patch {
+    /// BLAKE2s hash of the patch itself, which is transferred like any other file
+    pub hash: String,
+    /// BLAKE2s hash of the file the patch applies to
+    pub base_hash: String,
+    /// Path of the file the patch applies to, on the remote target
+    pub base_path: String,
+    /// BLAKE2s hash of the file the patch produces
+    pub file_hash: String,
+    /// Size of the file the patch produces, in bytes
+    pub file_len: u64,
+}
+
+// Rolling checksum of a block, which can be moved along a file one byte at a time
+#[derive(Clone, Copy)]
+struct Checksum {
+    a: u32,
+    b: u32,
+}
+
+impl Checksum {
+    fn new(block: &[u8]) -> Self {
+        let mut sum = Checksum { a: 0, b: 0 };
+        for (index, byte) in block.iter().enumerate() {
+            sum.a = sum.a.wrapping_add(u32::from(*byte));
+            sum.b = sum
+                .b
+                .wrapping_add((block.len() - index) as u32 * u32::from(*byte));
+        }
+        sum
+    }
+
+    // Move the block on by a byte, dropping `old` from its start and adding `new` to its end
+    fn roll(self, old: u8, new: u8) -> Self {
+        let a = self
+            .a
+            .wrapping_sub(u32::from(old))
+            .wrapping_add(u32::from(new));
+        let b = self
+            .b
+            .wrapping_sub(BLOCK_SIZE as u32 * u32::from(old))
+            .wrapping_add(a);
+        Checksum { a, b }
+    }
+
+    fn digest(self) -> u32 {
+        (self.b << 16) | (self.a & 0xffff)
+    }
+}
+
+// Create a patch which turns `base` into `new`
+pub fn diff(base: &[u8], new: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+    // Index the base file's blocks, so they can be found wherever they've moved to
+    let mut blocks: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (index, block) in base.chunks_exact(BLOCK_SIZE).enumerate() {
+        blocks
+            .entry(Checksum::new(block).digest())
+            .or_default()
+            .push(index * BLOCK_SIZE);
+    }
+
+    let mut instructions = vec![];
+    let mut literal_start = 0;
+    let mut pos = 0;
+    let mut checksum = None;
+    while pos + BLOCK_SIZE <= new.len() {
+        let block = &new[pos..pos + BLOCK_SIZE];
+        let sum = checksum.unwrap_or_else(|| Checksum::new(block));
+
+        let found = blocks.get(&sum.digest()).and_then(|offsets| {
+            offsets
+                .iter()
+                .find(|offset| &base[**offset..**offset + BLOCK_SIZE] == block)
+        });
+
+        match found {
+            Some(&offset) => {
+                // Carry the match on for as long as the files agree
+                let mut len = BLOCK_SIZE;
+                while offset + len < base.len()
+                    && pos + len < new.len()
+                    && base[offset + len] == new[pos + len]
+                {
+                    len += 1;
+                }
+
+                if literal_start < pos {
+                    instructions.push(Value::Bytes(new[literal_start..pos].to_vec()));
+                }
+                instructions.push(Value::Array(vec![
+                    Value::U64(offset as u64),
+                    Value::U64(len as u64),
+                ]));
+
+                pos += len;
+                literal_start = pos;
+                checksum = None;
+            }
+            None => {
+                checksum = new
+                    .get(pos + BLOCK_SIZE)
+                    .map(|next| sum.roll(new[pos], *next));
+                pos += 1;
+            }
+        }
+    }
+
+    if literal_start < new.len() {
+        instructions.push(Value::Bytes(new[literal_start..].to_vec()));
+    }
+
+    ser::to_vec_packed(&Value::Array(instructions)).map_err(|err| {
+        ProtocolError::MessageCreationError {
+            message: "patch".to_owned(),
+            err,
+        }
+    })
+}
+
+// Apply a patch created by `diff` to `base`, which must produce a file of `file_len` bytes.
+// The patch is rejected as soon as it would produce more, so a small patch can't build an
+// arbitrarily large file in memory
+pub fn apply(base: &[u8], patch: &[u8], file_len: u64) -> Result<Vec<u8>, ProtocolError> {
+    let instructions = match de::from_slice(patch) {
+        Ok(Value::Array(instructions)) => instructions,
+        _ => return Err(ProtocolError::InvalidPatch("not a patch".to_owned())),
+    };
+
+    let mut file = vec![];
+    for instruction in &instructions {
+        let data = match instruction {
+            Value::Bytes(data) => data.as_slice(),
+            Value::Array(range) => match range.as_slice() {
+                [Value::U64(offset), Value::U64(len)] => {
+                    let start = *offset as usize;
+                    let end = start.saturating_add(*len as usize);
+                    base.get(start..end).ok_or_else(|| {
+                        ProtocolError::InvalidPatch(format!(
+                            "copies {}..{} of a {} byte file",
+                            start,
+                            end,
+                            base.len()
+                        ))
+                    })?
+                }
+                _ => {
+                    return Err(ProtocolError::InvalidPatch(
+                        "malformed copy instruction".to_owned(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(ProtocolError::InvalidPatch(
+                    "unknown instruction".to_owned(),
+                ))
+            }
+        };
+
+        if data.len() as u64 > file_len - file.len() as u64 {
+            return Err(ProtocolError::InvalidPatch(format!(
+                "produces more than {} bytes",
+                file_len
+            )));
+        }
+        file.extend_from_slice(data);
+    }
+
+    if file.len() as u64 != file_len {
+        return Err(ProtocolError::InvalidPatch(format!(
+            "produces {} bytes rather than {}",
+            file.len(),
+            file_len
+        )));
+    }
+
+    Ok(file)
+}
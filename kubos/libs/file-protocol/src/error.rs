@@ -37,9 +37,23 @@ pub enum ProtocolError {
         /// The cause of the finalizing failure
         cause: String,
     },
+    /// An error was encountered when compressing or decompressing chunk data
+    #[error("Failed to {action}: {err}")]
+    CompressionError {
+        /// The action which generated the error
+        action: String,
+        /// The underlying std::io::Error
+        err: io::Error,
+    },
     /// A hash mismatch was found when finalizing the file
     #[error("File hash mismatch")]
     HashMismatch,
+    /// A transfer couldn't be resumed because chunks of the file are missing from storage
+    #[error("Storage is missing chunks of {0}")]
+    IncompleteFile(String),
+    /// A patch couldn't be applied
+    #[error("Unable to apply patch: {0}")]
+    InvalidPatch(String),
     /// An invalid value was found when parsing a message
     #[error("Unable to parse {0} message: Invalid {1} param")]
     InvalidParam(String, String),
@@ -86,6 +100,9 @@ pub enum ProtocolError {
     /// A timeout occurred when receiving data
     #[error("A receive timeout was encountered")]
     ReceiveTimeout,
+    /// A compression scheme wasn't recognized
+    #[error("Unknown compression scheme: {0}")]
+    UnknownCompression(String),
     /// An error was encountered when transmitting
     #[error("Transmission failure on channel {channel_id}: {error_message}")]
     TransmissionError {
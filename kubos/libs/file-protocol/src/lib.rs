@@ -86,18 +86,25 @@
 
 #![deny(missing_docs)]
 
+mod compression;
+mod delta;
 mod error;
 mod messages;
+mod ops;
 mod parsers;
 pub mod protocol;
 mod storage;
 
+pub use crate::compression::Compression;
+pub use crate::delta::Patch;
 pub use crate::error::ProtocolError;
+pub use crate::ops::{DirEntry, FileOp, FileOpReply};
 pub use crate::protocol::Protocol as FileProtocol;
 pub use crate::protocol::ProtocolConfig as FileProtocolConfig;
 pub use crate::protocol::State;
+pub use crate::storage::TransferStatus;
 
-pub use crate::parsers::parse_channel_id;
+pub use crate::parsers::{parse_channel_id, parse_message};
 
 /// File protocol message types
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -112,10 +119,18 @@ pub enum Message {
     ACK(u32, String),
     /// Receiver is missing the specified file data chunks
     NAK(u32, String, Option<Vec<(u32, u32)>>),
-    /// (Client Only) Message requesting the recipient to receive the specified file
-    ReqReceive(u32, String, String, Option<u32>),
-    /// (Client Only) Message requesting the recipient to transmit the specified file
-    ReqTransmit(u32, String),
+    /// (Client Only) Message requesting the recipient to receive the specified file,
+    /// whose chunks will be sent with the given compression
+    ReqReceive(u32, String, String, Option<u32>, Compression),
+    /// (Client Only) Message requesting the recipient to transmit the specified file,
+    /// compressing its chunks as given.
+    /// If a hash is given, the chunks already prepared for that hash are sent instead
+    /// of preparing the file again
+    ReqTransmit(u32, String, Option<String>, Compression),
+    /// (Client Only) Message requesting the recipient to receive a patch, whose chunks
+    /// will be sent with the given compression, apply it to one of its files and save
+    /// the result to the specified path
+    ReqPatch(u32, Patch, String, Option<u32>, Compression),
     /// (Server Only) Recipient has successfully processed a request to receive a file
     SuccessReceive(u32, String),
     /// (Server Only) Recipient has successfully prepared to transmit a file
@@ -124,12 +139,27 @@ pub enum Message {
     Failure(u32, String),
     /// Request Cleanup of either whole storage directory or individual file's storage
     Cleanup(u32, Option<String>),
+    /// Request how much of a file is present in the recipient's temporary storage
+    ReqStatus(u32, String),
+    /// How much of a file is present in temporary storage
+    Status(u32, TransferStatus),
+    /// Request a listing of the transfers held in the recipient's temporary storage
+    ReqTransfers(u32),
+    /// Transfers held in temporary storage, most recently changed first
+    Transfers(u32, Vec<TransferStatus>),
+    /// Request a file system operation from the recipient
+    ReqFileOp(u32, FileOp),
+    /// Result of a file system operation
+    FileOpReply(u32, FileOpReply),
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{messages, parsers, Message};
-    use serde_cbor::de;
+    use super::{
+        delta, messages, parsers, Compression, DirEntry, FileOp, FileOpReply, Message, Patch,
+        ProtocolError, TransferStatus,
+    };
+    use serde_cbor::{de, ser, Value};
 
     #[test]
     fn create_parse_export_request() {
@@ -138,13 +168,40 @@ mod tests {
         let target_path = "/path/to/file".to_owned();
         let mode = 0o623;
 
-        let raw = messages::export_request(channel_id, &hash, &target_path, mode).unwrap();
+        let raw =
+            messages::export_request(channel_id, &hash, &target_path, mode, Compression::None)
+                .unwrap();
 
         let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
 
         assert_eq!(
             msg.unwrap(),
-            Message::ReqReceive(channel_id, hash, target_path, Some(mode))
+            Message::ReqReceive(channel_id, hash, target_path, Some(mode), Compression::None)
+        );
+    }
+
+    #[test]
+    fn create_parse_compressed_export_request() {
+        let channel_id = 10;
+        let hash = "abcdedf".to_owned();
+        let target_path = "/path/to/file".to_owned();
+        let mode = 0o623;
+
+        let raw =
+            messages::export_request(channel_id, &hash, &target_path, mode, Compression::Deflate)
+                .unwrap();
+
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReqReceive(
+                channel_id,
+                hash,
+                target_path,
+                Some(mode),
+                Compression::Deflate
+            )
         );
     }
 
@@ -181,7 +238,8 @@ mod tests {
         let chunk_num = 10;
         let chunk_data: Vec<u8> = vec![1, 2, 3, 4, 5, 6];
 
-        let raw = messages::chunk(channel_id, &hash, chunk_num, &chunk_data).unwrap();
+        let raw =
+            messages::chunk(channel_id, &hash, chunk_num, &chunk_data, Compression::None).unwrap();
         let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
 
         assert_eq!(
@@ -190,6 +248,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn create_parse_compressed_chunk() {
+        let channel_id = 10;
+        let hash = "abcdefg".to_owned();
+        let chunk_num = 10;
+        let chunk_data: Vec<u8> = b"abcdefg".iter().cycle().take(1024).cloned().collect();
+
+        let raw = messages::chunk(
+            channel_id,
+            &hash,
+            chunk_num,
+            &chunk_data,
+            Compression::Deflate,
+        )
+        .unwrap();
+        assert!(raw.len() < chunk_data.len());
+
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReceiveChunk(channel_id, hash, chunk_num, chunk_data)
+        );
+    }
+
+    #[test]
+    fn create_incompressible_chunk() {
+        let channel_id = 10;
+        let hash = "abcdefg".to_owned();
+        let chunk_num = 10;
+        let chunk_data: Vec<u8> = vec![1, 2, 3, 4, 5, 6];
+
+        // Compressing such a small chunk would only make it bigger, so it's sent as it is
+        let raw = messages::chunk(
+            channel_id,
+            &hash,
+            chunk_num,
+            &chunk_data,
+            Compression::Deflate,
+        )
+        .unwrap();
+        assert_eq!(
+            raw,
+            messages::chunk(channel_id, &hash, chunk_num, &chunk_data, Compression::None).unwrap()
+        );
+
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReceiveChunk(channel_id, hash, chunk_num, chunk_data)
+        );
+    }
+
+    #[test]
+    fn parse_unknown_compression() {
+        let raw =
+            serde_cbor::ser::to_vec_packed(&(10, "import", "/path/to/file", (), "lzma")).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert!(msg.is_err());
+    }
+
     #[test]
     fn create_parse_ack() {
         let channel_id = 14;
@@ -217,4 +338,341 @@ mod tests {
             Message::NAK(channel_id, hash, Some(chunk_ranges))
         );
     }
+
+    #[test]
+    fn create_parse_resume_import() {
+        let channel_id = 12;
+        let source_path = "/path/to/file".to_owned();
+        let hash = "abcdefg".to_owned();
+
+        let raw =
+            messages::import_request(channel_id, &source_path, Some(&hash), Compression::None)
+                .unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReqTransmit(channel_id, source_path, Some(hash), Compression::None)
+        );
+    }
+
+    #[test]
+    fn create_parse_compressed_import() {
+        let channel_id = 12;
+        let source_path = "/path/to/file".to_owned();
+
+        let raw =
+            messages::import_request(channel_id, &source_path, None, Compression::Deflate).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReqTransmit(channel_id, source_path, None, Compression::Deflate)
+        );
+    }
+
+    #[test]
+    fn create_parse_patch_request() {
+        let channel_id = 12;
+        let patch = Patch {
+            hash: "abcdefg".to_owned(),
+            base_hash: "hijklmn".to_owned(),
+            base_path: "/path/to/base".to_owned(),
+            file_hash: "opqrstu".to_owned(),
+            file_len: 12345,
+        };
+        let target_path = "/path/to/file".to_owned();
+        let mode = 0o755;
+
+        let raw =
+            messages::patch_request(channel_id, &patch, &target_path, mode, Compression::Deflate)
+                .unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(
+            msg.unwrap(),
+            Message::ReqPatch(
+                channel_id,
+                patch,
+                target_path,
+                Some(mode),
+                Compression::Deflate
+            )
+        );
+    }
+
+    #[test]
+    fn diff_apply_patch() {
+        let base: Vec<u8> = (0..4096u32).map(|i| (i * 7 % 251) as u8).collect();
+
+        // Insert, remove and change a few parts of the file
+        let mut new = base.clone();
+        new.splice(100..100, b"inserted".iter().cloned());
+        new.drain(1000..1200);
+        new[3000] = 0xff;
+        new.extend_from_slice(b"appended");
+
+        let patch = delta::diff(&base, &new).unwrap();
+        assert!(patch.len() < new.len() / 4);
+        assert_eq!(delta::apply(&base, &patch, new.len() as u64).unwrap(), new);
+
+        // A new file with nothing in common with the base is sent whole
+        let unrelated = vec![3u8; 100];
+        let patch = delta::diff(&base, &unrelated).unwrap();
+        assert_eq!(
+            delta::apply(&base, &patch, unrelated.len() as u64).unwrap(),
+            unrelated
+        );
+    }
+
+    #[test]
+    fn apply_patch_too_long() {
+        // Each instruction copies the whole base, so a short patch builds a large file
+        let copy = Value::Array(vec![Value::U64(0), Value::U64(3)]);
+        let patch = ser::to_vec_packed(&Value::Array(vec![copy; 100])).unwrap();
+
+        assert_eq!(delta::apply(&[1, 2, 3], &patch, 300).unwrap().len(), 300);
+        for file_len in [299, 301].iter() {
+            match delta::apply(&[1, 2, 3], &patch, *file_len) {
+                Err(ProtocolError::InvalidPatch(_)) => {}
+                other => panic!("Patch wasn't rejected: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn apply_patch_out_of_range() {
+        let patch = ser::to_vec_packed(&Value::Array(vec![Value::Array(vec![
+            Value::U64(0),
+            Value::U64(10),
+        ])]))
+        .unwrap();
+
+        match delta::apply(&[1, 2, 3], &patch, 10) {
+            Err(ProtocolError::InvalidPatch(_)) => {}
+            other => panic!("Patch wasn't rejected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn create_parse_status_request() {
+        let channel_id = 13;
+        let hash = "abcdefg".to_owned();
+
+        let raw = messages::status_request(channel_id, &hash).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(msg.unwrap(), Message::ReqStatus(channel_id, hash));
+    }
+
+    #[test]
+    fn create_parse_status() {
+        let channel_id = 13;
+        let status = TransferStatus {
+            hash: "abcdefg".to_owned(),
+            num_chunks: Some(20),
+            chunks_present: 15,
+            missing: vec![(2, 4), (10, 13)],
+        };
+
+        let raw = messages::status(channel_id, &status).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(msg.unwrap(), Message::Status(channel_id, status));
+    }
+
+    #[test]
+    fn create_parse_status_unknown() {
+        let channel_id = 13;
+        let status = TransferStatus {
+            hash: "abcdefg".to_owned(),
+            num_chunks: None,
+            chunks_present: 0,
+            missing: vec![],
+        };
+
+        let raw = messages::status(channel_id, &status).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(msg.unwrap(), Message::Status(channel_id, status));
+    }
+
+    #[test]
+    fn create_parse_transfers_request() {
+        let channel_id = 15;
+
+        let raw = messages::transfers_request(channel_id).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(msg.unwrap(), Message::ReqTransfers(channel_id));
+    }
+
+    #[test]
+    fn create_parse_transfers() {
+        let channel_id = 15;
+        let transfers = vec![
+            TransferStatus {
+                hash: "abcdefg".to_owned(),
+                num_chunks: Some(20),
+                chunks_present: 15,
+                missing: vec![],
+            },
+            TransferStatus {
+                hash: "hijklmn".to_owned(),
+                num_chunks: None,
+                chunks_present: 3,
+                missing: vec![],
+            },
+        ];
+
+        let raw = messages::transfers(channel_id, &transfers, 1024).unwrap();
+        let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+        assert_eq!(msg.unwrap(), Message::Transfers(channel_id, transfers));
+    }
+
+    #[test]
+    fn create_transfers_truncated() {
+        let channel_id = 15;
+        let transfers: Vec<TransferStatus> = (0..100)
+            .map(|num| TransferStatus {
+                hash: format!("{:032x}", num),
+                num_chunks: Some(1000),
+                chunks_present: 1000,
+                missing: vec![],
+            })
+            .collect();
+
+        let raw = messages::transfers(channel_id, &transfers, 1024).unwrap();
+        assert!(raw.len() <= 1024);
+
+        match parsers::parse_message(de::from_slice(&raw).unwrap()).unwrap() {
+            Message::Transfers(_, listed) => {
+                assert!(!listed.is_empty());
+                assert_eq!(listed[..], transfers[..listed.len()]);
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn create_parse_file_op_requests() {
+        let channel_id = 16;
+        let ops = vec![
+            FileOp::List {
+                path: "/home/kubos".to_owned(),
+            },
+            FileOp::Stat {
+                path: "/home/kubos/file".to_owned(),
+            },
+            FileOp::Delete {
+                path: "/home/kubos/file".to_owned(),
+            },
+            FileOp::Mkdir {
+                path: "/home/kubos/dir".to_owned(),
+            },
+            FileOp::Move {
+                from: "/home/kubos/file".to_owned(),
+                to: "/home/kubos/dir/file".to_owned(),
+            },
+            FileOp::DiskUsage {
+                path: "/home/kubos".to_owned(),
+            },
+        ];
+
+        for op in ops {
+            let raw = messages::file_op_request(channel_id, &op).unwrap();
+            let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+            assert_eq!(msg.unwrap(), Message::ReqFileOp(channel_id, op));
+        }
+    }
+
+    #[test]
+    fn create_parse_file_op_replies() {
+        let channel_id = 16;
+        let replies = vec![
+            FileOpReply::List {
+                path: "/home/kubos".to_owned(),
+                total: 2,
+                entries: vec![
+                    DirEntry {
+                        name: "dir".to_owned(),
+                        is_dir: true,
+                        size: 4096,
+                    },
+                    DirEntry {
+                        name: "file".to_owned(),
+                        is_dir: false,
+                        size: 10,
+                    },
+                ],
+            },
+            FileOpReply::Stat {
+                path: "/home/kubos/file".to_owned(),
+                is_dir: false,
+                size: 10,
+                mode: 0o100644,
+                modified: 1_500_000_000,
+            },
+            FileOpReply::Delete {
+                path: "/home/kubos/file".to_owned(),
+            },
+            FileOpReply::Mkdir {
+                path: "/home/kubos/dir".to_owned(),
+            },
+            FileOpReply::Move {
+                from: "/home/kubos/file".to_owned(),
+                to: "/home/kubos/dir/file".to_owned(),
+            },
+            FileOpReply::DiskUsage {
+                path: "/home/kubos".to_owned(),
+                total: 4_000_000_000,
+                available: 1_000_000_000,
+            },
+        ];
+
+        for reply in replies {
+            let raw = messages::file_op_reply(channel_id, &reply, 1024).unwrap();
+            let msg = parsers::parse_message(de::from_slice(&raw).unwrap());
+
+            assert_eq!(msg.unwrap(), Message::FileOpReply(channel_id, reply));
+        }
+    }
+
+    #[test]
+    fn create_file_op_list_truncated() {
+        let channel_id = 16;
+        let entries: Vec<DirEntry> = (0..100)
+            .map(|num| DirEntry {
+                name: format!("file-with-a-long-name-{:03}", num),
+                is_dir: false,
+                size: 1000,
+            })
+            .collect();
+        let reply = FileOpReply::List {
+            path: "/home/kubos".to_owned(),
+            total: 100,
+            entries: entries.clone(),
+        };
+
+        let raw = messages::file_op_reply(channel_id, &reply, 1024).unwrap();
+        assert!(raw.len() <= 1024);
+
+        match parsers::parse_message(de::from_slice(&raw).unwrap()).unwrap() {
+            Message::FileOpReply(
+                _,
+                FileOpReply::List {
+                    total,
+                    entries: listed,
+                    ..
+                },
+            ) => {
+                assert_eq!(total, 100);
+                assert!(!listed.is_empty());
+                assert_eq!(listed[..], entries[..listed.len()]);
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
 }
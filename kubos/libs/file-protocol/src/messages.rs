@@ -14,7 +14,11 @@
 // limitations under the License.
 //
 
+use crate::compression::Compression;
+use crate::delta::Patch;
 use crate::error::ProtocolError;
+use crate::ops::{FileOp, FileOpReply};
+use crate::storage::TransferStatus;
 use log::info;
 use serde_cbor::{ser, Value};
 
@@ -24,28 +28,105 @@ pub fn export_request(
     hash: &str,
     target_path: &str,
     mode: u32,
+    compression: Compression,
 ) -> Result<Vec<u8>, ProtocolError> {
-    info!(
-        "-> {{ {}, export, {}, {}, {} }}",
-        channel_id, hash, target_path, mode
-    );
-
-    ser::to_vec_packed(&(channel_id, "export", hash, target_path, mode)).map_err(|err| {
-        ProtocolError::MessageCreationError {
-            message: "export".to_owned(),
-            err,
+    let result = match compression {
+        Compression::None => {
+            info!(
+                "-> {{ {}, export, {}, {}, {} }}",
+                channel_id, hash, target_path, mode
+            );
+            ser::to_vec_packed(&(channel_id, "export", hash, target_path, mode))
+        }
+        compression => {
+            info!(
+                "-> {{ {}, export, {}, {}, {}, {} }}",
+                channel_id, hash, target_path, mode, compression
+            );
+            ser::to_vec_packed(&(
+                channel_id,
+                "export",
+                hash,
+                target_path,
+                mode,
+                compression.name(),
+            ))
         }
+    };
+
+    result.map_err(|err| ProtocolError::MessageCreationError {
+        message: "export".to_owned(),
+        err,
     })
 }
 
 // Create import message
-pub fn import_request(channel_id: u32, source_path: &str) -> Result<Vec<u8>, ProtocolError> {
-    info!("-> {{ import, {} }}", source_path);
-    ser::to_vec_packed(&(channel_id, "import", source_path)).map_err(|err| {
-        ProtocolError::MessageCreationError {
-            message: "import".to_owned(),
-            err,
+pub fn import_request(
+    channel_id: u32,
+    source_path: &str,
+    hash: Option<&str>,
+    compression: Compression,
+) -> Result<Vec<u8>, ProtocolError> {
+    let result = match (hash, compression) {
+        (None, Compression::None) => {
+            info!("-> {{ import, {} }}", source_path);
+            ser::to_vec_packed(&(channel_id, "import", source_path))
         }
+        (Some(hash), Compression::None) => {
+            info!("-> {{ import, {}, {} }}", source_path, hash);
+            ser::to_vec_packed(&(channel_id, "import", source_path, hash))
+        }
+        (hash, compression) => {
+            info!(
+                "-> {{ import, {}, {:?}, {} }}",
+                source_path, hash, compression
+            );
+            ser::to_vec_packed(&(channel_id, "import", source_path, hash, compression.name()))
+        }
+    };
+
+    result.map_err(|err| ProtocolError::MessageCreationError {
+        message: "import".to_owned(),
+        err,
+    })
+}
+
+// Create patch message
+pub fn patch_request(
+    channel_id: u32,
+    patch: &Patch,
+    target_path: &str,
+    mode: u32,
+    compression: Compression,
+) -> Result<Vec<u8>, ProtocolError> {
+    info!(
+        "-> {{ {}, patch, {}, {}, {}, {}, {}, {}, {}, {} }}",
+        channel_id,
+        patch.hash,
+        patch.base_hash,
+        patch.base_path,
+        patch.file_hash,
+        patch.file_len,
+        target_path,
+        mode,
+        compression
+    );
+
+    ser::to_vec_packed(&(
+        channel_id,
+        "patch",
+        &patch.hash,
+        &patch.base_hash,
+        &patch.base_path,
+        &patch.file_hash,
+        patch.file_len,
+        target_path,
+        mode,
+        compression.name(),
+    ))
+    .map_err(|err| ProtocolError::MessageCreationError {
+        message: "patch".to_owned(),
+        err,
     })
 }
 
@@ -106,19 +187,45 @@ pub fn nak(channel_id: u32, hash: &str, missing_chunks: &[u32]) -> Result<Vec<u8
 }
 
 // Create chunk message
+//
+// The chunk is compressed if the compression makes up for the scheme's name being
+// added to the message. Otherwise it's sent as it is
 pub fn chunk(
     channel_id: u32,
     hash: &str,
     index: u32,
     chunk: &[u8],
+    compression: Compression,
 ) -> Result<Vec<u8>, ProtocolError> {
-    let chunk_bytes = Value::Bytes(chunk.to_vec());
-    info!("-> {{ {}, {}, {}, chunk_data }}", channel_id, hash, index);
-    ser::to_vec_packed(&(channel_id, hash, index, chunk_bytes)).map_err(|err| {
-        ProtocolError::MessageCreationError {
-            message: "chunk".to_owned(),
-            err,
+    let compressed = match compression {
+        Compression::None => None,
+        compression => Some(compression.compress(chunk)?)
+            .filter(|data| data.len() + compression.name().len() + 1 < chunk.len()),
+    };
+
+    let result = match compressed {
+        Some(data) => {
+            info!(
+                "-> {{ {}, {}, {}, chunk_data, {} }}",
+                channel_id, hash, index, compression
+            );
+            ser::to_vec_packed(&(
+                channel_id,
+                hash,
+                index,
+                Value::Bytes(data),
+                compression.name(),
+            ))
         }
+        None => {
+            info!("-> {{ {}, {}, {}, chunk_data }}", channel_id, hash, index);
+            ser::to_vec_packed(&(channel_id, hash, index, Value::Bytes(chunk.to_vec())))
+        }
+    };
+
+    result.map_err(|err| ProtocolError::MessageCreationError {
+        message: "chunk".to_owned(),
+        err,
     })
 }
 
@@ -183,3 +290,223 @@ pub fn cleanup(channel_id: u32, hash: Option<String>) -> Result<Vec<u8>, Protoco
         }
     })
 }
+
+// Create status request message
+pub fn status_request(channel_id: u32, hash: &str) -> Result<Vec<u8>, ProtocolError> {
+    info!("-> {{ {}, status, {} }}", channel_id, hash);
+    ser::to_vec_packed(&(channel_id, "status", hash)).map_err(|err| {
+        ProtocolError::MessageCreationError {
+            message: "status".to_owned(),
+            err,
+        }
+    })
+}
+
+// Create status response message. Like a NAK, only the first ten missing ranges are sent
+pub fn status(channel_id: u32, status: &TransferStatus) -> Result<Vec<u8>, ProtocolError> {
+    let missing: Vec<u32> = status
+        .missing
+        .iter()
+        .take(10)
+        .flat_map(|&(first, last)| vec![first, last])
+        .collect();
+
+    info!(
+        "-> {{ {}, status, {}, {:?}, {}, {:?} }}",
+        channel_id, status.hash, status.num_chunks, status.chunks_present, missing
+    );
+    ser::to_vec_packed(&(
+        channel_id,
+        "status",
+        &status.hash,
+        status.num_chunks,
+        status.chunks_present,
+        missing,
+    ))
+    .map_err(|err| ProtocolError::MessageCreationError {
+        message: "status".to_owned(),
+        err,
+    })
+}
+
+// Create transfer listing request message
+pub fn transfers_request(channel_id: u32) -> Result<Vec<u8>, ProtocolError> {
+    info!("-> {{ {}, transfers }}", channel_id);
+    ser::to_vec_packed(&(channel_id, "transfers")).map_err(|err| {
+        ProtocolError::MessageCreationError {
+            message: "transfers".to_owned(),
+            err,
+        }
+    })
+}
+
+// Create transfer listing response message. Transfers are dropped from the end of the listing
+// until the message fits in `max_size` bytes
+pub fn transfers(
+    channel_id: u32,
+    transfers: &[TransferStatus],
+    max_size: usize,
+) -> Result<Vec<u8>, ProtocolError> {
+    let mut entries: Vec<(&str, Option<u32>, u32)> = transfers
+        .iter()
+        .map(|transfer| {
+            (
+                transfer.hash.as_str(),
+                transfer.num_chunks,
+                transfer.chunks_present,
+            )
+        })
+        .collect();
+
+    loop {
+        let vec = ser::to_vec_packed(&(channel_id, "transfers", &entries)).map_err(|err| {
+            ProtocolError::MessageCreationError {
+                message: "transfers".to_owned(),
+                err,
+            }
+        })?;
+
+        if vec.len() <= max_size || entries.is_empty() {
+            info!(
+                "-> {{ {}, transfers, {} of {} }}",
+                channel_id,
+                entries.len(),
+                transfers.len()
+            );
+            return Ok(vec);
+        }
+        entries.pop();
+    }
+}
+
+// Create file operation request message
+pub fn file_op_request(channel_id: u32, op: &FileOp) -> Result<Vec<u8>, ProtocolError> {
+    let (name, result) = match op {
+        FileOp::List { path } => {
+            info!("-> {{ {}, list, {} }}", channel_id, path);
+            ("list", ser::to_vec_packed(&(channel_id, "list", path)))
+        }
+        FileOp::Stat { path } => {
+            info!("-> {{ {}, stat, {} }}", channel_id, path);
+            ("stat", ser::to_vec_packed(&(channel_id, "stat", path)))
+        }
+        FileOp::Delete { path } => {
+            info!("-> {{ {}, delete, {} }}", channel_id, path);
+            ("delete", ser::to_vec_packed(&(channel_id, "delete", path)))
+        }
+        FileOp::Mkdir { path } => {
+            info!("-> {{ {}, mkdir, {} }}", channel_id, path);
+            ("mkdir", ser::to_vec_packed(&(channel_id, "mkdir", path)))
+        }
+        FileOp::Move { from, to } => {
+            info!("-> {{ {}, move, {}, {} }}", channel_id, from, to);
+            ("move", ser::to_vec_packed(&(channel_id, "move", from, to)))
+        }
+        FileOp::DiskUsage { path } => {
+            info!("-> {{ {}, df, {} }}", channel_id, path);
+            ("df", ser::to_vec_packed(&(channel_id, "df", path)))
+        }
+    };
+
+    result.map_err(|err| ProtocolError::MessageCreationError {
+        message: name.to_owned(),
+        err,
+    })
+}
+
+// Create file operation reply message. Directory entries are dropped from the end of
+// a listing until the message fits in `max_size` bytes
+pub fn file_op_reply(
+    channel_id: u32,
+    reply: &FileOpReply,
+    max_size: usize,
+) -> Result<Vec<u8>, ProtocolError> {
+    let (name, result) = match reply {
+        FileOpReply::List {
+            path,
+            total,
+            entries,
+        } => {
+            let mut entries: Vec<(&str, bool, u64)> = entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.is_dir, entry.size))
+                .collect();
+
+            loop {
+                let result = ser::to_vec_packed(&(channel_id, "list", path, total, &entries));
+                let fits = match &result {
+                    Ok(vec) => vec.len() <= max_size,
+                    Err(_) => true,
+                };
+
+                if fits || entries.is_empty() {
+                    info!(
+                        "-> {{ {}, list, {}, {}, {} entries }}",
+                        channel_id,
+                        path,
+                        total,
+                        entries.len()
+                    );
+                    break ("list", result);
+                }
+                entries.pop();
+            }
+        }
+        FileOpReply::Stat {
+            path,
+            is_dir,
+            size,
+            mode,
+            modified,
+        } => {
+            info!(
+                "-> {{ {}, stat, {}, {}, {}, {:o}, {} }}",
+                channel_id, path, is_dir, size, mode, modified
+            );
+            (
+                "stat",
+                ser::to_vec_packed(&(channel_id, "stat", path, is_dir, size, mode, modified)),
+            )
+        }
+        FileOpReply::Delete { path } => {
+            info!("-> {{ {}, delete, {}, true }}", channel_id, path);
+            (
+                "delete",
+                ser::to_vec_packed(&(channel_id, "delete", path, true)),
+            )
+        }
+        FileOpReply::Mkdir { path } => {
+            info!("-> {{ {}, mkdir, {}, true }}", channel_id, path);
+            (
+                "mkdir",
+                ser::to_vec_packed(&(channel_id, "mkdir", path, true)),
+            )
+        }
+        FileOpReply::Move { from, to } => {
+            info!("-> {{ {}, move, {}, {}, true }}", channel_id, from, to);
+            (
+                "move",
+                ser::to_vec_packed(&(channel_id, "move", from, to, true)),
+            )
+        }
+        FileOpReply::DiskUsage {
+            path,
+            total,
+            available,
+        } => {
+            info!(
+                "-> {{ {}, df, {}, {}, {} }}",
+                channel_id, path, total, available
+            );
+            (
+                "df",
+                ser::to_vec_packed(&(channel_id, "df", path, total, available)),
+            )
+        }
+    };
+
+    result.map_err(|err| ProtocolError::MessageCreationError {
+        message: name.to_owned(),
+        err,
+    })
+}
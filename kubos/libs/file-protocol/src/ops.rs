@@ -0,0 +1,117 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+/// Operation on the remote target's file system
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FileOp {
+    /// List the contents of a directory
+    List {
+        /// Directory to list
+        path: String,
+    },
+    /// Get information about a file or directory
+    Stat {
+        /// File or directory to describe
+        path: String,
+    },
+    /// Delete a file or an empty directory
+    Delete {
+        /// File or directory to delete
+        path: String,
+    },
+    /// Create a directory. Its parent must already exist
+    Mkdir {
+        /// Directory to create
+        path: String,
+    },
+    /// Move or rename a file or directory within a file system
+    Move {
+        /// Current path
+        from: String,
+        /// New path
+        to: String,
+    },
+    /// Get the size of, and space available on, the file system holding a path
+    DiskUsage {
+        /// Any path on the file system
+        path: String,
+    },
+}
+
+/// Result of a successful file system operation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FileOpReply {
+    /// Contents of a directory
+    List {
+        /// Directory which was listed
+        path: String,
+        /// Total number of entries in the directory
+        total: u32,
+        /// Entries in name order. The listing is cut short if it won't fit in a single
+        /// message, in which case there are fewer than `total`
+        entries: Vec<DirEntry>,
+    },
+    /// Information about a file or directory
+    Stat {
+        /// File or directory which was described
+        path: String,
+        /// Whether the path is a directory
+        is_dir: bool,
+        /// Size in bytes
+        size: u64,
+        /// File mode
+        mode: u32,
+        /// Time of the last modification, in seconds since the Unix epoch
+        modified: u64,
+    },
+    /// A file or directory was deleted
+    Delete {
+        /// File or directory which was deleted
+        path: String,
+    },
+    /// A directory was created
+    Mkdir {
+        /// Directory which was created
+        path: String,
+    },
+    /// A file or directory was moved
+    Move {
+        /// Previous path
+        from: String,
+        /// New path
+        to: String,
+    },
+    /// Size of, and space available on, a file system
+    DiskUsage {
+        /// Path which was given
+        path: String,
+        /// Size of the file system in bytes
+        total: u64,
+        /// Space available to unprivileged users in bytes
+        available: u64,
+    },
+}
+
+/// Entry in a directory listing
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirEntry {
+    /// File name
+    pub name: String,
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+    /// Size in bytes
+    pub size: u64,
+}
@@ -15,7 +15,11 @@
 //
 
 use super::Message;
+use crate::compression::Compression;
+use crate::delta::Patch;
 use crate::error::ProtocolError;
+use crate::ops::{DirEntry, FileOp, FileOpReply};
+use crate::storage::TransferStatus;
 use serde_cbor::Value;
 use std::slice::Iter;
 
@@ -48,6 +52,7 @@ pub fn parse_channel_id(message: &Value) -> Result<u32, ProtocolError> {
     }
 }
 
+/// Parse a file protocol message
 pub fn parse_message(message: Value) -> Result<Message, ProtocolError> {
     let raw = match message {
         Value::Array(val) => val,
@@ -78,6 +83,18 @@ pub fn parse_message(message: Value) -> Result<Message, ProtocolError> {
         if let Some(msg) = parse_import_request(channel_id, pieces.to_owned())? {
             return Ok(msg);
         }
+        if let Some(msg) = parse_patch_request(channel_id, pieces.to_owned())? {
+            return Ok(msg);
+        }
+        if let Some(msg) = parse_status(channel_id, pieces.to_owned())? {
+            return Ok(msg);
+        }
+        if let Some(msg) = parse_transfers(channel_id, pieces.to_owned())? {
+            return Ok(msg);
+        }
+        if let Some(msg) = parse_file_op(channel_id, pieces.to_owned())? {
+            return Ok(msg);
+        }
         if let Some(msg) = parse_success_receive(channel_id, pieces.to_owned())? {
             return Ok(msg);
         }
@@ -134,7 +151,7 @@ pub fn parse_cleanup_request(
 }
 
 // Parse out export request
-// { channel_id, "export", hash, path, [, mode] }
+// { channel_id, "export", hash, path, [, mode [, compression]] }
 pub fn parse_export_request(
     channel_id: u32,
     mut pieces: Iter<Value>,
@@ -170,11 +187,14 @@ pub fn parse_export_request(
                 _ => None,
             };
 
+            let compression = parse_compression("export", pieces.next())?;
+
             return Ok(Some(Message::ReqReceive(
                 channel_id,
                 hash.to_owned(),
                 path.to_owned(),
                 mode,
+                compression,
             )));
         }
     }
@@ -183,7 +203,7 @@ pub fn parse_export_request(
 }
 
 // Parse out import request
-// { channel_id, "import", path }
+// { channel_id, "import", path [, hash [, compression]] }
 pub fn parse_import_request(
     channel_id: u32,
     mut pieces: Iter<Value>,
@@ -201,13 +221,339 @@ pub fn parse_import_request(
                     ));
                 }
             };
-            return Ok(Some(Message::ReqTransmit(channel_id, path.to_owned())));
+            let hash = match pieces.next() {
+                Some(Value::String(val)) => Some(val.to_owned()),
+                None | Some(Value::Null) => None,
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "import".to_owned(),
+                        "hash".to_owned(),
+                    ));
+                }
+            };
+            let compression = parse_compression("import", pieces.next())?;
+            return Ok(Some(Message::ReqTransmit(
+                channel_id,
+                path.to_owned(),
+                hash,
+                compression,
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+// Parse out patch request
+// { channel_id, "patch", hash, base_hash, base_path, file_hash, file_len, path [, mode [, compression]] }
+pub fn parse_patch_request(
+    channel_id: u32,
+    mut pieces: Iter<Value>,
+) -> Result<Option<Message>, ProtocolError> {
+    if let Some(Value::String(op)) = pieces.next() {
+        if op == "patch" {
+            let mut param = |name: &str| {
+                pieces
+                    .next()
+                    .ok_or_else(|| ProtocolError::MissingParam("patch".to_owned(), name.to_owned()))
+            };
+            let invalid =
+                |name: &str| ProtocolError::InvalidParam("patch".to_owned(), name.to_owned());
+            let string = |name: &str, value: &Value| match value {
+                Value::String(val) => Ok(val.to_owned()),
+                _ => Err(invalid(name)),
+            };
+
+            let patch = Patch {
+                hash: string("hash", param("hash")?)?,
+                base_hash: string("base_hash", param("base_hash")?)?,
+                base_path: string("base_path", param("base_path")?)?,
+                file_hash: string("file_hash", param("file_hash")?)?,
+                file_len: match param("file_len")? {
+                    Value::U64(len) => *len,
+                    _ => return Err(invalid("file_len")),
+                },
+            };
+            let path = string("path", param("path")?)?;
+
+            let mode = match pieces.next() {
+                Some(Value::U64(num)) => Some(*num as u32),
+                _ => None,
+            };
+
+            let compression = parse_compression("patch", pieces.next())?;
+
+            return Ok(Some(Message::ReqPatch(
+                channel_id,
+                patch,
+                path,
+                mode,
+                compression,
+            )));
         }
     }
 
     Ok(None)
 }
 
+// Parse out status request or reply
+// { channel_id, "status", hash }
+// or
+// { channel_id, "status", hash, num_chunks, chunks_present, [..missing_ranges] }
+pub fn parse_status(
+    channel_id: u32,
+    mut pieces: Iter<Value>,
+) -> Result<Option<Message>, ProtocolError> {
+    if let Some(Value::String(op)) = pieces.next() {
+        if op == "status" {
+            let hash = match pieces.next().ok_or_else(|| {
+                ProtocolError::MissingParam("status".to_owned(), "hash".to_owned())
+            })? {
+                Value::String(val) => val.to_owned(),
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "status".to_owned(),
+                        "hash".to_owned(),
+                    ));
+                }
+            };
+
+            let num_chunks = match pieces.next() {
+                None => return Ok(Some(Message::ReqStatus(channel_id, hash))),
+                Some(Value::U64(num)) => Some(*num as u32),
+                Some(Value::Null) => None,
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "status".to_owned(),
+                        "num chunks".to_owned(),
+                    ));
+                }
+            };
+
+            let chunks_present = match pieces.next().ok_or_else(|| {
+                ProtocolError::MissingParam("status".to_owned(), "chunks present".to_owned())
+            })? {
+                Value::U64(num) => *num as u32,
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "status".to_owned(),
+                        "chunks present".to_owned(),
+                    ));
+                }
+            };
+
+            let missing = match pieces.next() {
+                Some(Value::Array(entries)) => {
+                    let chunk_nums: Vec<u32> = entries
+                        .iter()
+                        .filter_map(|entry| match entry {
+                            Value::U64(num) => Some(*num as u32),
+                            _ => None,
+                        })
+                        .collect();
+
+                    chunk_nums
+                        .chunks_exact(2)
+                        .map(|range| (range[0], range[1]))
+                        .collect()
+                }
+                None => vec![],
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "status".to_owned(),
+                        "missing chunks".to_owned(),
+                    ));
+                }
+            };
+
+            return Ok(Some(Message::Status(
+                channel_id,
+                TransferStatus {
+                    hash,
+                    num_chunks,
+                    chunks_present,
+                    missing,
+                },
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+// Parse out transfer listing request or reply
+// { channel_id, "transfers" }
+// or
+// { channel_id, "transfers", [..[hash, num_chunks, chunks_present]] }
+pub fn parse_transfers(
+    channel_id: u32,
+    mut pieces: Iter<Value>,
+) -> Result<Option<Message>, ProtocolError> {
+    if let Some(Value::String(op)) = pieces.next() {
+        if op == "transfers" {
+            let entries = match pieces.next() {
+                None => return Ok(Some(Message::ReqTransfers(channel_id))),
+                Some(Value::Array(entries)) => entries,
+                _ => {
+                    return Err(ProtocolError::InvalidParam(
+                        "transfers".to_owned(),
+                        "transfers".to_owned(),
+                    ));
+                }
+            };
+
+            let mut transfers = vec![];
+            for entry in entries {
+                let transfer = match entry.as_array().map(|fields| fields.as_slice()) {
+                    Some([Value::String(hash), num_chunks, Value::U64(present)]) => {
+                        TransferStatus {
+                            hash: hash.to_owned(),
+                            num_chunks: num_chunks.as_u64().map(|num| num as u32),
+                            chunks_present: *present as u32,
+                            missing: vec![],
+                        }
+                    }
+                    _ => {
+                        return Err(ProtocolError::InvalidParam(
+                            "transfers".to_owned(),
+                            "transfer".to_owned(),
+                        ));
+                    }
+                };
+                transfers.push(transfer);
+            }
+
+            return Ok(Some(Message::Transfers(channel_id, transfers)));
+        }
+    }
+
+    Ok(None)
+}
+
+// Parse out file operation request or reply
+// { channel_id, "list", path }
+// { channel_id, "list", path, total, [..[name, is_dir, size]] }
+// { channel_id, "stat", path }
+// { channel_id, "stat", path, is_dir, size, mode, modified }
+// { channel_id, "delete", path [, true] }
+// { channel_id, "mkdir", path [, true] }
+// { channel_id, "move", from, to [, true] }
+// { channel_id, "df", path }
+// { channel_id, "df", path, total, available }
+pub fn parse_file_op(
+    channel_id: u32,
+    mut pieces: Iter<Value>,
+) -> Result<Option<Message>, ProtocolError> {
+    let op = match pieces.next() {
+        Some(Value::String(op)) => op.as_str(),
+        _ => return Ok(None),
+    };
+    if !["list", "stat", "delete", "mkdir", "move", "df"].contains(&op) {
+        return Ok(None);
+    }
+
+    let path = match pieces
+        .next()
+        .ok_or_else(|| ProtocolError::MissingParam(op.to_owned(), "path".to_owned()))?
+    {
+        Value::String(val) => val.to_owned(),
+        _ => {
+            return Err(ProtocolError::InvalidParam(
+                op.to_owned(),
+                "path".to_owned(),
+            ));
+        }
+    };
+
+    let args: Vec<&Value> = pieces.collect();
+    let message = match (op, args.as_slice()) {
+        ("list", []) => Message::ReqFileOp(channel_id, FileOp::List { path }),
+        ("list", [Value::U64(total), Value::Array(entries)]) => {
+            let mut listed = vec![];
+            for entry in entries {
+                let dir_entry = match entry.as_array().map(|fields| fields.as_slice()) {
+                    Some([Value::String(name), Value::Bool(is_dir), Value::U64(size)]) => {
+                        DirEntry {
+                            name: name.to_owned(),
+                            is_dir: *is_dir,
+                            size: *size,
+                        }
+                    }
+                    _ => {
+                        return Err(ProtocolError::InvalidParam(
+                            "list".to_owned(),
+                            "entry".to_owned(),
+                        ));
+                    }
+                };
+                listed.push(dir_entry);
+            }
+
+            Message::FileOpReply(
+                channel_id,
+                FileOpReply::List {
+                    path,
+                    total: *total as u32,
+                    entries: listed,
+                },
+            )
+        }
+        ("stat", []) => Message::ReqFileOp(channel_id, FileOp::Stat { path }),
+        (
+            "stat",
+            [Value::Bool(is_dir), Value::U64(size), Value::U64(mode), Value::U64(modified)],
+        ) => Message::FileOpReply(
+            channel_id,
+            FileOpReply::Stat {
+                path,
+                is_dir: *is_dir,
+                size: *size,
+                mode: *mode as u32,
+                modified: *modified,
+            },
+        ),
+        ("delete", []) => Message::ReqFileOp(channel_id, FileOp::Delete { path }),
+        ("delete", [Value::Bool(true)]) => {
+            Message::FileOpReply(channel_id, FileOpReply::Delete { path })
+        }
+        ("mkdir", []) => Message::ReqFileOp(channel_id, FileOp::Mkdir { path }),
+        ("mkdir", [Value::Bool(true)]) => {
+            Message::FileOpReply(channel_id, FileOpReply::Mkdir { path })
+        }
+        ("move", [Value::String(to)]) => Message::ReqFileOp(
+            channel_id,
+            FileOp::Move {
+                from: path,
+                to: to.to_owned(),
+            },
+        ),
+        ("move", [Value::String(to), Value::Bool(true)]) => Message::FileOpReply(
+            channel_id,
+            FileOpReply::Move {
+                from: path,
+                to: to.to_owned(),
+            },
+        ),
+        ("df", []) => Message::ReqFileOp(channel_id, FileOp::DiskUsage { path }),
+        ("df", [Value::U64(total), Value::U64(available)]) => Message::FileOpReply(
+            channel_id,
+            FileOpReply::DiskUsage {
+                path,
+                total: *total,
+                available: *available,
+            },
+        ),
+        _ => {
+            return Err(ProtocolError::InvalidParam(
+                op.to_owned(),
+                "arguments".to_owned(),
+            ));
+        }
+    };
+
+    Ok(Some(message))
+}
+
 // Parse out success received message
 // { channel_id, true }
 pub fn parse_success_receive(
@@ -367,7 +713,7 @@ pub fn parse_nak(
 }
 
 // Parse out chunk
-// { hash, chunk_index, data }
+// { hash, chunk_index, data [, compression] }
 pub fn parse_chunk(
     channel_id: u32,
     mut pieces: Iter<Value>,
@@ -376,11 +722,12 @@ pub fn parse_chunk(
         if let Some(Value::U64(num)) = pieces.next() {
             if let Some(third_param) = pieces.next() {
                 if let Value::Bytes(data) = third_param {
+                    let compression = parse_compression("chunk", pieces.next())?;
                     return Ok(Some(Message::ReceiveChunk(
                         channel_id,
                         hash.to_owned(),
                         *num as u32,
-                        data.to_vec(),
+                        compression.decompress(data)?,
                     )));
                 } else {
                     return Err(ProtocolError::InvalidParam(
@@ -395,6 +742,20 @@ pub fn parse_chunk(
     Ok(None)
 }
 
+// Parse out the compression scheme which may end a message
+fn parse_compression(message: &str, piece: Option<&Value>) -> Result<Compression, ProtocolError> {
+    match piece {
+        Some(Value::String(name)) => name
+            .parse::<Compression>()
+            .map_err(|_| ProtocolError::InvalidParam(message.to_owned(), "compression".to_owned())),
+        None | Some(Value::Null) => Ok(Compression::None),
+        _ => Err(ProtocolError::InvalidParam(
+            message.to_owned(),
+            "compression".to_owned(),
+        )),
+    }
+}
+
 // Parse out sync
 // { hash, num_chunks }
 // or
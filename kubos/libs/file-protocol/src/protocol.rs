@@ -16,9 +16,13 @@
 
 //! File transfer protocol module
 
+use super::compression::Compression;
+use super::delta::Patch;
 use super::messages;
+use super::ops::{FileOp, FileOpReply};
 use super::parsers;
 use super::storage;
+use super::storage::TransferStatus;
 use super::Message;
 use crate::error::ProtocolError;
 use cbor_protocol::Protocol as CborProtocol;
@@ -26,10 +30,11 @@ use log::{error, info, warn};
 use rand::{self, Rng};
 use serde_cbor::Value;
 use std::cell::Cell;
+use std::cmp;
 use std::net::SocketAddr;
 use std::str;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Configuration data for Protocol
 #[derive(Clone)]
@@ -47,6 +52,10 @@ pub struct ProtocolConfig {
     max_chunks_transmit: Option<u32>,
     // Chunk size used in storage hashing
     hash_chunk_size: usize,
+    // Compression to use for chunks we send or request
+    compression: Compression,
+    // Max average rate, in bytes per second, at which to transmit chunks
+    max_bandwidth: Option<u32>,
 }
 
 impl ProtocolConfig {
@@ -66,14 +75,60 @@ impl ProtocolConfig {
             inter_chunk_delay: Duration::from_millis(inter_chunk_delay),
             max_chunks_transmit,
             hash_chunk_size,
+            compression: Compression::None,
+            max_bandwidth: None,
         }
     }
+
+    /// Compress the chunks of files we upload, and ask for the chunks of files we
+    /// download to be compressed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048)
+    ///     .with_compression(Compression::Deflate);
+    /// ```
+    ///
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Limit the average rate at which chunks are transmitted, leaving room on a shared
+    /// link for other traffic
+    ///
+    /// Chunks are held back as needed to stay under the limit, in addition to the
+    /// inter-chunk delay. A limit of `None` or zero removes the cap.
+    ///
+    /// # Arguments
+    ///
+    /// * max_bandwidth - Maximum rate, in bytes per second
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048)
+    ///     .with_max_bandwidth(Some(4096));
+    /// ```
+    ///
+    pub fn with_max_bandwidth(mut self, max_bandwidth: Option<u32>) -> Self {
+        self.max_bandwidth = max_bandwidth.filter(|rate| *rate > 0);
+        self
+    }
 }
 
 /// File protocol information structure
 pub struct Protocol {
     cbor_proto: CborProtocol,
     remote_addr: Cell<SocketAddr>,
+    // Compression used for the chunks we send. Set by the remote target when it
+    // requests a file from us
+    compression: Cell<Compression>,
     config: ProtocolConfig,
 }
 
@@ -102,6 +157,8 @@ pub enum State {
         path: String,
         /// File mode
         mode: Option<u32>,
+        /// Patch to apply to an existing file, if the file being received is a patch
+        patch: Option<Patch>,
     },
     /// All file chunks have been received
     ReceivingDone {
@@ -113,6 +170,8 @@ pub enum State {
         path: String,
         /// File mode
         mode: Option<u32>,
+        /// Patch to apply to an existing file, if the file being received is a patch
+        patch: Option<Patch>,
     },
     /// Currenty transmitting a file
     Transmitting,
@@ -160,6 +219,7 @@ impl Protocol {
                     })
                     .unwrap(),
             ),
+            compression: Cell::new(config.compression),
             config,
         }
     }
@@ -335,6 +395,59 @@ impl Protocol {
             hash,
             target_path,
             mode,
+            self.compression.get(),
+        )?)?;
+
+        Ok(())
+    }
+
+    /// Request remote target to receive a patch prepared by `initialize_patch`, apply it
+    /// to its copy of the patch's base file, and save the result to `target_path`
+    ///
+    /// The remote target checks the file it produces against the patch's `file_hash`
+    /// before saving it, so the target file is left as it was if the patch doesn't apply.
+    /// As with `send_export`, the patch's metadata should be sent first.
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * patch - Patch to upload
+    /// * target_path - Destination file path, which may be the patch's base file
+    /// * mode - File mode
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    ///
+    /// let (patch, num_chunks, mode) = f_protocol
+    ///     .initialize_patch("old/app", "new/app", "/home/system/usr/bin/app")
+    ///     .unwrap();
+    /// let channel_id = f_protocol.generate_channel().unwrap();
+    /// f_protocol.send_metadata(channel_id, &patch.hash, num_chunks);
+    /// f_protocol.send_patch(channel_id, &patch, "/home/system/usr/bin/app", mode);
+    /// ```
+    ///
+    pub fn send_patch(
+        &self,
+        channel_id: u32,
+        patch: &Patch,
+        target_path: &str,
+        mode: u32,
+    ) -> Result<(), ProtocolError> {
+        self.send(&messages::patch_request(
+            channel_id,
+            patch,
+            target_path,
+            mode,
+            self.compression.get(),
         )?)?;
 
         Ok(())
@@ -363,10 +476,315 @@ impl Protocol {
     /// ```
     ///
     pub fn send_import(&self, channel_id: u32, source_path: &str) -> Result<(), ProtocolError> {
-        self.send(&messages::import_request(channel_id, source_path)?)?;
+        self.send(&messages::import_request(
+            channel_id,
+            source_path,
+            None,
+            self.config.compression,
+        )?)?;
+        Ok(())
+    }
+
+    /// Request a file from a remote target, continuing an earlier request for it
+    ///
+    /// If the remote target still holds all of the chunks it prepared for the file's hash,
+    /// it sends them without needing the source file. Otherwise the file is prepared again.
+    /// Only the chunks missing from local temporary storage are then requested, as with
+    /// `send_import`.
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * source_path - File remote target should send
+    /// * hash - BLAKE2s hash of file, as given in the remote target's reply to the earlier request
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    /// let channel_id = f_protocol.generate_channel().unwrap();
+    ///
+    /// f_protocol.send_resume_import(channel_id, "service.txt", "a8e5ea1eb44c2d0ec8f2da2e9cb8c9d2");
+    /// ```
+    ///
+    pub fn send_resume_import(
+        &self,
+        channel_id: u32,
+        source_path: &str,
+        hash: &str,
+    ) -> Result<(), ProtocolError> {
+        self.send(&messages::import_request(
+            channel_id,
+            source_path,
+            Some(hash),
+            self.config.compression,
+        )?)?;
         Ok(())
     }
 
+    /// Request remote target to receive a file which was prepared by an earlier call to
+    /// `initialize_file`, without needing the source file
+    ///
+    /// The remote target only asks for the chunks it is missing, so an interrupted upload
+    /// continues where it left off.
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * hash - BLAKE2s hash of file
+    /// * target_path - Destination file path
+    /// * mode - File mode
+    ///
+    /// # Errors
+    ///
+    /// - If any chunks of the file are missing from local temporary storage, it will return
+    ///   `ProtocolError::IncompleteFile`
+    /// - If this function encounters any other errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    /// let channel_id = f_protocol.generate_channel().unwrap();
+    ///
+    /// f_protocol.send_resume_export(channel_id, "a8e5ea1eb44c2d0ec8f2da2e9cb8c9d2", "final/dir/service.txt", 0o644);
+    /// ```
+    ///
+    pub fn send_resume_export(
+        &self,
+        channel_id: u32,
+        hash: &str,
+        target_path: &str,
+        mode: u32,
+    ) -> Result<(), ProtocolError> {
+        let status = self.local_status(hash)?;
+        let num_chunks = match status.num_chunks {
+            Some(num_chunks) if status.missing.is_empty() => num_chunks,
+            _ => return Err(ProtocolError::IncompleteFile(hash.to_owned())),
+        };
+
+        self.send_metadata(channel_id, hash, num_chunks)?;
+        self.send_export(channel_id, hash, target_path, mode)
+    }
+
+    /// Report how much of a file is present in local temporary storage
+    ///
+    /// # Arguments
+    ///
+    /// * hash - BLAKE2s hash of file
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    pub fn local_status(&self, hash: &str) -> Result<TransferStatus, ProtocolError> {
+        storage::transfer_status(&self.config.storage_prefix, hash)
+    }
+
+    /// List the transfers held in local temporary storage, most recently changed first
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    pub fn local_transfers(&self) -> Result<Vec<TransferStatus>, ProtocolError> {
+        storage::list_transfers(&self.config.storage_prefix)
+    }
+
+    /// Ask a remote target how much of a file is present in its temporary storage
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * hash - BLAKE2s hash of file
+    /// * timeout - Maximum time to wait for the reply
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    /// use std::time::Duration;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    /// let channel_id = f_protocol.generate_channel().unwrap();
+    ///
+    /// let status = f_protocol
+    ///     .request_status(channel_id, "a8e5ea1eb44c2d0ec8f2da2e9cb8c9d2", Duration::from_secs(2))
+    ///     .unwrap();
+    /// println!("{} of {:?} chunks present", status.chunks_present, status.num_chunks);
+    /// ```
+    ///
+    pub fn request_status(
+        &self,
+        channel_id: u32,
+        hash: &str,
+        timeout: Duration,
+    ) -> Result<TransferStatus, ProtocolError> {
+        self.send(&messages::status_request(channel_id, hash)?)?;
+
+        match self.recv_reply(channel_id, timeout)? {
+            Message::Status(_, status) => Ok(status),
+            _ => Err(ProtocolError::MessageParseError {
+                err: "Reply was not a status message".to_owned(),
+            }),
+        }
+    }
+
+    /// Ask a remote target for the transfers held in its temporary storage, most recently
+    /// changed first
+    ///
+    /// The listing is cut short if it won't fit in a single message.
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * timeout - Maximum time to wait for the reply
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    pub fn request_transfers(
+        &self,
+        channel_id: u32,
+        timeout: Duration,
+    ) -> Result<Vec<TransferStatus>, ProtocolError> {
+        self.send(&messages::transfers_request(channel_id)?)?;
+
+        match self.recv_reply(channel_id, timeout)? {
+            Message::Transfers(_, transfers) => Ok(transfers),
+            _ => Err(ProtocolError::MessageParseError {
+                err: "Reply was not a transfers message".to_owned(),
+            }),
+        }
+    }
+
+    /// Ask a remote target to carry out a file system operation
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID used for transaction
+    /// * op - Operation to carry out
+    /// * timeout - Maximum time to wait for the reply
+    ///
+    /// # Errors
+    ///
+    /// - If the remote target refuses or fails to carry out the operation, it will return
+    ///   `ProtocolError::TransmissionError` with the reason
+    /// - If this function encounters any other errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    /// use std::time::Duration;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    /// let channel_id = f_protocol.generate_channel().unwrap();
+    ///
+    /// let op = FileOp::List { path: "/home/kubos".to_owned() };
+    /// if let Ok(FileOpReply::List { entries, .. }) =
+    ///     f_protocol.request_file_op(channel_id, &op, Duration::from_secs(2))
+    /// {
+    ///     for entry in entries {
+    ///         println!("{}", entry.name);
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn request_file_op(
+        &self,
+        channel_id: u32,
+        op: &FileOp,
+        timeout: Duration,
+    ) -> Result<FileOpReply, ProtocolError> {
+        self.send(&messages::file_op_request(channel_id, op)?)?;
+
+        match self.recv_reply(channel_id, timeout)? {
+            Message::FileOpReply(_, reply) => Ok(reply),
+            _ => Err(ProtocolError::MessageParseError {
+                err: "Reply was not a file operation result".to_owned(),
+            }),
+        }
+    }
+
+    /// Send the result of a file system operation requested by the remote target
+    ///
+    /// Directory listings are cut short if they won't fit in a single message.
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID of the request
+    /// * reply - Result of the operation
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    pub fn send_file_op_reply(
+        &self,
+        channel_id: u32,
+        reply: &FileOpReply,
+    ) -> Result<(), ProtocolError> {
+        self.send(&messages::file_op_reply(
+            channel_id,
+            reply,
+            self.config.transfer_chunk_size,
+        )?)
+    }
+
+    /// Tell the remote target that its request has failed
+    ///
+    /// # Arguments
+    ///
+    /// * channel_id - Channel ID of the request
+    /// * error - Reason for the failure
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    pub fn send_failure(&self, channel_id: u32, error: &str) -> Result<(), ProtocolError> {
+        self.send(&messages::operation_failure(channel_id, error)?)
+    }
+
+    // Wait for the reply to a request, skipping messages left over from other transactions
+    fn recv_reply(&self, channel_id: u32, timeout: Duration) -> Result<Message, ProtocolError> {
+        loop {
+            let message = self.recv(Some(timeout))?;
+            if parsers::parse_channel_id(&message)? != channel_id {
+                continue;
+            }
+
+            return match parsers::parse_message(message)? {
+                Message::Failure(channel_id, error_message) => {
+                    info!("<- {{ {}, false, {} }}", channel_id, error_message);
+                    Err(ProtocolError::TransmissionError {
+                        channel_id,
+                        error_message,
+                    })
+                }
+                reply => Ok(reply),
+            };
+        }
+    }
+
     /// Prepare a file for transfer
     ///
     /// Imports the file into temporary storage and calculates the BLAKE2s hash
@@ -401,6 +819,86 @@ impl Protocol {
         )
     }
 
+    /// Prepare a patch which turns a copy of the remote target's file at `remote_base_path`
+    /// into the file at `source_path`, so that only the differences need to be sent
+    ///
+    /// Returns the patch, the number of chunks it was split into, and the source file's mode
+    ///
+    /// # Arguments
+    ///
+    /// * base_path - Local copy of the remote target's file
+    /// * source_path - New version of the file
+    /// * remote_base_path - Path of the file the patch applies to, on the remote target
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any errors, it will return an error message string
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use file_protocol::*;
+    ///
+    /// let config = FileProtocolConfig::new(None, 1024, 5, 1, None, 2048);
+    /// let f_protocol = FileProtocol::new("0.0.0.0:8000", "0.0.0.0:7000", config);
+    ///
+    /// let (_patch, _num_chunks, _mode) = f_protocol
+    ///     .initialize_patch("old/app", "new/app", "/home/system/usr/bin/app")
+    ///     .unwrap();
+    /// ```
+    ///
+    pub fn initialize_patch(
+        &self,
+        base_path: &str,
+        source_path: &str,
+        remote_base_path: &str,
+    ) -> Result<(Patch, u32, u32), ProtocolError> {
+        storage::initialize_patch(
+            &self.config.storage_prefix,
+            base_path,
+            source_path,
+            remote_base_path,
+            self.config.transfer_chunk_size,
+        )
+    }
+
+    // Handle a request to receive a file.
+    // See what state the file is currently in on our side, and ask for any missing chunks
+    fn start_receive(
+        &self,
+        channel_id: u32,
+        hash: &str,
+        path: &str,
+        mode: Option<u32>,
+        patch: Option<Patch>,
+    ) -> Result<State, ProtocolError> {
+        match storage::validate_file(&self.config.storage_prefix, hash, None)? {
+            (true, _) => {
+                // We've already got all the file data in temporary storage
+                self.send(&messages::ack(channel_id, hash, None)?)?;
+
+                Ok(State::ReceivingDone {
+                    channel_id,
+                    hash: hash.to_string(),
+                    path: path.to_string(),
+                    mode,
+                    patch,
+                })
+            }
+            (false, chunks) => {
+                // We're missing some number of data chunks of the requrested file
+                self.send(&messages::nak(channel_id, hash, &chunks)?)?;
+                Ok(State::Receiving {
+                    channel_id,
+                    hash: hash.to_string(),
+                    path: path.to_string(),
+                    mode,
+                    patch,
+                })
+            }
+        }
+    }
+
     // Verify the integrity of received file data and then transfer into the requested permanent file location.
     // Notify the connection peer of the results
     //
@@ -408,26 +906,56 @@ impl Protocol {
     //     a) All of the chunks of a file have been received
     //     b) That the calculated hash of said chunks matches the expected hash
     //
+    // If the file received is a patch, it's applied first, and the file it produces is
+    // verified and transferred instead. The target is untouched if the patch doesn't apply,
+    // and the file it produced is removed from storage
+    //
     fn finalize_file(
         &self,
         channel_id: u32,
         hash: &str,
         target_path: &str,
         mode: Option<u32>,
+        patch: Option<&Patch>,
     ) -> Result<(), ProtocolError> {
-        match storage::finalize_file(
-            &self.config.storage_prefix,
-            hash,
-            target_path,
-            mode,
-            self.config.hash_chunk_size,
-        ) {
+        let result = match patch {
+            Some(patch) => storage::apply_patch(
+                &self.config.storage_prefix,
+                patch,
+                self.config.transfer_chunk_size,
+            )
+            .and_then(|_| {
+                storage::finalize_file(
+                    &self.config.storage_prefix,
+                    &patch.file_hash,
+                    target_path,
+                    mode,
+                    self.config.hash_chunk_size,
+                )
+            }),
+            None => storage::finalize_file(
+                &self.config.storage_prefix,
+                hash,
+                target_path,
+                mode,
+                self.config.hash_chunk_size,
+            ),
+        };
+
+        match result {
             Ok(_) => {
                 self.send(&messages::operation_success(channel_id, hash)?)?;
                 storage::delete_file(&self.config.storage_prefix, hash)?;
+                if let Some(patch) = patch {
+                    storage::delete_file(&self.config.storage_prefix, &patch.file_hash)?;
+                }
                 Ok(())
             }
             Err(e) => {
+                if let Some(patch) = patch {
+                    // The patched file isn't stored if the patch failed to apply
+                    let _ = storage::delete_file(&self.config.storage_prefix, &patch.file_hash);
+                }
                 self.send(&messages::operation_failure(channel_id, &format!("{}", e))?)?;
                 Err(e)
             }
@@ -446,11 +974,23 @@ impl Protocol {
         hash: &str,
         chunks: &[(u32, u32)],
     ) -> Result<(), ProtocolError> {
+        let start = Instant::now();
+        let mut bytes_transmitted = 0;
         let mut chunks_transmitted = 0;
         for (first, last) in chunks {
             for chunk_index in *first..*last {
                 match storage::load_chunk(&self.config.storage_prefix, hash, chunk_index) {
-                    Ok(c) => self.send(&messages::chunk(channel_id, hash, chunk_index, &c)?)?,
+                    Ok(c) => {
+                        let message = messages::chunk(
+                            channel_id,
+                            hash,
+                            chunk_index,
+                            &c,
+                            self.compression.get(),
+                        )?;
+                        self.send(&message)?;
+                        bytes_transmitted += message.len() as u64;
+                    }
                     Err(e) => {
                         warn!("Failed to load chunk {}:{} : {}", hash, chunk_index, e);
                         storage::delete_file(&self.config.storage_prefix, hash)?;
@@ -464,12 +1004,29 @@ impl Protocol {
                     }
                 }
 
-                thread::sleep(self.config.inter_chunk_delay);
+                thread::sleep(self.chunk_delay(start.elapsed(), bytes_transmitted));
             }
         }
         Ok(())
     }
 
+    // Time to wait before sending the next chunk. Chunks are always at least the
+    // inter-chunk delay apart, and are held back further if they've gotten ahead of
+    // the bandwidth cap
+    fn chunk_delay(&self, elapsed: Duration, bytes_transmitted: u64) -> Duration {
+        match self.config.max_bandwidth {
+            Some(max_bandwidth) => {
+                let due =
+                    Duration::from_micros(bytes_transmitted * 1_000_000 / u64::from(max_bandwidth));
+                cmp::max(
+                    self.config.inter_chunk_delay,
+                    due.checked_sub(elapsed).unwrap_or_default(),
+                )
+            }
+            None => self.config.inter_chunk_delay,
+        }
+    }
+
     /// Listen for and process file protocol messages
     ///
     /// # Arguments
@@ -525,6 +1082,7 @@ impl Protocol {
                         hash,
                         path,
                         mode,
+                        patch,
                     } => {
                         match storage::validate_file(&self.config.storage_prefix, &hash, None) {
                             Ok((true, _)) => {
@@ -534,6 +1092,7 @@ impl Protocol {
                                     hash: hash.clone(),
                                     path: path.clone(),
                                     mode,
+                                    patch: patch.clone(),
                                 };
                             }
                             Ok((false, chunks)) => {
@@ -547,7 +1106,7 @@ impl Protocol {
                             Err(e) => return Err(e),
                         };
 
-                        match self.finalize_file(channel_id, &hash, &path, mode) {
+                        match self.finalize_file(channel_id, &hash, &path, mode, patch.as_ref()) {
                             Ok(_) => {
                                 return Ok(());
                             }
@@ -567,10 +1126,11 @@ impl Protocol {
                         hash,
                         path,
                         mode,
+                        patch,
                     } => {
                         // We've got all the chunks of data we want.
                         // Stitch it back together and verify the hash of the official file
-                        self.finalize_file(channel_id, &hash, &path, mode)?;
+                        self.finalize_file(channel_id, &hash, &path, mode, patch.as_ref())?;
                         return Ok(());
                     }
                     State::Done => {
@@ -614,10 +1174,11 @@ impl Protocol {
                     hash,
                     path,
                     mode,
+                    patch,
                 } => {
                     // We've got all the chunks of data we want.
                     // Stitch it back together and verify the hash of the official file
-                    self.finalize_file(channel_id, &hash, &path, mode)?;
+                    self.finalize_file(channel_id, &hash, &path, mode, patch.as_ref())?;
                     return Ok(());
                 }
                 State::Done => return Ok(()),
@@ -704,42 +1265,47 @@ impl Protocol {
                 // TODO: Maybe trigger a failure?
                 state.clone()
             }
-            Message::ReqReceive(channel_id, hash, path, mode) => {
+            Message::ReqReceive(channel_id, hash, path, mode, compression) => {
                 info!(
-                    "<- {{ {}, export, {}, {}, {:?} }}",
-                    channel_id, hash, path, mode
+                    "<- {{ {}, export, {}, {}, {:?}, {} }}",
+                    channel_id, hash, path, mode, compression
                 );
-                // The client wants to send us a file.
-                // See what state the file is currently in on our side
-                match storage::validate_file(&self.config.storage_prefix, hash, None) {
-                    Ok((true, _)) => {
-                        // We've already got all the file data in temporary storage
-                        self.send(&messages::ack(*channel_id, hash, None)?)?;
-
-                        State::ReceivingDone {
-                            channel_id: *channel_id,
-                            hash: hash.to_string(),
-                            path: path.to_string(),
-                            mode: *mode,
-                        }
-                    }
-                    Ok((false, chunks)) => {
-                        // We're missing some number of data chunks of the requrested file
-                        self.send(&messages::nak(*channel_id, hash, &chunks)?)?;
-                        State::Receiving {
-                            channel_id: *channel_id,
-                            hash: hash.to_string(),
-                            path: path.to_string(),
-                            mode: *mode,
-                        }
-                    }
-                    Err(e) => return Err(e),
-                }
+                self.start_receive(*channel_id, hash, path, *mode, None)?
+            }
+            Message::ReqPatch(channel_id, patch, path, mode, compression) => {
+                info!(
+                    "<- {{ {}, patch, {}, {}, {}, {}, {}, {:?}, {} }}",
+                    channel_id,
+                    patch.hash,
+                    patch.base_hash,
+                    patch.base_path,
+                    patch.file_hash,
+                    path,
+                    mode,
+                    compression
+                );
+                // The patch itself is received like any other file, and only applied
+                // once all of it has arrived
+                self.start_receive(*channel_id, &patch.hash, path, *mode, Some(patch.clone()))?
             }
-            Message::ReqTransmit(channel_id, path) => {
-                info!("<- {{ {}, import, {} }}", channel_id, path);
-                // Set up the requested file for transmission
-                match self.initialize_file(path) {
+            Message::ReqTransmit(channel_id, path, hash, compression) => {
+                info!(
+                    "<- {{ {}, import, {}, {:?}, {} }}",
+                    channel_id, path, hash, compression
+                );
+                // Send the chunks the way the requester asked for them
+                self.compression.set(*compression);
+                // Set up the requested file for transmission, reusing the chunks from an
+                // earlier request if they're all still in storage
+                let stored = match hash {
+                    Some(hash) => storage::stored_file(&self.config.storage_prefix, hash, path)
+                        .unwrap_or_else(|error| {
+                            warn!("Failed to check stored chunks of {}: {}", hash, error);
+                            None
+                        }),
+                    None => None,
+                };
+                match stored.map_or_else(|| self.initialize_file(path), Ok) {
                     Ok((hash, num_chunks, mode)) => {
                         // It worked, let the requester know we're ready to send
                         self.send(&messages::import_setup_success(
@@ -789,6 +1355,7 @@ impl Protocol {
                                 hash: hash.to_string(),
                                 path,
                                 mode: *mode,
+                                patch: None,
                             },
                             _ => State::Done,
                         }
@@ -801,6 +1368,7 @@ impl Protocol {
                                 hash: hash.to_string(),
                                 path,
                                 mode: *mode,
+                                patch: None,
                             },
                             _ => state.clone(),
                         }
@@ -825,6 +1393,60 @@ impl Protocol {
                 storage::delete_storage(&self.config.storage_prefix)?;
                 State::Done
             }
+            Message::ReqStatus(channel_id, hash) => {
+                info!("<- {{ {}, status, {} }}", channel_id, hash);
+                match storage::transfer_status(&self.config.storage_prefix, hash) {
+                    Ok(status) => self.send(&messages::status(*channel_id, &status)?)?,
+                    Err(error) => self.send(&messages::operation_failure(
+                        *channel_id,
+                        &format!("{}", error),
+                    )?)?,
+                }
+                State::Done
+            }
+            Message::Status(channel_id, status) => {
+                info!(
+                    "<- {{ {}, status, {}, {:?}, {}, {:?} }}",
+                    channel_id,
+                    status.hash,
+                    status.num_chunks,
+                    status.chunks_present,
+                    status.missing
+                );
+                state.clone()
+            }
+            Message::ReqTransfers(channel_id) => {
+                info!("<- {{ {}, transfers }}", channel_id);
+                match storage::list_transfers(&self.config.storage_prefix) {
+                    Ok(transfers) => self.send(&messages::transfers(
+                        *channel_id,
+                        &transfers,
+                        self.config.transfer_chunk_size,
+                    )?)?,
+                    Err(error) => self.send(&messages::operation_failure(
+                        *channel_id,
+                        &format!("{}", error),
+                    )?)?,
+                }
+                State::Done
+            }
+            Message::ReqFileOp(channel_id, op) => {
+                info!("<- {{ {}, {:?} }}", channel_id, op);
+                // File system operations are carried out by the service, not the protocol
+                self.send(&messages::operation_failure(
+                    *channel_id,
+                    "File operations are not supported",
+                )?)?;
+                State::Done
+            }
+            Message::FileOpReply(channel_id, reply) => {
+                info!("<- {{ {}, {:?} }}", channel_id, reply);
+                state.clone()
+            }
+            Message::Transfers(channel_id, transfers) => {
+                info!("<- {{ {}, transfers, {} }}", channel_id, transfers.len());
+                state.clone()
+            }
         };
         Ok(new_state)
     }
@@ -14,6 +14,7 @@
 // limitations under the License.
 //
 
+use crate::delta::{self, Patch};
 use crate::error::ProtocolError;
 use blake2_rfc::blake2s::Blake2s;
 use log::warn;
@@ -21,16 +22,30 @@ use serde_cbor::{de, to_vec, Value};
 use std::fs;
 use std::fs::File;
 use std::fs::Permissions;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
 use std::os::unix::fs::MetadataExt;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::str;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 const HASH_SIZE: usize = 16;
 
+/// How much of a file is present in temporary storage
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransferStatus {
+    /// BLAKE2s hash of the file
+    pub hash: String,
+    /// Total number of chunks in the file, if known
+    pub num_chunks: Option<u32>,
+    /// Number of chunks present in storage
+    pub chunks_present: u32,
+    /// Ranges of missing chunks, first inclusive and last exclusive. Only the first
+    /// ranges are reported, and none are reported in transfer listings
+    pub missing: Vec<(u32, u32)>,
+}
+
 // Save new chunk in a temporary storage file
 pub fn store_chunk(prefix: &str, hash: &str, index: u32, data: &[u8]) -> Result<(), ProtocolError> {
     let file_name = format!("{}", index);
@@ -249,6 +264,117 @@ pub fn validate_file(
     Ok((missing_ranges.is_empty(), missing_ranges))
 }
 
+// Numbers of the chunks of a file present in its temporary storage directory
+fn stored_chunks(prefix: &str, hash: &str) -> Result<Vec<u32>, ProtocolError> {
+    let hash_path = Path::new(&format!("{}/storage", prefix)).join(hash);
+
+    let entries = fs::read_dir(hash_path.clone()).map_err(|err| ProtocolError::StorageError {
+        action: format!("read {:?} directory", hash_path),
+        err,
+    })?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|name| name.parse::<u32>().ok())
+        .collect())
+}
+
+// Report how much of a file is present in temporary storage.
+// A file with no storage directory is reported as having no chunks
+pub fn transfer_status(prefix: &str, hash: &str) -> Result<TransferStatus, ProtocolError> {
+    let mut status = TransferStatus {
+        hash: hash.to_owned(),
+        num_chunks: None,
+        chunks_present: 0,
+        missing: vec![],
+    };
+
+    if !Path::new(&format!("{}/storage", prefix))
+        .join(hash)
+        .is_dir()
+    {
+        return Ok(status);
+    }
+
+    status.chunks_present = stored_chunks(prefix, hash)?.len() as u32;
+
+    // Chunks can arrive before the metadata, in which case we can't tell what's missing
+    if let Ok(num_chunks) = load_meta(prefix, hash) {
+        let (_, missing) = validate_file(prefix, hash, None)?;
+        status.num_chunks = Some(num_chunks);
+        status.missing = missing
+            .chunks_exact(2)
+            .map(|range| (range[0], range[1]))
+            .collect();
+    }
+
+    Ok(status)
+}
+
+// List the files in temporary storage, most recently changed first
+pub fn list_transfers(prefix: &str) -> Result<Vec<TransferStatus>, ProtocolError> {
+    let storage_path = format!("{}/storage", prefix);
+
+    let entries = match fs::read_dir(&storage_path) {
+        Ok(entries) => entries,
+        // Nothing has been transferred yet
+        Err(ref err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => {
+            return Err(ProtocolError::StorageError {
+                action: format!("read {} directory", storage_path),
+                err,
+            });
+        }
+    };
+
+    // Temporary copies made while initializing a file are hidden, so skip them
+    let mut dirs: Vec<(SystemTime, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') {
+                None
+            } else {
+                Some((modified, name))
+            }
+        })
+        .collect();
+
+    dirs.sort_by(|a, b| b.cmp(a));
+
+    dirs.iter()
+        .map(|(_, hash)| {
+            transfer_status(prefix, hash).map(|mut status| {
+                status.missing.clear();
+                status
+            })
+        })
+        .collect()
+}
+
+// Look up a file whose chunks are all still in temporary storage from an earlier
+// transfer, so that it can be sent again without the source file.
+// Returns the same values as `initialize_file`, or `None` if any chunks are missing
+pub fn stored_file(
+    prefix: &str,
+    hash: &str,
+    source_path: &str,
+) -> Result<Option<(String, u32, u32)>, ProtocolError> {
+    let status = transfer_status(prefix, hash)?;
+    match status.num_chunks {
+        Some(num_chunks) if status.missing.is_empty() => {
+            let mode = fs::metadata(source_path)
+                .map(|meta| meta.mode())
+                .unwrap_or(0o644);
+            Ok(Some((hash.to_owned(), num_chunks, mode)))
+        }
+        _ => Ok(None),
+    }
+}
+
 /// Create temporary folder for chunks
 /// Stream copy file from mutable space to immutable space
 /// Move folder to hash of contents
@@ -396,6 +522,103 @@ pub fn finalize_file(
     }
 }
 
+// Store data which is already in memory as a file ready for transfer.
+// Returns the number of chunks
+fn store_data(
+    prefix: &str,
+    hash: &str,
+    data: &[u8],
+    transfer_chunk_size: usize,
+) -> Result<u32, ProtocolError> {
+    let mut num_chunks = 0;
+    for chunk in data.chunks(transfer_chunk_size) {
+        store_chunk(prefix, hash, num_chunks, chunk)?;
+        num_chunks += 1;
+    }
+    store_meta(prefix, hash, num_chunks)?;
+
+    Ok(num_chunks)
+}
+
+// Load a file whose chunks are all in temporary storage
+fn load_data(prefix: &str, hash: &str) -> Result<Vec<u8>, ProtocolError> {
+    let (result, _) = validate_file(prefix, hash, None)?;
+    if !result {
+        return Err(ProtocolError::IncompleteFile(hash.to_owned()));
+    }
+
+    let mut data = vec![];
+    for chunk_num in 0..load_meta(prefix, hash)? {
+        data.append(&mut load_chunk(prefix, hash, chunk_num)?);
+    }
+
+    Ok(data)
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, ProtocolError> {
+    fs::read(path).map_err(|err| ProtocolError::StorageError {
+        action: format!("read {}", path),
+        err,
+    })
+}
+
+/// Create a patch which turns the file at `base_path` into the file at `source_path`
+/// Import the patch into chunked storage for transfer
+/// The patch is to be applied to the copy of the base file at `remote_base_path`
+pub fn initialize_patch(
+    prefix: &str,
+    base_path: &str,
+    source_path: &str,
+    remote_base_path: &str,
+    transfer_chunk_size: usize,
+) -> Result<(Patch, u32, u32), ProtocolError> {
+    let base = read_file(base_path)?;
+    let source = read_file(source_path)?;
+
+    let data = delta::diff(&base, &source)?;
+    let patch = Patch {
+        hash: calc_hash(&data),
+        base_hash: calc_hash(&base),
+        base_path: remote_base_path.to_owned(),
+        file_hash: calc_hash(&source),
+        file_len: source.len() as u64,
+    };
+    let num_chunks = store_data(prefix, &patch.hash, &data, transfer_chunk_size)?;
+
+    let mode = fs::metadata(source_path)
+        .map(|meta| meta.mode())
+        .unwrap_or(0o644);
+
+    Ok((patch, num_chunks, mode))
+}
+
+// Apply a received patch, and store the file it produces in temporary storage, ready
+// to be finalized. The base file and the patched file must match the patch's hashes
+pub fn apply_patch(
+    prefix: &str,
+    patch: &Patch,
+    transfer_chunk_size: usize,
+) -> Result<(), ProtocolError> {
+    let data = load_data(prefix, &patch.hash)?;
+
+    let base = read_file(&patch.base_path)?;
+    if calc_hash(&base) != patch.base_hash {
+        return Err(ProtocolError::InvalidPatch(format!(
+            "{} is not the file the patch applies to",
+            patch.base_path
+        )));
+    }
+
+    let file = delta::apply(&base, &data, patch.file_len)?;
+    if calc_hash(&file) != patch.file_hash {
+        return Err(ProtocolError::HashMismatch);
+    }
+
+    store_data(prefix, &patch.file_hash, &file, transfer_chunk_size)?;
+
+    Ok(())
+}
+
 pub fn delete_chunk(prefix: &str, hash: &str, index: u32) -> Result<(), ProtocolError> {
     let path = Path::new(&format!("{}/storage", prefix))
         .join(hash)
@@ -430,6 +653,18 @@ pub fn delete_storage(prefix: &str) -> Result<(), ProtocolError> {
     Ok(())
 }
 
+/// Calculate the blake2s hash of data in memory, matching `calc_file_hash`
+fn calc_hash(data: &[u8]) -> String {
+    let mut hasher = Blake2s::new(HASH_SIZE);
+    hasher.update(data);
+
+    hasher
+        .finalize()
+        .as_bytes()
+        .iter()
+        .fold(String::new(), |acc, val| format!("{}{:02x}", acc, val))
+}
+
 /// Calculate the blake2s hash for a file at given path
 fn calc_file_hash(path: &str, hash_chunk_size: usize) -> Result<String, ProtocolError> {
     let mut hasher = Blake2s::new(HASH_SIZE);
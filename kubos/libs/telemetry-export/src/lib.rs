@@ -0,0 +1,455 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Compact binary format for telemetry exported by the telemetry service
+//!
+//! An export holds a table of the subsystem/parameter pairs it contains, followed by the
+//! points themselves. Each point refers to its parameter by index into the table, stores its
+//! timestamp as the difference from the previous point's, and stores typed values in binary
+//! rather than as text.
+//!
+//! ```text
+//! export    = "KTLM" version:u8 count:varint parameter* count:varint point*
+//! parameter = subsystem:text parameter:text
+//! point     = id:varint delta:zigzag-varint tag:u8 value [text]
+//! text      = length:varint utf-8 bytes
+//! ```
+//!
+//! Timestamps are kept to the nearest microsecond. The low four bits of a point's tag give
+//! the type of its value: `0` text, `1` integer (zigzag varint), `2` float (little-endian
+//! f64), `3` false or `4` true. If the `0x10` bit is set, a typed value is followed by the
+//! text it was received as, for values whose text is not the canonical form of the number.
+//!
+//! # Examples
+//!
+//! ```
+//! use telemetry_export::*;
+//!
+//! let points = vec![Point {
+//!     timestamp: 1000.5,
+//!     subsystem: "eps".to_owned(),
+//!     parameter: "voltage".to_owned(),
+//!     value: "3.3".to_owned(),
+//!     int_value: None,
+//!     float_value: Some(3.3),
+//!     bool_value: None,
+//! }];
+//!
+//! let data = encode(&points);
+//! assert_eq!(decode(&data).unwrap(), points);
+//! ```
+//!
+
+#![deny(missing_docs)]
+#![deny(warnings)]
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// First bytes of every export
+pub const MAGIC: &[u8; 4] = b"KTLM";
+/// Version of the format written by [`encode`]
+pub const VERSION: u8 = 1;
+
+const TAG_TEXT: u8 = 0;
+const TAG_INTEGER: u8 = 1;
+const TAG_FLOAT: u8 = 2;
+const TAG_FALSE: u8 = 3;
+const TAG_TRUE: u8 = 4;
+const TAG_TYPE_MASK: u8 = 0x0F;
+const FLAG_TEXT: u8 = 0x10;
+
+/// An error encountered while decoding an export
+#[derive(Debug, Error, PartialEq)]
+pub enum ExportError {
+    /// The data does not start with the export magic bytes
+    #[error("Not a telemetry export")]
+    BadMagic,
+    /// The export was written in a different version of the format
+    #[error("Unsupported export version {version}")]
+    UnsupportedVersion {
+        /// Version found in the export
+        version: u8,
+    },
+    /// The data ended part way through the export
+    #[error("Export ended unexpectedly")]
+    Truncated,
+    /// A number was too large for its field
+    #[error("Invalid number in export")]
+    BadNumber,
+    /// A point refers to a parameter missing from the parameter table
+    #[error("Invalid parameter id {id}")]
+    BadParameter {
+        /// Parameter id found in the point
+        id: u64,
+    },
+    /// A point's value has an unknown type
+    #[error("Invalid value tag {tag}")]
+    BadTag {
+        /// Tag found in the point
+        tag: u8,
+    },
+    /// Text in the export is not valid UTF-8
+    #[error("Invalid text in export")]
+    BadText,
+    /// There is data after the last point
+    #[error("{count} unexpected bytes after the last point")]
+    TrailingData {
+        /// Number of bytes left over
+        count: usize,
+    },
+}
+
+/// One telemetry entry, with the typed form of its value if its parameter has one
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Point {
+    /// Timestamp
+    pub timestamp: f64,
+    /// Subsystem name
+    pub subsystem: String,
+    /// Telemetry parameter
+    pub parameter: String,
+    /// Value as it was received
+    pub value: String,
+    /// Value of a parameter defined as an integer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub int_value: Option<i64>,
+    /// Value of a parameter defined as a float
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_value: Option<f64>,
+    /// Value of a parameter defined as a boolean
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bool_value: Option<bool>,
+}
+
+/// Encode points in the binary export format
+///
+/// Points keep their order. A point should have at most one typed value; if it has more,
+/// only the first of the integer, float and boolean values is kept.
+pub fn encode(points: &[Point]) -> Vec<u8> {
+    let mut ids: HashMap<(&str, &str), u64> = HashMap::new();
+    let mut parameters = vec![];
+    for point in points {
+        let key = (point.subsystem.as_str(), point.parameter.as_str());
+        ids.entry(key).or_insert_with(|| {
+            parameters.push(key);
+            parameters.len() as u64 - 1
+        });
+    }
+
+    let mut data = MAGIC.to_vec();
+    data.push(VERSION);
+
+    write_varint(&mut data, parameters.len() as u64);
+    for (subsystem, parameter) in &parameters {
+        write_text(&mut data, subsystem);
+        write_text(&mut data, parameter);
+    }
+
+    write_varint(&mut data, points.len() as u64);
+    let mut previous = 0;
+    for point in points {
+        write_varint(
+            &mut data,
+            ids[&(point.subsystem.as_str(), point.parameter.as_str())],
+        );
+
+        let micros = (point.timestamp * 1_000_000.0).round() as i64;
+        write_varint(&mut data, zigzag(micros.wrapping_sub(previous)));
+        previous = micros;
+
+        let (tag, canonical) = if let Some(value) = point.int_value {
+            (TAG_INTEGER, value.to_string())
+        } else if let Some(value) = point.float_value {
+            (TAG_FLOAT, value.to_string())
+        } else if let Some(value) = point.bool_value {
+            (if value { TAG_TRUE } else { TAG_FALSE }, value.to_string())
+        } else {
+            (TAG_TEXT, point.value.clone())
+        };
+        let with_text = tag != TAG_TEXT && canonical != point.value;
+        data.push(if with_text { tag | FLAG_TEXT } else { tag });
+
+        match tag {
+            TAG_INTEGER => write_varint(&mut data, zigzag(point.int_value.unwrap_or_default())),
+            TAG_FLOAT => {
+                data.extend_from_slice(&point.float_value.unwrap_or_default().to_le_bytes())
+            }
+            _ => (),
+        }
+        if tag == TAG_TEXT || with_text {
+            write_text(&mut data, &point.value);
+        }
+    }
+
+    data
+}
+
+/// Decode an export produced by [`encode`]
+pub fn decode(data: &[u8]) -> Result<Vec<Point>, ExportError> {
+    let mut reader = Reader { data, position: 0 };
+
+    if reader
+        .take(MAGIC.len())
+        .map_err(|_| ExportError::BadMagic)?
+        != MAGIC
+    {
+        return Err(ExportError::BadMagic);
+    }
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(ExportError::UnsupportedVersion { version });
+    }
+
+    // Counts come from the data, so capacities are bounded by its length
+    let count = reader.varint()?;
+    let mut parameters = Vec::with_capacity(count.min(data.len() as u64) as usize);
+    for _ in 0..count {
+        let subsystem = reader.text()?;
+        let parameter = reader.text()?;
+        parameters.push((subsystem, parameter));
+    }
+
+    let count = reader.varint()?;
+    let mut points = Vec::with_capacity(count.min(data.len() as u64) as usize);
+    let mut micros: i64 = 0;
+    for _ in 0..count {
+        let id = reader.varint()?;
+        let (subsystem, parameter) = parameters
+            .get(id as usize)
+            .ok_or(ExportError::BadParameter { id })?;
+
+        micros = micros.wrapping_add(unzigzag(reader.varint()?));
+
+        let tag = reader.byte()?;
+        let mut point = Point {
+            timestamp: micros as f64 / 1_000_000.0,
+            subsystem: subsystem.clone(),
+            parameter: parameter.clone(),
+            value: String::new(),
+            int_value: None,
+            float_value: None,
+            bool_value: None,
+        };
+        let canonical = match tag & TAG_TYPE_MASK {
+            TAG_TEXT if tag & FLAG_TEXT == 0 => None,
+            TAG_INTEGER => {
+                let value = unzigzag(reader.varint()?);
+                point.int_value = Some(value);
+                Some(value.to_string())
+            }
+            TAG_FLOAT => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(reader.take(8)?);
+                let value = f64::from_le_bytes(bytes);
+                point.float_value = Some(value);
+                Some(value.to_string())
+            }
+            TAG_FALSE | TAG_TRUE => {
+                let value = tag & TAG_TYPE_MASK == TAG_TRUE;
+                point.bool_value = Some(value);
+                Some(value.to_string())
+            }
+            _ => return Err(ExportError::BadTag { tag }),
+        };
+        point.value = match canonical {
+            Some(canonical) if tag & FLAG_TEXT == 0 => canonical,
+            _ => reader.text()?,
+        };
+
+        points.push(point);
+    }
+
+    match data.len() - reader.position {
+        0 => Ok(points),
+        count => Err(ExportError::TrailingData { count }),
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn write_text(data: &mut Vec<u8>, text: &str) {
+    write_varint(data, text.len() as u64);
+    data.extend_from_slice(text.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ExportError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(ExportError::Truncated)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, ExportError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, ExportError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            if shift == 63 && byte > 1 {
+                return Err(ExportError::BadNumber);
+            }
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ExportError::BadNumber)
+    }
+
+    fn text(&mut self) -> Result<String, ExportError> {
+        let length = self.varint()?;
+        let length = usize::try_from(length).map_err(|_| ExportError::BadNumber)?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ExportError::BadText)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: f64, subsystem: &str, parameter: &str, value: &str) -> Point {
+        Point {
+            timestamp,
+            subsystem: subsystem.to_owned(),
+            parameter: parameter.to_owned(),
+            value: value.to_owned(),
+            int_value: None,
+            float_value: None,
+            bool_value: None,
+        }
+    }
+
+    fn sample() -> Vec<Point> {
+        let mut points = vec![];
+        for i in 0..100 {
+            let timestamp = 1_559_594_402.0 - f64::from(i) * 0.25;
+            points.push(Point {
+                float_value: Some(3.0 + f64::from(i) / 8.0),
+                ..point(
+                    timestamp,
+                    "eps",
+                    "voltage",
+                    &(3.0 + f64::from(i) / 8.0).to_string(),
+                )
+            });
+            points.push(Point {
+                int_value: Some(-i64::from(i)),
+                ..point(timestamp, "eps", "counter", &(-i64::from(i)).to_string())
+            });
+            points.push(point(timestamp, "gps", "fix", "3D"));
+        }
+        points
+    }
+
+    #[test]
+    fn round_trip() {
+        let points = sample();
+        assert_eq!(decode(&encode(&points)).unwrap(), points);
+    }
+
+    #[test]
+    fn round_trip_non_canonical_text() {
+        let points = vec![
+            Point {
+                float_value: Some(3.0),
+                ..point(1000.0, "eps", "voltage", "3.0")
+            },
+            Point {
+                int_value: Some(7),
+                ..point(999.5, "eps", "counter", " 7")
+            },
+            Point {
+                bool_value: Some(true),
+                ..point(1_001.000_001, "eps", "charging", "TRUE")
+            },
+            point(1001.0, "eps", "mode", ""),
+        ];
+        assert_eq!(decode(&encode(&points)).unwrap(), points);
+    }
+
+    #[test]
+    fn empty_export() {
+        let data = encode(&[]);
+        assert_eq!(data, b"KTLM\x01\x00\x00");
+        assert_eq!(decode(&data).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn smaller_than_json() {
+        let points = sample();
+        let json = serde_json::to_vec(&points).unwrap();
+        assert!(encode(&points).len() * 4 < json.len());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let data = encode(&sample());
+
+        assert_eq!(decode(b"JSON[]"), Err(ExportError::BadMagic));
+        assert_eq!(decode(b"KT"), Err(ExportError::BadMagic));
+        assert_eq!(
+            decode(b"KTLM\x02\x00\x00"),
+            Err(ExportError::UnsupportedVersion { version: 2 })
+        );
+        assert_eq!(decode(&data[..data.len() - 1]), Err(ExportError::Truncated));
+        assert_eq!(
+            decode(&[&data[..], b"\x00"].concat()),
+            Err(ExportError::TrailingData { count: 1 })
+        );
+        assert_eq!(
+            decode(b"KTLM\x01\x00\x01\x00\x00\x00"),
+            Err(ExportError::BadParameter { id: 0 })
+        );
+        assert_eq!(
+            decode(b"KTLM\x01\x01\x01a\x01b\x01\x00\x00\x07"),
+            Err(ExportError::BadTag { tag: 7 })
+        );
+        assert_eq!(
+            decode(b"KTLM\x01\x01\x01\xff\x01b\x00"),
+            Err(ExportError::BadText)
+        );
+    }
+}
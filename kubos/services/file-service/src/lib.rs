@@ -17,14 +17,178 @@
 #![allow(clippy::blocks_in_conditions)]
 #![allow(clippy::map_entry)]
 
-use file_protocol::{FileProtocol, FileProtocolConfig, ProtocolError, State};
+use file_protocol::{
+    DirEntry, FileOp, FileOpReply, FileProtocol, FileProtocolConfig, Message, ProtocolError, State,
+};
 use log::{error, info, warn};
+use nix::sys::statvfs::statvfs;
 use radsat_system::Config as ServiceConfig;
 use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
+
+// Resolve a path requested by a client and check that it's within one of the allowed
+// directories. If `follow` is false, a final symlink is treated as the path itself
+// rather than its target, and the path itself doesn't need to exist.
+fn resolve_path(allowed_paths: &[PathBuf], path: &str, follow: bool) -> Result<PathBuf, String> {
+    let requested = Path::new(path);
+    let resolved = if follow {
+        requested.canonicalize()
+    } else {
+        match (requested.parent(), requested.file_name()) {
+            (Some(parent), Some(name)) => {
+                let parent = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+                parent.canonicalize().map(|parent| parent.join(name))
+            }
+            _ => return Err(format!("Invalid path {}", path)),
+        }
+    }
+    .map_err(|err| format!("Failed to resolve {}: {}", path, err))?;
+
+    if allowed_paths
+        .iter()
+        .any(|allowed| resolved.starts_with(allowed))
+    {
+        Ok(resolved)
+    } else {
+        Err(format!("{} is not within an allowed path", path))
+    }
+}
+
+// Resolve a path which is about to be removed or moved. The allowed directories
+// themselves can't be
+fn resolve_removable(allowed_paths: &[PathBuf], path: &str) -> Result<PathBuf, String> {
+    let resolved = resolve_path(allowed_paths, path, false)?;
+    if allowed_paths.contains(&resolved) {
+        return Err(format!("{} is an allowed path and can't be removed", path));
+    }
+    Ok(resolved)
+}
+
+// Carry out a file system operation requested by a client
+fn file_op(allowed_paths: &[PathBuf], op: &FileOp) -> Result<FileOpReply, String> {
+    match op {
+        FileOp::List { path } => {
+            let resolved = resolve_path(allowed_paths, path, true)?;
+            let mut entries: Vec<DirEntry> = fs::read_dir(&resolved)
+                .map_err(|err| format!("Failed to list {}: {}", path, err))?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let meta = entry.metadata().ok()?;
+                    Some(DirEntry {
+                        name: entry.file_name().into_string().ok()?,
+                        is_dir: meta.is_dir(),
+                        size: meta.len(),
+                    })
+                })
+                .collect();
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+            Ok(FileOpReply::List {
+                path: path.to_owned(),
+                total: entries.len() as u32,
+                entries,
+            })
+        }
+        FileOp::Stat { path } => {
+            let resolved = resolve_path(allowed_paths, path, true)?;
+            let meta = fs::metadata(&resolved)
+                .map_err(|err| format!("Failed to stat {}: {}", path, err))?;
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|time| time.as_secs())
+                .unwrap_or(0);
+
+            Ok(FileOpReply::Stat {
+                path: path.to_owned(),
+                is_dir: meta.is_dir(),
+                size: meta.len(),
+                mode: meta.mode(),
+                modified,
+            })
+        }
+        FileOp::Delete { path } => {
+            let resolved = resolve_removable(allowed_paths, path)?;
+            let meta = fs::symlink_metadata(&resolved)
+                .map_err(|err| format!("Failed to delete {}: {}", path, err))?;
+            // Only empty directories are removed, so a mistyped path can't take out a tree
+            if meta.is_dir() {
+                fs::remove_dir(&resolved)
+            } else {
+                fs::remove_file(&resolved)
+            }
+            .map_err(|err| format!("Failed to delete {}: {}", path, err))?;
+
+            Ok(FileOpReply::Delete {
+                path: path.to_owned(),
+            })
+        }
+        FileOp::Mkdir { path } => {
+            let resolved = resolve_path(allowed_paths, path, false)?;
+            fs::create_dir(&resolved)
+                .map_err(|err| format!("Failed to create {}: {}", path, err))?;
+
+            Ok(FileOpReply::Mkdir {
+                path: path.to_owned(),
+            })
+        }
+        FileOp::Move { from, to } => {
+            let resolved_from = resolve_removable(allowed_paths, from)?;
+            let resolved_to = resolve_path(allowed_paths, to, false)?;
+            fs::rename(&resolved_from, &resolved_to)
+                .map_err(|err| format!("Failed to move {} to {}: {}", from, to, err))?;
+
+            Ok(FileOpReply::Move {
+                from: from.to_owned(),
+                to: to.to_owned(),
+            })
+        }
+        FileOp::DiskUsage { path } => {
+            let resolved = resolve_path(allowed_paths, path, true)?;
+            let stats = statvfs(resolved.as_path())
+                .map_err(|err| format!("Failed to get disk usage of {}: {}", path, err))?;
+            // Block counts are in units of the fragment size
+            let fragment_size = stats.fragment_size() as u64;
+
+            Ok(FileOpReply::DiskUsage {
+                path: path.to_owned(),
+                total: stats.blocks() as u64 * fragment_size,
+                available: stats.blocks_available() as u64 * fragment_size,
+            })
+        }
+    }
+}
+
+// Carry out a file system operation and send the result to the client
+fn reply_file_op(
+    f_protocol: &FileProtocol,
+    channel_id: u32,
+    allowed_paths: &[PathBuf],
+    op: &FileOp,
+) {
+    let result = match file_op(allowed_paths, op) {
+        Ok(reply) => f_protocol.send_file_op_reply(channel_id, &reply),
+        Err(error) => {
+            warn!("File operation failed: {}", error);
+            f_protocol.send_failure(channel_id, &error)
+        }
+    };
+
+    if let Err(e) = result {
+        warn!("Failed to send file operation result: {}", e);
+    }
+}
 
 // We need this in this lib.rs file so we can build integration tests
 pub fn recv_loop(config: &ServiceConfig) -> Result<(), failure::Error> {
@@ -92,11 +256,37 @@ pub fn recv_loop(config: &ServiceConfig) -> Result<(), failure::Error> {
         .and_then(|chunks| chunks.as_integer())
         .map(|chunks| chunks as u32);
 
+    // Get the cap on the rate at which chunks are transmitted
+    let max_bandwidth = config
+        .get("max_bandwidth")
+        .and_then(|rate| rate.as_integer())
+        .map(|rate| rate as u32);
+
+    // Get the directories which clients may operate on. File operations are refused
+    // outside of them, and entirely if there are none
+    let allowed_paths: Vec<PathBuf> = config
+        .get("allowed_paths")
+        .and_then(|paths| paths.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| path.as_str())
+        .filter_map(|path| match Path::new(path).canonicalize() {
+            Ok(resolved) => Some(resolved),
+            Err(err) => {
+                warn!("Ignoring allowed path {}: {}", path, err);
+                None
+            }
+        })
+        .collect();
+
     info!("Starting file transfer service");
     info!("Listening on {}", host);
     info!("Downlinking to {}:{}", downlink_ip, downlink_port);
     info!("Transfer Chunk {}", transfer_chunk_size);
     info!("Hash Chunk Size {}", hash_chunk_size);
+    if let Some(rate) = max_bandwidth {
+        info!("Max Bandwidth {} bytes/s", rate);
+    }
 
     let f_config = FileProtocolConfig::new(
         prefix,
@@ -105,10 +295,18 @@ pub fn recv_loop(config: &ServiceConfig) -> Result<(), failure::Error> {
         inter_chunk_delay,
         max_chunks_transmit,
         hash_chunk_size,
-    );
+    )
+    .with_max_bandwidth(max_bandwidth);
 
     let c_protocol = cbor_protocol::Protocol::new(&host.clone(), transfer_chunk_size);
 
+    // File operations are answered from a single reply socket rather than their own threads
+    let ops_protocol = FileProtocol::new(
+        &format!("{}:{}", host_ip, 0),
+        &format!("{}:{}", downlink_ip, downlink_port),
+        f_config.clone(),
+    );
+
     let timeout = config
         .get("timeout")
         .and_then(|val| val.as_integer().map(|num| Duration::from_secs(num as u64)))
@@ -152,6 +350,14 @@ pub fn recv_loop(config: &ServiceConfig) -> Result<(), failure::Error> {
             .unwrap()
             .contains_key(&channel_id)
         {
+            // File operations are answered straight away, without starting a transaction
+            if let Ok(Message::ReqFileOp(_, op)) =
+                file_protocol::parse_message(first_message.clone())
+            {
+                reply_file_op(&ops_protocol, channel_id, &allowed_paths, &op);
+                continue;
+            }
+
             let (sender, receiver): (Sender<serde_cbor::Value>, Receiver<serde_cbor::Value>) =
                 mpsc::channel();
 
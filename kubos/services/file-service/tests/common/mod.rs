@@ -17,7 +17,7 @@
 #![allow(dead_code)]
 
 use blake2_rfc::blake2s::Blake2s;
-use file_protocol::{FileProtocol, FileProtocolConfig, ProtocolError, State};
+use file_protocol::{Compression, FileProtocol, FileProtocolConfig, ProtocolError, State};
 use serde_cbor::{from_slice, ser};
 use std::fs::File;
 use std::io::prelude::*;
@@ -27,6 +27,19 @@ use std::time::Duration;
 #[macro_export]
 macro_rules! service_new {
     ($port:expr, $down_port:expr, $chunk_size:expr, $storage_dir:expr) => {{
+        service_new!($port, $down_port, $chunk_size, $storage_dir, 2)
+    }};
+    ($port:expr, $down_port:expr, $chunk_size:expr, $storage_dir:expr, $timeout:expr) => {{
+        service_new!($port, $down_port, $chunk_size, $storage_dir, $timeout, "")
+    }};
+    (
+        $port:expr,
+        $down_port:expr,
+        $chunk_size:expr,
+        $storage_dir:expr,
+        $timeout:expr,
+        $extra_config:expr
+    ) => {{
         thread::spawn(move || {
             recv_loop(
                 &ServiceConfig::new_from_str(
@@ -37,13 +50,15 @@ macro_rules! service_new {
                 storage_dir = "{}"
                 transfer_chunk_size = {}
                 hold_count = 5
+                timeout = {}
                 downlink_ip = "127.0.0.1"
                 downlink_port = {}
+                {}
                 [file-transfer-service.addr]
                 ip = "127.0.0.1"
                 port = {}
                 "#,
-                        $storage_dir, $chunk_size, $down_port, $port
+                        $storage_dir, $chunk_size, $timeout, $down_port, $extra_config, $port
                     ),
                 )
                 .unwrap(),
@@ -55,6 +70,23 @@ macro_rules! service_new {
     }};
 }
 
+// Client on `port` talking to the service on `service_port`, both on localhost
+pub fn client(
+    port: u16,
+    service_port: u16,
+    prefix: String,
+    max_chunks: Option<u32>,
+    compression: Compression,
+) -> FileProtocol {
+    let config = FileProtocolConfig::new(Some(prefix), 1024, 5, 1, max_chunks, 2048)
+        .with_compression(compression);
+    FileProtocol::new(
+        &format!("127.0.0.1:{}", port),
+        &format!("127.0.0.1:{}", service_port),
+        config,
+    )
+}
+
 pub fn download(
     host_ip: &str,
     host_port: u16,
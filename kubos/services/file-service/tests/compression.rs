@@ -0,0 +1,175 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod common;
+
+use crate::common::*;
+use file_protocol::{Compression, FileProtocol, State};
+use file_service::recv_loop;
+use radsat_system::Config as ServiceConfig;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// NOTE: Each test's file contents must be unique. Otherwise the hash is the same, so
+// the same storage directory is used across all of them, creating conflicts
+
+fn compressible(seed: u8, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| seed.wrapping_add((i % 16) as u8))
+        .collect()
+}
+
+fn download(f_protocol: &FileProtocol, source: &str, dest: &str) {
+    let channel = f_protocol.generate_channel().unwrap();
+    f_protocol.send_import(channel, source).unwrap();
+
+    let reply = f_protocol.recv(None).unwrap();
+    let state = f_protocol
+        .process_message(
+            reply,
+            &State::StartReceive {
+                path: dest.to_owned(),
+            },
+        )
+        .unwrap();
+    f_protocol
+        .message_engine(|d| f_protocol.recv(Some(d)), Duration::from_secs(2), &state)
+        .unwrap();
+}
+
+// Upload a file whose chunks are compressed in transit
+#[test]
+fn upload_compressed() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let service_port = 7106;
+    let downlink_port = 6106;
+
+    let contents = compressible(31, 5000);
+
+    let hash = create_test_file(&source, &contents);
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::Deflate,
+    );
+
+    let (file_hash, num_chunks, mode) = f_protocol.initialize_file(&source).unwrap();
+    assert_eq!(file_hash, hash);
+
+    let channel = f_protocol.generate_channel().unwrap();
+    f_protocol
+        .send_metadata(channel, &hash, num_chunks)
+        .unwrap();
+    f_protocol.send_export(channel, &hash, &dest, mode).unwrap();
+    f_protocol
+        .message_engine(
+            |d| f_protocol.recv(Some(d)),
+            Duration::from_secs(2),
+            &State::Transmitting,
+        )
+        .unwrap();
+
+    // Verify the final file's contents
+    let dest_contents = fs::read(dest).unwrap();
+    assert_eq!(contents, dest_contents);
+}
+
+// Download a file whose chunks are compressed in transit
+#[test]
+fn download_compressed() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let service_port = 7107;
+    let downlink_port = 6107;
+
+    let contents = compressible(32, 5000);
+
+    create_test_file(&source, &contents);
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::Deflate,
+    );
+
+    download(&f_protocol, &source, &dest);
+
+    // Verify the final file's contents
+    let dest_contents = fs::read(dest).unwrap();
+    assert_eq!(contents, dest_contents);
+}
+
+// Download a file from a service whose bandwidth is capped
+#[test]
+fn download_rate_limited() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let service_port = 7108;
+    let downlink_port = 6108;
+
+    // Incompressible, so that every chunk is sent in full
+    let contents: Vec<u8> = (0..16 * 1024).map(|_| rand::random::<u8>()).collect();
+
+    create_test_file(&source, &contents);
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(
+        service_port,
+        downlink_port,
+        1024,
+        storage_dir,
+        2,
+        "max_bandwidth = 2048"
+    );
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::Deflate,
+    );
+
+    let start = Instant::now();
+    download(&f_protocol, &source, &dest);
+
+    // Sixteen chunks of over 1KB each take at least eight seconds at 2KB/s
+    assert!(start.elapsed() >= Duration::from_secs(8));
+
+    // Verify the final file's contents
+    let dest_contents = fs::read(dest).unwrap();
+    assert_eq!(contents, dest_contents);
+}
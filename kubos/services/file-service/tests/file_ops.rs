@@ -0,0 +1,289 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod common;
+
+use crate::common::*;
+use file_protocol::{Compression, DirEntry, FileOp, FileOpReply, FileProtocol, ProtocolError};
+use file_service::recv_loop;
+use radsat_system::Config as ServiceConfig;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn request(f_protocol: &FileProtocol, op: FileOp) -> Result<FileOpReply, ProtocolError> {
+    let channel = f_protocol.generate_channel()?;
+    f_protocol.request_file_op(channel, &op, Duration::from_secs(2))
+}
+
+// Reason the service gave for refusing an operation
+fn refusal(result: Result<FileOpReply, ProtocolError>) -> String {
+    match result {
+        Err(ProtocolError::TransmissionError { error_message, .. }) => error_message,
+        other => panic!("Operation wasn't refused: {:?}", other),
+    }
+}
+
+#[test]
+fn file_ops() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let allowed = format!("{}/allowed", test_dir_str);
+    let service_port = 7103;
+    let downlink_port = 6103;
+
+    fs::create_dir(&allowed).unwrap();
+    fs::write(format!("{}/outside", test_dir_str), "outside").unwrap();
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    let extra_config = format!("allowed_paths = [\"{}\"]", allowed);
+    service_new!(
+        service_port,
+        downlink_port,
+        1024,
+        storage_dir,
+        2,
+        extra_config
+    );
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    let dir = format!("{}/dir", allowed);
+    assert_eq!(
+        request(&f_protocol, FileOp::Mkdir { path: dir.clone() }).unwrap(),
+        FileOpReply::Mkdir { path: dir.clone() }
+    );
+    assert!(fs::metadata(&dir).unwrap().is_dir());
+
+    let file = format!("{}/file", allowed);
+    fs::write(&file, "file_ops").unwrap();
+
+    match request(
+        &f_protocol,
+        FileOp::List {
+            path: allowed.clone(),
+        },
+    )
+    .unwrap()
+    {
+        FileOpReply::List {
+            path,
+            total,
+            entries,
+        } => {
+            assert_eq!(path, allowed);
+            assert_eq!(total, 2);
+            assert_eq!(entries[0].name, "dir");
+            assert!(entries[0].is_dir);
+            assert_eq!(
+                entries[1],
+                DirEntry {
+                    name: "file".to_owned(),
+                    is_dir: false,
+                    size: 8,
+                }
+            );
+        }
+        other => panic!("Unexpected reply {:?}", other),
+    }
+
+    match request(&f_protocol, FileOp::Stat { path: file.clone() }).unwrap() {
+        FileOpReply::Stat {
+            path,
+            is_dir,
+            size,
+            modified,
+            ..
+        } => {
+            assert_eq!(path, file);
+            assert!(!is_dir);
+            assert_eq!(size, 8);
+            assert!(modified > 0);
+        }
+        other => panic!("Unexpected reply {:?}", other),
+    }
+
+    let moved = format!("{}/moved", dir);
+    assert_eq!(
+        request(
+            &f_protocol,
+            FileOp::Move {
+                from: file.clone(),
+                to: moved.clone(),
+            }
+        )
+        .unwrap(),
+        FileOpReply::Move {
+            from: file.clone(),
+            to: moved.clone(),
+        }
+    );
+    assert!(fs::metadata(&file).is_err());
+    assert_eq!(fs::read_to_string(&moved).unwrap(), "file_ops");
+
+    // Directories are only deleted once they're empty
+    refusal(request(&f_protocol, FileOp::Delete { path: dir.clone() }));
+    assert_eq!(
+        request(
+            &f_protocol,
+            FileOp::Delete {
+                path: moved.clone()
+            }
+        )
+        .unwrap(),
+        FileOpReply::Delete {
+            path: moved.clone()
+        }
+    );
+    assert_eq!(
+        request(&f_protocol, FileOp::Delete { path: dir.clone() }).unwrap(),
+        FileOpReply::Delete { path: dir.clone() }
+    );
+    assert!(fs::metadata(&dir).is_err());
+
+    match request(
+        &f_protocol,
+        FileOp::DiskUsage {
+            path: allowed.clone(),
+        },
+    )
+    .unwrap()
+    {
+        FileOpReply::DiskUsage {
+            total, available, ..
+        } => {
+            assert!(total > 0);
+            assert!(available <= total);
+        }
+        other => panic!("Unexpected reply {:?}", other),
+    }
+}
+
+#[test]
+fn file_ops_outside_allowed_paths() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let allowed = format!("{}/allowed", test_dir_str);
+    let outside = format!("{}/outside", test_dir_str);
+    let service_port = 7104;
+    let downlink_port = 6104;
+
+    fs::create_dir(&allowed).unwrap();
+    fs::write(&outside, "file_ops_outside").unwrap();
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    let extra_config = format!("allowed_paths = [\"{}\"]", allowed);
+    service_new!(
+        service_port,
+        downlink_port,
+        1024,
+        storage_dir,
+        2,
+        extra_config
+    );
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    let error = refusal(request(
+        &f_protocol,
+        FileOp::List {
+            path: test_dir_str.to_owned(),
+        },
+    ));
+    assert!(error.contains("not within an allowed path"));
+
+    // Paths can't climb out of an allowed directory
+    refusal(request(
+        &f_protocol,
+        FileOp::Stat {
+            path: format!("{}/../outside", allowed),
+        },
+    ));
+    refusal(request(
+        &f_protocol,
+        FileOp::Move {
+            from: outside.clone(),
+            to: format!("{}/outside", allowed),
+        },
+    ));
+    refusal(request(
+        &f_protocol,
+        FileOp::Delete {
+            path: format!("{}/../outside", allowed),
+        },
+    ));
+    assert_eq!(fs::read_to_string(&outside).unwrap(), "file_ops_outside");
+
+    // Nor can they escape through a symlink
+    let link = format!("{}/link", allowed);
+    std::os::unix::fs::symlink(test_dir_str, &link).unwrap();
+    refusal(request(&f_protocol, FileOp::List { path: link.clone() }));
+    refusal(request(
+        &f_protocol,
+        FileOp::Delete {
+            path: format!("{}/outside", link),
+        },
+    ));
+    assert_eq!(fs::read_to_string(&outside).unwrap(), "file_ops_outside");
+
+    // The allowed directory itself can't be removed
+    refusal(request(
+        &f_protocol,
+        FileOp::Delete {
+            path: allowed.clone(),
+        },
+    ));
+    assert!(fs::metadata(&allowed).unwrap().is_dir());
+}
+
+#[test]
+fn file_ops_disabled() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let service_port = 7105;
+    let downlink_port = 6105;
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    refusal(request(
+        &f_protocol,
+        FileOp::List {
+            path: test_dir_str.to_owned(),
+        },
+    ));
+}
@@ -0,0 +1,139 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod common;
+
+use crate::common::*;
+use file_protocol::{Compression, FileProtocol, ProtocolError, State};
+use file_service::recv_loop;
+use radsat_system::Config as ServiceConfig;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+// NOTE: Each test's file contents must be unique. Otherwise the hash is the same, so
+// the same storage directory is used across all of them, creating conflicts
+
+fn contents(seed: u8, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| seed.wrapping_add((i * 7 % 251) as u8))
+        .collect()
+}
+
+// Upload a patch turning `base` into `new` and apply it to the service's copy of the
+// base file, at `remote_base`
+fn upload_patch(
+    f_protocol: &FileProtocol,
+    base: &str,
+    new: &str,
+    remote_base: &str,
+) -> Result<(), ProtocolError> {
+    let (patch, num_chunks, mode) = f_protocol.initialize_patch(base, new, remote_base)?;
+
+    let channel = f_protocol.generate_channel()?;
+    f_protocol.send_metadata(channel, &patch.hash, num_chunks)?;
+    f_protocol.send_patch(channel, &patch, remote_base, mode)?;
+    f_protocol.message_engine(
+        |d| f_protocol.recv(Some(d)),
+        Duration::from_secs(2),
+        &State::Transmitting,
+    )
+}
+
+// Update a file on the service side by sending only what has changed
+#[test]
+fn upload_patch_good() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let base = format!("{}/base", test_dir_str);
+    let new = format!("{}/new", test_dir_str);
+    let remote = format!("{}/remote", test_dir_str);
+    let service_port = 7109;
+    let downlink_port = 6109;
+
+    let old_contents = contents(41, 20000);
+    let mut new_contents = old_contents.clone();
+    new_contents.splice(5000..5100, vec![0; 10]);
+    new_contents.extend_from_slice(b"upload_patch_good");
+
+    fs::write(&base, &old_contents).unwrap();
+    fs::write(&remote, &old_contents).unwrap();
+    fs::write(&new, &new_contents).unwrap();
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    // Only a small patch needs to be sent
+    let (_, num_chunks, _) = f_protocol.initialize_patch(&base, &new, &remote).unwrap();
+    assert_eq!(num_chunks, 1);
+
+    upload_patch(&f_protocol, &base, &new, &remote).unwrap();
+
+    // Verify the final file's contents
+    let remote_contents = fs::read(remote).unwrap();
+    assert_eq!(new_contents, remote_contents);
+}
+
+// A patch for a different version of the file is rejected, leaving the file untouched
+#[test]
+fn upload_patch_wrong_base() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let base = format!("{}/base", test_dir_str);
+    let new = format!("{}/new", test_dir_str);
+    let remote = format!("{}/remote", test_dir_str);
+    let service_port = 7110;
+    let downlink_port = 6110;
+
+    let old_contents = contents(42, 20000);
+    let mut remote_contents = old_contents.clone();
+    remote_contents[10000] ^= 0xff;
+    let mut new_contents = old_contents.clone();
+    new_contents.extend_from_slice(b"upload_patch_wrong_base");
+
+    fs::write(&base, &old_contents).unwrap();
+    fs::write(&remote, &remote_contents).unwrap();
+    fs::write(&new, &new_contents).unwrap();
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    match upload_patch(&f_protocol, &base, &new, &remote) {
+        Err(ProtocolError::TransmissionError { error_message, .. }) => {
+            assert!(error_message.contains("patch"))
+        }
+        other => panic!("Patch wasn't rejected: {:?}", other),
+    }
+
+    assert_eq!(fs::read(remote).unwrap(), remote_contents);
+}
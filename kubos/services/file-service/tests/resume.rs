@@ -0,0 +1,339 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod common;
+
+use crate::common::*;
+use file_protocol::{Compression, ProtocolError, State, TransferStatus};
+use file_service::recv_loop;
+use radsat_system::Config as ServiceConfig;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+// NOTE: Each test's file contents must be unique. Otherwise the hash is the same, so
+// the same storage directory is used across all of them, creating conflicts
+
+// Seconds the service waits for each message. With a hold count of 5, the service gives
+// up on an abandoned transfer after about 8 seconds
+const SERVICE_TIMEOUT: u64 = 1;
+
+// Time between simulated passes, long enough for the service to give up on the
+// transfer which was interrupted
+const PASS_GAP: Duration = Duration::from_secs(9);
+
+// Upload a file over two passes, the first of which ends after two chunks
+#[test]
+fn upload_interrupted() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let client_dir = format!("{}/client", test_dir_str);
+    let service_port = 7100;
+    let downlink_port = 6100;
+
+    let contents = [21; 5000];
+
+    let hash = create_test_file(&source, &contents);
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(
+        service_port,
+        downlink_port,
+        1024,
+        storage_dir,
+        SERVICE_TIMEOUT
+    );
+
+    // First pass: only two chunks make it across
+    let mode = {
+        let f_protocol = client(
+            downlink_port,
+            service_port,
+            client_dir.clone(),
+            Some(2),
+            Compression::None,
+        );
+
+        let (file_hash, num_chunks, mode) = f_protocol.initialize_file(&source).unwrap();
+        assert_eq!(file_hash, hash);
+        assert_eq!(num_chunks, 5);
+
+        let channel = f_protocol.generate_channel().unwrap();
+        f_protocol
+            .send_metadata(channel, &hash, num_chunks)
+            .unwrap();
+        f_protocol.send_export(channel, &hash, &dest, mode).unwrap();
+
+        // The service asks for every chunk, and we send as many as we're allowed
+        let reply = f_protocol.recv(Some(Duration::from_secs(2))).unwrap();
+        f_protocol
+            .process_message(reply, &State::Transmitting)
+            .unwrap();
+
+        mode
+    };
+
+    thread::sleep(PASS_GAP);
+
+    // The source file is no longer needed
+    fs::remove_file(&source).unwrap();
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        client_dir,
+        Some(2),
+        Compression::None,
+    );
+
+    // Between passes, both sides can tell how far the transfer got
+    assert_eq!(
+        f_protocol.local_status(&hash).unwrap(),
+        TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 5,
+            missing: vec![],
+        }
+    );
+
+    let channel = f_protocol.generate_channel().unwrap();
+    assert_eq!(
+        f_protocol
+            .request_status(channel, &hash, Duration::from_secs(2))
+            .unwrap(),
+        TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 2,
+            missing: vec![(2, 5)],
+        }
+    );
+
+    let channel = f_protocol.generate_channel().unwrap();
+    assert_eq!(
+        f_protocol
+            .request_transfers(channel, Duration::from_secs(2))
+            .unwrap(),
+        vec![TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 2,
+            missing: vec![],
+        }]
+    );
+
+    // Second pass: continue the upload by hash
+    let channel = f_protocol.generate_channel().unwrap();
+    f_protocol
+        .send_resume_export(channel, &hash, &dest, mode)
+        .unwrap();
+    f_protocol
+        .message_engine(
+            |d| f_protocol.recv(Some(d)),
+            Duration::from_secs(2),
+            &State::Transmitting,
+        )
+        .unwrap();
+
+    // Verify the final file's contents
+    let dest_contents = fs::read(dest).unwrap();
+    assert_eq!(&contents[..], dest_contents.as_slice());
+
+    // Both sides have cleaned up their temporary storage
+    assert_eq!(f_protocol.local_status(&hash).unwrap().chunks_present, 0);
+    let channel = f_protocol.generate_channel().unwrap();
+    assert_eq!(
+        f_protocol
+            .request_transfers(channel, Duration::from_secs(2))
+            .unwrap(),
+        vec![]
+    );
+}
+
+// Download a file over two passes, the first of which ends after two chunks
+#[test]
+fn download_interrupted() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let client_dir = format!("{}/client", test_dir_str);
+    let service_port = 7101;
+    let downlink_port = 6101;
+
+    let contents = [22; 5000];
+
+    let hash = create_test_file(&source, &contents);
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(
+        service_port,
+        downlink_port,
+        1024,
+        storage_dir,
+        SERVICE_TIMEOUT
+    );
+
+    // First pass: only two chunks make it across
+    {
+        let f_protocol = client(
+            downlink_port,
+            service_port,
+            client_dir.clone(),
+            None,
+            Compression::None,
+        );
+
+        let channel = f_protocol.generate_channel().unwrap();
+        f_protocol.send_import(channel, &source).unwrap();
+
+        let reply = f_protocol.recv(None).unwrap();
+        let state = f_protocol
+            .process_message(reply, &State::StartReceive { path: dest.clone() })
+            .unwrap();
+        match &state {
+            State::Receiving { hash: h, .. } => assert_eq!(h, &hash),
+            other => panic!("Unexpected state {:?}", other),
+        }
+
+        for _ in 0..2 {
+            let chunk = f_protocol.recv(Some(Duration::from_secs(2))).unwrap();
+            f_protocol.process_message(chunk, &state).unwrap();
+        }
+    }
+
+    assert!(fs::metadata(&dest).is_err());
+
+    // The service no longer has the source file, only the chunks it prepared
+    fs::remove_file(&source).unwrap();
+
+    thread::sleep(PASS_GAP);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        client_dir,
+        None,
+        Compression::None,
+    );
+
+    // Between passes, both sides can tell how far the transfer got
+    assert_eq!(
+        f_protocol.local_status(&hash).unwrap(),
+        TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 2,
+            missing: vec![(2, 5)],
+        }
+    );
+    assert_eq!(
+        f_protocol.local_transfers().unwrap(),
+        vec![TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 2,
+            missing: vec![],
+        }]
+    );
+
+    let channel = f_protocol.generate_channel().unwrap();
+    assert_eq!(
+        f_protocol
+            .request_status(channel, &hash, Duration::from_secs(2))
+            .unwrap(),
+        TransferStatus {
+            hash: hash.clone(),
+            num_chunks: Some(5),
+            chunks_present: 5,
+            missing: vec![],
+        }
+    );
+
+    // Second pass: continue the download by hash
+    let channel = f_protocol.generate_channel().unwrap();
+    f_protocol
+        .send_resume_import(channel, &source, &hash)
+        .unwrap();
+
+    let reply = f_protocol.recv(None).unwrap();
+    let state = f_protocol
+        .process_message(reply, &State::StartReceive { path: dest.clone() })
+        .unwrap();
+    f_protocol
+        .message_engine(|d| f_protocol.recv(Some(d)), Duration::from_secs(2), &state)
+        .unwrap();
+
+    // Verify the final file's contents
+    let dest_contents = fs::read(dest).unwrap();
+    assert_eq!(&contents[..], dest_contents.as_slice());
+}
+
+// Resuming a download fails if the service has neither the chunks nor the source file
+#[test]
+fn download_resume_unknown() {
+    let test_dir = TempDir::new().expect("Failed to create test dir");
+    let test_dir_str = test_dir.path().to_str().unwrap();
+    let source = format!("{}/source", test_dir_str);
+    let dest = format!("{}/dest", test_dir_str);
+    let service_port = 7102;
+    let downlink_port = 6102;
+
+    let storage_dir = format!("{}/service", test_dir_str);
+    service_new!(service_port, downlink_port, 1024, storage_dir);
+
+    let f_protocol = client(
+        downlink_port,
+        service_port,
+        format!("{}/client", test_dir_str),
+        None,
+        Compression::None,
+    );
+
+    let channel = f_protocol.generate_channel().unwrap();
+    f_protocol
+        .send_resume_import(channel, &source, "0123456789abcdef0123456789abcdef")
+        .unwrap();
+
+    let reply = f_protocol.recv(Some(Duration::from_secs(2))).unwrap();
+    match f_protocol.process_message(reply, &State::StartReceive { path: dest }) {
+        Err(ProtocolError::TransmissionError { channel_id, .. }) => {
+            assert_eq!(channel_id, channel)
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let channel = f_protocol.generate_channel().unwrap();
+    assert_eq!(
+        f_protocol
+            .request_status(
+                channel,
+                "0123456789abcdef0123456789abcdef",
+                Duration::from_secs(2)
+            )
+            .unwrap(),
+        TransferStatus {
+            hash: "0123456789abcdef0123456789abcdef".to_owned(),
+            num_chunks: None,
+            chunks_present: 0,
+            missing: vec![],
+        }
+    );
+}
@@ -93,6 +93,24 @@
 //! ).start();
 //! ```
 //!
+//! # Creating a service which streams data to GraphQL subscriptions.
+//!
+//! Subscriptions are served over WebSocket at `ws://ip:port/ws`.
+//!
+//! ```rust,ignore
+//! use kubos_service::{Config, Service};
+//! use model::Subsystem;
+//! use schema::{MutationRoot, QueryRoot, SubscriptionRoot};
+//!
+//! Service::new_with_subscription(
+//!     Config::new("service-name").unwrap(),
+//!     Subsystem::new(),
+//!     QueryRoot,
+//!     MutationRoot,
+//!     SubscriptionRoot,
+//! ).start();
+//! ```
+//!
 //! # Running a service with the default config file (`/etc/kubos-config.toml`).
 //!
 //! ```bash
@@ -107,10 +125,14 @@
 
 mod macros;
 mod service;
+mod telemetry;
 
 pub use crate::macros::process_anyhow_chain;
 
 pub use crate::service::{Context, Service};
-pub use async_graphql::{EmptySubscription, EmptyMutation, ObjectType, Schema, SimpleObject};
+pub use crate::telemetry::telemetry_url;
+pub use async_graphql::{
+    EmptySubscription, EmptyMutation, ObjectType, Schema, SimpleObject, SubscriptionType,
+};
 pub use radsat_system::logger as Logger;
 pub use radsat_system::Config;
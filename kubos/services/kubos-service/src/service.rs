@@ -14,8 +14,10 @@
 // limitations under the License.
 //
 
-use async_graphql::{EmptySubscription, ObjectType, Schema, http::GraphiQLSource};
-use async_graphql_axum::GraphQL;
+use async_graphql::{
+    EmptySubscription, ObjectType, Schema, SubscriptionType, http::GraphiQLSource,
+};
+use async_graphql_axum::{GraphQL, GraphQLSubscription};
 use axum::{
     Router,
     response::{self, IntoResponse},
@@ -91,6 +93,9 @@ impl<T> Context<T> {
 /// of exposing a subsystem to GraphQL queries and means
 /// for persistence throughout GraphQL queries.
 ///
+/// Services created with `new_with_subscription` also serve
+/// GraphQL subscriptions over WebSocket at `/ws`.
+///
 /// ### Examples
 ///
 /// # Creating and starting a service.
@@ -105,16 +110,16 @@ impl<T> Context<T> {
 ///     schema::MutationRoot,
 /// ).start();
 /// ```
-pub struct Service<Query, Mutation, S> {
+pub struct Service<Query, Mutation, S, Subscription = EmptySubscription> {
     config: Config,
-    schema: Schema<Query, Mutation, EmptySubscription>,
+    schema: Schema<Query, Mutation, Subscription>,
     _phantom: std::marker::PhantomData<S>,
 }
 
-impl<Query, Mutation, S> Service<Query, Mutation, S> {
+impl<Query, Mutation, S, Subscription> Service<Query, Mutation, S, Subscription> {
     /// Returns a reference to the GraphQL schema.
     /// Useful for testing purposes.
-    pub fn schema(&self) -> &Schema<Query, Mutation, EmptySubscription> {
+    pub fn schema(&self) -> &Schema<Query, Mutation, Subscription> {
         &self.schema
     }
 }
@@ -138,6 +143,33 @@ where
         subsystem: S,
         query: Query,
         mutation: Mutation,
+    ) -> Self {
+        Self::new_with_subscription(config, subsystem, query, mutation, EmptySubscription)
+    }
+}
+
+impl<Query, Mutation, S, Subscription> Service<Query, Mutation, S, Subscription>
+where
+    Query: ObjectType + 'static,
+    Mutation: ObjectType + 'static,
+    S: Send + Sync + Clone + 'static,
+    Subscription: SubscriptionType + 'static,
+{
+    /// Creates a new service instance which also serves GraphQL subscriptions
+    ///
+    /// # Arguments
+    ///
+    /// `config` - The service configuration
+    /// `subsystem` - An instance of the subsystem struct. This one instance will be used by all queries.
+    /// `query` - The root query struct holding all other GraphQL queries.
+    /// `mutation` - The root mutation struct holding all other GraphQL mutations.
+    /// `subscription` - The root subscription struct holding all GraphQL subscriptions.
+    pub fn new_with_subscription(
+        config: Config,
+        subsystem: S,
+        query: Query,
+        mutation: Mutation,
+        subscription: Subscription,
     ) -> Self {
         let context = Context {
             subsystem,
@@ -145,7 +177,7 @@ where
         };
 
         // Build the GraphQL schema with the context as data
-        let schema = Schema::build(query, mutation, EmptySubscription)
+        let schema = Schema::build(query, mutation, subscription)
             .data(context)
             .finish();
 
@@ -158,7 +190,12 @@ where
 
     /// GraphiQL endpoint handler
     async fn graphiql() -> impl IntoResponse {
-        response::Html(GraphiQLSource::build().endpoint("/graphql").finish())
+        response::Html(
+            GraphiQLSource::build()
+                .endpoint("/graphql")
+                .subscription_endpoint("/ws")
+                .finish(),
+        )
     }
 
 
@@ -204,10 +241,13 @@ where
         // "/" is the root GraphQL endpoint used by the kubos_app Python library
         // (which posts to http://ip:port/ with no path suffix).
         // "/graphql" serves the same handler and also exposes the GraphiQL browser UI.
+        // "/ws" serves subscriptions over WebSocket, using either the graphql-ws or
+        // graphql-transport-ws protocol.
         let app = Router::new()
             .route("/", axum::routing::post_service(GraphQL::new(self.schema.clone())))
-            .route("/graphql", get(Self::graphiql).post_service(GraphQL::new(self.schema)))
-            .route("/graphiql", get(Self::graphiql));
+            .route("/graphql", get(Self::graphiql).post_service(GraphQL::new(self.schema.clone())))
+            .route("/graphiql", get(Self::graphiql))
+            .route_service("/ws", GraphQLSubscription::new(self.schema));
 
         info!("Listening on: {}", addr);
 
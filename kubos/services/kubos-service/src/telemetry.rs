@@ -0,0 +1,40 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use radsat_system::Config;
+
+/// Look up the address of the telemetry service's direct UDP port, which
+/// services forward telemetry to, from the shared config file
+///
+/// Returns `None`, after logging why, if the telemetry service's config can't be
+/// loaded or has no `direct_port`
+pub fn telemetry_url() -> Option<String> {
+    let config = Config::new("telemetry-service")
+        .map_err(|err| log::warn!("Failed to load telemetry-service config: {:?}", err))
+        .ok()?;
+
+    let port = match config.get("direct_port").and_then(|v| v.as_integer()) {
+        Some(port) => port,
+        None => {
+            log::warn!("telemetry-service has no direct_port. Telemetry will not be forwarded");
+            return None;
+        }
+    };
+    let host = config.hosturl()?;
+    let ip = host.split(':').next()?;
+
+    Some(format!("{}:{}", ip, port))
+}
@@ -20,11 +20,13 @@
 
 use crate::error::SchedulerError;
 use crate::schema::GenericResponse;
+use crate::trigger::query_service;
 use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
 use log::{debug, error, info};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::from_str;
+use serde_json::{from_str, json};
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -77,6 +79,31 @@ pub async fn service_query(query: &str, hosturl: &str) -> Result<StartAppGraphQL
     })
 }
 
+// Exit status of an app run, as reported by app-service's app monitor
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitStatus {
+    pub finished: DateTime<Utc>,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+impl ExitStatus {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+// App monitor entry fields used to find a run's exit status
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppStatus {
+    start_time: String,
+    end_time: Option<String>,
+    running: bool,
+    last_rc: Option<i32>,
+    last_signal: Option<i32>,
+}
+
 // Configuration used for execution of an app
 #[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct App {
@@ -121,4 +148,64 @@ impl App {
             }
         }
     }
+
+    /// Fetch the exit status of the run started at `started` from the app
+    /// monitor. Returns None while the run has not finished.
+    pub async fn exit_status(
+        &self,
+        service_url: &str,
+        started: DateTime<Utc>,
+    ) -> Result<Option<ExitStatus>, SchedulerError> {
+        let query = format!(
+            "{{ appStatus(name: {}) {{ startTime, endTime, running, lastRc, lastSignal }} }}",
+            json!(self.name)
+        );
+        let response = query_service(&query, service_url).await?;
+        let entries: Vec<AppStatus> = serde_json::from_value(response["data"]["appStatus"].clone())
+            .map_err(|e| SchedulerError::QueryError {
+                err: format!("Error parsing app status: {}", e),
+            })?;
+
+        // The monitor keeps only the latest run of each app version, so
+        // entries started before this run belong to other runs
+        let parse_time = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .map(|time| time.with_timezone(&Utc))
+                .ok()
+        };
+        let earliest = started - chrono::Duration::seconds(1);
+        for entry in entries {
+            if parse_time(&entry.start_time).is_none_or(|start| start < earliest) {
+                continue;
+            }
+            if entry.running {
+                return Ok(None);
+            }
+            return Ok(Some(ExitStatus {
+                finished: entry
+                    .end_time
+                    .as_deref()
+                    .and_then(parse_time)
+                    .unwrap_or_else(Utc::now),
+                code: entry.last_rc,
+                signal: entry.last_signal,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Ask the app service whether this app has an active registered version
+    pub async fn is_registered(&self, service_url: &str) -> Result<bool, SchedulerError> {
+        let query = format!(
+            "{{ registeredApps(name: {}, active: true) {{ active }} }}",
+            json!(self.name)
+        );
+        let response = query_service(&query, service_url).await?;
+        response["data"]["registeredApps"]
+            .as_array()
+            .map(|entries| !entries.is_empty())
+            .ok_or_else(|| SchedulerError::QueryError {
+                err: "Error parsing registered apps".to_owned(),
+            })
+    }
 }
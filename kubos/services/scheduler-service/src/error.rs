@@ -122,6 +122,24 @@ pub enum SchedulerError {
         /// The description of task that failed to parse
         description: String,
     },
+    // An error was raised when loading or evaluating mode transitions
+    #[error("Mode transition error: {err}")]
+    TransitionError {
+        /// The specific error encountered
+        err: String,
+    },
+    // An error was raised when handling TLEs, ground stations or orbit events
+    #[error("Orbit error: {err}")]
+    OrbitError {
+        /// The specific error encountered
+        err: String,
+    },
+    // An error was raised when simulating a schedule
+    #[error("Simulation error: {err}")]
+    SimulationError {
+        /// The specific error encountered
+        err: String,
+    },
 }
 
 impl From<String> for SchedulerError {
@@ -0,0 +1,354 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Persistent log of task executions and per-task failure counters
+//!
+
+use crate::app::ExitStatus;
+use crate::error::SchedulerError;
+use crate::mode::get_active_mode;
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+// Execution log and counters, kept across reboots
+pub static HISTORY_FILE: &str = "execution_log.json";
+// Millisecond precision, so scheduled and actual times can be compared
+pub static TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+// Number of executions kept in the log
+const HISTORY_LIMIT: usize = 512;
+
+// Record of one attempt to run a task's app
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    #[graphql(skip)]
+    pub id: u64,
+    // Description of the task
+    pub task: String,
+    // Mode active when the task ran
+    pub mode: Option<String>,
+    pub app: String,
+    // Attempt number, counting from 1. Later attempts are retries
+    pub attempt: u32,
+    // Time the task was due to run
+    pub scheduled: String,
+    // Time startApp was sent to app-service
+    pub started: String,
+    // Whether app-service started the app
+    pub start_success: bool,
+    // Errors returned by app-service when starting the app
+    pub response: String,
+    // Time the app exited, from app-service's app monitor
+    pub finished: Option<String>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    // The app started and did not exit with a failure. False until the
+    // outcome is known
+    pub success: bool,
+}
+
+// Run and failure counts of one task
+#[derive(Clone, Debug, Default, SimpleObject, Serialize, Deserialize)]
+pub struct TaskCounters {
+    pub task: String,
+    // Attempts made, including retries
+    pub runs: u32,
+    // Attempts which failed
+    pub failures: u32,
+    // Attempts which failed since the last success
+    pub consecutive_failures: u32,
+    pub last_success: Option<String>,
+    pub last_failure: Option<String>,
+}
+
+// Persisted portion of the log
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LogState {
+    next_id: u64,
+    records: VecDeque<ExecutionRecord>,
+    counters: BTreeMap<String, TaskCounters>,
+}
+
+// Execution log shared by all scheduled tasks
+#[derive(Debug)]
+pub struct ExecutionLog {
+    dir: String,
+    state: LogState,
+}
+
+impl ExecutionLog {
+    // Read the log from the schedules directory. A missing or unreadable
+    // log starts a new one.
+    pub fn load(dir: &str) -> ExecutionLog {
+        let path = Path::new(dir).join(HISTORY_FILE);
+        let state = if path.is_file() {
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+            {
+                Ok(state) => state,
+                Err(e) => {
+                    error!("Failed to read execution log, starting a new one: {}", e);
+                    LogState::default()
+                }
+            }
+        } else {
+            LogState::default()
+        };
+
+        ExecutionLog {
+            dir: dir.to_owned(),
+            state,
+        }
+    }
+
+    // Record the start of an attempt and return its id. Attempts which
+    // app-service failed to start are complete once recorded.
+    pub fn begin(
+        &mut self,
+        task: &str,
+        app: &str,
+        attempt: u32,
+        scheduled: DateTime<Utc>,
+        started: DateTime<Utc>,
+        result: &Result<(), SchedulerError>,
+    ) -> u64 {
+        let id = self.state.next_id;
+        self.state.next_id += 1;
+
+        let record = ExecutionRecord {
+            id,
+            task: task.to_owned(),
+            mode: get_active_mode(&self.dir)
+                .ok()
+                .flatten()
+                .map(|mode| mode.name),
+            app: app.to_owned(),
+            attempt,
+            scheduled: scheduled.format(TIME_FORMAT).to_string(),
+            started: started.format(TIME_FORMAT).to_string(),
+            start_success: result.is_ok(),
+            response: match result {
+                Ok(()) => "".to_owned(),
+                Err(e) => e.to_string(),
+            },
+            finished: None,
+            exit_code: None,
+            signal: None,
+            success: false,
+        };
+
+        self.state.records.push_back(record);
+        while self.state.records.len() > HISTORY_LIMIT {
+            self.state.records.pop_front();
+        }
+        if result.is_err() {
+            self.count(task, false);
+        }
+        self.save();
+        id
+    }
+
+    // Record the outcome of a started attempt. The exit status is None if
+    // it is not being monitored or the app did not exit in time.
+    pub fn complete(&mut self, id: u64, status: Option<&ExitStatus>) {
+        let record = match self.state.records.iter_mut().find(|record| record.id == id) {
+            Some(record) if record.start_success => record,
+            _ => return,
+        };
+
+        record.success = status.is_none_or(|status| status.success());
+        if let Some(status) = status {
+            record.finished = Some(status.finished.format(TIME_FORMAT).to_string());
+            record.exit_code = status.code;
+            record.signal = status.signal;
+        }
+        let (task, success) = (record.task.to_owned(), record.success);
+        self.count(&task, success);
+        self.save();
+    }
+
+    // Most recent executions first, optionally of one task only
+    pub fn records(&self, task: Option<&str>, limit: Option<usize>) -> Vec<ExecutionRecord> {
+        self.state
+            .records
+            .iter()
+            .rev()
+            .filter(|record| task.is_none_or(|task| record.task == task))
+            .take(limit.unwrap_or(HISTORY_LIMIT))
+            .cloned()
+            .collect()
+    }
+
+    // Counters of every task which has run, or of one task
+    pub fn counters(&self, task: Option<&str>) -> Vec<TaskCounters> {
+        self.state
+            .counters
+            .values()
+            .filter(|counters| task.is_none_or(|task| counters.task == task))
+            .cloned()
+            .collect()
+    }
+
+    // Clear the counters of every task, or of one task
+    pub fn reset_counters(&mut self, task: Option<&str>) {
+        match task {
+            Some(task) => {
+                self.state.counters.remove(task);
+            }
+            None => self.state.counters.clear(),
+        }
+        self.save();
+    }
+
+    fn count(&mut self, task: &str, success: bool) {
+        let now = Utc::now().format(TIME_FORMAT).to_string();
+        let counters = self
+            .state
+            .counters
+            .entry(task.to_owned())
+            .or_insert_with(|| TaskCounters {
+                task: task.to_owned(),
+                ..Default::default()
+            });
+
+        counters.runs += 1;
+        if success {
+            counters.consecutive_failures = 0;
+            counters.last_success = Some(now);
+        } else {
+            counters.failures += 1;
+            counters.consecutive_failures += 1;
+            counters.last_failure = Some(now);
+        }
+    }
+
+    // Write then rename, so a reset mid-write keeps the previous log
+    fn save(&self) {
+        let temp = Path::new(&self.dir).join(format!("new_{}", HISTORY_FILE));
+        let result = serde_json::to_string(&self.state)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| fs::write(&temp, contents).map_err(|e| e.to_string()))
+            .and_then(|_| {
+                fs::rename(&temp, Path::new(&self.dir).join(HISTORY_FILE))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            error!("Failed to save execution log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn start_error() -> Result<(), SchedulerError> {
+        Err(SchedulerError::GenericError {
+            err: "App not registered".to_owned(),
+        })
+    }
+
+    fn exit(code: i32) -> ExitStatus {
+        ExitStatus {
+            finished: Utc::now(),
+            code: Some(code),
+            signal: None,
+        }
+    }
+
+    #[test]
+    fn test_counts_failures() {
+        let dir = TempDir::new().unwrap();
+        let mut log = ExecutionLog::load(dir.path().to_str().unwrap());
+        let now = Utc::now();
+
+        log.begin("beacon", "beacon-app", 1, now, now, &start_error());
+        let id = log.begin("beacon", "beacon-app", 2, now, now, &Ok(()));
+        log.complete(id, Some(&exit(1)));
+
+        let counters = &log.counters(Some("beacon"))[0];
+        assert_eq!(counters.runs, 2);
+        assert_eq!(counters.failures, 2);
+        assert_eq!(counters.consecutive_failures, 2);
+
+        let id = log.begin("beacon", "beacon-app", 1, now, now, &Ok(()));
+        log.complete(id, Some(&exit(0)));
+        let counters = &log.counters(Some("beacon"))[0];
+        assert_eq!(counters.runs, 3);
+        assert_eq!(counters.failures, 2);
+        assert_eq!(counters.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_records_newest_first() {
+        let dir = TempDir::new().unwrap();
+        let mut log = ExecutionLog::load(dir.path().to_str().unwrap());
+        let now = Utc::now();
+
+        let id = log.begin("first", "first-app", 1, now, now, &Ok(()));
+        log.complete(id, None);
+        log.begin("second", "second-app", 1, now, now, &start_error());
+
+        let records = log.records(None, None);
+        assert_eq!(records[0].task, "second");
+        assert!(!records[0].success);
+        assert_eq!(
+            records[0].response,
+            "Scheduler error encountered: App not registered"
+        );
+        assert_eq!(records[1].task, "first");
+        assert!(records[1].success);
+        assert_eq!(log.records(Some("first"), Some(5)).len(), 1);
+    }
+
+    #[test]
+    fn test_log_persists() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let now = Utc::now();
+        {
+            let mut log = ExecutionLog::load(path);
+            let id = log.begin("beacon", "beacon-app", 1, now, now, &Ok(()));
+            log.complete(id, Some(&exit(3)));
+        }
+
+        let log = ExecutionLog::load(path);
+        let record = &log.records(None, None)[0];
+        assert_eq!(record.exit_code, Some(3));
+        assert!(!record.success);
+        assert_eq!(log.counters(None)[0].failures, 1);
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let dir = TempDir::new().unwrap();
+        let mut log = ExecutionLog::load(dir.path().to_str().unwrap());
+        let now = Utc::now();
+
+        for _ in 0..HISTORY_LIMIT + 10 {
+            log.begin("beacon", "beacon-app", 1, now, now, &start_error());
+        }
+        assert_eq!(log.records(None, None).len(), HISTORY_LIMIT);
+        assert_eq!(log.counters(None)[0].runs as usize, HISTORY_LIMIT + 10);
+    }
+}
@@ -21,19 +21,27 @@
 
 mod app;
 mod error;
+mod history;
 mod mode;
+mod orbit;
 mod scheduler;
 mod schema;
+mod sgp4;
+mod simulation;
 mod task;
 mod task_list;
+mod transitions;
+mod trigger;
 
 use crate::error::SchedulerError;
 use kubos_service::{Config, Logger, Service};
-use log::{error};
+use log::{error, warn};
 use scheduler::{Scheduler, DEFAULT_SCHEDULES_DIR};
 use schema::{MutationRoot, QueryRoot};
+use task::parse_hms_field;
 
-fn main() -> Result<(), SchedulerError> {
+#[tokio::main]
+async fn main() -> Result<(), SchedulerError> {
     eprintln!("Starting scheduler service main()");
     Logger::init("kubos-scheduler-service").unwrap();
     eprintln!("Logger initialized");
@@ -53,6 +61,23 @@ fn main() -> Result<(), SchedulerError> {
         String::from(DEFAULT_SCHEDULES_DIR)
     };
 
+    // Exit statuses are only collected if a poll period is configured
+    let exit_status_poll = match config.get("exit_status_poll") {
+        Some(poll) => {
+            let poll = poll.as_str().ok_or_else(|| SchedulerError::StartError {
+                err: "Error parsing exit_status_poll".to_owned(),
+            })?;
+            let poll = parse_hms_field(poll.to_owned())?;
+            if poll.is_zero() {
+                return Err(SchedulerError::StartError {
+                    err: "exit_status_poll must be greater than zero".to_owned(),
+                });
+            }
+            Some(poll)
+        }
+        None => None,
+    };
+
     let apps_service_config =
         Config::new("app-service").map_err(|err| SchedulerError::StartError {
             err: format!("Failed to load app service config: {:?}", err),
@@ -65,7 +90,18 @@ fn main() -> Result<(), SchedulerError> {
                 err: "Failed to fetch app service url".to_owned(),
             })?;
 
-    let scheduler = Scheduler::new(&scheduler_dir, &apps_service_url)?;
+    // Telemetry and FRAM services are only needed by task triggers and
+    // transition guards, so their absence is not fatal
+    let telemetry_service_url = optional_service_url("telemetry-service");
+    let fram_service_url = optional_service_url("fram-service");
+
+    let scheduler = Scheduler::new(
+        &scheduler_dir,
+        &apps_service_url,
+        telemetry_service_url,
+        fram_service_url,
+        exit_status_poll,
+    )?;
 
     scheduler.init()?;
 
@@ -74,6 +110,21 @@ fn main() -> Result<(), SchedulerError> {
         error!("Failed to schedule tasks: {:?}", e);
     }
 
-    Service::new(config, scheduler, QueryRoot, MutationRoot).start();
+    // Automatic mode transitions run for the life of the service
+    scheduler.start_transitions();
+
+    Service::new(config, scheduler, QueryRoot, MutationRoot)
+        .start_async()
+        .await;
     Ok(())
 }
+
+fn optional_service_url(name: &str) -> Option<String> {
+    match Config::new(name) {
+        Ok(config) => config.hosturl(),
+        Err(err) => {
+            warn!("Failed to load {} config: {:?}", name, err);
+            None
+        }
+    }
+}
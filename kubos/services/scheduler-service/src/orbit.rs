@@ -0,0 +1,693 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Definitions and functions for tasks which run relative to ground passes
+//! and eclipses
+//!
+
+use crate::app::App;
+use crate::error::SchedulerError;
+use crate::sgp4::{dot, in_shadow, sun_position, teme_to_ecef, Sgp4, Tle};
+use crate::task::{parse_hms_field, run_task, RetryPolicy, TaskContext};
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+// TLE and ground stations, kept in the schedules directory
+pub static ORBIT_FILE: &str = "orbit.json";
+static TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// Step used to look for pass and eclipse boundaries, in seconds. Passes
+// shorter than this may be missed.
+const SEARCH_STEP: f64 = 20.0;
+// Boundaries are refined to within this many seconds
+const SEARCH_RESOLUTION: f64 = 0.5;
+// How far ahead orbit tasks look for their next event, in seconds
+const SEARCH_WINDOW: f64 = 24.0 * 3600.0;
+// Longest look-ahead allowed for the upcoming event queries, in hours
+pub const MAX_LOOKAHEAD_HOURS: i32 = 168;
+// Events this close together, in seconds, are taken to be the same event
+// when a new TLE moves them
+const SAME_EVENT: f64 = 60.0;
+// Orbit tasks re-plan at least this often, so clock corrections and missing
+// TLEs are picked up
+const REPLAN: Duration = Duration::from_secs(3600);
+// WGS-84 ellipsoid, used for ground station positions
+const WGS84_A: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+// Orbit events a task can be scheduled against
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrbitEventKind {
+    PassStart,
+    PassEnd,
+    EclipseEntry,
+    EclipseExit,
+}
+
+// Ground station used to compute pass windows
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroundStation {
+    pub name: String,
+    // Geodetic latitude and longitude in degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    // Height above the WGS-84 ellipsoid in metres
+    pub altitude: Option<f64>,
+    // Elevation in degrees above which the satellite is in view, default 0
+    pub min_elevation: Option<f64>,
+}
+
+// Orbit event a task runs relative to
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct OrbitTiming {
+    pub event: OrbitEventKind,
+    // Time from the event to the task, in Xh Ym Zs format. A leading '-'
+    // runs the task before the event
+    pub offset: Option<String>,
+    // Ground station of pass events. Passes over any station are used if not given
+    pub station: Option<String>,
+}
+
+// Upcoming pass or eclipse boundary
+#[derive(Clone, Debug, SimpleObject)]
+pub struct OrbitEvent {
+    pub kind: OrbitEventKind,
+    // Ground station of pass events
+    pub station: Option<String>,
+    pub time: String,
+    #[graphql(skip)]
+    pub timestamp: f64,
+}
+
+// Upcoming run of an orbit task in the active mode
+#[derive(Clone, Debug, SimpleObject)]
+pub struct OrbitTaskRun {
+    pub task_list: String,
+    pub description: String,
+    pub event: OrbitEventKind,
+    pub station: Option<String>,
+    // Time of the event
+    pub event_time: String,
+    // Time the task runs, after the offset is applied
+    pub time: String,
+}
+
+// Persisted orbit settings
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OrbitFile {
+    line1: Option<String>,
+    line2: Option<String>,
+    #[serde(default)]
+    stations: Vec<GroundStation>,
+}
+
+// Station position and local vertical in the Earth-fixed frame
+#[derive(Clone, Debug)]
+struct StationFrame {
+    name: String,
+    position: [f64; 3],
+    up: [f64; 3],
+    min_elevation: f64,
+}
+
+// Current TLE and ground stations
+#[derive(Clone, Debug, Default)]
+pub struct Orbit {
+    tle: Option<(String, String)>,
+    propagator: Option<Sgp4>,
+    stations: Vec<GroundStation>,
+    frames: Vec<StationFrame>,
+}
+
+impl GroundStation {
+    fn validate(&self) -> Result<(), SchedulerError> {
+        let orbit_error = |err: &str| SchedulerError::OrbitError {
+            err: format!("Ground station '{}': {}", self.name, err),
+        };
+        if self.name.is_empty() {
+            return Err(orbit_error("Name must not be empty"));
+        }
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(orbit_error("Latitude must be between -90 and 90 degrees"));
+        }
+        if !(-180.0..=360.0).contains(&self.longitude) {
+            return Err(orbit_error(
+                "Longitude must be between -180 and 360 degrees",
+            ));
+        }
+        if let Some(elevation) = self.min_elevation {
+            if !(-90.0..90.0).contains(&elevation) {
+                return Err(orbit_error(
+                    "Minimum elevation must be between -90 and 90 degrees",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn frame(&self) -> StationFrame {
+        let (sin_lat, cos_lat) = self.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.to_radians().sin_cos();
+        let e2 = WGS84_F * (2.0 - WGS84_F);
+        let n = WGS84_A / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        let height = self.altitude.unwrap_or(0.0) / 1000.0;
+
+        StationFrame {
+            name: self.name.to_owned(),
+            position: [
+                (n + height) * cos_lat * cos_lon,
+                (n + height) * cos_lat * sin_lon,
+                (n * (1.0 - e2) + height) * sin_lat,
+            ],
+            up: [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+            min_elevation: self.min_elevation.unwrap_or(0.0).to_radians(),
+        }
+    }
+}
+
+impl StationFrame {
+    fn visible(&self, satellite: [f64; 3]) -> bool {
+        let range = [
+            satellite[0] - self.position[0],
+            satellite[1] - self.position[1],
+            satellite[2] - self.position[2],
+        ];
+        let elevation = (dot(range, self.up) / dot(range, range).sqrt()).asin();
+        elevation > self.min_elevation
+    }
+}
+
+impl OrbitTiming {
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        self.get_offset(description)?;
+        if self.station.is_some()
+            && !matches!(
+                self.event,
+                OrbitEventKind::PassStart | OrbitEventKind::PassEnd
+            )
+        {
+            return Err(SchedulerError::TaskParseError {
+                err: "Station may only be given for pass events".to_owned(),
+                description: description.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    // Offset from the event in seconds
+    pub fn get_offset(&self, description: &str) -> Result<f64, SchedulerError> {
+        let offset = match &self.offset {
+            Some(offset) => offset.trim(),
+            None => return Ok(0.0),
+        };
+        let (sign, field) = match offset.strip_prefix('-') {
+            Some(field) => (-1.0, field),
+            None => (1.0, offset),
+        };
+        parse_hms_field(field.to_owned())
+            .map(|duration| sign * duration.as_secs_f64())
+            .map_err(|e| SchedulerError::TaskParseError {
+                err: format!("Invalid offset: {}", e),
+                description: description.to_owned(),
+            })
+    }
+
+    pub fn matches(&self, event: &OrbitEvent) -> bool {
+        event.kind == self.event
+            && self
+                .station
+                .as_ref()
+                .is_none_or(|station| event.station.as_ref() == Some(station))
+    }
+
+    // Run the task at every matching event for as long as the mode is active.
+    // The next event is re-planned whenever the TLE or stations change.
+    pub fn schedule(
+        &self,
+        description: &str,
+        app: &App,
+        retry: Option<RetryPolicy>,
+        context: &TaskContext,
+    ) -> Result<JoinHandle<()>, SchedulerError> {
+        let offset = self.get_offset(description)?;
+        let timing = self.clone();
+        let description = description.to_owned();
+        let app = app.clone();
+        let context = context.clone();
+        let mut orbit = context.orbit.clone();
+
+        Ok(tokio::spawn(async move {
+            let mut last_event: Option<f64> = None;
+            loop {
+                let current = orbit.borrow_and_update().clone();
+                let now = unix_now();
+                let mut after = now - offset;
+                if let Some(last) = last_event {
+                    after = after.max(last + SAME_EVENT);
+                }
+                let next = match current.next_event(&timing, after, after + SEARCH_WINDOW) {
+                    Ok(next) => next,
+                    Err(e) => {
+                        warn!("Failed to plan orbit task '{}': {}", description, e);
+                        None
+                    }
+                };
+
+                let due = next.as_ref().map(|event| event.timestamp + offset - now);
+                let wait = match due {
+                    Some(due) if due < REPLAN.as_secs_f64() => {
+                        Duration::from_secs_f64(due.max(0.0))
+                    }
+                    _ => REPLAN,
+                };
+                tokio::select! {
+                    _ = sleep(wait) => {}
+                    changed = orbit.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                }
+
+                if let (Some(event), Some(due)) = (next, due) {
+                    if wait.as_secs_f64() < due {
+                        // Woke up to re-plan before the event was due
+                        continue;
+                    }
+                    info!(
+                        "Executing orbit task '{}' for {:?} at {}",
+                        description, event.kind, event.time
+                    );
+                    let scheduled = DateTime::from_timestamp_millis(
+                        ((event.timestamp + offset) * 1000.0).round() as i64,
+                    )
+                    .unwrap_or_else(Utc::now);
+                    if let Err(e) =
+                        run_task(&app, &description, scheduled, retry.as_ref(), &context).await
+                    {
+                        error!("Failed to execute orbit task '{}': {}", description, e);
+                    }
+                    last_event = Some(event.timestamp);
+                }
+            }
+        }))
+    }
+}
+
+impl Orbit {
+    // Read the stored orbit settings. Problems are logged and the scheduler
+    // carries on without them, so orbit tasks wait for a new upload.
+    pub fn load(dir: &str) -> Orbit {
+        let path = Path::new(dir).join(ORBIT_FILE);
+        if !path.is_file() {
+            return Orbit::default();
+        }
+        let file: OrbitFile = match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Failed to read orbit settings: {}", e);
+                return Orbit::default();
+            }
+        };
+
+        let mut orbit = Orbit::default();
+        match orbit.with_stations(file.stations) {
+            Ok(with_stations) => orbit = with_stations,
+            Err(e) => error!("Failed to load ground stations: {}", e),
+        }
+        if let (Some(line1), Some(line2)) = (file.line1, file.line2) {
+            match orbit.with_tle(&line1, &line2) {
+                Ok(with_tle) => orbit = with_tle,
+                Err(e) => error!("Failed to load TLE: {}", e),
+            }
+        }
+        orbit
+    }
+
+    pub fn save(&self, dir: &str) -> Result<(), SchedulerError> {
+        let file = OrbitFile {
+            line1: self.tle.as_ref().map(|tle| tle.0.to_owned()),
+            line2: self.tle.as_ref().map(|tle| tle.1.to_owned()),
+            stations: self.stations.clone(),
+        };
+        let contents =
+            serde_json::to_string_pretty(&file).map_err(|e| SchedulerError::OrbitError {
+                err: format!("Failed to serialize orbit settings: {}", e),
+            })?;
+        // Write then rename, so a reset mid-write keeps the previous settings
+        let temp = Path::new(dir).join(format!("new_{}", ORBIT_FILE));
+        fs::write(&temp, contents)
+            .and_then(|_| fs::rename(&temp, Path::new(dir).join(ORBIT_FILE)))
+            .map_err(|e| SchedulerError::OrbitError {
+                err: format!("Failed to save orbit settings: {}", e),
+            })
+    }
+
+    // Whether a TLE has been uploaded, so events can be predicted
+    pub fn has_tle(&self) -> bool {
+        self.propagator.is_some()
+    }
+
+    // Copy of these settings with a new TLE
+    pub fn with_tle(&self, line1: &str, line2: &str) -> Result<Orbit, SchedulerError> {
+        let tle = Tle::parse(line1, line2)?;
+        let propagator = Sgp4::new(&tle)?;
+        // Check the elements can be propagated to now
+        propagator.propagate(unix_now())?;
+
+        Ok(Orbit {
+            tle: Some((line1.trim_end().to_owned(), line2.trim_end().to_owned())),
+            propagator: Some(propagator),
+            ..self.clone()
+        })
+    }
+
+    // Copy of these settings with new ground stations
+    pub fn with_stations(&self, stations: Vec<GroundStation>) -> Result<Orbit, SchedulerError> {
+        for (index, station) in stations.iter().enumerate() {
+            station.validate()?;
+            if stations[..index]
+                .iter()
+                .any(|other| other.name == station.name)
+            {
+                return Err(SchedulerError::OrbitError {
+                    err: format!("Ground station '{}' is defined twice", station.name),
+                });
+            }
+        }
+
+        Ok(Orbit {
+            frames: stations.iter().map(GroundStation::frame).collect(),
+            stations,
+            ..self.clone()
+        })
+    }
+
+    // All pass and eclipse boundaries between two Unix times, in time order
+    pub fn events(&self, start: f64, end: f64) -> Result<Vec<OrbitEvent>, SchedulerError> {
+        let propagator = self
+            .propagator
+            .as_ref()
+            .ok_or_else(|| SchedulerError::OrbitError {
+                err: "No TLE has been uploaded".to_owned(),
+            })?;
+        let sample = |time: f64| -> Result<(bool, Vec<bool>), SchedulerError> {
+            let (position, _) = propagator.propagate(time)?;
+            let fixed = teme_to_ecef(position, time);
+            Ok((
+                in_shadow(position, sun_position(time)),
+                self.frames
+                    .iter()
+                    .map(|frame| frame.visible(fixed))
+                    .collect(),
+            ))
+        };
+
+        let mut events = vec![];
+        let mut time = start;
+        let mut previous = sample(time)?;
+        while time < end {
+            let next_time = (time + SEARCH_STEP).min(end);
+            let current = sample(next_time)?;
+
+            if current.0 != previous.0 {
+                let crossing = refine(time, next_time, previous.0, |t| Ok(sample(t)?.0))?;
+                let kind = if current.0 {
+                    OrbitEventKind::EclipseEntry
+                } else {
+                    OrbitEventKind::EclipseExit
+                };
+                events.push(new_event(kind, None, crossing));
+            }
+            for (index, frame) in self.frames.iter().enumerate() {
+                if current.1[index] != previous.1[index] {
+                    let crossing = refine(time, next_time, previous.1[index], |t| {
+                        Ok(sample(t)?.1[index])
+                    })?;
+                    let kind = if current.1[index] {
+                        OrbitEventKind::PassStart
+                    } else {
+                        OrbitEventKind::PassEnd
+                    };
+                    events.push(new_event(kind, Some(frame.name.to_owned()), crossing));
+                }
+            }
+
+            previous = current;
+            time = next_time;
+        }
+
+        events.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        Ok(events)
+    }
+
+    // First event matching a task's timing between two Unix times
+    pub fn next_event(
+        &self,
+        timing: &OrbitTiming,
+        start: f64,
+        end: f64,
+    ) -> Result<Option<OrbitEvent>, SchedulerError> {
+        if let Some(station) = &timing.station {
+            if !self.stations.iter().any(|known| &known.name == station) {
+                return Err(SchedulerError::OrbitError {
+                    err: format!("Unknown ground station '{}'", station),
+                });
+            }
+        }
+        Ok(self
+            .events(start, end)?
+            .into_iter()
+            .find(|event| timing.matches(event)))
+    }
+
+    pub fn runs(
+        &self,
+        task_list: &str,
+        description: &str,
+        timing: &OrbitTiming,
+        events: &[OrbitEvent],
+        start: f64,
+    ) -> Result<Vec<OrbitTaskRun>, SchedulerError> {
+        let offset = timing.get_offset(description)?;
+        Ok(events
+            .iter()
+            .filter(|event| timing.matches(event) && event.timestamp + offset >= start)
+            .map(|event| OrbitTaskRun {
+                task_list: task_list.to_owned(),
+                description: description.to_owned(),
+                event: event.kind,
+                station: event.station.clone(),
+                event_time: event.time.to_owned(),
+                time: format_time(event.timestamp + offset),
+            })
+            .collect())
+    }
+}
+
+// Find the time a sampled condition changes between two times by bisection
+fn refine<F>(mut low: f64, mut high: f64, low_value: bool, test: F) -> Result<f64, SchedulerError>
+where
+    F: Fn(f64) -> Result<bool, SchedulerError>,
+{
+    while high - low > SEARCH_RESOLUTION {
+        let middle = (low + high) / 2.0;
+        if test(middle)? == low_value {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Ok(high)
+}
+
+fn new_event(kind: OrbitEventKind, station: Option<String>, timestamp: f64) -> OrbitEvent {
+    OrbitEvent {
+        kind,
+        station,
+        time: format_time(timestamp),
+        timestamp,
+    }
+}
+
+pub fn unix_now() -> f64 {
+    Utc::now().timestamp_millis() as f64 / 1000.0
+}
+
+pub fn format_time(timestamp: f64) -> String {
+    DateTime::from_timestamp(timestamp.round() as i64, 0)
+        .map(|time: DateTime<Utc>| time.format(TIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Equatorial orbit at about 500 km, which is eclipsed every revolution
+    // and passes over a station on the equator every revolution
+    const LINE1: &str = "1 99999U 24001A   24001.00000000  .00000000  00000-0  00000-0 0  9999";
+    const LINE2: &str = "2 99999   0.0000   0.0000 0001000   0.0000   0.0000 15.20000000    06";
+    // 2024-01-01 00:00:00 UTC
+    const EPOCH: f64 = 1_704_067_200.0;
+
+    fn station() -> GroundStation {
+        GroundStation {
+            name: "equator".to_owned(),
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude: None,
+            min_elevation: Some(10.0),
+        }
+    }
+
+    fn orbit() -> Orbit {
+        Orbit::default()
+            .with_stations(vec![station()])
+            .unwrap()
+            .with_tle(LINE1, LINE2)
+            .unwrap()
+    }
+
+    fn timing(event: OrbitEventKind, offset: Option<&str>) -> OrbitTiming {
+        OrbitTiming {
+            event,
+            offset: offset.map(|offset| offset.to_owned()),
+            station: None,
+        }
+    }
+
+    #[test]
+    fn test_events_alternate() {
+        let events = orbit().events(EPOCH, EPOCH + 6.0 * 3600.0).unwrap();
+        let eclipses: Vec<&OrbitEvent> = events
+            .iter()
+            .filter(|event| event.station.is_none())
+            .collect();
+        let passes: Vec<&OrbitEvent> = events
+            .iter()
+            .filter(|event| event.station.is_some())
+            .collect();
+
+        // About four revolutions in six hours
+        assert!(eclipses.len() >= 6, "{:?}", eclipses);
+        assert!(passes.len() >= 6, "{:?}", passes);
+        for pair in eclipses.windows(2) {
+            assert_ne!(pair[0].kind, pair[1].kind);
+        }
+        for pair in passes.windows(2) {
+            assert_ne!(pair[0].kind, pair[1].kind);
+        }
+        for pair in events.windows(2) {
+            assert!(pair[0].timestamp <= pair[1].timestamp);
+        }
+    }
+
+    #[test]
+    fn test_eclipse_duration() {
+        let events = orbit().events(EPOCH, EPOCH + 6.0 * 3600.0).unwrap();
+        let entry = events
+            .iter()
+            .find(|event| event.kind == OrbitEventKind::EclipseEntry)
+            .unwrap();
+        let exit = events
+            .iter()
+            .find(|event| {
+                event.kind == OrbitEventKind::EclipseExit && event.timestamp > entry.timestamp
+            })
+            .unwrap();
+        // A 500 km orbit spends roughly 35 minutes of its 95 in shadow
+        let minutes = (exit.timestamp - entry.timestamp) / 60.0;
+        assert!((30.0..40.0).contains(&minutes), "{} minutes", minutes);
+    }
+
+    #[test]
+    fn test_next_event_unknown_station() {
+        let mut timing = timing(OrbitEventKind::PassStart, None);
+        timing.station = Some("nowhere".to_owned());
+        assert_eq!(
+            orbit()
+                .next_event(&timing, EPOCH, EPOCH + 3600.0)
+                .unwrap_err(),
+            SchedulerError::OrbitError {
+                err: "Unknown ground station 'nowhere'".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_events_need_tle() {
+        assert!(Orbit::default().events(EPOCH, EPOCH + 60.0).is_err());
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(
+            timing(OrbitEventKind::PassStart, Some("-2m")).get_offset("task"),
+            Ok(-120.0)
+        );
+        assert_eq!(
+            timing(OrbitEventKind::PassEnd, Some("1m 5s")).get_offset("task"),
+            Ok(65.0)
+        );
+        assert_eq!(
+            timing(OrbitEventKind::PassEnd, None).get_offset("task"),
+            Ok(0.0)
+        );
+        assert!(timing(OrbitEventKind::PassEnd, Some("-"))
+            .get_offset("task")
+            .is_err());
+    }
+
+    #[test]
+    fn test_runs_apply_offset() {
+        let orbit = orbit();
+        let events = orbit.events(EPOCH, EPOCH + 3.0 * 3600.0).unwrap();
+        let timing = timing(OrbitEventKind::EclipseEntry, Some("-1m"));
+        let runs = orbit
+            .runs("power", "heaters", &timing, &events, EPOCH)
+            .unwrap();
+        let entry = events
+            .iter()
+            .find(|event| event.kind == OrbitEventKind::EclipseEntry)
+            .unwrap();
+
+        assert_eq!(runs[0].event_time, entry.time);
+        assert_eq!(runs[0].time, format_time(entry.timestamp - 60.0));
+    }
+
+    #[test]
+    fn test_station_validation() {
+        let mut bad = station();
+        bad.latitude = 91.0;
+        assert!(Orbit::default().with_stations(vec![bad]).is_err());
+        assert!(Orbit::default()
+            .with_stations(vec![station(), station()])
+            .is_err());
+    }
+}
@@ -18,21 +18,36 @@
 //! Structures and functions concerning the actual running of a schedule
 //!
 
+use crate::app::App;
 use crate::error::SchedulerError;
+use crate::history::{ExecutionLog, ExecutionRecord, TaskCounters};
 use crate::mode::{
     activate_mode, create_mode, get_active_mode, get_available_modes, is_mode_active,
 };
+use crate::orbit::{unix_now, GroundStation, Orbit, OrbitEvent, OrbitTaskRun, MAX_LOOKAHEAD_HOURS};
+use crate::simulation::{
+    check_window, parse_time, simulate, validate, SimulatedRun, ValidationReport,
+};
+use crate::task::{run_task, TaskContext};
 use crate::task_list::{get_mode_task_lists, validate_task_list, TaskList};
+use crate::transitions::{new_record, ModeMachine, TransitionCause, TransitionRecord};
+use crate::trigger::check_condition;
+use chrono::Utc;
 use log::{error, info, warn};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
 use tokio::task::JoinHandle;
+use tokio::time::sleep;
 
 pub static DEFAULT_SCHEDULES_DIR: &str = "/home/system/etc/schedules";
 pub static SAFE_MODE: &str = "safe";
+// Number of task outcomes buffered for each triggered task
+const TASK_EVENT_CAPACITY: usize = 64;
 
 // Handle to primitives controlling scheduler runtime context  
 pub struct SchedulerHandle {
@@ -44,16 +59,26 @@ pub struct SchedulerHandle {
 pub struct Scheduler {
     // Path to directory where schedules/modes are stored
     pub scheduler_dir: String,
-    // URL of App Service - for start app queries
-    app_service_url: String,
+    // Service URLs and task outcome channel handed to scheduled tasks
+    task_context: TaskContext,
     // Map of active task list names and scheduler handles. This allows us to
     // start/stop tasks associated with individual task lists
     scheduler_map: Arc<Mutex<HashMap<String, SchedulerHandle>>>,
+    // Mode transition graph, time in the current mode and transition history
+    modes: Arc<Mutex<ModeMachine>>,
+    // TLE and ground stations, published to orbit tasks when they change
+    orbit: Arc<watch::Sender<Arc<Orbit>>>,
 }
 
 impl Scheduler {
     // Create new Scheduler
-    pub fn new(sched_dir: &str, app_service_url: &str) -> Result<Scheduler, SchedulerError> {
+    pub fn new(
+        sched_dir: &str,
+        app_service_url: &str,
+        telemetry_service_url: Option<String>,
+        fram_service_url: Option<String>,
+        exit_status_poll: Option<Duration>,
+    ) -> Result<Scheduler, SchedulerError> {
         // Convert sched_dir to an absolute path
         let sched_dir_path = Path::new(sched_dir);
         let scheduler_dir = if sched_dir_path.is_relative() {
@@ -75,10 +100,23 @@ impl Scheduler {
             sched_dir.to_owned()
         };
 
+        let (orbit, orbit_receiver) = watch::channel(Arc::new(Orbit::load(&scheduler_dir)));
+        let history = Arc::new(Mutex::new(ExecutionLog::load(&scheduler_dir)));
+
         Ok(Scheduler {
+            modes: Arc::new(Mutex::new(ModeMachine::load(&scheduler_dir))),
+            orbit: Arc::new(orbit),
             scheduler_dir,
             scheduler_map: Arc::new(Mutex::new(HashMap::<String, SchedulerHandle>::new())),
-            app_service_url: app_service_url.to_owned(),
+            task_context: TaskContext {
+                app_service_url: app_service_url.to_owned(),
+                telemetry_service_url,
+                fram_service_url,
+                events: broadcast::channel(TASK_EVENT_CAPACITY).0,
+                orbit: orbit_receiver,
+                history,
+                exit_status_poll,
+            },
         })
     }
 
@@ -137,7 +175,7 @@ impl Scheduler {
     // Schedules tasks associated with task list
     fn start_task_list(&self, list: TaskList) -> Result<(), SchedulerError> {
         let mut schedules_map = self.scheduler_map.lock().unwrap();
-        let scheduler_handle = list.schedule_tasks(&self.app_service_url)?;
+        let scheduler_handle = list.schedule_tasks(&self.task_context)?;
         schedules_map.insert(list.filename, scheduler_handle);
         Ok(())
     }
@@ -172,7 +210,21 @@ impl Scheduler {
                         "Failed to start mode '{}', failing over: {}",
                         active_mode.name, err
                     );
-                    activate_mode(&self.scheduler_dir, SAFE_MODE)?;
+                    let actions = self
+                        .modes
+                        .lock()
+                        .unwrap()
+                        .actions(Some(&active_mode.name), SAFE_MODE);
+                    let result = activate_mode(&self.scheduler_dir, SAFE_MODE);
+                    self.record_transition(
+                        Some(active_mode.name),
+                        SAFE_MODE,
+                        TransitionCause::Failover,
+                        &err.to_string(),
+                        &result,
+                    );
+                    result?;
+                    self.run_actions(actions);
                     self.start()?;
                 }
             }
@@ -217,4 +269,286 @@ impl Scheduler {
             Ok(())
         }
     }
+
+    // Change to another mode, if the transition graph allows it, and start
+    // the exit actions of the old mode and entry actions of the new one
+    pub fn transition(
+        &self,
+        raw_name: &str,
+        cause: TransitionCause,
+        reason: &str,
+    ) -> Result<(), SchedulerError> {
+        let name = raw_name.to_lowercase();
+        let from = get_active_mode(&self.scheduler_dir)?.map(|mode| mode.name);
+
+        let (allowed, actions) = {
+            let modes = self.modes.lock().unwrap();
+            (
+                modes.allows(from.as_deref(), &name),
+                modes.actions(from.as_deref(), &name),
+            )
+        };
+        let result = if allowed {
+            activate_mode(&self.scheduler_dir, &name)
+        } else {
+            Err(SchedulerError::ActivateError {
+                err: format!(
+                    "Transition from '{}' is not allowed",
+                    from.as_deref().unwrap_or_default()
+                ),
+                name: name.to_owned(),
+            })
+        };
+        self.record_transition(from, &name, cause, reason, &result);
+        result?;
+
+        self.run_actions(actions);
+        self.stop()?;
+        self.start()
+    }
+
+    // Periodically check the guards of transitions out of the active mode
+    pub fn start_transitions(&self) {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let period = scheduler.modes.lock().unwrap().evaluate_period();
+                sleep(period).await;
+                if let Err(e) = scheduler.evaluate_transitions().await {
+                    warn!("Failed to evaluate mode transitions: {}", e);
+                }
+            }
+        });
+    }
+
+    // Re-read the transition graph from the schedules directory
+    pub fn reload_transitions(&self) -> Result<(), SchedulerError> {
+        self.modes.lock().unwrap().reload()
+    }
+
+    // Most recent mode transitions first
+    pub fn transition_history(&self, limit: Option<usize>) -> Vec<TransitionRecord> {
+        self.modes.lock().unwrap().history(limit)
+    }
+
+    // Most recent task executions first, optionally of one task only
+    pub fn task_history(&self, task: Option<&str>, limit: Option<usize>) -> Vec<ExecutionRecord> {
+        self.task_context
+            .history
+            .lock()
+            .unwrap()
+            .records(task, limit)
+    }
+
+    // Run and failure counters of every task, or of one task
+    pub fn task_counters(&self, task: Option<&str>) -> Vec<TaskCounters> {
+        self.task_context.history.lock().unwrap().counters(task)
+    }
+
+    pub fn reset_task_counters(&self, task: Option<&str>) {
+        self.task_context
+            .history
+            .lock()
+            .unwrap()
+            .reset_counters(task)
+    }
+
+    // Replace the TLE used to compute passes and eclipses
+    pub fn upload_tle(&self, line1: &str, line2: &str) -> Result<(), SchedulerError> {
+        let orbit = self.orbit.borrow().with_tle(line1, line2)?;
+        self.update_orbit(orbit)
+    }
+
+    // Replace the ground stations used to compute passes with a JSON list
+    pub fn set_ground_stations(&self, json: &str) -> Result<(), SchedulerError> {
+        let stations: Vec<GroundStation> =
+            serde_json::from_str(json).map_err(|e| SchedulerError::OrbitError {
+                err: format!("Failed to parse ground stations: {}", e),
+            })?;
+        let orbit = self.orbit.borrow().with_stations(stations)?;
+        self.update_orbit(orbit)
+    }
+
+    // Passes and eclipses in the next number of hours, in time order
+    pub fn upcoming_orbit_events(&self, hours: i32) -> Result<Vec<OrbitEvent>, SchedulerError> {
+        let (start, end) = lookahead(hours)?;
+        let orbit = self.orbit.borrow().clone();
+        orbit.events(start, end)
+    }
+
+    // Runs of the active mode's orbit tasks in the next number of hours, in time order
+    pub fn upcoming_orbit_tasks(&self, hours: i32) -> Result<Vec<OrbitTaskRun>, SchedulerError> {
+        let (start, end) = lookahead(hours)?;
+        let orbit = self.orbit.borrow().clone();
+        let mode = match get_active_mode(&self.scheduler_dir)? {
+            Some(mode) => mode,
+            None => return Ok(vec![]),
+        };
+
+        let lists = get_mode_task_lists(&mode.path)?;
+        let tasks: Vec<_> = lists
+            .iter()
+            .flat_map(|list| list.tasks.iter().map(move |task| (list, task)))
+            .filter_map(|(list, task)| task.orbit.as_ref().map(|timing| (list, task, timing)))
+            .collect();
+        if tasks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let events = orbit.events(start, end)?;
+        let mut runs = vec![];
+        for (list, task, timing) in tasks {
+            runs.extend(orbit.runs(&list.filename, &task.description, timing, &events, start)?);
+        }
+        runs.sort_by(|a, b| a.time.cmp(&b.time));
+        Ok(runs)
+    }
+
+    // Check a task list without importing it. Overlaps are checked against
+    // the other task lists of a mode, if given, over the next number of hours.
+    pub async fn validate_task_list(
+        &self,
+        name: &str,
+        json: &str,
+        mode: Option<&str>,
+        hours: i32,
+    ) -> Result<ValidationReport, SchedulerError> {
+        let from = unix_now();
+        let to = from + f64::from(hours) * 3600.0;
+        check_window(from, to)?;
+        let existing = match mode {
+            Some(mode) => get_mode_task_lists(&self.mode_path(mode)?)?,
+            None => vec![],
+        };
+        let orbit = self.orbit.borrow().clone();
+        Ok(validate(
+            &name.to_lowercase(),
+            json,
+            &existing,
+            &orbit,
+            &self.task_context.app_service_url,
+            (from, to),
+        )
+        .await)
+    }
+
+    // Executions of a mode's tasks between two times, as if the mode were
+    // activated at the first
+    pub fn simulate_schedule(
+        &self,
+        mode: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<SimulatedRun>, SchedulerError> {
+        let lists = get_mode_task_lists(&self.mode_path(mode)?)?;
+        let orbit = self.orbit.borrow().clone();
+        simulate(&lists, &orbit, parse_time(from)?, parse_time(to)?)
+    }
+
+    fn mode_path(&self, mode: &str) -> Result<String, SchedulerError> {
+        let path = format!("{}/{}", self.scheduler_dir, mode.to_lowercase());
+        if !Path::new(&path).is_dir() {
+            return Err(SchedulerError::SimulationError {
+                err: format!("Mode '{}' not found", mode),
+            });
+        }
+        Ok(path)
+    }
+
+    fn update_orbit(&self, orbit: Orbit) -> Result<(), SchedulerError> {
+        orbit.save(&self.scheduler_dir)?;
+        self.orbit.send_replace(Arc::new(orbit));
+        Ok(())
+    }
+
+    async fn evaluate_transitions(&self) -> Result<(), SchedulerError> {
+        let mode = match get_active_mode(&self.scheduler_dir)? {
+            Some(mode) => mode.name,
+            None => return Ok(()),
+        };
+
+        let candidates = self.modes.lock().unwrap().candidates(&mode);
+        for (index, transition) in candidates {
+            let guard = match &transition.guard {
+                Some(guard) => guard,
+                None => continue,
+            };
+            let met = match check_condition(
+                guard.telemetry.as_ref(),
+                guard.flag.as_ref(),
+                &self.task_context,
+            )
+            .await
+            {
+                Ok(met) => met,
+                Err(e) => {
+                    warn!(
+                        "Failed to check guard of transition to '{}': {}",
+                        transition.to, e
+                    );
+                    continue;
+                }
+            };
+
+            let hold = guard.get_debounce()?;
+            let take = self.modes.lock().unwrap().update_guard(index, hold, met);
+            if take {
+                info!(
+                    "Guard '{}' met, moving from '{}' to '{}'",
+                    guard.describe(),
+                    mode,
+                    transition.to
+                );
+                return self.transition(&transition.to, TransitionCause::Guard, &guard.describe());
+            }
+        }
+        Ok(())
+    }
+
+    fn record_transition(
+        &self,
+        from: Option<String>,
+        to: &str,
+        cause: TransitionCause,
+        reason: &str,
+        result: &Result<(), SchedulerError>,
+    ) {
+        let active = get_active_mode(&self.scheduler_dir)
+            .ok()
+            .flatten()
+            .map(|mode| mode.name);
+        let record = new_record(from, to, cause, reason, result);
+        self.modes.lock().unwrap().record(record, active);
+    }
+
+    // Start mode exit and entry apps in order. They are not tied to the
+    // mode's task lists, so stopping the old mode does not cancel them.
+    fn run_actions(&self, actions: Vec<(String, App)>) {
+        if actions.is_empty() {
+            return;
+        }
+        let context = self.task_context.clone();
+        tokio::spawn(async move {
+            for (description, app) in actions {
+                info!("Running mode action '{}'", description);
+                if let Err(e) = run_task(&app, &description, Utc::now(), None, &context).await {
+                    error!("Failed to run mode action '{}': {}", description, e);
+                }
+            }
+        });
+    }
+}
+
+// Start and end Unix times of a look-ahead window
+fn lookahead(hours: i32) -> Result<(f64, f64), SchedulerError> {
+    if !(1..=MAX_LOOKAHEAD_HOURS).contains(&hours) {
+        return Err(SchedulerError::OrbitError {
+            err: format!(
+                "Look-ahead must be between 1 and {} hours",
+                MAX_LOOKAHEAD_HOURS
+            ),
+        });
+    }
+    let start = unix_now();
+    Ok((start, start + f64::from(hours) * 3600.0))
 }
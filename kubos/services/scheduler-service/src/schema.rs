@@ -18,9 +18,13 @@
 //! GraphQL schema for scheduler service's public interface
 //!
 
+use crate::history::{ExecutionRecord, TaskCounters};
 use crate::mode::*;
+use crate::orbit::{OrbitEvent, OrbitTaskRun};
 use crate::scheduler::{Scheduler, SAFE_MODE};
+use crate::simulation::{SimulatedRun, ValidationReport};
 use crate::task_list::{import_raw_task_list, import_task_list, remove_task_list};
+use crate::transitions::{TransitionCause, TransitionRecord};
 use async_graphql::{Context, Object, Result, SimpleObject};
 use serde::Deserialize;
 
@@ -31,6 +35,9 @@ pub struct GenericResponse {
     pub errors: String,
 }
 
+// Look-ahead of the upcoming orbit queries and task list validation if none is given
+const DEFAULT_LOOKAHEAD_HOURS: i32 = 24;
+
 pub struct QueryRoot;
 
 // Base GraphQL query model
@@ -55,6 +62,54 @@ impl QueryRoot {
         Ok(get_available_modes(&context.subsystem().scheduler_dir, name)
             .map_err(|err| async_graphql::Error::new(format!("Failed to get available modes: {}", err)))?)
     }
+
+    /// Returns the most recent mode transitions, newest first
+    async fn transition_history(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<TransitionRecord>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        let limit = limit.map(|limit| limit.max(0) as usize);
+        Ok(context.subsystem().transition_history(limit))
+    }
+
+    /// Returns the most recent task executions, newest first
+    async fn task_history(&self, ctx: &Context<'_>, task: Option<String>, limit: Option<i32>) -> Result<Vec<ExecutionRecord>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        let limit = limit.map(|limit| limit.max(0) as usize);
+        Ok(context.subsystem().task_history(task.as_deref(), limit))
+    }
+
+    /// Returns run and failure counters of each task
+    async fn task_counters(&self, ctx: &Context<'_>, task: Option<String>) -> Result<Vec<TaskCounters>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(context.subsystem().task_counters(task.as_deref()))
+    }
+
+    /// Returns ground passes and eclipse boundaries in the next `hours` (default 24)
+    async fn upcoming_orbit_events(&self, ctx: &Context<'_>, hours: Option<i32>) -> Result<Vec<OrbitEvent>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(context.subsystem().upcoming_orbit_events(hours.unwrap_or(DEFAULT_LOOKAHEAD_HOURS))
+            .map_err(|err| async_graphql::Error::new(format!("Failed to get orbit events: {}", err)))?)
+    }
+
+    /// Returns when the active mode's orbit tasks will run in the next `hours` (default 24)
+    async fn upcoming_orbit_tasks(&self, ctx: &Context<'_>, hours: Option<i32>) -> Result<Vec<OrbitTaskRun>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(context.subsystem().upcoming_orbit_tasks(hours.unwrap_or(DEFAULT_LOOKAHEAD_HOURS))
+            .map_err(|err| async_graphql::Error::new(format!("Failed to get orbit tasks: {}", err)))?)
+    }
+
+    /// Checks a task list without importing it, against the other task lists of `mode` if given
+    async fn validate_task_list(&self, ctx: &Context<'_>, name: String, json: String, mode: Option<String>, hours: Option<i32>) -> Result<ValidationReport> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(context.subsystem().validate_task_list(&name, &json, mode.as_deref(), hours.unwrap_or(DEFAULT_LOOKAHEAD_HOURS)).await
+            .map_err(|err| async_graphql::Error::new(format!("Failed to validate task list: {}", err)))?)
+    }
+
+    /// Returns the executions of a mode's tasks between `from` and `to`, as if the mode were activated at `from`
+    async fn simulate_schedule(&self, ctx: &Context<'_>, mode: String, from: String, to: String) -> Result<Vec<SimulatedRun>> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(context.subsystem().simulate_schedule(&mode, &from, &to)
+            .map_err(|err| async_graphql::Error::new(format!("Failed to simulate schedule: {}", err)))?)
+    }
 }
 
 pub struct MutationRoot;
@@ -91,9 +146,7 @@ impl MutationRoot {
         }
         
         let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
-        Ok(match activate_mode(&context.subsystem().scheduler_dir, &name)
-        .and_then(|_| context.subsystem().stop())
-        .and_then(|_| context.subsystem().start()) {
+        Ok(match context.subsystem().transition(&name, TransitionCause::Command, "activateMode") {
             Ok(_) => {
                 GenericResponse { success: true, errors: "".to_owned() }
             },
@@ -104,9 +157,7 @@ impl MutationRoot {
     /// Activates the safe mode
     async fn safe_mode(&self, ctx: &Context<'_>) -> Result<GenericResponse> {
         let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
-        Ok(match activate_mode(&context.subsystem().scheduler_dir, SAFE_MODE)
-        .and_then(|_| context.subsystem().stop())
-        .and_then(|_| context.subsystem().start()) {
+        Ok(match context.subsystem().transition(SAFE_MODE, TransitionCause::Command, "safeMode") {
             Ok(_) => {
                 GenericResponse { success: true, errors: "".to_owned() }
             },
@@ -114,6 +165,40 @@ impl MutationRoot {
         })
     }
 
+    /// Re-reads the mode transition graph from the schedules directory
+    async fn reload_transitions(&self, ctx: &Context<'_>) -> Result<GenericResponse> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(match context.subsystem().reload_transitions() {
+            Ok(_) => GenericResponse { success: true, errors: "".to_owned() },
+            Err(error) => GenericResponse { success: false, errors: error.to_string() }
+        })
+    }
+
+    /// Clears the run and failure counters of one task, or of every task
+    async fn reset_task_counters(&self, ctx: &Context<'_>, task: Option<String>) -> Result<GenericResponse> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        context.subsystem().reset_task_counters(task.as_deref());
+        Ok(GenericResponse { success: true, errors: "".to_owned() })
+    }
+
+    /// Replaces the TLE used to compute ground passes and eclipses
+    async fn upload_tle(&self, ctx: &Context<'_>, line1: String, line2: String) -> Result<GenericResponse> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(match context.subsystem().upload_tle(&line1, &line2) {
+            Ok(_) => GenericResponse { success: true, errors: "".to_owned() },
+            Err(error) => GenericResponse { success: false, errors: error.to_string() }
+        })
+    }
+
+    /// Replaces the ground stations used to compute passes
+    async fn set_ground_stations(&self, ctx: &Context<'_>, json: String) -> Result<GenericResponse> {
+        let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
+        Ok(match context.subsystem().set_ground_stations(&json) {
+            Ok(_) => GenericResponse { success: true, errors: "".to_owned() },
+            Err(error) => GenericResponse { success: false, errors: error.to_string() }
+        })
+    }
+
     /// Imports a new task list into a mode
     async fn import_task_list(&self, ctx: &Context<'_>, name: String, path: String, mode: String) -> Result<GenericResponse> {
         let context = ctx.data::<kubos_service::Context<Scheduler>>()?;
@@ -0,0 +1,680 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Two-line element parsing and SGP4 orbit propagation
+//!
+//! This follows Vallado's revised SGP4 (AIAA 2006-6753) with WGS-72 constants,
+//! as used to generate TLEs. Only near-Earth orbits (period under 225 minutes)
+//! are supported, which covers every LEO mission this service runs on.
+//!
+
+use crate::error::SchedulerError;
+use std::f64::consts::PI;
+
+const TWO_PI: f64 = 2.0 * PI;
+const MINUTES_PER_DAY: f64 = 1440.0;
+// Julian date of the Unix epoch
+const JD_UNIX_EPOCH: f64 = 2_440_587.5;
+const JD_J2000: f64 = 2_451_545.0;
+const AU_KM: f64 = 149_597_870.7;
+
+// WGS-72 constants
+pub const EARTH_RADIUS_KM: f64 = 6378.135;
+const MU: f64 = 398_600.8;
+const J2: f64 = 0.001_082_616;
+const J3: f64 = -0.000_002_538_81;
+const J4: f64 = -0.000_001_655_97;
+
+// Orbits with longer periods need the deep-space perturbations
+const DEEP_SPACE_PERIOD_MIN: f64 = 225.0;
+
+// Mean elements from a two-line element set
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tle {
+    // Epoch as seconds since the Unix epoch
+    pub epoch: f64,
+    // Drag term, in inverse earth radii
+    pub bstar: f64,
+    // Angles in radians
+    pub inclination: f64,
+    pub raan: f64,
+    pub eccentricity: f64,
+    pub arg_perigee: f64,
+    pub mean_anomaly: f64,
+    // Kozai mean motion, in radians per minute
+    pub mean_motion: f64,
+}
+
+impl Tle {
+    pub fn parse(line1: &str, line2: &str) -> Result<Tle, SchedulerError> {
+        let line1 = line1.trim_end();
+        let line2 = line2.trim_end();
+        check_line(line1, '1')?;
+        check_line(line2, '2')?;
+
+        if field(line1, 2, 7) != field(line2, 2, 7) {
+            return Err(tle_error("Catalog numbers of the two lines differ"));
+        }
+
+        let year = parse_number(line1, 18, 20, "epoch year")? as i64;
+        let day = parse_number(line1, 20, 32, "epoch day")?;
+        // Two-digit years 57-99 are 1957-1999, as defined for the TLE format
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let epoch = (days_from_civil(year, 1, 1) as f64 + day - 1.0) * 86400.0;
+
+        let bstar = parse_exponent(field(line1, 53, 61), "bstar")?;
+        let eccentricity = format!("0.{}", field(line2, 26, 33).trim())
+            .parse::<f64>()
+            .map_err(|_| tle_error("Failed to parse eccentricity"))?;
+        let revs_per_day = parse_number(line2, 52, 63, "mean motion")?;
+        if revs_per_day <= 0.0 {
+            return Err(tle_error("Mean motion must be positive"));
+        }
+
+        Ok(Tle {
+            epoch,
+            bstar,
+            inclination: parse_number(line2, 8, 16, "inclination")?.to_radians(),
+            raan: parse_number(line2, 17, 25, "right ascension")?.to_radians(),
+            eccentricity,
+            arg_perigee: parse_number(line2, 34, 42, "argument of perigee")?.to_radians(),
+            mean_anomaly: parse_number(line2, 43, 51, "mean anomaly")?.to_radians(),
+            mean_motion: revs_per_day * TWO_PI / MINUTES_PER_DAY,
+        })
+    }
+}
+
+fn tle_error(err: &str) -> SchedulerError {
+    SchedulerError::OrbitError {
+        err: format!("Invalid TLE: {}", err),
+    }
+}
+
+fn field(line: &str, start: usize, end: usize) -> &str {
+    line.get(start..end.min(line.len())).unwrap_or("")
+}
+
+// Check the line number, length and modulo 10 checksum
+fn check_line(line: &str, number: char) -> Result<(), SchedulerError> {
+    if !line.is_ascii() || line.len() != 69 {
+        return Err(tle_error(&format!(
+            "Line {} must be 69 ASCII characters",
+            number
+        )));
+    }
+    if !line.starts_with(number) {
+        return Err(tle_error(&format!(
+            "Line {} has the wrong line number",
+            number
+        )));
+    }
+
+    let sum: u32 = line[..68]
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    if line[68..].parse::<u32>() != Ok(sum % 10) {
+        return Err(tle_error(&format!("Line {} checksum mismatch", number)));
+    }
+    Ok(())
+}
+
+fn parse_number(line: &str, start: usize, end: usize, name: &str) -> Result<f64, SchedulerError> {
+    field(line, start, end)
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| tle_error(&format!("Failed to parse {}", name)))
+}
+
+// Parse fields like " 28098-4", meaning 0.28098e-4
+fn parse_exponent(raw: &str, name: &str) -> Result<f64, SchedulerError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(0.0);
+    }
+    let error = || tle_error(&format!("Failed to parse {}", name));
+    let split = raw.rfind(|c| c == '-' || c == '+').filter(|i| *i > 0);
+    let (mantissa, exponent) = match split {
+        Some(index) => (&raw[..index], &raw[index..]),
+        None => (raw, "0"),
+    };
+    let (sign, digits) = match mantissa.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, mantissa.trim_start_matches('+')),
+    };
+    let mantissa: f64 = format!("0.{}", digits).parse().map_err(|_| error())?;
+    let exponent: i32 = exponent.parse().map_err(|_| error())?;
+    Ok(sign * mantissa * 10f64.powi(exponent))
+}
+
+// Days from 1970-01-01 to the given date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Initialised SGP4 propagator for one element set
+#[derive(Clone, Debug)]
+pub struct Sgp4 {
+    epoch: f64,
+    bstar: f64,
+    ecco: f64,
+    inclo: f64,
+    nodeo: f64,
+    argpo: f64,
+    mo: f64,
+    no: f64,
+    xke: f64,
+    // Simplified drag model for perigees below 220 km
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    pub fn new(tle: &Tle) -> Result<Sgp4, SchedulerError> {
+        let orbit_error = |err: &str| SchedulerError::OrbitError {
+            err: err.to_owned(),
+        };
+        if tle.eccentricity >= 1.0 {
+            return Err(orbit_error("Eccentricity must be less than one"));
+        }
+
+        let xke = 60.0 / (EARTH_RADIUS_KM.powi(3) / MU).sqrt();
+        let j3oj2 = J3 / J2;
+        let x2o3 = 2.0 / 3.0;
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination;
+        let argpo = tle.arg_perigee;
+
+        // Recover the original (Brouwer) mean motion from the Kozai one
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / tle.mean_motion).powf(x2o3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = tle.mean_motion / (1.0 + del);
+
+        if TWO_PI / no >= DEEP_SPACE_PERIOD_MIN {
+            return Err(orbit_error(
+                "Deep-space orbits (period of 225 minutes or more) are not supported",
+            ));
+        }
+
+        let ao = (xke / no).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+        if rp < 1.0 {
+            return Err(orbit_error("Perigee is below the surface of the Earth"));
+        }
+
+        // Atmospheric density parameters, adjusted for low perigees
+        let ss = 78.0 / EARTH_RADIUS_KM + 1.0;
+        let isimp = rp < 220.0 / EARTH_RADIUS_KM + 1.0;
+        let mut sfour = ss;
+        let mut qzms24 = ((120.0 - 78.0) / EARTH_RADIUS_KM).powi(4);
+        let perige = (rp - 1.0) * EARTH_RADIUS_KM;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / EARTH_RADIUS_KM).powi(4);
+            sfour = sfour / EARTH_RADIUS_KM + 1.0;
+        }
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = tle.bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * j3oj2 * no * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates from J2 and J4
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+
+        let omgcof = tle.bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 {
+            -x2o3 * coef * tle.bstar / eeta
+        } else {
+            0.0
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        // Avoid dividing by zero for an inclination of 180 degrees
+        let xlcof_den = if (cosio + 1.0).abs() > 1.5e-12 {
+            1.0 + cosio
+        } else {
+            1.5e-12
+        };
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / xlcof_den;
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * tle.mean_anomaly.cos()).powi(3);
+        let sinmao = tle.mean_anomaly.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) =
+            (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Sgp4 {
+            epoch: tle.epoch,
+            bstar: tle.bstar,
+            ecco,
+            inclo,
+            nodeo: tle.raan,
+            argpo,
+            mo: tle.mean_anomaly,
+            no,
+            xke,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        })
+    }
+
+    // Position (km) and velocity (km/s) in the TEME frame at a Unix time
+    pub fn propagate(&self, time: f64) -> Result<([f64; 3], [f64; 3]), SchedulerError> {
+        self.propagate_minutes((time - self.epoch) / 60.0)
+    }
+
+    // Position (km) and velocity (km/s) in the TEME frame, minutes from epoch
+    pub fn propagate_minutes(&self, t: f64) -> Result<([f64; 3], [f64; 3]), SchedulerError> {
+        let orbit_error = |err: &str| SchedulerError::OrbitError {
+            err: format!("Propagation failed {:.0} minutes from epoch: {}", t, err),
+        };
+        let x2o3 = 2.0 / 3.0;
+        let vkmpersec = EARTH_RADIUS_KM * self.xke / 60.0;
+
+        // Secular gravity and drag
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let t2 = t * t;
+        let nodem = nodedf + self.nodecf * t2;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let am = (self.xke / self.no).powf(x2o3) * tempa * tempa;
+        let nm = self.xke / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(orbit_error("eccentricity out of range"));
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += self.no * templ;
+        let xlm = mm + argpm + nodem;
+
+        let nodem = nodem % TWO_PI;
+        let argpm = argpm % TWO_PI;
+        let xlm = xlm % TWO_PI;
+        let mm = (xlm - argpm - nodem) % TWO_PI;
+
+        let sinip = self.inclo.sin();
+        let cosip = self.inclo.cos();
+
+        // Long period periodics
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Solve Kepler's equation
+        let u = (xl - nodem) % TWO_PI;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut sineo1 = 0.0;
+        let mut coseo1 = 0.0;
+        let mut ktr = 1;
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            if tem5.abs() >= 0.95 {
+                tem5 = 0.95 * tem5.signum();
+            }
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // Short period periodics
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(orbit_error("semi-latus rectum is negative"));
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        let su = su - 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / self.xke;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / self.xke;
+
+        if mrt < 1.0 {
+            return Err(orbit_error("satellite has decayed"));
+        }
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        let radius = mrt * EARTH_RADIUS_KM;
+        Ok((
+            [radius * ux, radius * uy, radius * uz],
+            [
+                (mvt * ux + rvdot * vx) * vkmpersec,
+                (mvt * uy + rvdot * vy) * vkmpersec,
+                (mvt * uz + rvdot * vz) * vkmpersec,
+            ],
+        ))
+    }
+}
+
+// Julian date of a Unix time
+fn julian_date(time: f64) -> f64 {
+    time / 86400.0 + JD_UNIX_EPOCH
+}
+
+// Greenwich mean sidereal time in radians (IAU 1982)
+pub fn gmst(time: f64) -> f64 {
+    let tut1 = (julian_date(time) - JD_J2000) / 36525.0;
+    let seconds = -6.2e-6 * tut1.powi(3)
+        + 0.093_104 * tut1 * tut1
+        + (876_600.0 * 3600.0 + 8_640_184.812_866) * tut1
+        + 67_310.548_41;
+    (seconds.to_radians() / 240.0).rem_euclid(TWO_PI)
+}
+
+// Rotate a TEME position into the Earth-fixed frame, ignoring polar motion
+pub fn teme_to_ecef(position: [f64; 3], time: f64) -> [f64; 3] {
+    let (sin_g, cos_g) = gmst(time).sin_cos();
+    [
+        cos_g * position[0] + sin_g * position[1],
+        -sin_g * position[0] + cos_g * position[1],
+        position[2],
+    ]
+}
+
+// Low precision Sun position in km (Astronomical Almanac, about 0.01 degrees)
+pub fn sun_position(time: f64) -> [f64; 3] {
+    let t = (julian_date(time) - JD_J2000) / 36525.0;
+    let mean_longitude = 280.460 + 36_000.771 * t;
+    let mean_anomaly = (357.529_109_2 + 35_999.050_34 * t).to_radians();
+    let ecliptic_longitude = (mean_longitude
+        + 1.914_666_471 * mean_anomaly.sin()
+        + 0.019_994_643 * (2.0 * mean_anomaly).sin())
+    .to_radians();
+    let obliquity = (23.439_291 - 0.013_004_2 * t).to_radians();
+    let distance = (1.000_140_612
+        - 0.016_708_617 * mean_anomaly.cos()
+        - 0.000_139_589 * (2.0 * mean_anomaly).cos())
+        * AU_KM;
+
+    [
+        distance * ecliptic_longitude.cos(),
+        distance * obliquity.cos() * ecliptic_longitude.sin(),
+        distance * obliquity.sin() * ecliptic_longitude.sin(),
+    ]
+}
+
+// Whether a position (km, TEME) is inside the Earth's cylindrical shadow
+pub fn in_shadow(position: [f64; 3], sun: [f64; 3]) -> bool {
+    let sun_norm = dot(sun, sun).sqrt();
+    let along = dot(position, sun) / sun_norm;
+    if along >= 0.0 {
+        return false;
+    }
+    let across = (dot(position, position) - along * along).max(0.0).sqrt();
+    across < EARTH_RADIUS_KM
+}
+
+pub fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vallado's SGP4 verification case for catalog number 5
+    const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+    const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < tolerance, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_tle() {
+        let tle = Tle::parse(LINE1, LINE2).unwrap();
+        assert!((tle.bstar - 2.8098e-5).abs() < 1e-12);
+        assert!((tle.eccentricity - 0.1859667).abs() < 1e-12);
+        // 2000-06-27 18:50:19.733568 UTC
+        assert!((tle.epoch - 962_131_819.733_568).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_parse_tle_checksum() {
+        let bad = LINE1.replace("4753", "4754");
+        assert_eq!(
+            Tle::parse(&bad, LINE2),
+            Err(SchedulerError::OrbitError {
+                err: "Invalid TLE: Line 1 checksum mismatch".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_propagate_epoch() {
+        let sgp4 = Sgp4::new(&Tle::parse(LINE1, LINE2).unwrap()).unwrap();
+        let (position, velocity) = sgp4.propagate_minutes(0.0).unwrap();
+        assert_close(
+            position,
+            [7022.465_292_66, -1400.082_967_55, 0.039_951_55],
+            1e-5,
+        );
+        assert_close(
+            velocity,
+            [1.893_841_015, 6.405_893_759, 4.534_807_250],
+            1e-8,
+        );
+    }
+
+    #[test]
+    fn test_propagate_six_hours() {
+        let sgp4 = Sgp4::new(&Tle::parse(LINE1, LINE2).unwrap()).unwrap();
+        let (position, velocity) = sgp4.propagate_minutes(360.0).unwrap();
+        assert_close(
+            position,
+            [-7154.031_202_02, -3783.176_825_04, -3536.194_122_94],
+            1e-5,
+        );
+        assert_close(
+            velocity,
+            [4.741_887_409, -4.151_817_765, -2.093_935_425],
+            1e-8,
+        );
+    }
+
+    #[test]
+    fn test_reject_deep_space() {
+        // Molniya orbit with a 12 hour period
+        let line1 = "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813";
+        let line2 = "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656";
+        let tle = Tle::parse(line1, line2).unwrap();
+        assert!(Sgp4::new(&tle).is_err());
+    }
+
+    #[test]
+    fn test_shadow() {
+        let sun = [AU_KM, 0.0, 0.0];
+        assert!(in_shadow([-7000.0, 0.0, 0.0], sun));
+        assert!(!in_shadow([7000.0, 0.0, 0.0], sun));
+        assert!(!in_shadow([-7000.0, 7000.0, 0.0], sun));
+    }
+}
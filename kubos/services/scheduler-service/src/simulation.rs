@@ -0,0 +1,559 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Dry runs of schedules: checking task lists before they are imported and
+//! predicting when a mode's tasks will run
+//!
+
+use crate::error::SchedulerError;
+use crate::orbit::{format_time, Orbit, OrbitEvent, MAX_LOOKAHEAD_HOURS};
+use crate::task::Task;
+use crate::task_list::{parse_tasks, TaskList};
+use async_graphql::SimpleObject;
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+
+static TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// Tasks without a runtime are taken to hold their app and resources this long
+const MIN_RUNTIME: f64 = 1.0;
+// Most executions a simulation will produce
+const MAX_RUNS: usize = 10_000;
+
+// Predicted execution of a task
+#[derive(Clone, Debug, SimpleObject)]
+pub struct SimulatedRun {
+    pub task_list: String,
+    pub description: String,
+    pub app: String,
+    // Time the task runs
+    pub time: String,
+    // Time the app is expected to finish, if the task gives a runtime
+    pub end: Option<String>,
+}
+
+// Outcome of checking a task list before it is imported
+#[derive(Debug, Default, SimpleObject)]
+pub struct ValidationReport {
+    // No errors were found
+    pub valid: bool,
+    // Problems which would stop the task list importing or running as intended
+    pub errors: Vec<String>,
+    // Possible problems, such as an app started while it may still be running
+    pub warnings: Vec<String>,
+}
+
+// Execution of a task within a simulation window
+struct Run<'a> {
+    task_list: &'a str,
+    task: &'a Task,
+    start: f64,
+    end: f64,
+    // Belongs to the task list being validated
+    candidate: bool,
+}
+
+// Parse a window boundary in yyyy-mm-dd hh:mm:ss format
+pub fn parse_time(time: &str) -> Result<f64, SchedulerError> {
+    NaiveDateTime::parse_from_str(time, TIME_FORMAT)
+        .map(|time| time.and_utc().timestamp() as f64)
+        .map_err(|e| SchedulerError::SimulationError {
+            err: format!("Failed to parse time '{}': {}", time, e),
+        })
+}
+
+// Check that a window ends after it starts and is no longer than orbit
+// events can be predicted for
+pub fn check_window(from: f64, to: f64) -> Result<(), SchedulerError> {
+    if to <= from {
+        return Err(SchedulerError::SimulationError {
+            err: "Window must end after it starts".to_owned(),
+        });
+    }
+    if to - from > f64::from(MAX_LOOKAHEAD_HOURS) * 3600.0 {
+        return Err(SchedulerError::SimulationError {
+            err: format!("Window must be at most {} hours", MAX_LOOKAHEAD_HOURS),
+        });
+    }
+    Ok(())
+}
+
+fn too_many_runs() -> SchedulerError {
+    SchedulerError::SimulationError {
+        err: format!(
+            "More than {} executions in the window, use a shorter one",
+            MAX_RUNS
+        ),
+    }
+}
+
+// Orbit events in the window, if any task needs them and a TLE is available
+fn orbit_events(
+    tasks: &[(&str, &Task)],
+    orbit: &Orbit,
+    window: (f64, f64),
+) -> Result<Vec<OrbitEvent>, SchedulerError> {
+    if orbit.has_tle() && tasks.iter().any(|(_, task)| task.orbit.is_some()) {
+        orbit.events(window.0, window.1)
+    } else {
+        Ok(vec![])
+    }
+}
+
+// Runs of a task within the window, with delays counted from its start.
+// Triggered tasks depend on conditions at runtime, so never appear.
+fn task_runs<'a>(
+    task_list: &'a str,
+    task: &'a Task,
+    events: &[OrbitEvent],
+    window: (f64, f64),
+    candidate: bool,
+) -> Result<Vec<Run<'a>>, SchedulerError> {
+    let (from, to) = window;
+    let runtime = task.get_runtime()?.as_secs_f64().max(MIN_RUNTIME);
+    let mut starts = vec![];
+
+    if task.trigger.is_some() {
+        // Nothing to predict
+    } else if let Some(timing) = &task.orbit {
+        let offset = timing.get_offset(&task.description)?;
+        starts.extend(
+            events
+                .iter()
+                .filter(|event| timing.matches(event))
+                .map(|event| event.timestamp + offset),
+        );
+    } else {
+        // A delay takes precedence over a time, as it does when scheduling
+        let first = match (&task.delay, &task.time) {
+            (None, Some(time)) => NaiveDateTime::parse_from_str(time, TIME_FORMAT)
+                .map_err(|e| SchedulerError::TaskParseError {
+                    err: format!("Failed to parse time field '{}': {}", time, e),
+                    description: task.description.to_owned(),
+                })?
+                .and_utc()
+                .timestamp() as f64,
+            _ => from + task.get_duration()?.as_secs_f64(),
+        };
+        match task.get_period()? {
+            Some(period) => {
+                let period = period.as_secs_f64();
+                let skipped = ((from - first) / period).ceil().max(0.0);
+                let mut start = first + skipped * period;
+                while start <= to {
+                    if starts.len() >= MAX_RUNS {
+                        return Err(too_many_runs());
+                    }
+                    starts.push(start);
+                    start += period;
+                }
+            }
+            None => starts.push(first),
+        }
+    }
+
+    Ok(starts
+        .into_iter()
+        .filter(|start| (from..=to).contains(start))
+        .map(|start| Run {
+            task_list,
+            task,
+            start,
+            end: start + runtime,
+            candidate,
+        })
+        .collect())
+}
+
+// Executions of a mode's tasks within the window in time order, as if the
+// mode were activated at its start. Orbit tasks only appear once a TLE has
+// been uploaded.
+pub fn simulate(
+    lists: &[TaskList],
+    orbit: &Orbit,
+    from: f64,
+    to: f64,
+) -> Result<Vec<SimulatedRun>, SchedulerError> {
+    check_window(from, to)?;
+    let tasks: Vec<(&str, &Task)> = lists
+        .iter()
+        .flat_map(|list| {
+            list.tasks
+                .iter()
+                .map(move |task| (list.filename.as_str(), task))
+        })
+        .collect();
+    let events = orbit_events(&tasks, orbit, (from, to))?;
+
+    let mut runs = vec![];
+    for (task_list, task) in tasks {
+        runs.extend(task_runs(task_list, task, &events, (from, to), false)?);
+        if runs.len() > MAX_RUNS {
+            return Err(too_many_runs());
+        }
+    }
+    runs.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    Ok(runs
+        .iter()
+        .map(|run| SimulatedRun {
+            task_list: run.task_list.to_owned(),
+            description: run.task.description.to_owned(),
+            app: run.task.app.name.to_owned(),
+            time: format_time(run.start),
+            end: run.task.runtime.as_ref().map(|_| format_time(run.end)),
+        })
+        .collect())
+}
+
+// Check a task list before it is imported: the rules applied on import,
+// registration of its apps with the app service, and overlaps with itself
+// and the `existing` task lists within the window
+pub async fn validate(
+    name: &str,
+    json: &str,
+    existing: &[TaskList],
+    orbit: &Orbit,
+    app_service_url: &str,
+    window: (f64, f64),
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let tasks = match parse_tasks(name, json) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+    // The task list being validated replaces any existing one of its name
+    let others: Vec<(&str, &Task)> = existing
+        .iter()
+        .filter(|list| list.filename != name)
+        .flat_map(|list| {
+            list.tasks
+                .iter()
+                .map(move |task| (list.filename.as_str(), task))
+        })
+        .collect();
+
+    let mut valid_tasks = vec![];
+    let mut descriptions = HashSet::new();
+    for task in &tasks {
+        let errors = task.check();
+        if errors.is_empty() {
+            valid_tasks.push((name, task));
+        }
+        report.errors.extend(errors.iter().map(|e| e.to_string()));
+        if !descriptions.insert(task.description.as_str()) {
+            report.warnings.push(format!(
+                "Task '{}' is defined more than once",
+                task.description
+            ));
+        }
+    }
+
+    descriptions.extend(others.iter().map(|(_, task)| task.description.as_str()));
+    for task in &tasks {
+        if let Some(condition) = task
+            .trigger
+            .as_ref()
+            .and_then(|trigger| trigger.task.as_ref())
+        {
+            if !descriptions.contains(condition.description.as_str()) {
+                report.warnings.push(format!(
+                    "Task '{}' waits on task '{}', which is not scheduled",
+                    task.description, condition.description
+                ));
+            }
+        }
+    }
+
+    check_apps(&tasks, app_service_url, &mut report).await;
+
+    let all: Vec<(&str, &Task)> = valid_tasks.iter().chain(others.iter()).copied().collect();
+    if !orbit.has_tle() && valid_tasks.iter().any(|(_, task)| task.orbit.is_some()) {
+        report.warnings.push(
+            "No TLE has been uploaded, so orbit tasks were not checked for overlaps".to_owned(),
+        );
+    }
+    let events = orbit_events(&all, orbit, window).unwrap_or_else(|e| {
+        report
+            .warnings
+            .push(format!("Orbit tasks were not checked for overlaps: {}", e));
+        vec![]
+    });
+
+    let mut runs = vec![];
+    for (task_list, task) in valid_tasks {
+        match task_runs(task_list, task, &events, window, true) {
+            Ok(task_runs) => runs.extend(task_runs),
+            Err(e) => report.warnings.push(format!(
+                "Task '{}' was not checked for overlaps: {}",
+                task.description, e
+            )),
+        }
+    }
+    // Problems in the existing task lists were reported when they were imported
+    for (task_list, task) in others {
+        if let Ok(task_runs) = task_runs(task_list, task, &events, window, false) {
+            runs.extend(task_runs);
+        }
+    }
+    find_overlaps(&mut runs, &mut report);
+
+    report.valid = report.errors.is_empty();
+    report
+}
+
+// Check that each task's app has an active version registered with the app
+// service. Each app is only looked up once.
+async fn check_apps(tasks: &[Task], app_service_url: &str, report: &mut ValidationReport) {
+    let mut registered: HashMap<&str, bool> = HashMap::new();
+    for task in tasks {
+        let name = task.app.name.as_str();
+        let found = match registered.get(name) {
+            Some(found) => *found,
+            None => match task.app.is_registered(app_service_url).await {
+                Ok(found) => {
+                    registered.insert(name, found);
+                    found
+                }
+                Err(e) => {
+                    report
+                        .warnings
+                        .push(format!("Failed to check app registrations: {}", e));
+                    return;
+                }
+            },
+        };
+        if !found {
+            report.errors.push(format!(
+                "Task '{}': app '{}' is not registered",
+                task.description, name
+            ));
+        }
+    }
+}
+
+// Report runs of the task list being validated which overlap a run of the
+// same app, or of another task needing one of the same resources. Each pair
+// of tasks is reported once.
+fn find_overlaps(runs: &mut [Run], report: &mut ValidationReport) {
+    runs.sort_by(|a, b| a.start.total_cmp(&b.start));
+    let mut reported = HashSet::new();
+
+    for (index, first) in runs.iter().enumerate() {
+        for second in runs[index + 1..]
+            .iter()
+            .take_while(|second| second.start < first.end)
+        {
+            if !first.candidate && !second.candidate {
+                continue;
+            }
+            let (a, b) = (&first.task.description, &second.task.description);
+            let pair = if a <= b { (a, b) } else { (b, a) };
+            let time = format_time(second.start);
+
+            let app = &first.task.app.name;
+            if *app == second.task.app.name && reported.insert((pair, None)) {
+                report.warnings.push(if a == b {
+                    format!(
+                        "Task '{}' may start app '{}' at {} before its previous run ends",
+                        a, app, time
+                    )
+                } else {
+                    format!(
+                        "Tasks '{}' and '{}' may run app '{}' at the same time at {}",
+                        a, b, app, time
+                    )
+                });
+            }
+            if a == b {
+                continue;
+            }
+
+            let shared = first.task.resources.iter().flatten().filter(|resource| {
+                second
+                    .task
+                    .resources
+                    .iter()
+                    .flatten()
+                    .any(|other| other == *resource)
+            });
+            for resource in shared {
+                if reported.insert((pair, Some(resource))) {
+                    report.errors.push(format!(
+                        "Tasks '{}' and '{}' both use resource '{}' at {}",
+                        a, b, resource, time
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 00:00:00 UTC
+    const START: f64 = 1_704_067_200.0;
+
+    fn task(description: &str, app: &str) -> Task {
+        serde_json::from_value(serde_json::json!({
+            "description": description,
+            "delay": "0s",
+            "app": { "name": app }
+        }))
+        .unwrap()
+    }
+
+    fn list(tasks: Vec<Task>) -> TaskList {
+        TaskList {
+            tasks,
+            path: "".to_owned(),
+            filename: "list".to_owned(),
+            time_imported: "".to_owned(),
+        }
+    }
+
+    fn times(runs: &[SimulatedRun]) -> Vec<&str> {
+        runs.iter().map(|run| run.time.as_str()).collect()
+    }
+
+    #[test]
+    fn test_simulate_recurring() {
+        let mut beacon = task("beacon", "beacon-app");
+        beacon.delay = Some("10m".to_owned());
+        beacon.period = Some("20m".to_owned());
+
+        let runs = simulate(
+            &[list(vec![beacon])],
+            &Orbit::default(),
+            START,
+            START + 3600.0,
+        )
+        .unwrap();
+        assert_eq!(
+            times(&runs),
+            vec![
+                "2024-01-01 00:10:00",
+                "2024-01-01 00:30:00",
+                "2024-01-01 00:50:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate_order_and_window() {
+        let mut late = task("late", "late-app");
+        late.delay = Some("30m".to_owned());
+        let mut fixed = task("fixed", "fixed-app");
+        fixed.delay = None;
+        fixed.time = Some("2024-01-01 00:15:00".to_owned());
+        fixed.runtime = Some("5m".to_owned());
+        let mut outside = task("outside", "outside-app");
+        outside.delay = Some("2h".to_owned());
+        let mut triggered = task("triggered", "triggered-app");
+        triggered.trigger = Some(
+            serde_json::from_value(serde_json::json!({
+                "flag": { "name": "deployed", "value": true }
+            }))
+            .unwrap(),
+        );
+
+        let runs = simulate(
+            &[list(vec![late, fixed, outside, triggered])],
+            &Orbit::default(),
+            START,
+            START + 3600.0,
+        )
+        .unwrap();
+        assert_eq!(
+            times(&runs),
+            vec!["2024-01-01 00:15:00", "2024-01-01 00:30:00"]
+        );
+        assert_eq!(runs[0].description, "fixed");
+        assert_eq!(runs[0].end.as_deref(), Some("2024-01-01 00:20:00"));
+        assert_eq!(runs[1].end, None);
+    }
+
+    #[test]
+    fn test_simulate_limits() {
+        let mut fast = task("fast", "fast-app");
+        fast.period = Some("1s".to_owned());
+        let lists = [list(vec![fast])];
+
+        assert!(simulate(&lists, &Orbit::default(), START, START).is_err());
+        assert!(simulate(&lists, &Orbit::default(), START, START + 200.0 * 3600.0).is_err());
+        assert_eq!(
+            simulate(&lists, &Orbit::default(), START, START + 3.0 * 3600.0).unwrap_err(),
+            too_many_runs()
+        );
+    }
+
+    #[test]
+    fn test_find_overlaps() {
+        let mut camera = task("camera", "camera-app");
+        camera.resources = Some(vec!["payload-power".to_owned()]);
+        camera.runtime = Some("10m".to_owned());
+        let mut radio = task("radio", "radio-app");
+        radio.resources = Some(vec!["payload-power".to_owned()]);
+        let second_camera = task("camera again", "camera-app");
+
+        let mut runs = vec![
+            Run {
+                task_list: "list",
+                task: &camera,
+                start: START,
+                end: START + 600.0,
+                candidate: true,
+            },
+            Run {
+                task_list: "list",
+                task: &radio,
+                start: START + 60.0,
+                end: START + 61.0,
+                candidate: false,
+            },
+            Run {
+                task_list: "list",
+                task: &second_camera,
+                start: START + 120.0,
+                end: START + 121.0,
+                candidate: true,
+            },
+            Run {
+                task_list: "list",
+                task: &radio,
+                start: START + 900.0,
+                end: START + 901.0,
+                candidate: true,
+            },
+        ];
+        let mut report = ValidationReport::default();
+        find_overlaps(&mut runs, &mut report);
+
+        assert_eq!(
+            report.errors,
+            vec!["Tasks 'camera' and 'radio' both use resource 'payload-power' at 2024-01-01 00:01:00"]
+        );
+        assert_eq!(
+            report.warnings,
+            vec!["Tasks 'camera' and 'camera again' may run app 'camera-app' at the same time at 2024-01-01 00:02:00"]
+        );
+    }
+}
@@ -18,14 +18,25 @@
 //! Definitions and functions for dealing with tasks & scheduling
 //!
 
-use crate::app::App;
+use crate::app::{App, ExitStatus};
 use crate::error::SchedulerError;
+use crate::history::ExecutionLog;
+use crate::orbit::{Orbit, OrbitTiming};
+use crate::trigger::{TaskEvent, Trigger};
 use async_graphql::SimpleObject;
-use log::{error, info};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::task::JoinHandle;
-use tokio::time::{sleep, interval_at, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{interval_at, sleep, Instant};
+
+// Wait between attempts if a retry policy gives no delay
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+// Longest an app is watched for its exit status
+const EXIT_STATUS_TIMEOUT: Duration = Duration::from_secs(3600);
 
 // Configuration used to schedule app execution
 #[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
@@ -41,10 +52,184 @@ pub struct Task {
     // Period of recurrence specified in Xh Ym Zs format
     // Used by recurring tasks
     pub period: Option<String>,
+    // Condition which causes the task to run
+    // Used by triggered tasks
+    pub trigger: Option<Trigger>,
+    // Pass or eclipse event the task runs relative to
+    // Used by orbit tasks
+    pub orbit: Option<OrbitTiming>,
+    // Retries of a failed run
+    // Used by all tasks
+    pub retry: Option<RetryPolicy>,
+    // Expected run time of the app in Xh Ym Zs format
+    // Used to find overlapping tasks
+    pub runtime: Option<String>,
+    // Hardware or other resources the app needs exclusive use of
+    // Used to find conflicting tasks
+    pub resources: Option<Vec<String>>,
     // Details of the app to be executed
     pub app: App,
 }
 
+// How a failed run is retried. A run fails if app-service cannot start the
+// app or, when exit statuses are monitored, the app exits with an error.
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    // Number of retries after the first attempt
+    pub attempts: u32,
+    // Wait before each retry, in Xh Ym Zs format
+    pub delay: Option<String>,
+}
+
+// Services and notifications shared by all scheduled tasks
+#[derive(Clone)]
+pub struct TaskContext {
+    // URL of App Service - for start app queries
+    pub app_service_url: String,
+    // URL of Telemetry Service - for telemetry triggers
+    pub telemetry_service_url: Option<String>,
+    // URL of FRAM Service - for mission flag triggers
+    pub fram_service_url: Option<String>,
+    // Outcomes of task runs, consumed by task triggers
+    pub events: broadcast::Sender<TaskEvent>,
+    // Current TLE and ground stations, consumed by orbit tasks
+    pub orbit: watch::Receiver<Arc<Orbit>>,
+    // Log of task executions and failure counters
+    pub history: Arc<Mutex<ExecutionLog>>,
+    // How often app-service is asked for the exit status of started apps,
+    // if at all
+    pub exit_status_poll: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        if self.attempts == 0 {
+            return Err(SchedulerError::TaskParseError {
+                err: "Retry attempts must be greater than zero".to_owned(),
+                description: description.to_owned(),
+            });
+        }
+        self.get_delay()?;
+        Ok(())
+    }
+
+    pub fn get_delay(&self) -> Result<Duration, SchedulerError> {
+        match &self.delay {
+            Some(delay) => parse_hms_field(delay.to_owned()),
+            None => Ok(DEFAULT_RETRY_DELAY),
+        }
+    }
+}
+
+// Execute a task's app, retrying on failure if the task has a retry policy.
+// Every attempt is logged and published to triggered tasks.
+pub async fn run_task(
+    app: &App,
+    description: &str,
+    scheduled: DateTime<Utc>,
+    retry: Option<&RetryPolicy>,
+    context: &TaskContext,
+) -> Result<(), SchedulerError> {
+    let attempts = retry.map_or(1, |retry| retry.attempts.saturating_add(1));
+    let delay = match retry {
+        Some(retry) => retry.get_delay()?,
+        None => DEFAULT_RETRY_DELAY,
+    };
+
+    let mut attempt = 1;
+    loop {
+        let err = match run_attempt(app, description, scheduled, attempt, context).await {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt >= attempts => return Err(err),
+            Err(err) => err,
+        };
+        warn!(
+            "Task '{}' failed on attempt {} of {}, retrying in {:?}: {}",
+            description, attempt, attempts, delay, err
+        );
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+async fn run_attempt(
+    app: &App,
+    description: &str,
+    scheduled: DateTime<Utc>,
+    attempt: u32,
+    context: &TaskContext,
+) -> Result<(), SchedulerError> {
+    let started = Utc::now();
+    let result = app.execute(&context.app_service_url).await;
+    let id = context.history.lock().unwrap().begin(
+        description,
+        &app.name,
+        attempt,
+        scheduled,
+        started,
+        &result,
+    );
+    let result = match result {
+        Ok(()) => {
+            let status = match context.exit_status_poll {
+                Some(poll) => wait_for_exit(app, started, poll, context).await,
+                None => None,
+            };
+            context
+                .history
+                .lock()
+                .unwrap()
+                .complete(id, status.as_ref());
+            match status {
+                Some(status) if !status.success() => Err(SchedulerError::GenericError {
+                    err: format!(
+                        "App {} exited with code {:?}, signal {:?}",
+                        app.name, status.code, status.signal
+                    ),
+                }),
+                _ => Ok(()),
+            }
+        }
+        Err(err) => Err(err),
+    };
+
+    // Sending only fails if no triggered tasks are listening
+    let _ = context.events.send(TaskEvent {
+        description: description.to_owned(),
+        success: result.is_ok(),
+    });
+    result
+}
+
+// Poll app-service's app monitor until the run started at `started` exits
+async fn wait_for_exit(
+    app: &App,
+    started: DateTime<Utc>,
+    poll: Duration,
+    context: &TaskContext,
+) -> Option<ExitStatus> {
+    let deadline = Instant::now() + EXIT_STATUS_TIMEOUT;
+    while Instant::now() < deadline {
+        sleep(poll).await;
+        match app.exit_status(&context.app_service_url, started).await {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to fetch exit status of app {}: {}", app.name, e),
+        }
+    }
+    warn!(
+        "App {} did not exit within {:?}",
+        app.name, EXIT_STATUS_TIMEOUT
+    );
+    None
+}
+
+// Wall clock time of a tokio instant
+fn wall_clock(instant: Instant) -> DateTime<Utc> {
+    let elapsed = Instant::now().saturating_duration_since(instant);
+    Utc::now() - chrono::Duration::from_std(elapsed).unwrap_or_else(|_| chrono::Duration::zero())
+}
+
 impl Task {
     // Parse timer delay duration from either delay or time fields
     pub fn get_duration(&self) -> Result<Duration, SchedulerError> {
@@ -83,6 +268,10 @@ impl Task {
                         description: self.description.to_owned(),
                     })?)
             }
+        } else if self.trigger.is_some() || self.orbit.is_some() {
+            // Triggered tasks are armed immediately unless given a delay.
+            // Orbit tasks wait for their event instead.
+            Ok(Duration::from_secs(0))
         } else {
             Err(SchedulerError::TaskParseError {
                 err: "No delay or time defined".to_owned(),
@@ -93,45 +282,162 @@ impl Task {
 
     pub fn get_period(&self) -> Result<Option<Duration>, SchedulerError> {
         if let Some(period) = &self.period {
-            Ok(Some(parse_hms_field(period.to_owned())?))
+            let period = parse_hms_field(period.to_owned())?;
+            if period.is_zero() {
+                return Err(SchedulerError::TaskParseError {
+                    err: "Period must be greater than zero".to_owned(),
+                    description: self.description.to_owned(),
+                });
+            }
+            Ok(Some(period))
         } else {
             Ok(None)
         }
     }
 
+    pub fn get_runtime(&self) -> Result<Duration, SchedulerError> {
+        if let Some(runtime) = &self.runtime {
+            Ok(parse_hms_field(runtime.to_owned())?)
+        } else {
+            Ok(Duration::from_secs(0))
+        }
+    }
+
+    // Check the trigger, and that it is not combined with time or period
+    pub fn validate_trigger(&self) -> Result<(), SchedulerError> {
+        if let Some(trigger) = &self.trigger {
+            if self.time.is_some() || self.period.is_some() {
+                return Err(SchedulerError::TaskParseError {
+                    err: "Triggered tasks cannot define time or period".to_owned(),
+                    description: self.description.to_owned(),
+                });
+            }
+            trigger.validate(&self.description)?;
+        }
+        Ok(())
+    }
+
+    // Check the orbit timing, and that it is not combined with other timing
+    pub fn validate_orbit(&self) -> Result<(), SchedulerError> {
+        if let Some(orbit) = &self.orbit {
+            if self.delay.is_some()
+                || self.time.is_some()
+                || self.period.is_some()
+                || self.trigger.is_some()
+            {
+                return Err(SchedulerError::TaskParseError {
+                    err: "Orbit tasks cannot define delay, time, period or trigger".to_owned(),
+                    description: self.description.to_owned(),
+                });
+            }
+            orbit.validate(&self.description)?;
+        }
+        Ok(())
+    }
+
+    // Check the retry policy
+    pub fn validate_retry(&self) -> Result<(), SchedulerError> {
+        match &self.retry {
+            Some(retry) => retry.validate(&self.description),
+            None => Ok(()),
+        }
+    }
+
+    // Check every field of the task, returning all errors found
+    pub fn check(&self) -> Vec<SchedulerError> {
+        vec![
+            self.get_duration().map(|_| ()),
+            self.get_period().map(|_| ()),
+            self.validate_trigger(),
+            self.validate_orbit(),
+            self.validate_retry(),
+            self.get_runtime().map(|_| ()),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect()
+    }
+
     /// Schedule this task to run using modern tokio
     /// Returns a JoinHandle that can be used to manage the task
-    pub fn schedule(&self, service_url: String) -> Result<JoinHandle<()>, SchedulerError> {
+    pub fn schedule(&self, context: &TaskContext) -> Result<JoinHandle<()>, SchedulerError> {
         let name = self.app.name.clone();
         let description = self.description.clone();
         let duration = self.get_duration()?;
         let period = self.get_period()?;
         let app = self.app.clone();
+        let retry = self.retry.clone();
 
         info!("Scheduling task '{}': {}", name, description);
 
+        self.validate_retry()?;
+        if let Some(trigger) = &self.trigger {
+            self.validate_trigger()?;
+            return trigger.schedule(&description, &app, retry, duration, context);
+        }
+
+        if let Some(orbit) = &self.orbit {
+            self.validate_orbit()?;
+            return orbit.schedule(&description, &app, retry, context);
+        }
+
+        let context = context.clone();
+
         let handle = match period {
             Some(period_duration) => {
                 // Recurring task
                 tokio::spawn(async move {
                     let start_time = Instant::now() + duration;
                     let mut interval = interval_at(start_time, period_duration);
-                    
+                    // Runs which wait for an exit status or retry can outlast the
+                    // period, so each run is watched separately and the period keeps
+                    // ticking. Runs still in progress end with the task.
+                    let mut runs = JoinSet::new();
+
                     loop {
-                        interval.tick().await;
-                        info!("Executing recurring task '{}'", name);
-                        if let Err(e) = app.execute(&service_url).await {
-                            error!("Failed to execute recurring task '{}': {}", name, e);
+                        tokio::select! {
+                            tick = interval.tick() => {
+                                let scheduled = wall_clock(tick);
+                                info!("Executing recurring task '{}'", name);
+                                let (app, name, description, retry, context) = (
+                                    app.clone(),
+                                    name.clone(),
+                                    description.clone(),
+                                    retry.clone(),
+                                    context.clone(),
+                                );
+                                runs.spawn(async move {
+                                    if let Err(e) = run_task(
+                                        &app,
+                                        &description,
+                                        scheduled,
+                                        retry.as_ref(),
+                                        &context,
+                                    )
+                                    .await
+                                    {
+                                        error!(
+                                            "Failed to execute recurring task '{}': {}",
+                                            name, e
+                                        );
+                                    }
+                                });
+                            }
+                            Some(_) = runs.join_next() => {}
                         }
                     }
                 })
             }
             None => {
                 // One-time task
+                let start_time = Instant::now() + duration;
                 tokio::spawn(async move {
                     sleep(duration).await;
                     info!("Executing one-time task '{}'", name);
-                    if let Err(e) = app.execute(&service_url).await {
+                    let scheduled = wall_clock(start_time);
+                    if let Err(e) =
+                        run_task(&app, &description, scheduled, retry.as_ref(), &context).await
+                    {
                         error!("Failed to execute one-time task '{}': {}", name, e);
                     }
                 })
@@ -142,7 +448,7 @@ impl Task {
     }
 }
 
-fn parse_hms_field(field: String) -> Result<Duration, SchedulerError> {
+pub fn parse_hms_field(field: String) -> Result<Duration, SchedulerError> {
     let field_parts: Vec<String> = field.split(' ').map(|s| s.to_owned()).collect();
     let mut duration: u64 = 0;
     if field_parts.is_empty() {
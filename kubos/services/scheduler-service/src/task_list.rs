@@ -20,7 +20,7 @@
 
 use crate::error::SchedulerError;
 use crate::scheduler::SchedulerHandle;
-use crate::task::Task;
+use crate::task::{Task, TaskContext};
 use chrono::{DateTime, Utc};
 use async_graphql::SimpleObject;
 use log::info;
@@ -88,14 +88,7 @@ impl TaskList {
                 name: filename.to_owned(),
             })?;
 
-        let list_contents: ListContents = serde_json::from_str(&list_contents).map_err(|e| {
-            SchedulerError::TaskListParseError {
-                err: format!("Failed to parse json: {}", e),
-                name: filename.to_owned(),
-            }
-        })?;
-
-        let tasks = list_contents.tasks;
+        let tasks = parse_tasks(&filename, &list_contents)?;
 
         Ok(TaskList {
             path,
@@ -106,13 +99,17 @@ impl TaskList {
     }
 
     /// Schedules the tasks contained in this task list using modern tokio
-    pub fn schedule_tasks(&self, app_service_url: &str) -> Result<SchedulerHandle, SchedulerError> {
+    pub fn schedule_tasks(&self, context: &TaskContext) -> Result<SchedulerHandle, SchedulerError> {
         let mut task_handles = Vec::new();
         
         info!("Scheduling {} tasks from task list '{}'", self.tasks.len(), self.filename);
         
-        for task in &self.tasks {
-            let handle = task.schedule(app_service_url.to_string())?;
+        // Triggered tasks go first so they are listening before any of the
+        // tasks they wait on can run
+        let (triggered, timed): (Vec<&Task>, Vec<&Task>) =
+            self.tasks.iter().partition(|task| task.trigger.is_some());
+        for task in triggered.into_iter().chain(timed) {
+            let handle = task.schedule(context)?;
             task_handles.push(handle);
         }
         
@@ -120,6 +117,16 @@ impl TaskList {
     }
 }
 
+// Parse the tasks of a task list's json
+pub fn parse_tasks(name: &str, json: &str) -> Result<Vec<Task>, SchedulerError> {
+    let list_contents: ListContents =
+        serde_json::from_str(json).map_err(|e| SchedulerError::TaskListParseError {
+            err: format!("Failed to parse json: {}", e),
+            name: name.to_owned(),
+        })?;
+    Ok(list_contents.tasks)
+}
+
 // Copy a task list into a mode directory
 pub fn import_task_list(
     scheduler_dir: &str,
@@ -275,8 +282,9 @@ pub fn validate_task_list(path: &str) -> Result<(), SchedulerError> {
     let task_path = Path::new(path);
     let task_list = TaskList::from_path(task_path)?;
     for task in task_list.tasks {
-        let _ = task.get_duration()?;
-        let _ = task.get_period()?;
+        if let Some(error) = task.check().into_iter().next() {
+            return Err(error);
+        }
     }
     Ok(())
 }
@@ -0,0 +1,570 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Definitions and functions concerning automatic transitions between modes
+//!
+
+use crate::app::App;
+use crate::error::SchedulerError;
+use crate::scheduler::SAFE_MODE;
+use crate::task::parse_hms_field;
+use crate::trigger::{Debouncer, FlagCondition, TelemetryCondition};
+use async_graphql::{Enum, SimpleObject};
+use chrono::Utc;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// Mode transition graph, read from the schedules directory
+pub static TRANSITIONS_FILE: &str = "transitions.json";
+// Current mode entry time and transition history, kept across reboots
+static STATE_FILE: &str = "mode_state.json";
+// Transition source matching every mode
+static ANY_MODE: &str = "*";
+static TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// How often guards are checked if the graph gives no evaluate period
+const DEFAULT_EVALUATE: Duration = Duration::from_secs(10);
+// Number of transitions kept in the history
+const HISTORY_LIMIT: usize = 256;
+
+// Rules applying while a mode is active
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ModeRules {
+    // Minimum time in the mode before guards are checked, in Xh Ym Zs format
+    pub min_dwell: Option<String>,
+    // Apps started when the mode is entered
+    #[serde(default)]
+    pub on_entry: Vec<App>,
+    // Apps started when the mode is left
+    #[serde(default)]
+    pub on_exit: Vec<App>,
+}
+
+// Condition which moves the scheduler along a transition
+#[derive(Clone, Debug, Deserialize)]
+pub struct Guard {
+    pub telemetry: Option<TelemetryCondition>,
+    pub flag: Option<FlagCondition>,
+    // Time the condition must hold before the transition is taken, in Xh Ym Zs format
+    pub debounce: Option<String>,
+}
+
+// Allowed change from one mode to another. Transitions without a guard
+// may only be taken by command.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Transition {
+    // Mode being left, or "*" for any mode
+    pub from: String,
+    pub to: String,
+    pub guard: Option<Guard>,
+}
+
+// Mode transition graph
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModeGraph {
+    // How often guards are checked, in Xh Ym Zs format
+    pub evaluate: Option<String>,
+    #[serde(default)]
+    pub modes: HashMap<String, ModeRules>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+// What caused a mode change
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransitionCause {
+    Command,
+    Guard,
+    Failover,
+}
+
+// Record of one mode change
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct TransitionRecord {
+    pub from: Option<String>,
+    pub to: String,
+    pub time: String,
+    pub cause: TransitionCause,
+    pub reason: String,
+    pub success: bool,
+    pub errors: String,
+}
+
+// Persisted portion of the mode state
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ModeState {
+    mode: Option<String>,
+    entered: Option<String>,
+    history: VecDeque<TransitionRecord>,
+}
+
+impl Guard {
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        let condition = match (&self.telemetry, &self.flag) {
+            (Some(telemetry), None) => telemetry.validate(description),
+            (None, Some(flag)) => flag.validate(description),
+            _ => {
+                return Err(SchedulerError::TransitionError {
+                    err: format!(
+                        "Guard of {} needs exactly one telemetry or flag condition",
+                        description
+                    ),
+                })
+            }
+        };
+        // Condition errors are worded for tasks, attribute them to the transition
+        condition.map_err(|e| match e {
+            SchedulerError::TaskParseError { err, description } => {
+                SchedulerError::TransitionError {
+                    err: format!("{}: {}", description, err),
+                }
+            }
+            other => other,
+        })?;
+        self.get_debounce()?;
+        Ok(())
+    }
+
+    pub fn get_debounce(&self) -> Result<Duration, SchedulerError> {
+        match &self.debounce {
+            Some(debounce) => parse_hms_field(debounce.to_owned()),
+            None => Ok(Duration::from_secs(0)),
+        }
+    }
+
+    // Human readable form of the condition for the transition history
+    pub fn describe(&self) -> String {
+        if let Some(telemetry) = &self.telemetry {
+            let mut parts = vec![format!("{}/{}", telemetry.subsystem, telemetry.parameter)];
+            if let Some(above) = telemetry.above {
+                parts.push(format!("above {}", above));
+            }
+            if let Some(below) = telemetry.below {
+                parts.push(format!("below {}", below));
+            }
+            parts.join(" ")
+        } else if let Some(flag) = &self.flag {
+            format!("{} is {}", flag.name, flag.value)
+        } else {
+            "no condition".to_owned()
+        }
+    }
+}
+
+impl Transition {
+    fn leaves(&self, mode: Option<&str>) -> bool {
+        self.from == ANY_MODE || Some(self.from.as_str()) == mode
+    }
+
+    fn describe(&self) -> String {
+        format!("transition '{}' -> '{}'", self.from, self.to)
+    }
+}
+
+impl ModeGraph {
+    pub fn from_json(json: &str) -> Result<ModeGraph, SchedulerError> {
+        let mut graph: ModeGraph =
+            serde_json::from_str(json).map_err(|e| SchedulerError::TransitionError {
+                err: format!("Failed to parse json: {}", e),
+            })?;
+
+        // Mode names are always lower case inside the scheduler
+        graph.modes = std::mem::take(&mut graph.modes)
+            .into_iter()
+            .map(|(name, rules)| (name.to_lowercase(), rules))
+            .collect();
+        for transition in graph.transitions.iter_mut() {
+            transition.from = transition.from.to_lowercase();
+            transition.to = transition.to.to_lowercase();
+        }
+
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    // Load the graph from the schedules directory, if there is one
+    pub fn from_dir(scheduler_dir: &str) -> Result<Option<ModeGraph>, SchedulerError> {
+        let path = Path::new(scheduler_dir).join(TRANSITIONS_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(&path).map_err(|e| SchedulerError::TransitionError {
+            err: format!("Failed to read {}: {}", TRANSITIONS_FILE, e),
+        })?;
+        Ok(Some(ModeGraph::from_json(&json)?))
+    }
+
+    fn validate(&self) -> Result<(), SchedulerError> {
+        self.get_evaluate()?;
+        for (name, rules) in &self.modes {
+            if let Some(dwell) = &rules.min_dwell {
+                parse_hms_field(dwell.to_owned()).map_err(|e| SchedulerError::TransitionError {
+                    err: format!("Invalid min_dwell for mode '{}': {}", name, e),
+                })?;
+            }
+        }
+        for transition in &self.transitions {
+            if transition.to == ANY_MODE || transition.to.is_empty() {
+                return Err(SchedulerError::TransitionError {
+                    err: format!("Invalid destination in {}", transition.describe()),
+                });
+            }
+            if let Some(guard) = &transition.guard {
+                guard.validate(&transition.describe())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_evaluate(&self) -> Result<Duration, SchedulerError> {
+        match &self.evaluate {
+            Some(evaluate) => {
+                let duration = parse_hms_field(evaluate.to_owned())?;
+                if duration.as_secs() == 0 {
+                    Err(SchedulerError::HmsParseError {
+                        err: "Evaluate must be at least 1s".to_owned(),
+                        field: evaluate.to_owned(),
+                    })
+                } else {
+                    Ok(duration)
+                }
+            }
+            None => Ok(DEFAULT_EVALUATE),
+        }
+    }
+
+    // Safe mode can always be entered and the active mode re-activated,
+    // anything else needs a transition
+    pub fn allows(&self, from: Option<&str>, to: &str) -> bool {
+        to == SAFE_MODE
+            || from == Some(to)
+            || self
+                .transitions
+                .iter()
+                .any(|transition| transition.leaves(from) && transition.to == to)
+    }
+
+    pub fn min_dwell(&self, mode: &str) -> Duration {
+        self.modes
+            .get(mode)
+            .and_then(|rules| rules.min_dwell.as_ref())
+            .and_then(|dwell| parse_hms_field(dwell.to_owned()).ok())
+            .unwrap_or_else(|| Duration::from_secs(0))
+    }
+}
+
+// Transition graph and state of the current mode
+pub struct ModeMachine {
+    scheduler_dir: String,
+    graph: Option<ModeGraph>,
+    state: ModeState,
+    // Time the current mode was entered, relative to this boot
+    entered: Instant,
+    // Debounce state of each guarded transition out of the current mode
+    debouncers: HashMap<usize, Debouncer>,
+}
+
+impl ModeMachine {
+    pub fn load(scheduler_dir: &str) -> ModeMachine {
+        let graph = ModeGraph::from_dir(scheduler_dir).unwrap_or_else(|e| {
+            error!("Automatic mode transitions disabled: {}", e);
+            None
+        });
+
+        let state_path = Path::new(scheduler_dir).join(STATE_FILE);
+        let state: ModeState = match fs::read_to_string(&state_path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Discarding unreadable mode state: {}", e);
+                ModeState::default()
+            }),
+            Err(_) => ModeState::default(),
+        };
+
+        // Carry the time spent in the mode over from before the reboot
+        let elapsed = state
+            .entered
+            .as_ref()
+            .and_then(|entered| chrono::NaiveDateTime::parse_from_str(entered, TIME_FORMAT).ok())
+            .and_then(|entered| (Utc::now() - entered.and_utc()).to_std().ok())
+            .unwrap_or_else(|| Duration::from_secs(0));
+        let now = Instant::now();
+
+        ModeMachine {
+            scheduler_dir: scheduler_dir.to_owned(),
+            graph,
+            state,
+            entered: now.checked_sub(elapsed).unwrap_or(now),
+            debouncers: HashMap::new(),
+        }
+    }
+
+    pub fn reload(&mut self) -> Result<(), SchedulerError> {
+        self.graph = ModeGraph::from_dir(&self.scheduler_dir)?;
+        self.debouncers.clear();
+        Ok(())
+    }
+
+    // How often guards should be checked
+    pub fn evaluate_period(&self) -> Duration {
+        self.graph
+            .as_ref()
+            .and_then(|graph| graph.get_evaluate().ok())
+            .unwrap_or(DEFAULT_EVALUATE)
+    }
+
+    // Without a graph every mode change is allowed
+    pub fn allows(&self, from: Option<&str>, to: &str) -> bool {
+        self.graph
+            .as_ref()
+            .is_none_or(|graph| graph.allows(from, to))
+    }
+
+    // Apps to start when moving between two modes: exit actions, then entry actions
+    pub fn actions(&self, from: Option<&str>, to: &str) -> Vec<(String, App)> {
+        let graph = match &self.graph {
+            Some(graph) => graph,
+            None => return vec![],
+        };
+        let mut actions = vec![];
+        if let Some(from) = from {
+            if let Some(rules) = graph.modes.get(from) {
+                for app in &rules.on_exit {
+                    actions.push((format!("exit {}", from), app.clone()));
+                }
+            }
+        }
+        if let Some(rules) = graph.modes.get(to) {
+            for app in &rules.on_entry {
+                actions.push((format!("enter {}", to), app.clone()));
+            }
+        }
+        actions
+    }
+
+    // Guarded transitions out of the mode which may be checked now. A wildcard
+    // transition into the mode itself would only re-enter it, so it is skipped
+    pub fn candidates(&mut self, mode: &str) -> Vec<(usize, Transition)> {
+        if self.state.mode.as_deref() != Some(mode) {
+            // The mode was changed outside the state machine, e.g. on first boot
+            self.state.mode = Some(mode.to_owned());
+            self.state.entered = Some(Utc::now().format(TIME_FORMAT).to_string());
+            self.entered = Instant::now();
+            self.debouncers.clear();
+            self.save();
+        }
+
+        let graph = match &self.graph {
+            Some(graph) => graph,
+            None => return vec![],
+        };
+        if self.entered.elapsed() < graph.min_dwell(mode) {
+            return vec![];
+        }
+        graph
+            .transitions
+            .iter()
+            .enumerate()
+            .filter(|(_, transition)| {
+                transition.guard.is_some() && transition.leaves(Some(mode)) && transition.to != mode
+            })
+            .map(|(index, transition)| (index, transition.clone()))
+            .collect()
+    }
+
+    // Record the latest value of a transition's guard, returns true if the
+    // transition should be taken
+    pub fn update_guard(&mut self, index: usize, hold: Duration, met: bool) -> bool {
+        self.debouncers
+            .entry(index)
+            .or_insert_with(|| Debouncer::new(hold))
+            .update(met, Instant::now())
+    }
+
+    // Add a transition to the history and note the mode now active
+    pub fn record(&mut self, record: TransitionRecord, active: Option<String>) {
+        if active != self.state.mode {
+            self.state.mode = active;
+            self.state.entered = Some(record.time.clone());
+            self.entered = Instant::now();
+            self.debouncers.clear();
+        }
+        self.state.history.push_back(record);
+        while self.state.history.len() > HISTORY_LIMIT {
+            self.state.history.pop_front();
+        }
+        self.save();
+    }
+
+    // Most recent transitions first
+    pub fn history(&self, limit: Option<usize>) -> Vec<TransitionRecord> {
+        self.state
+            .history
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(HISTORY_LIMIT))
+            .cloned()
+            .collect()
+    }
+
+    fn save(&self) {
+        let path = Path::new(&self.scheduler_dir).join(STATE_FILE);
+        let new_path = Path::new(&self.scheduler_dir).join(format!("new_{}", STATE_FILE));
+        let result = serde_json::to_string(&self.state)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&new_path, json).map_err(|e| e.to_string()))
+            .and_then(|_| fs::rename(&new_path, &path).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Failed to save mode state: {}", e);
+        }
+    }
+}
+
+pub fn new_record(
+    from: Option<String>,
+    to: &str,
+    cause: TransitionCause,
+    reason: &str,
+    result: &Result<(), SchedulerError>,
+) -> TransitionRecord {
+    TransitionRecord {
+        from,
+        to: to.to_owned(),
+        time: Utc::now().format(TIME_FORMAT).to_string(),
+        cause,
+        reason: reason.to_owned(),
+        success: result.is_ok(),
+        errors: match result {
+            Ok(()) => "".to_owned(),
+            Err(e) => e.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> ModeGraph {
+        ModeGraph::from_json(
+            r#"{
+                "modes": {
+                    "NormalLink": { "min_dwell": "5m", "on_exit": [{ "name": "stow" }] },
+                    "fearlink": { "on_entry": [{ "name": "beacon", "args": ["fast"] }] }
+                },
+                "transitions": [
+                    {
+                        "from": "normallink",
+                        "to": "FearLink",
+                        "guard": {
+                            "telemetry": { "subsystem": "eps", "parameter": "battery", "below": 30.0 },
+                            "debounce": "30s"
+                        }
+                    },
+                    { "from": "*", "to": "normallink" }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allowed_transitions() {
+        let graph = graph();
+
+        assert!(graph.allows(Some("normallink"), "fearlink"));
+        assert!(graph.allows(Some("fearlink"), "normallink"));
+        assert!(graph.allows(None, "normallink"));
+        assert!(graph.allows(Some("fearlink"), SAFE_MODE));
+        assert!(graph.allows(Some("fearlink"), "fearlink"));
+        assert!(!graph.allows(Some(SAFE_MODE), "fearlink"));
+    }
+
+    #[test]
+    fn test_min_dwell() {
+        let graph = graph();
+
+        assert_eq!(graph.min_dwell("normallink"), Duration::from_secs(300));
+        assert_eq!(graph.min_dwell("fearlink"), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_guard_needs_one_condition() {
+        let result = ModeGraph::from_json(
+            r#"{ "transitions": [ { "from": "safe", "to": "normallink", "guard": {} } ] }"#,
+        );
+
+        assert_eq!(
+            result.unwrap_err(),
+            SchedulerError::TransitionError {
+                err: "Guard of transition 'safe' -> 'normallink' needs exactly one telemetry or flag condition"
+                    .to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_wildcard_destination() {
+        let result =
+            ModeGraph::from_json(r#"{ "transitions": [ { "from": "safe", "to": "*" } ] }"#);
+
+        assert_eq!(
+            result.unwrap_err(),
+            SchedulerError::TransitionError {
+                err: "Invalid destination in transition 'safe' -> '*'".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_candidates_skip_current_mode() {
+        let scheduler_dir = tempfile::tempdir().unwrap();
+        let mut graph = graph();
+        graph.transitions[1].guard = graph.transitions[0].guard.clone();
+        let mut machine = ModeMachine {
+            scheduler_dir: scheduler_dir.path().to_str().unwrap().to_owned(),
+            graph: Some(graph),
+            state: ModeState {
+                mode: Some("normallink".to_owned()),
+                ..Default::default()
+            },
+            entered: Instant::now() - Duration::from_secs(300),
+            debouncers: HashMap::new(),
+        };
+
+        let targets = |candidates: Vec<(usize, Transition)>| {
+            candidates
+                .into_iter()
+                .map(|(_, transition)| transition.to)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(targets(machine.candidates("normallink")), vec!["fearlink"]);
+        assert_eq!(targets(machine.candidates("fearlink")), vec!["normallink"]);
+    }
+
+    #[test]
+    fn test_guard_description() {
+        let graph = graph();
+        let guard = graph.transitions[0].guard.as_ref().unwrap();
+
+        assert_eq!(guard.describe(), "eps/battery below 30");
+    }
+}
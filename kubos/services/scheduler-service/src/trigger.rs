@@ -0,0 +1,590 @@
+/*
+ * Copyright (C) 2019 Kubos Corporation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Definitions and functions for tasks which run when a condition is met
+//!
+
+use crate::app::App;
+use crate::error::SchedulerError;
+use crate::task::{parse_hms_field, run_task, RetryPolicy, TaskContext};
+use async_graphql::{Enum, SimpleObject};
+use chrono::Utc;
+use log::{error, info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, Instant, MissedTickBehavior};
+
+// How often telemetry and flag conditions are checked if no poll is given
+const DEFAULT_POLL: Duration = Duration::from_secs(10);
+// Time allowed for telemetry-service or fram-service to answer a check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Outcome of a task run, as published to triggered tasks
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskEvent {
+    // Description of the task which ran
+    pub description: String,
+    // Whether the app started and, when exit statuses are monitored,
+    // exited successfully
+    pub success: bool,
+}
+
+// Task outcomes a task condition can wait for
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskOutcome {
+    Success,
+    Failure,
+    Any,
+}
+
+// Condition on the most recent value of a telemetry-service parameter
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct TelemetryCondition {
+    pub subsystem: String,
+    pub parameter: String,
+    // Condition holds while the value is greater than this
+    pub above: Option<f64>,
+    // Condition holds while the value is less than this
+    pub below: Option<f64>,
+    // Samples older than this are ignored, in Xh Ym Zs format
+    pub max_age: Option<String>,
+}
+
+// Condition on a mission flag kept by fram-service
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct FlagCondition {
+    // Name of the flag in fram-service's missionState query, e.g. "deployed"
+    pub name: String,
+    // Condition holds while the flag has this value
+    pub value: bool,
+}
+
+// Condition on the outcome of another task
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct TaskCondition {
+    // Description of the task to wait for
+    pub description: String,
+    pub outcome: TaskOutcome,
+}
+
+// Condition which causes a task to run. Exactly one of telemetry, flag or
+// task must be given.
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
+pub struct Trigger {
+    pub telemetry: Option<TelemetryCondition>,
+    pub flag: Option<FlagCondition>,
+    pub task: Option<TaskCondition>,
+    // How often telemetry and flag conditions are checked, in Xh Ym Zs format
+    pub poll: Option<String>,
+    // Telemetry and flag conditions must hold this long before the task runs.
+    // Task conditions run at most once per debounce. In Xh Ym Zs format
+    pub debounce: Option<String>,
+    // Number of times the task runs before the trigger is retired
+    pub max_runs: Option<u32>,
+}
+
+impl Trigger {
+    // Check the trigger for errors which would stop it from ever running
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        let parse_error = |err: &str| SchedulerError::TaskParseError {
+            err: err.to_owned(),
+            description: description.to_owned(),
+        };
+
+        let conditions = [
+            self.telemetry.is_some(),
+            self.flag.is_some(),
+            self.task.is_some(),
+        ];
+        match conditions.iter().filter(|set| **set).count() {
+            0 => return Err(parse_error("No trigger condition defined")),
+            1 => {}
+            _ => return Err(parse_error("More than one trigger condition defined")),
+        }
+
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.validate(description)?;
+        }
+        if let Some(flag) = &self.flag {
+            flag.validate(description)?;
+        }
+
+        if self.max_runs == Some(0) {
+            return Err(parse_error("max_runs must be greater than zero"));
+        }
+
+        self.get_poll()?;
+        self.get_debounce()?;
+        Ok(())
+    }
+
+    pub fn get_poll(&self) -> Result<Duration, SchedulerError> {
+        match &self.poll {
+            Some(poll) => {
+                let duration = parse_hms_field(poll.to_owned())?;
+                if duration.as_secs() == 0 {
+                    Err(SchedulerError::HmsParseError {
+                        err: "Poll must be at least 1s".to_owned(),
+                        field: poll.to_owned(),
+                    })
+                } else {
+                    Ok(duration)
+                }
+            }
+            None => Ok(DEFAULT_POLL),
+        }
+    }
+
+    pub fn get_debounce(&self) -> Result<Duration, SchedulerError> {
+        match &self.debounce {
+            Some(debounce) => parse_hms_field(debounce.to_owned()),
+            None => Ok(Duration::from_secs(0)),
+        }
+    }
+
+    /// Spawn a task which runs the app each time the trigger fires
+    pub fn schedule(
+        &self,
+        description: &str,
+        app: &App,
+        retry: Option<RetryPolicy>,
+        delay: Duration,
+        context: &TaskContext,
+    ) -> Result<JoinHandle<()>, SchedulerError> {
+        self.validate(description)?;
+        let trigger = self.clone();
+        let description = description.to_owned();
+        let app = app.clone();
+        let context = context.clone();
+        let poll = self.get_poll()?;
+        let debounce = self.get_debounce()?;
+
+        // Subscribe now rather than in the spawned task so that outcomes of
+        // tasks scheduled alongside this one are not missed
+        let mut events = context.events.subscribe();
+
+        let handle = tokio::spawn(async move {
+            if !delay.is_zero() {
+                sleep(delay).await;
+                // Outcomes from before the delay expired should not count
+                events = events.resubscribe();
+            }
+
+            let mut runs = 0;
+            if let Some(condition) = &trigger.task {
+                let mut last_run: Option<Instant> = None;
+                loop {
+                    let event = match events.recv().await {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!(
+                                "Triggered task '{}' missed {} task outcomes",
+                                description, skipped
+                            );
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return,
+                    };
+                    if !condition.matches(&event) {
+                        continue;
+                    }
+                    if last_run.is_some_and(|last| last.elapsed() < debounce) {
+                        info!(
+                            "Skipping triggered task '{}', last run was within debounce",
+                            description
+                        );
+                        continue;
+                    }
+                    last_run = Some(Instant::now());
+
+                    fire(&app, &description, retry.as_ref(), &context).await;
+                    runs += 1;
+                    if trigger.max_runs.is_some_and(|max| runs >= max) {
+                        break;
+                    }
+                }
+            } else {
+                let mut debouncer = Debouncer::new(debounce);
+                let mut ticks = interval(poll);
+                ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    ticks.tick().await;
+                    let met = match trigger.check(&context).await {
+                        Ok(met) => met,
+                        Err(e) => {
+                            warn!("Failed to check trigger for task '{}': {}", description, e);
+                            continue;
+                        }
+                    };
+                    if !debouncer.update(met, Instant::now()) {
+                        continue;
+                    }
+
+                    fire(&app, &description, retry.as_ref(), &context).await;
+                    runs += 1;
+                    if trigger.max_runs.is_some_and(|max| runs >= max) {
+                        break;
+                    }
+                }
+            }
+            info!(
+                "Triggered task '{}' reached its limit of {} runs",
+                description, runs
+            );
+        });
+
+        Ok(handle)
+    }
+
+    async fn check(&self, context: &TaskContext) -> Result<bool, SchedulerError> {
+        check_condition(self.telemetry.as_ref(), self.flag.as_ref(), context).await
+    }
+}
+
+// Evaluate a telemetry or flag condition
+pub async fn check_condition(
+    telemetry: Option<&TelemetryCondition>,
+    flag: Option<&FlagCondition>,
+    context: &TaskContext,
+) -> Result<bool, SchedulerError> {
+    if let Some(telemetry) = telemetry {
+        let url =
+            context
+                .telemetry_service_url
+                .as_ref()
+                .ok_or_else(|| SchedulerError::QueryError {
+                    err: "No telemetry-service configured".to_owned(),
+                })?;
+        telemetry.check(url).await
+    } else if let Some(flag) = flag {
+        let url = context
+            .fram_service_url
+            .as_ref()
+            .ok_or_else(|| SchedulerError::QueryError {
+                err: "No fram-service configured".to_owned(),
+            })?;
+        flag.check(url).await
+    } else {
+        Ok(false)
+    }
+}
+
+impl TelemetryCondition {
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        if self.above.is_none() && self.below.is_none() {
+            return Err(SchedulerError::TaskParseError {
+                err: "Telemetry condition needs an above or below threshold".to_owned(),
+                description: description.to_owned(),
+            });
+        }
+        if let Some(max_age) = &self.max_age {
+            parse_hms_field(max_age.to_owned())?;
+        }
+        Ok(())
+    }
+
+    // Whether a value satisfies the thresholds
+    pub fn holds(&self, value: f64) -> bool {
+        self.above.is_none_or(|above| value > above) && self.below.is_none_or(|below| value < below)
+    }
+
+    async fn check(&self, url: &str) -> Result<bool, SchedulerError> {
+        let query = format!(
+            "{{ telemetry(subsystem: {}, parameter: {}, limit: 1) {{ timestamp, value }} }}",
+            json!(self.subsystem),
+            json!(self.parameter)
+        );
+        let response = query_service(&query, url).await?;
+
+        let entry = match response["data"]["telemetry"].get(0) {
+            Some(entry) => entry,
+            // Nothing recorded yet
+            None => return Ok(false),
+        };
+
+        if let Some(max_age) = &self.max_age {
+            let max_age = parse_hms_field(max_age.to_owned())?.as_secs_f64();
+            let timestamp = entry["timestamp"].as_f64().unwrap_or(0.0);
+            let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+            if now - timestamp > max_age {
+                return Ok(false);
+            }
+        }
+
+        let value = entry["value"].as_str().unwrap_or_default();
+        let value: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| SchedulerError::QueryError {
+                err: format!(
+                    "Telemetry value '{}' for {}/{} is not a number",
+                    value, self.subsystem, self.parameter
+                ),
+            })?;
+        Ok(self.holds(value))
+    }
+}
+
+impl FlagCondition {
+    pub fn validate(&self, description: &str) -> Result<(), SchedulerError> {
+        // The name is placed directly in the missionState query
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(SchedulerError::TaskParseError {
+                err: format!("Invalid flag name '{}'", self.name),
+                description: description.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn check(&self, url: &str) -> Result<bool, SchedulerError> {
+        let query = format!("{{ missionState {{ {} }} }}", self.name);
+        let response = query_service(&query, url).await?;
+
+        let flag = response["data"]["missionState"][&self.name]
+            .as_bool()
+            .ok_or_else(|| SchedulerError::QueryError {
+                err: format!("Mission flag '{}' not found", self.name),
+            })?;
+        Ok(flag == self.value)
+    }
+}
+
+impl TaskCondition {
+    pub fn matches(&self, event: &TaskEvent) -> bool {
+        event.description == self.description
+            && match self.outcome {
+                TaskOutcome::Success => event.success,
+                TaskOutcome::Failure => !event.success,
+                TaskOutcome::Any => true,
+            }
+    }
+}
+
+// Turns a sampled condition into run decisions. The task runs once the
+// condition has held for the debounce time, and not again until the
+// condition has cleared.
+pub struct Debouncer {
+    hold: Duration,
+    since: Option<Instant>,
+    armed: bool,
+}
+
+impl Debouncer {
+    pub fn new(hold: Duration) -> Debouncer {
+        Debouncer {
+            hold,
+            since: None,
+            armed: true,
+        }
+    }
+
+    // Record the latest state of the condition, returns true if the task
+    // should run now
+    pub fn update(&mut self, met: bool, now: Instant) -> bool {
+        if !met {
+            self.since = None;
+            self.armed = true;
+            return false;
+        }
+
+        let since = *self.since.get_or_insert(now);
+        if self.armed && now.duration_since(since) >= self.hold {
+            self.armed = false;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+async fn fire(app: &App, description: &str, retry: Option<&RetryPolicy>, context: &TaskContext) {
+    info!("Executing triggered task '{}'", description);
+    if let Err(e) = run_task(app, description, Utc::now(), retry, context).await {
+        error!("Failed to execute triggered task '{}': {}", description, e);
+    }
+}
+
+// Send a GraphQL query to another service and return its data
+pub async fn query_service(query: &str, hosturl: &str) -> Result<Value, SchedulerError> {
+    let client = Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| SchedulerError::QueryError {
+            err: format!("Error building client: {}", e),
+        })?;
+
+    let response: Value = client
+        .post(format!("http://{}", hosturl))
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .map_err(|e| SchedulerError::QueryError {
+            err: format!("Error posting query: {}", e),
+        })?
+        .json()
+        .await
+        .map_err(|e| SchedulerError::QueryError {
+            err: format!("Error parsing response as JSON: {}", e),
+        })?;
+
+    match response.get("errors") {
+        Some(errors) if !errors.is_null() => Err(SchedulerError::QueryError {
+            err: format!("Service returned errors: {}", errors),
+        }),
+        _ => Ok(response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_trigger() -> Trigger {
+        Trigger {
+            telemetry: None,
+            flag: None,
+            task: Some(TaskCondition {
+                description: "deploy".to_owned(),
+                outcome: TaskOutcome::Failure,
+            }),
+            poll: None,
+            debounce: None,
+            max_runs: None,
+        }
+    }
+
+    #[test]
+    fn test_debounce_waits_for_hold() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_secs(5));
+
+        assert!(!debouncer.update(true, start));
+        assert!(!debouncer.update(true, start + Duration::from_secs(4)));
+        assert!(debouncer.update(true, start + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_debounce_fires_once_until_cleared() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_secs(0));
+
+        assert!(debouncer.update(true, start));
+        assert!(!debouncer.update(true, start + Duration::from_secs(1)));
+        assert!(!debouncer.update(false, start + Duration::from_secs(2)));
+        assert!(debouncer.update(true, start + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_debounce_resets_on_glitch() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_secs(5));
+
+        assert!(!debouncer.update(true, start));
+        assert!(!debouncer.update(false, start + Duration::from_secs(3)));
+        assert!(!debouncer.update(true, start + Duration::from_secs(6)));
+        assert!(debouncer.update(true, start + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn test_telemetry_thresholds() {
+        let condition = TelemetryCondition {
+            subsystem: "eps".to_owned(),
+            parameter: "battery_percent".to_owned(),
+            above: Some(10.0),
+            below: Some(30.0),
+            max_age: None,
+        };
+
+        assert!(condition.holds(29.9));
+        assert!(!condition.holds(30.0));
+        assert!(!condition.holds(10.0));
+    }
+
+    #[test]
+    fn test_task_condition_outcome() {
+        let condition = task_trigger().task.unwrap();
+        let event = |description: &str, success| TaskEvent {
+            description: description.to_owned(),
+            success,
+        };
+
+        assert!(condition.matches(&event("deploy", false)));
+        assert!(!condition.matches(&event("deploy", true)));
+        assert!(!condition.matches(&event("beacon", false)));
+    }
+
+    #[test]
+    fn test_validate_single_condition() {
+        let mut trigger = task_trigger();
+        assert_eq!(trigger.validate("task"), Ok(()));
+
+        trigger.flag = Some(FlagCondition {
+            name: "deployed".to_owned(),
+            value: true,
+        });
+        assert_eq!(
+            trigger.validate("task"),
+            Err(SchedulerError::TaskParseError {
+                err: "More than one trigger condition defined".to_owned(),
+                description: "task".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_flag_name() {
+        let mut trigger = task_trigger();
+        trigger.task = None;
+        trigger.flag = Some(FlagCondition {
+            name: "deployed } }".to_owned(),
+            value: true,
+        });
+
+        assert_eq!(
+            trigger.validate("task"),
+            Err(SchedulerError::TaskParseError {
+                err: "Invalid flag name 'deployed } }'".to_owned(),
+                description: "task".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_max_runs() {
+        let mut trigger = task_trigger();
+        trigger.max_runs = Some(0);
+
+        assert_eq!(
+            trigger.validate("task"),
+            Err(SchedulerError::TaskParseError {
+                err: "max_runs must be greater than zero".to_owned(),
+                description: "task".to_owned(),
+            })
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod util;
+
+use serde_json::json;
+use std::time::Duration;
+use util::{BasicAppResponder, LowBatteryResponder, SchedulerFixture};
+use utils::testing::ServiceListener;
+
+fn start_app_query(name: &str) -> String {
+    format!(
+        r#"{{"query":"mutation {{ startApp(name: \"{}\") {{ success, errors }} }}"}}"#,
+        name
+    )
+}
+
+// Collect every request the listener receives within the timeout
+fn drain_requests(listener: &ServiceListener, timeout: Duration) -> Vec<String> {
+    let mut requests = vec![];
+    let start = std::time::Instant::now();
+    while start.elapsed() < timeout {
+        if let Some(request) = listener.wait_for_request(Duration::from_millis(100), None) {
+            requests.push(request);
+        }
+    }
+    requests
+}
+
+#[tokio::test]
+async fn run_on_task_success() {
+    let listener = ServiceListener::spawn_with_responder("127.0.0.1", 9040, BasicAppResponder);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8040);
+
+    fixture.create_mode("init").await;
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "deploy",
+                "delay": "0s",
+                "app": {
+                    "name": "deploy-app"
+                }
+            },
+            {
+                "description": "announce",
+                "trigger": {
+                    "task": {
+                        "description": "deploy",
+                        "outcome": "success"
+                    }
+                },
+                "app": {
+                    "name": "announce-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    fixture
+        .import_task_list("deploy", &schedule_path, "init")
+        .await;
+    fixture.activate_mode("init").await;
+
+    let request = listener.expect_request(Duration::from_secs(5), "deploy-app startApp");
+    assert_eq!(request, start_app_query("deploy-app"));
+    let request = listener.expect_request(Duration::from_secs(5), "announce-app startApp");
+    assert_eq!(request, start_app_query("announce-app"));
+    assert_eq!(
+        listener.wait_for_request(Duration::from_secs(1), None),
+        None
+    );
+}
+
+#[tokio::test]
+async fn run_on_task_failure_max_runs() {
+    // The default responder's reply is not a valid startApp response,
+    // so every run of the recurring task fails
+    let listener = ServiceListener::spawn("127.0.0.1", 9041);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8041);
+
+    fixture.create_mode("init").await;
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "beacon",
+                "delay": "0s",
+                "period": "1s",
+                "app": {
+                    "name": "beacon-app"
+                }
+            },
+            {
+                "description": "fear",
+                "trigger": {
+                    "task": {
+                        "description": "beacon",
+                        "outcome": "failure"
+                    },
+                    "max_runs": 1
+                },
+                "app": {
+                    "name": "fear-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    fixture
+        .import_task_list("beacon", &schedule_path, "init")
+        .await;
+    fixture.activate_mode("init").await;
+
+    let requests = drain_requests(&listener, Duration::from_secs(4));
+    let fear_runs = requests
+        .iter()
+        .filter(|request| **request == start_app_query("fear-app"))
+        .count();
+    let beacon_runs = requests
+        .iter()
+        .filter(|request| **request == start_app_query("beacon-app"))
+        .count();
+    assert!(beacon_runs >= 2, "beacon ran {} times", beacon_runs);
+    assert_eq!(fear_runs, 1);
+}
+
+#[tokio::test]
+async fn run_on_telemetry_threshold() {
+    let listener = ServiceListener::spawn_with_responder("127.0.0.1", 9042, BasicAppResponder);
+    let telemetry = ServiceListener::spawn_with_responder("127.0.0.1", 9542, LowBatteryResponder);
+    let fixture = SchedulerFixture::spawn_with_config(
+        "127.0.0.1",
+        8042,
+        r#"
+        [telemetry-service.addr]
+        ip = "127.0.0.1"
+        port = 9542
+        "#,
+    );
+
+    fixture.create_mode("init").await;
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "low-power",
+                "trigger": {
+                    "telemetry": {
+                        "subsystem": "eps",
+                        "parameter": "battery_percent",
+                        "below": 30.0
+                    },
+                    "poll": "1s",
+                    "max_runs": 1
+                },
+                "app": {
+                    "name": "panic-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    fixture
+        .import_task_list("power", &schedule_path, "init")
+        .await;
+    fixture.activate_mode("init").await;
+
+    let request = listener.expect_request(Duration::from_secs(5), "panic-app startApp");
+    assert_eq!(request, start_app_query("panic-app"));
+    let check = telemetry.expect_request(Duration::from_secs(1), "telemetry query");
+    assert!(check.contains("battery_percent"), "{}", check);
+
+    // The condition still holds, but the trigger is retired after one run
+    assert_eq!(
+        listener.wait_for_request(Duration::from_secs(3), None),
+        None
+    );
+}
+
+#[tokio::test]
+async fn validate_trigger_with_period() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8043);
+
+    fixture.create_mode("operational").await;
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "first-task",
+                "period": "10s",
+                "trigger": {
+                    "flag": {
+                        "name": "deployed",
+                        "value": true
+                    }
+                },
+                "app": {
+                    "name": "first-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    assert_eq!(
+        fixture
+            .import_task_list("first", &schedule_path, "operational")
+            .await,
+        json!({
+            "data" : {
+                "importTaskList": {
+                    "errors": "Failed to parse task 'first-task': Triggered tasks cannot define time or period",
+                    "success": false
+                }
+            }
+        })
+    );
+}
@@ -0,0 +1,164 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod util;
+
+use serde_json::json;
+use std::time::Duration;
+use util::{BasicAppResponder, SchedulerFixture};
+use utils::testing::ServiceListener;
+
+fn schedule(retry: Option<serde_json::Value>) -> String {
+    let mut task = json!({
+        "description": "basic-task",
+        "delay": "1s",
+        "app": {
+            "name": "basic-app"
+        }
+    });
+    if let Some(retry) = retry {
+        task["retry"] = retry;
+    }
+    json!({ "tasks": [task] }).to_string()
+}
+
+#[tokio::test]
+async fn record_successful_run() {
+    let listener = ServiceListener::spawn_with_responder("127.0.0.1", 9051, BasicAppResponder);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8051);
+
+    fixture.create_mode("init").await;
+    let schedule_path = fixture.create_task_list(Some(schedule(None)));
+    fixture.import_task_list("imaging", &schedule_path, "init").await;
+    fixture.activate_mode("init").await;
+
+    listener.expect_request(Duration::from_secs(5), "startApp mutation (basic-app)");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(
+        fixture
+            .query(r#"{ taskHistory(task: "basic-task") { task, mode, app, attempt, startSuccess, success } }"#)
+            .await,
+        json!({
+            "data": {
+                "taskHistory": [
+                    {
+                        "task": "basic-task",
+                        "mode": "init",
+                        "app": "basic-app",
+                        "attempt": 1,
+                        "startSuccess": true,
+                        "success": true
+                    }
+                ]
+            }
+        })
+    );
+    assert_eq!(
+        fixture
+            .query(r#"{ taskCounters { task, runs, failures, consecutiveFailures } }"#)
+            .await,
+        json!({
+            "data": {
+                "taskCounters": [
+                    {
+                        "task": "basic-task",
+                        "runs": 1,
+                        "failures": 0,
+                        "consecutiveFailures": 0
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn retry_failed_run() {
+    // The default responder gives app-service replies without a startApp
+    // result, so every attempt fails to start
+    let listener = ServiceListener::spawn("127.0.0.1", 9052);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8052);
+
+    fixture.create_mode("init").await;
+    let retry = json!({ "attempts": 2, "delay": "1s" });
+    let schedule_path = fixture.create_task_list(Some(schedule(Some(retry))));
+    fixture.import_task_list("imaging", &schedule_path, "init").await;
+    fixture.activate_mode("init").await;
+
+    for attempt in 1..=3 {
+        listener.expect_request(
+            Duration::from_secs(5),
+            &format!("startApp mutation (basic-app) attempt {}", attempt),
+        );
+    }
+    assert_eq!(listener.wait_for_request(Duration::from_secs(2), None), None);
+
+    let history = fixture
+        .query(r#"{ taskHistory(limit: 2) { attempt, startSuccess, success } }"#)
+        .await;
+    assert_eq!(
+        history["data"]["taskHistory"],
+        json!([
+            { "attempt": 3, "startSuccess": false, "success": false },
+            { "attempt": 2, "startSuccess": false, "success": false }
+        ])
+    );
+    assert_eq!(
+        fixture
+            .query(r#"{ taskCounters(task: "basic-task") { runs, failures, consecutiveFailures } }"#)
+            .await["data"]["taskCounters"],
+        json!([{ "runs": 3, "failures": 3, "consecutiveFailures": 3 }])
+    );
+
+    assert_eq!(
+        fixture
+            .query(r#"mutation { resetTaskCounters(task: "basic-task") { errors, success } }"#)
+            .await,
+        json!({
+            "data": {
+                "resetTaskCounters": {
+                    "errors": "",
+                    "success": true
+                }
+            }
+        })
+    );
+    assert_eq!(
+        fixture.query(r#"{ taskCounters { runs } }"#).await["data"]["taskCounters"],
+        json!([])
+    );
+}
+
+#[tokio::test]
+async fn validate_retry_attempts() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8053);
+
+    fixture.create_mode("init").await;
+    let retry = json!({ "attempts": 0 });
+    let schedule_path = fixture.create_task_list(Some(schedule(Some(retry))));
+    assert_eq!(
+        fixture.import_task_list("imaging", &schedule_path, "init").await,
+        json!({
+            "data": {
+                "importTaskList": {
+                    "errors": "Failed to parse task 'basic-task': Retry attempts must be greater than zero",
+                    "success": false
+                }
+            }
+        })
+    );
+}
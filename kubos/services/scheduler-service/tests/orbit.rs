@@ -0,0 +1,185 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod util;
+
+use chrono::NaiveDateTime;
+use serde_json::json;
+use util::SchedulerFixture;
+
+// Equatorial orbit at about 500 km without drag, which is eclipsed and
+// passes over a station on the equator every revolution
+static LINE1: &str = "1 99999U 24001A   24001.00000000  .00000000  00000-0  00000-0 0  9999";
+static LINE2: &str = "2 99999   0.0000   0.0000 0001000   0.0000   0.0000 15.20000000    06";
+
+fn stations() -> String {
+    json!([
+        {
+            "name": "equator",
+            "latitude": 0.0,
+            "longitude": 0.0,
+            "min_elevation": 10.0
+        }
+    ])
+    .to_string()
+    .escape_default()
+    .collect()
+}
+
+fn parse_time(value: &serde_json::Value) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value.as_str().unwrap(), "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+async fn setup_orbit(fixture: &SchedulerFixture) {
+    let success = json!({ "errors": "", "success": true });
+    assert_eq!(
+        fixture.set_ground_stations(&stations()).await["data"]["setGroundStations"],
+        success
+    );
+    assert_eq!(
+        fixture.upload_tle(LINE1, LINE2).await["data"]["uploadTle"],
+        success
+    );
+}
+
+#[tokio::test]
+async fn upload_invalid_tle() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8047);
+
+    let bad_line1 = LINE1.replace("9999", "9998");
+    assert_eq!(
+        fixture.upload_tle(&bad_line1, LINE2).await,
+        json!({
+            "data": {
+                "uploadTle": {
+                    "errors": "Orbit error: Invalid TLE: Line 1 checksum mismatch",
+                    "success": false
+                }
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn upcoming_events_persist() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8048);
+    setup_orbit(&fixture).await;
+    fixture.restart();
+
+    let response = fixture
+        .query(r#"{ upcomingOrbitEvents(hours: 6) { kind, station, time } }"#)
+        .await;
+    let events = response["data"]["upcomingOrbitEvents"].as_array().unwrap();
+
+    let eclipses: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|event| event["station"].is_null())
+        .collect();
+    let passes: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|event| event["station"] == json!("equator"))
+        .collect();
+    assert!(eclipses.len() >= 6, "{:?}", eclipses);
+    assert!(passes.len() >= 6, "{:?}", passes);
+    for pair in eclipses.windows(2) {
+        assert_ne!(pair[0]["kind"], pair[1]["kind"]);
+    }
+    for pair in passes.windows(2) {
+        assert_ne!(pair[0]["kind"], pair[1]["kind"]);
+    }
+    for pair in events.windows(2) {
+        assert!(parse_time(&pair[0]["time"]) <= parse_time(&pair[1]["time"]));
+    }
+}
+
+#[tokio::test]
+async fn upcoming_orbit_tasks() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8049);
+    setup_orbit(&fixture).await;
+
+    fixture.create_mode("operational").await;
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "radio-warmup",
+                "orbit": {
+                    "event": "pass_start",
+                    "offset": "-2m",
+                    "station": "equator"
+                },
+                "app": {
+                    "name": "radio-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    fixture
+        .import_task_list("comms", &schedule_path, "operational")
+        .await;
+    fixture.activate_mode("operational").await;
+
+    let response = fixture
+        .query(r#"{ upcomingOrbitTasks(hours: 6) { taskList, description, event, station, eventTime, time } }"#)
+        .await;
+    let runs = response["data"]["upcomingOrbitTasks"].as_array().unwrap();
+    assert!(runs.len() >= 3, "{:?}", runs);
+
+    let run = &runs[0];
+    assert_eq!(run["taskList"], json!("comms"));
+    assert_eq!(run["description"], json!("radio-warmup"));
+    assert_eq!(run["event"], json!("PASS_START"));
+    assert_eq!(run["station"], json!("equator"));
+    assert_eq!(
+        parse_time(&run["eventTime"]) - parse_time(&run["time"]),
+        chrono::Duration::minutes(2)
+    );
+}
+
+#[tokio::test]
+async fn validate_orbit_with_delay() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8050);
+
+    fixture.create_mode("operational").await;
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "heaters",
+                "delay": "10s",
+                "orbit": {
+                    "event": "eclipse_entry"
+                },
+                "app": {
+                    "name": "heater-app"
+                }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    assert_eq!(
+        fixture
+            .import_task_list("power", &schedule_path, "operational")
+            .await,
+        json!({
+            "data" : {
+                "importTaskList": {
+                    "errors": "Failed to parse task 'heaters': Orbit tasks cannot define delay, time, period or trigger",
+                    "success": false
+                }
+            }
+        })
+    );
+}
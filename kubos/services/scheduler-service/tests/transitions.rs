@@ -0,0 +1,193 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod util;
+
+use serde_json::json;
+use std::time::Duration;
+use util::{BasicAppResponder, LowBatteryResponder, SchedulerFixture};
+use utils::testing::ServiceListener;
+
+#[tokio::test]
+async fn transition_not_allowed() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8044);
+
+    fixture.create_mode("normallink").await;
+    fixture.create_mode("fearlink").await;
+    fixture.write_transitions(
+        &json!({
+            "transitions": [
+                { "from": "safe", "to": "normallink" }
+            ]
+        })
+        .to_string(),
+    );
+    assert_eq!(
+        fixture.reload_transitions().await,
+        json!({
+            "data": {
+                "reloadTransitions": {
+                    "errors": "",
+                    "success": true
+                }
+            }
+        })
+    );
+
+    assert_eq!(
+        fixture.activate_mode("fearlink").await,
+        json!({
+            "data": {
+                "activateMode": {
+                    "errors": "Failed to activate 'fearlink': Transition from 'safe' is not allowed",
+                    "success": false
+                }
+            }
+        })
+    );
+    assert_eq!(
+        fixture.activate_mode("normallink").await,
+        json!({
+            "data": {
+                "activateMode": {
+                    "errors": "",
+                    "success": true
+                }
+            }
+        })
+    );
+
+    assert_eq!(
+        fixture.transition_history().await,
+        json!({
+            "data": {
+                "transitionHistory": [
+                    {
+                        "from": "safe",
+                        "to": "normallink",
+                        "cause": "COMMAND",
+                        "reason": "activateMode",
+                        "success": true,
+                        "errors": ""
+                    },
+                    {
+                        "from": "safe",
+                        "to": "fearlink",
+                        "cause": "COMMAND",
+                        "reason": "activateMode",
+                        "success": false,
+                        "errors": "Failed to activate 'fearlink': Transition from 'safe' is not allowed"
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn transition_on_guard() {
+    let listener = ServiceListener::spawn_with_responder("127.0.0.1", 9045, BasicAppResponder);
+    let _telemetry = ServiceListener::spawn_with_responder("127.0.0.1", 9545, LowBatteryResponder);
+    let fixture = SchedulerFixture::spawn_with_config(
+        "127.0.0.1",
+        8045,
+        r#"
+        [telemetry-service.addr]
+        ip = "127.0.0.1"
+        port = 9545
+        "#,
+    );
+
+    fixture.create_mode("fearlink").await;
+    fixture.write_transitions(
+        &json!({
+            "evaluate": "1s",
+            "modes": {
+                "fearlink": {
+                    "on_entry": [{ "name": "fear-beacon" }]
+                }
+            },
+            "transitions": [
+                {
+                    "from": "safe",
+                    "to": "fearlink",
+                    "guard": {
+                        "telemetry": {
+                            "subsystem": "eps",
+                            "parameter": "battery_percent",
+                            "below": 30.0
+                        }
+                    }
+                }
+            ]
+        })
+        .to_string(),
+    );
+    // The evaluation period is read when the service starts
+    fixture.restart();
+
+    let request = listener.expect_request(Duration::from_secs(5), "fear-beacon entry action");
+    assert_eq!(
+        request,
+        r#"{"query":"mutation { startApp(name: \"fear-beacon\") { success, errors } }"}"#
+    );
+
+    assert_eq!(
+        fixture.query(r#"{ activeMode { name } }"#).await,
+        json!({
+            "data": {
+                "activeMode": {
+                    "name": "fearlink"
+                }
+            }
+        })
+    );
+    assert_eq!(
+        fixture.transition_history().await["data"]["transitionHistory"][0],
+        json!({
+            "from": "safe",
+            "to": "fearlink",
+            "cause": "GUARD",
+            "reason": "eps/battery_percent below 30",
+            "success": true,
+            "errors": ""
+        })
+    );
+}
+
+#[tokio::test]
+async fn mode_persists_across_restart() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8046);
+
+    fixture.create_mode("normallink").await;
+    fixture.activate_mode("normallink").await;
+    fixture.restart();
+
+    assert_eq!(
+        fixture.query(r#"{ activeMode { name } }"#).await,
+        json!({
+            "data": {
+                "activeMode": {
+                    "name": "normallink"
+                }
+            }
+        })
+    );
+    assert_eq!(
+        fixture.transition_history().await["data"]["transitionHistory"][0]["to"],
+        json!("normallink")
+    );
+}
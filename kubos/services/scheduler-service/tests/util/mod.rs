@@ -42,17 +42,62 @@ impl ServiceResponder for BasicAppResponder {
     }
 }
 
+// Telemetry service reporting a battery level below any sensible threshold
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct LowBatteryResponder;
+impl ServiceResponder for LowBatteryResponder {
+    fn respond(&self, _body: &str) -> String {
+        json!({
+            "data": {
+                "telemetry": [
+                    {
+                        "timestamp": 0.0,
+                        "value": "25.0",
+                    }
+                ]
+            }
+        })
+        .to_string()
+    }
+}
+
+// App service which only knows of apps named "registered-app"
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct RegistryResponder;
+impl ServiceResponder for RegistryResponder {
+    fn respond(&self, body: &str) -> String {
+        let entries = if body.contains("registered-app") {
+            json!([{ "active": true }])
+        } else {
+            json!([])
+        };
+        json!({
+            "data": {
+                "registeredApps": entries
+            }
+        })
+        .to_string()
+    }
+}
+
 pub struct SchedulerFixture {
     service: RefCell<TestService>,
     ip: String,
     port: u16,
-    _schedules_dir: TempDir,
+    schedules_dir: TempDir,
     schedules_holder: RefCell<Vec<NamedTempFile>>,
 }
 
 #[allow(dead_code)]
 impl SchedulerFixture {
     pub fn spawn(ip: &str, port: u16) -> SchedulerFixture {
+        SchedulerFixture::spawn_with_config(ip, port, "")
+    }
+
+    // Spawns the service with extra config, e.g. addresses of services used by triggers
+    pub fn spawn_with_config(ip: &str, port: u16, extra_config: &str) -> SchedulerFixture {
         let schedules_dir = TempDir::new().unwrap();
         let schedules_dir_path = schedules_dir.path().to_str().unwrap();
 
@@ -63,10 +108,12 @@ impl SchedulerFixture {
         port = {}
         [scheduler-service]
         schedules_dir = "{}"
+        {}
         "#,
             ip,
             (port + 1000),
             schedules_dir_path,
+            extra_config,
         );
 
         let mut scheduler_service = TestService::new("scheduler-service", ip, port);
@@ -80,7 +127,7 @@ impl SchedulerFixture {
             service: RefCell::new(scheduler_service),
             ip: ip.to_owned(),
             port,
-            _schedules_dir: schedules_dir,
+            schedules_dir,
             schedules_holder: RefCell::new(vec![]),
         }
     }
@@ -151,7 +198,12 @@ impl SchedulerFixture {
         service_query(&mutation, &self.ip, self.port).await
     }
 
-    pub async fn import_raw_task_list(&self, name: &str, mode: &str, json: &str) -> serde_json::Value {
+    pub async fn import_raw_task_list(
+        &self,
+        name: &str,
+        mode: &str,
+        json: &str,
+    ) -> serde_json::Value {
         let mutation = format!(
             r#"mutation {{ importRawTaskList(name: "{}", mode: "{}", json: "{}") {{ errors, success }} }}"#,
             name, mode, json
@@ -169,6 +221,63 @@ impl SchedulerFixture {
         service_query(&mutation, &self.ip, self.port).await
     }
 
+    // Writes the mode transition graph into the schedules directory
+    pub fn write_transitions(&self, contents: &str) {
+        std::fs::write(self.schedules_dir.path().join("transitions.json"), contents).unwrap();
+    }
+
+    pub async fn reload_transitions(&self) -> serde_json::Value {
+        let mutation = r#"mutation { reloadTransitions { errors, success } }"#;
+
+        service_query(mutation, &self.ip, self.port).await
+    }
+
+    pub async fn transition_history(&self) -> serde_json::Value {
+        let query = r#"{ transitionHistory { from, to, cause, reason, success, errors } }"#;
+
+        service_query(query, &self.ip, self.port).await
+    }
+
+    pub async fn upload_tle(&self, line1: &str, line2: &str) -> serde_json::Value {
+        let mutation = format!(
+            r#"mutation {{ uploadTle(line1: "{}", line2: "{}") {{ errors, success }} }}"#,
+            line1, line2
+        );
+
+        service_query(&mutation, &self.ip, self.port).await
+    }
+
+    pub async fn set_ground_stations(&self, json: &str) -> serde_json::Value {
+        let mutation = format!(
+            r#"mutation {{ setGroundStations(json: "{}") {{ errors, success }} }}"#,
+            json
+        );
+
+        service_query(&mutation, &self.ip, self.port).await
+    }
+
+    pub async fn validate_task_list(
+        &self,
+        name: &str,
+        json: &str,
+        mode: Option<&str>,
+    ) -> serde_json::Value {
+        let mode = mode.map_or("".to_owned(), |mode| format!(", mode: \"{}\"", mode));
+        let query = format!(
+            r#"{{ validateTaskList(name: "{}", json: "{}"{}) {{ valid, errors, warnings }} }}"#,
+            name, json, mode
+        );
+        service_query(&query, &self.ip, self.port).await
+    }
+
+    pub async fn simulate_schedule(&self, mode: &str, from: &str, to: &str) -> serde_json::Value {
+        let query = format!(
+            r#"{{ simulateSchedule(mode: "{}", from: "{}", to: "{}") {{ taskList, description, app, time, end }} }}"#,
+            mode, from, to
+        );
+        service_query(&query, &self.ip, self.port).await
+    }
+
     pub async fn query(&self, query: &str) -> serde_json::Value {
         service_query(query, &self.ip, self.port).await
     }
@@ -0,0 +1,230 @@
+//
+// Copyright (C) 2019 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod util;
+
+use serde_json::json;
+use util::{RegistryResponder, SchedulerFixture};
+use utils::testing::ServiceListener;
+
+fn escape(value: serde_json::Value) -> String {
+    value.to_string().escape_default().collect()
+}
+
+#[tokio::test]
+async fn validate_task_list_errors() {
+    let _listener = ServiceListener::spawn_with_responder("127.0.0.1", 9054, RegistryResponder);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8054);
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "capture",
+                "delay": "1m",
+                "runtime": "10m",
+                "resources": ["payload-power"],
+                "app": { "name": "registered-app" }
+            },
+            {
+                "description": "downlink",
+                "delay": "5m",
+                "resources": ["payload-power"],
+                "app": { "name": "missing-app" }
+            },
+            {
+                "description": "bad",
+                "delay": "5x",
+                "app": { "name": "registered-app" }
+            }
+        ]
+    });
+    let response = fixture.validate_task_list("first", &escape(schedule), None).await;
+    let report = &response["data"]["validateTaskList"];
+    let errors = report["errors"].as_array().unwrap();
+
+    assert_eq!(report["valid"], json!(false));
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0], json!("Failed to parse hms field '5x': Found invalid unit"));
+    assert_eq!(errors[1], json!("Task 'downlink': app 'missing-app' is not registered"));
+    assert!(errors[2]
+        .as_str()
+        .unwrap()
+        .starts_with("Tasks 'capture' and 'downlink' both use resource 'payload-power' at "));
+    assert_eq!(report["warnings"], json!([]));
+}
+
+#[tokio::test]
+async fn validate_against_mode() {
+    let _listener = ServiceListener::spawn_with_responder("127.0.0.1", 9055, RegistryResponder);
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8055);
+
+    fixture.create_mode("operational").await;
+    let existing = json!({
+        "tasks": [
+            {
+                "description": "beacon",
+                "delay": "0s",
+                "period": "1m",
+                "app": { "name": "registered-app" }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(existing.to_string()));
+    fixture.import_task_list("existing", &schedule_path, "operational").await;
+
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "extra beacon",
+                "delay": "0s",
+                "app": { "name": "registered-app" }
+            },
+            {
+                "description": "after beacon",
+                "trigger": { "task": { "description": "beacon", "outcome": "success" } },
+                "app": { "name": "registered-app" }
+            },
+            {
+                "description": "after nothing",
+                "trigger": { "task": { "description": "missing", "outcome": "success" } },
+                "app": { "name": "registered-app" }
+            }
+        ]
+    });
+    let response = fixture
+        .validate_task_list("new", &escape(schedule.clone()), Some("operational"))
+        .await;
+    let report = &response["data"]["validateTaskList"];
+    let warnings = report["warnings"].as_array().unwrap();
+
+    assert_eq!(report["valid"], json!(true));
+    assert_eq!(report["errors"], json!([]));
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        warnings[0],
+        json!("Task 'after nothing' waits on task 'missing', which is not scheduled")
+    );
+    assert!(warnings[1]
+        .as_str()
+        .unwrap()
+        .starts_with("Tasks 'extra beacon' and 'beacon' may run app 'registered-app' at the same time at "));
+
+    // A task list replaces the existing one of the same name, so is not
+    // checked against it
+    let response = fixture
+        .validate_task_list("existing", &escape(schedule), Some("operational"))
+        .await;
+    assert_eq!(
+        response["data"]["validateTaskList"]["warnings"],
+        json!([
+            "Task 'after beacon' waits on task 'beacon', which is not scheduled",
+            "Task 'after nothing' waits on task 'missing', which is not scheduled"
+        ])
+    );
+}
+
+#[tokio::test]
+async fn simulate_mode_schedule() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8056);
+
+    fixture.create_mode("operational").await;
+    let schedule = json!({
+        "tasks": [
+            {
+                "description": "beacon",
+                "delay": "10m",
+                "period": "20m",
+                "app": { "name": "beacon-app" }
+            },
+            {
+                "description": "capture",
+                "delay": "15m",
+                "runtime": "5m",
+                "app": { "name": "camera-app" }
+            },
+            {
+                "description": "deployed",
+                "trigger": { "flag": { "name": "deployed", "value": true } },
+                "app": { "name": "deploy-app" }
+            }
+        ]
+    });
+    let schedule_path = fixture.create_task_list(Some(schedule.to_string()));
+    fixture.import_task_list("imaging", &schedule_path, "operational").await;
+
+    assert_eq!(
+        fixture
+            .simulate_schedule("operational", "2030-01-01 00:00:00", "2030-01-01 01:00:00")
+            .await,
+        json!({
+            "data": {
+                "simulateSchedule": [
+                    {
+                        "taskList": "imaging",
+                        "description": "beacon",
+                        "app": "beacon-app",
+                        "time": "2030-01-01 00:10:00",
+                        "end": null
+                    },
+                    {
+                        "taskList": "imaging",
+                        "description": "capture",
+                        "app": "camera-app",
+                        "time": "2030-01-01 00:15:00",
+                        "end": "2030-01-01 00:20:00"
+                    },
+                    {
+                        "taskList": "imaging",
+                        "description": "beacon",
+                        "app": "beacon-app",
+                        "time": "2030-01-01 00:30:00",
+                        "end": null
+                    },
+                    {
+                        "taskList": "imaging",
+                        "description": "beacon",
+                        "app": "beacon-app",
+                        "time": "2030-01-01 00:50:00",
+                        "end": null
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn simulate_invalid_window() {
+    let fixture = SchedulerFixture::spawn("127.0.0.1", 8057);
+
+    fixture.create_mode("operational").await;
+
+    let response = fixture
+        .simulate_schedule("operational", "2030-01-01 01:00:00", "2030-01-01 00:00:00")
+        .await;
+    assert_eq!(
+        response["errors"][0]["message"],
+        json!("Failed to simulate schedule: Simulation error: Window must end after it starts")
+    );
+
+    let response = fixture
+        .simulate_schedule("missing", "2030-01-01 00:00:00", "2030-01-01 01:00:00")
+        .await;
+    assert_eq!(
+        response["errors"][0]["message"],
+        json!("Failed to simulate schedule: Simulation error: Mode 'missing' not found")
+    );
+}
@@ -0,0 +1,249 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::feed::Feed;
+use crate::ingest::IngestStats;
+use crate::limits::Limits;
+use crate::validation::Validator;
+use cbor_protocol::{Protocol, ProtocolError};
+use kubos_telemetry_db::{Database, TypedEntry};
+use log::{error, info, warn};
+use serde_cbor::{ser, Value};
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+// Largest message accepted, in bytes
+const MAX_MESSAGE: usize = 8192;
+
+// Batch of points, sent as [sequence, ack, base timestamp, [[id, offset, value], ...]]
+struct Batch {
+    // Sender's number for the message, echoed in its acknowledgement
+    sequence: u64,
+    // Whether the sender wants an acknowledgement
+    ack: bool,
+    // Timestamp the points' offsets are added to. The time of receipt if not given
+    base: Option<f64>,
+    points: Vec<Value>,
+}
+
+// Finite number from an integer or float
+fn number(value: &Value) -> Option<f64> {
+    match *value {
+        Value::U64(value) => Some(value as f64),
+        Value::I64(value) => Some(value as f64),
+        Value::F64(value) if value.is_finite() => Some(value),
+        _ => None,
+    }
+}
+
+// Text form of a point's value, as it would be sent to the JSON port
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::U64(value) => Some(value.to_string()),
+        Value::I64(value) => Some(value.to_string()),
+        Value::F64(value) if value.is_finite() => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn parse_batch(message: Value) -> Result<Batch, String> {
+    let mut pieces = match message {
+        Value::Array(pieces) => pieces.into_iter(),
+        _ => return Err("Message is not an array".to_owned()),
+    };
+
+    let sequence = match pieces.next() {
+        Some(Value::U64(sequence)) => sequence,
+        _ => return Err("Sequence number must be an unsigned integer".to_owned()),
+    };
+    let ack = match pieces.next() {
+        Some(Value::Bool(ack)) => ack,
+        _ => return Err("Acknowledgement flag must be a boolean".to_owned()),
+    };
+    let base = match pieces.next() {
+        Some(Value::Null) => None,
+        Some(base) => Some(number(&base).ok_or("Base timestamp must be a number or null")?),
+        None => return Err("Missing base timestamp".to_owned()),
+    };
+    let points = match pieces.next() {
+        Some(Value::Array(points)) => points,
+        _ => return Err("Points must be an array".to_owned()),
+    };
+    if pieces.next().is_some() {
+        return Err("Unexpected fields after the points".to_owned());
+    }
+
+    Ok(Batch {
+        sequence,
+        ack,
+        base,
+        points,
+    })
+}
+
+// Receives batches of telemetry encoded with CBOR
+pub struct CborUdp {
+    db: Arc<Mutex<Database>>,
+    validator: Arc<Validator>,
+    limits: Arc<Limits>,
+    stats: Arc<IngestStats>,
+    feed: Arc<Feed>,
+}
+
+impl CborUdp {
+    pub fn new(
+        db: Arc<Mutex<Database>>,
+        validator: Arc<Validator>,
+        limits: Arc<Limits>,
+        stats: Arc<IngestStats>,
+        feed: Arc<Feed>,
+    ) -> Self {
+        CborUdp {
+            db,
+            validator,
+            limits,
+            stats,
+            feed,
+        }
+    }
+
+    pub fn start(&self, url: String) {
+        let protocol = Protocol::new(&url, MAX_MESSAGE);
+
+        info!("CBOR UDP listening on: {}", url);
+
+        loop {
+            // The sender is needed even if the message cannot be parsed
+            let peer = match protocol.peek_peer() {
+                Ok(peer) => peer,
+                Err(err) => {
+                    error!("Failed to receive a message: {}", err);
+                    continue;
+                }
+            };
+
+            match protocol.recv_message() {
+                Ok(message) => self.process(&protocol, peer, message),
+                Err(ProtocolError::ParseFail { err }) => {
+                    warn!("Couldn't decode CBOR message from {}: {}", peer, err);
+                    self.stats.malformed(&peer, err);
+                }
+                // Empty messages and control frames
+                Err(ProtocolError::NoDataReceived) => (),
+                Err(err) => error!("Failed to receive a message: {}", err),
+            }
+        }
+    }
+
+    fn process(&self, protocol: &Protocol, peer: SocketAddr, message: Value) {
+        let batch = match parse_batch(message) {
+            Ok(batch) => batch,
+            Err(err) => {
+                warn!("Invalid CBOR message from {}: {}", peer, err);
+                self.stats.malformed(&peer, err);
+                return;
+            }
+        };
+
+        let base = batch.base.unwrap_or_else(kubos_telemetry_db::systime);
+        let total = batch.points.len() as u64;
+        let mut entries = vec![];
+        let mut last_error = None;
+        for point in batch.points {
+            match self.entry(base, point) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        let accepted = match self.store(&entries) {
+            Ok(()) => entries.len() as u64,
+            Err(err) => {
+                last_error = Some(err);
+                0
+            }
+        };
+        let rejected = total - accepted;
+        self.stats.message(&peer, accepted, rejected, last_error);
+
+        if batch.ack {
+            let reply = ser::to_vec_packed(&(batch.sequence, accepted, rejected))
+                .map_err(|err| format!("{}", err))
+                .and_then(|reply| {
+                    protocol
+                        .send_message(&reply, peer)
+                        .map_err(|err| format!("{}", err))
+                });
+            if let Err(err) = reply {
+                error!("Failed to acknowledge message to {}: {}", peer, err);
+            }
+        }
+    }
+
+    // Checks one [id, offset, value] point
+    fn entry(&self, base: f64, point: Value) -> Result<TypedEntry, String> {
+        let fields = match point {
+            Value::Array(fields) if fields.len() == 3 => fields,
+            _ => return Err("Point must be an [id, offset, value] array".to_owned()),
+        };
+
+        let definition = match fields[0] {
+            Value::U64(id) => u32::try_from(id)
+                .ok()
+                .and_then(|id| self.validator.dictionary().get_by_id(id))
+                .ok_or_else(|| format!("Unknown parameter id {}", id))?,
+            _ => return Err("Parameter id must be an unsigned integer".to_owned()),
+        };
+        let name = format!("{}/{}", definition.subsystem, definition.parameter);
+
+        let offset = number(&fields[1])
+            .ok_or_else(|| format!("{}: timestamp offset must be a number", name))?;
+        let value =
+            text(&fields[2]).ok_or_else(|| format!("{}: value has an unsupported type", name))?;
+
+        self.validator.entry(
+            base + offset,
+            &definition.subsystem,
+            &definition.parameter,
+            &value,
+        )
+    }
+
+    // Stores a batch's valid points, all or none of them
+    fn store(&self, entries: &[TypedEntry]) -> Result<(), String> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut db = self.db.lock().map_err(|err| {
+            error!("cbor - Failed to get lock on database: {}", err);
+            format!("{}", err)
+        })?;
+        db.insert_bulk_typed(entries).map_err(|err| {
+            error!("cbor - Failed to insert telemetry: {}", err);
+            format!("Failed to store points: {}", err)
+        })?;
+        for entry in entries {
+            self.limits.check(&mut db, entry);
+            self.feed.publish(entry);
+        }
+
+        Ok(())
+    }
+}
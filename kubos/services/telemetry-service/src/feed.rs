@@ -0,0 +1,50 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::schema::Entry;
+use kubos_telemetry_db::TypedEntry;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+// Points held for each subscriber. Subscribers which fall further behind than
+// this miss the oldest points.
+const CAPACITY: usize = 1024;
+
+// Passes newly stored telemetry to GraphQL subscriptions
+pub struct Feed {
+    sender: broadcast::Sender<Entry>,
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Feed { sender }
+    }
+}
+
+impl Feed {
+    // Sends a stored entry to every subscriber
+    pub fn publish(&self, entry: &TypedEntry) {
+        // Fails only if nothing is subscribed
+        let _ = self.sender.send(Entry::from(entry));
+    }
+
+    // Entries stored from now on
+    pub fn subscribe(&self) -> impl Stream<Item = Entry> {
+        BroadcastStream::new(self.sender.subscribe()).filter_map(|entry| entry.ok())
+    }
+}
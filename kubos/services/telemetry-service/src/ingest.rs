@@ -0,0 +1,91 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+
+// Telemetry received from one source IP address
+#[derive(Clone, Debug, Default)]
+pub struct SourceStats {
+    // Datagrams received, including malformed ones
+    pub messages: u64,
+    // Points stored
+    pub accepted: u64,
+    // Points which were not stored
+    pub rejected: u64,
+    // Datagrams which could not be decoded
+    pub malformed: u64,
+    // System time of the most recent datagram
+    pub last_seen: f64,
+    pub last_error: Option<String>,
+}
+
+// Counts of telemetry received on the UDP ports, by source IP address. Ports are
+// left out, as senders which bind a new socket for each message would otherwise
+// add a source every time
+#[derive(Default)]
+pub struct IngestStats {
+    sources: Mutex<BTreeMap<IpAddr, SourceStats>>,
+}
+
+impl IngestStats {
+    fn record<F: FnOnce(&mut SourceStats)>(&self, source: &SocketAddr, update: F) {
+        if let Ok(mut sources) = self.sources.lock() {
+            let stats = sources.entry(source.ip()).or_default();
+            stats.messages += 1;
+            stats.last_seen = kubos_telemetry_db::systime();
+            update(stats);
+        }
+    }
+
+    // Records a decoded datagram, and the reason its last rejected point was
+    // rejected
+    pub fn message(
+        &self,
+        source: &SocketAddr,
+        accepted: u64,
+        rejected: u64,
+        error: Option<String>,
+    ) {
+        self.record(source, |stats| {
+            stats.accepted += accepted;
+            stats.rejected += rejected;
+            if error.is_some() {
+                stats.last_error = error;
+            }
+        });
+    }
+
+    // Records a datagram which could not be decoded
+    pub fn malformed(&self, source: &SocketAddr, error: String) {
+        self.record(source, |stats| {
+            stats.malformed += 1;
+            stats.last_error = Some(error);
+        });
+    }
+
+    // Statistics of each source which has sent telemetry, ordered by address
+    pub fn sources(&self) -> Vec<(String, SourceStats)> {
+        match self.sources.lock() {
+            Ok(sources) => sources
+                .iter()
+                .map(|(source, stats)| (source.to_string(), stats.clone()))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
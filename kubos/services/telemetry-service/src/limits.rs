@@ -0,0 +1,378 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use kubos_service::Config;
+use kubos_telemetry_db::{Database, Dictionary, NewAlarm, TypedEntry, ValueType};
+use log::{error, info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+// Name of the scheduler's safe mode, which has its own mutation
+const SAFE_MODE: &str = "safe";
+// Time allowed for scheduler-service to answer a mode request
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Range of limits a parameter's value is in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Nominal,
+    Yellow,
+    Red,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Nominal => "nominal",
+            Severity::Yellow => "yellow",
+            Severity::Red => "red",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "yellow" => Some(Severity::Yellow),
+            "red" => Some(Severity::Red),
+            _ => None,
+        }
+    }
+}
+
+fn default_persistence() -> u32 {
+    1
+}
+
+// Yellow and red limits of one parameter. Values equal to a limit are
+// within it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Limit {
+    pub subsystem: String,
+    pub parameter: String,
+    pub yellow_low: Option<f64>,
+    pub yellow_high: Option<f64>,
+    pub red_low: Option<f64>,
+    pub red_high: Option<f64>,
+    // Consecutive values which must fall in a new range before alarms are
+    // raised or cleared
+    #[serde(default = "default_persistence")]
+    pub persistence: u32,
+    // Scheduler mode requested when the parameter goes red
+    pub red_mode: Option<String>,
+}
+
+impl Limit {
+    fn validate(&self, dictionary: &Dictionary) -> Result<(), String> {
+        let name = format!("{}/{}", self.subsystem, self.parameter);
+
+        if self.yellow_low.is_none()
+            && self.yellow_high.is_none()
+            && self.red_low.is_none()
+            && self.red_high.is_none()
+        {
+            return Err(format!("{}: no limits given", name));
+        }
+        if self.persistence == 0 {
+            return Err(format!("{}: persistence must be at least 1", name));
+        }
+        for (low, high) in &[
+            (self.yellow_low, self.yellow_high),
+            (self.red_low, self.red_high),
+        ] {
+            if let (Some(low), Some(high)) = (low, high) {
+                if low >= high {
+                    return Err(format!("{}: low limit must be below the high limit", name));
+                }
+            }
+        }
+        if let (Some(red), Some(yellow)) = (self.red_low, self.yellow_low) {
+            if red > yellow {
+                return Err(format!(
+                    "{}: red low limit is above the yellow low limit",
+                    name
+                ));
+            }
+        }
+        if let (Some(red), Some(yellow)) = (self.red_high, self.yellow_high) {
+            if red < yellow {
+                return Err(format!(
+                    "{}: red high limit is below the yellow high limit",
+                    name
+                ));
+            }
+        }
+        if let Some(definition) = dictionary.get(&self.subsystem, &self.parameter) {
+            if definition.value_type != ValueType::Integer
+                && definition.value_type != ValueType::Float
+            {
+                return Err(format!("{}: only numeric parameters may have limits", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Range a value is in, and a description of the limit it is outside of
+    fn check(&self, value: f64) -> (Severity, String) {
+        if let Some(limit) = self.red_low.filter(|limit| value < *limit) {
+            (
+                Severity::Red,
+                format!("below the red low limit of {}", limit),
+            )
+        } else if let Some(limit) = self.red_high.filter(|limit| value > *limit) {
+            (
+                Severity::Red,
+                format!("above the red high limit of {}", limit),
+            )
+        } else if let Some(limit) = self.yellow_low.filter(|limit| value < *limit) {
+            (
+                Severity::Yellow,
+                format!("below the yellow low limit of {}", limit),
+            )
+        } else if let Some(limit) = self.yellow_high.filter(|limit| value > *limit) {
+            (
+                Severity::Yellow,
+                format!("above the yellow high limit of {}", limit),
+            )
+        } else {
+            (Severity::Nominal, String::new())
+        }
+    }
+}
+
+// Range a parameter is in, and how long its values have been in another one
+#[derive(Clone, Copy, Debug)]
+struct Tracking {
+    severity: Severity,
+    pending: Severity,
+    count: u32,
+}
+
+impl Tracking {
+    fn new(severity: Severity) -> Self {
+        Tracking {
+            severity,
+            pending: severity,
+            count: 0,
+        }
+    }
+
+    // Counts a value in a range, returning true once enough consecutive
+    // values have been in it for the parameter to move there
+    fn advance(&mut self, severity: Severity, persistence: u32) -> bool {
+        if severity == self.severity {
+            self.count = 0;
+            return false;
+        }
+
+        if severity == self.pending {
+            self.count += 1;
+        } else {
+            self.pending = severity;
+            self.count = 1;
+        }
+
+        if self.count >= persistence {
+            self.severity = severity;
+            self.count = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Checks stored telemetry against the configured limits, raising and
+// clearing alarms
+pub struct Limits {
+    limits: BTreeMap<(String, String), Limit>,
+    tracking: Mutex<HashMap<(String, String), Tracking>>,
+    // Address of scheduler-service, set if any limit requests a mode
+    scheduler: Option<String>,
+}
+
+impl Limits {
+    pub fn from_config(config: &Config, dictionary: &Dictionary) -> Result<Self, String> {
+        let configured = match config.get("limits") {
+            Some(limits) => limits
+                .try_into::<Vec<Limit>>()
+                .map_err(|err| format!("Failed to parse limits: {}", err))?,
+            None => vec![],
+        };
+
+        let mut limits = BTreeMap::new();
+        for limit in configured {
+            limit.validate(dictionary)?;
+            let key = (limit.subsystem.clone(), limit.parameter.clone());
+            if let Some(limit) = limits.insert(key, limit) {
+                return Err(format!(
+                    "{}/{}: limits defined more than once",
+                    limit.subsystem, limit.parameter
+                ));
+            }
+        }
+
+        let scheduler = if limits.values().any(|limit| limit.red_mode.is_some()) {
+            let url = Config::new("scheduler-service")
+                .ok()
+                .and_then(|config| config.hosturl())
+                .ok_or("red_mode needs the scheduler-service address to be configured")?;
+            Some(url)
+        } else {
+            None
+        };
+
+        Ok(Limits {
+            limits,
+            tracking: Mutex::new(HashMap::new()),
+            scheduler,
+        })
+    }
+
+    // Picks up the alarms left active when the service last stopped, so they
+    // are cleared once their parameters are back within limits
+    pub fn restore(&self, db: &mut Database) {
+        let alarms = match db.active_alarms() {
+            Ok(alarms) => alarms,
+            Err(err) => {
+                error!("Failed to load active alarms: {}", err);
+                return;
+            }
+        };
+
+        if let Ok(mut tracking) = self.tracking.lock() {
+            for alarm in alarms {
+                if let Some(severity) = Severity::from_name(&alarm.severity) {
+                    tracking.insert((alarm.subsystem, alarm.parameter), Tracking::new(severity));
+                }
+            }
+        }
+    }
+
+    // Checks a stored entry against its parameter's limits. Values which are
+    // not numbers are ignored.
+    pub fn check(&self, db: &mut Database, entry: &TypedEntry) {
+        let key = (entry.subsystem.clone(), entry.parameter.clone());
+        let limit = match self.limits.get(&key) {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        let value = match entry
+            .int_value
+            .map(|value| value as f64)
+            .or(entry.float_value)
+            .or_else(|| entry.value.trim().parse::<f64>().ok())
+        {
+            Some(value) if value.is_finite() => value,
+            _ => return,
+        };
+
+        let (severity, message) = limit.check(value);
+        let moved = match self.tracking.lock() {
+            Ok(mut tracking) => tracking
+                .entry(key)
+                .or_insert_with(|| Tracking::new(Severity::Nominal))
+                .advance(severity, limit.persistence),
+            Err(err) => {
+                error!("Failed to get lock on limit states: {}", err);
+                return;
+            }
+        };
+        if !moved {
+            return;
+        }
+
+        let name = format!("{}/{}", entry.subsystem, entry.parameter);
+
+        // A parameter has at most one active alarm, for the range it is in
+        if let Err(err) = db.clear_alarms(&entry.subsystem, &entry.parameter, entry.timestamp) {
+            error!("Failed to clear alarms of {}: {}", name, err);
+        }
+        if severity == Severity::Nominal {
+            info!("{} is back within its limits", name);
+            return;
+        }
+
+        let alarm = NewAlarm {
+            timestamp: entry.timestamp,
+            subsystem: entry.subsystem.clone(),
+            parameter: entry.parameter.clone(),
+            severity: severity.name().to_owned(),
+            value: entry.value.clone(),
+            message,
+        };
+        match db.raise_alarm(&alarm) {
+            Ok(id) => warn!(
+                "Alarm {}: {} value {} is {}",
+                id, name, alarm.value, alarm.message
+            ),
+            Err(err) => error!("Failed to store alarm for {}: {}", name, err),
+        }
+
+        if severity == Severity::Red {
+            if let (Some(mode), Some(url)) = (&limit.red_mode, &self.scheduler) {
+                request_mode(url.clone(), mode.clone());
+            }
+        }
+    }
+}
+
+// Asks scheduler-service to change mode, without holding up the insert which
+// raised the alarm
+fn request_mode(url: String, mode: String) {
+    let (field, mutation) = if mode == SAFE_MODE {
+        (
+            "safeMode",
+            "mutation { safeMode { success, errors } }".to_owned(),
+        )
+    } else {
+        (
+            "activateMode",
+            format!(
+                "mutation {{ activateMode(name: {}) {{ success, errors }} }}",
+                json!(mode)
+            ),
+        )
+    };
+
+    thread::spawn(move || {
+        let response = reqwest::blocking::Client::builder()
+            .timeout(HOOK_TIMEOUT)
+            .build()
+            .and_then(|client| {
+                client
+                    .post(&format!("http://{}", url))
+                    .json(&json!({ "query": mutation }))
+                    .send()
+            })
+            .and_then(|response| response.json::<serde_json::Value>());
+
+        match response {
+            Ok(response) => {
+                if response["data"][field]["success"].as_bool() == Some(true) {
+                    info!("Scheduler switched to mode {}", mode);
+                } else {
+                    error!("Scheduler refused mode {}: {}", mode, response);
+                }
+            }
+            Err(err) => error!("Failed to request scheduler mode {}: {}", mode, err),
+        }
+    });
+}
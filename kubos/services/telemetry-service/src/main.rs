@@ -36,6 +36,143 @@
 //! service's IP address, and `port` specifies the port on which the service will be
 //! listening for UDP packets.
 //!
+//! # Rollups and Retention
+//!
+//! Every `maintenance_interval` seconds (default 60) the service summarises numeric telemetry
+//! into per-minute and per-hour rollups holding the minimum, maximum, mean and count of each
+//! parameter, then removes expired rollups and raw telemetry:
+//!
+//! ```
+//! [telemetry-service]
+//! maintenance_interval = 60
+//!
+//! [telemetry-service.rollups]
+//! enabled = true
+//! minute_max_age = 604800
+//! hour_max_age = 31536000
+//!
+//! [[telemetry-service.retention]]
+//! max_age = 86400
+//!
+//! [[telemetry-service.retention]]
+//! subsystem = "eps"
+//! max_age = 604800
+//!
+//! [[telemetry-service.retention]]
+//! subsystem = "eps"
+//! parameter = "voltage"
+//! max_age = 2592000
+//! ```
+//!
+//! Ages are in seconds. Each retention rule covers all telemetry, one subsystem, or one
+//! parameter of a subsystem, and the most specific matching rule applies to each entry.
+//! Telemetry without a matching rule is kept forever. While rollups are enabled, raw
+//! telemetry is kept until it has been summarised, whatever its rule.
+//!
+//! # Parameter Dictionary
+//!
+//! Parameters may be described in a dictionary. Values of a defined parameter are checked
+//! against its type and valid range when they are inserted, whether through GraphQL or the
+//! direct UDP port, and stored in a column for their type as well as in their text form:
+//!
+//! ```
+//! [telemetry-service]
+//! require_dictionary = false
+//!
+//! [[telemetry-service.dictionary]]
+//! subsystem = "eps"
+//! parameter = "voltage"
+//! type = "float"
+//! unit = "V"
+//! min = 0.0
+//! max = 20.0
+//! description = "Battery bus voltage"
+//! ```
+//!
+//! `type` is one of `integer`, `float`, `bool` or `string`, and only numeric parameters may
+//! have a `min` or `max`. A parameter may also be given a unique numeric `id`, which identifies
+//! it on the CBOR port. Values of parameters missing from the dictionary are stored as
+//! received, unless `require_dictionary` is `true`, in which case they are rejected.
+//! Rejected points are counted by parameter and reported by the `rejectedPoints` query.
+//!
+//! # CBOR Telemetry
+//!
+//! Payloads which send telemetry at a high rate can send it to the `cbor_port` UDP port as
+//! batches encoded with CBOR, using the `cbor-protocol` crate, instead of as JSON to the
+//! `direct_port`:
+//!
+//! ```
+//! [telemetry-service]
+//! cbor_port = 8022
+//! ```
+//!
+//! Each message is an array of `[sequence, ack, base, points]`, where `points` is an array of
+//! `[id, offset, value]` arrays. `id` is the `id` of the parameter in the dictionary, and the
+//! point's timestamp is `base` plus `offset`, or the time the message was received plus
+//! `offset` if `base` is null. `value` may be an integer, float, boolean or string, and is
+//! checked against the dictionary like any other value. The valid points of a message are
+//! stored together.
+//!
+//! If `ack` is `true`, the service replies to the sender with `[sequence, accepted, rejected]`,
+//! giving the number of points which were and were not stored.
+//!
+//! The number of messages and points received on both UDP ports from each source IP address are
+//! reported by the `ingestStatistics` query.
+//!
+//! # Limits and Alarms
+//!
+//! Numeric parameters may be given yellow and red limits, which each value is checked against
+//! when it is inserted:
+//!
+//! ```
+//! [[telemetry-service.limits]]
+//! subsystem = "eps"
+//! parameter = "voltage"
+//! yellow_low = 3.5
+//! yellow_high = 4.2
+//! red_low = 3.3
+//! red_high = 4.4
+//! persistence = 3
+//! red_mode = "safe"
+//! ```
+//!
+//! Every limit is optional, but the yellow limits must lie within the red limits. Values equal
+//! to a limit are within it. Once `persistence` consecutive values (default 1) are outside the
+//! yellow or red limits an alarm is raised, and once as many are back within the limits the
+//! alarm is cleared. A parameter has at most one active alarm: moving between yellow and red
+//! clears the old alarm and raises a new one. Alarms are kept in the database, so survive
+//! restarts, and are listed by the `alarms` query until deleted from the database.
+//!
+//! If a limit has a `red_mode`, the scheduler service is asked to switch to that mode whenever
+//! the parameter goes red, using the `safeMode` mutation for `safe` and `activateMode` for any
+//! other mode. The scheduler's address is read from its `[scheduler-service.addr]` section of
+//! the configuration file.
+//!
+//! # Integrity and Size
+//!
+//! The database is checked with SQLite's `quick_check` when the service starts. If it is
+//! damaged, the file and its journal are moved to `<database>.corrupt-<time>`, where they can be
+//! downloaded, and a fresh database is created holding whatever tables could still be read in
+//! full. The check can be repeated with the `checkDatabase` mutation, and its latest result is
+//! reported by the `databaseHealth` query.
+//!
+//! The space the database may use can be limited, in bytes:
+//!
+//! ```
+//! [telemetry-service]
+//! max_size = 50000000
+//! ```
+//!
+//! Whenever maintenance runs, the oldest telemetry entries are removed until the database is
+//! within its budget. Rollups and alarms are kept. Space freed within the file is reused rather
+//! than returned to the filesystem, so the file itself does not shrink.
+//!
+//! # Subscriptions
+//!
+//! Telemetry can be streamed as it is stored, from GraphQL, the direct UDP port or the CBOR
+//! port, by subscribing over a websocket at `ws://ip:port/ws` with the `graphql-transport-ws`
+//! protocol. A subscriber which falls more than 1024 entries behind misses the oldest of them.
+//!
 //! # Starting the Service
 //!
 //! The service should be started automatically by its init script, but may also be started manually:
@@ -50,6 +187,7 @@
 //! Attempts to grab database path from Configuration and will `panic!` if not found.
 //! Attempts to connect to database at provided path and will `panic!` if connection fails.
 //! Attempts to create telemetry table and will `panic!` if table creation fails.
+//! Attempts to move a damaged database aside and will `panic!` if it can't be moved.
 //!
 //! # GraphQL Schema
 //!
@@ -59,13 +197,105 @@
 //!   subsystem: String!
 //!   parameter: String!
 //!   value: Float!
+//!   intValue: Int
+//!   floatValue: Float
+//!   boolValue: Boolean
+//! }
+//!
+//! type Parameter {
+//!   subsystem: String!
+//!   parameter: String!
+//!   id: Int
+//!   type: ParameterType!
+//!   unit: String
+//!   min: Float
+//!   max: Float
+//!   description: String
+//! }
+//!
+//! enum ParameterType { INTEGER, FLOAT, BOOL, STRING }
+//!
+//! type RejectedPoints {
+//!   subsystem: String!
+//!   parameter: String!
+//!   count: Int!
+//!   lastError: String!
+//! }
+//!
+//! type IngestSource {
+//!   source: String!
+//!   messages: Int!
+//!   accepted: Int!
+//!   rejected: Int!
+//!   malformed: Int!
+//!   lastSeen: Float!
+//!   lastError: String
+//! }
+//!
+//! type Rollup {
+//!   timestamp: Float!
+//!   subsystem: String!
+//!   parameter: String!
+//!   min: Float!
+//!   max: Float!
+//!   mean: Float!
+//!   count: Int!
+//! }
+//!
+//! enum Resolution { MINUTE, HOUR }
+//!
+//! type Alarm {
+//!   id: Int!
+//!   timestamp: Float!
+//!   subsystem: String!
+//!   parameter: String!
+//!   severity: AlarmSeverity!
+//!   value: String!
+//!   message: String!
+//!   clearedAt: Float
+//!   acknowledgedAt: Float
+//! }
+//!
+//! enum AlarmSeverity { YELLOW, RED }
+//!
+//! enum ExportFormat { JSON, BINARY }
+//!
+//! type DatabaseCheck {
+//!   timestamp: Float!
+//!   ok: Boolean!
+//!   problems: [String!]!
+//!   quarantined: String
+//!   recoveredRows: Int!
+//! }
+//!
+//! type TableRows {
+//!   table: String!
+//!   rows: Int!
+//! }
+//!
+//! type DatabaseHealth {
+//!   size: Int!
+//!   usedSize: Int!
+//!   maxSize: Int
+//!   tables: [TableRows!]!
+//!   lastCheck: DatabaseCheck!
 //! }
 //!
 //! query ping: "pong"
 //! query telemetry(timestampGe: Integer, timestampLe: Integer, subsystem: String, parameter: String, parameters: [String]): Entry
-//! query routedTelemetry(timestampGe: Integer, timestampLe: Integer, subsystem: String, parameter: String, parameters: [String], output: String!, compress: Boolean = true): String!
+//! query parameters(subsystem: String): [Parameter!]!
+//! query rejectedPoints: [RejectedPoints!]!
+//! query ingestStatistics: [IngestSource!]!
+//! query databaseHealth: DatabaseHealth!
+//! query alarms(subsystem: String, parameter: String, active: Boolean, acknowledged: Boolean, limit: Int): [Alarm!]!
+//! query rollups(resolution: Resolution!, timestampGe: Float, timestampLe: Float, subsystem: String, parameter: String, parameters: [String], limit: Int): [Rollup!]!
+//! query routedTelemetry(timestampGe: Integer, timestampLe: Integer, subsystem: String, parameter: String, parameters: [String], output: String!, compress: Boolean = true, format: ExportFormat = JSON): String!
 //!
 //! mutation insert(timestamp: Integer, subsystem: String!, parameter: String!, value: String!):{ success: Boolean!, errors: String! }
+//! mutation acknowledgeAlarm(id: Int!):{ success: Boolean!, errors: String! }
+//! mutation checkDatabase: DatabaseCheck!
+//!
+//! subscription telemetry(subsystem: String, parameter: String, parameters: [String]): Entry!
 //! ```
 //!
 //! # Example Queries
@@ -154,6 +384,19 @@
 //! }
 //! ```
 //!
+//! ## Select hourly summaries of the eps voltage starting between the timestamps 3600 and 7200
+//! ```graphql
+//! {
+//!   rollups(resolution: HOUR, subsystem: "eps", parameter: "voltage", timestampGe: 3600, timestampLe: 7200) {
+//!     timestamp,
+//!     min,
+//!     max,
+//!     mean,
+//!     count
+//!   }
+//! }
+//! ```
+//!
 //! ## Repeat the previous query, but route the output to compressed file `/home/system/recent_telem.tar.gz`
 //! ```graphql
 //! {
@@ -168,6 +411,28 @@
 //! }
 //! ```
 //!
+//! ## Repeat the previous query, but route the output to a compact binary file
+//! ```graphql
+//! {
+//!   routedTelemetry(limit: 10, timestampGe: 1008, output: "/home/system/recent_telem", format: BINARY)
+//! }
+//! ```
+//!
+//! ## Select the active alarms which have not been acknowledged
+//! ```graphql
+//! {
+//!   alarms(active: true, acknowledged: false) {
+//!     id,
+//!     timestamp,
+//!     subsystem,
+//!     parameter,
+//!     severity,
+//!     value,
+//!     message
+//!   }
+//! }
+//! ```
+//!
 //! # Example Mutations
 //!
 //! ## Insert a new entry, allowing the service to generate the timestamp
@@ -191,6 +456,16 @@
 //!
 //! ```
 //!
+//! ## Acknowledge alarm 4
+//! ```graphql
+//! mutation {
+//!     acknowledgeAlarm(id: 4) {
+//!         success,
+//!         errors
+//!     }
+//! }
+//! ```
+//!
 //! ## Delete all entries from the EPS subsystem occuring before timestamp 1003
 //! ```graphql
 //! mutation {
@@ -201,18 +476,58 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Example Subscriptions
+//!
+//! ## Stream new entries of the eps subsystem
+//! ```graphql
+//! subscription {
+//!     telemetry(subsystem: "eps") {
+//!         timestamp,
+//!         parameter,
+//!         value
+//!     }
+//! }
+//! ```
 
 use env_logger;
 use kubos_service::Config;
 use kubos_service::Service;
 use log;
 
+mod cbor_udp;
+mod feed;
+mod ingest;
+mod limits;
+mod maintenance;
 mod schema;
 mod udp;
+mod validation;
 
-use crate::schema::{MutationRoot, QueryRoot, Subsystem};
+use crate::limits::Limits;
+use crate::maintenance::Policy;
+use crate::schema::{MutationRoot, QueryRoot, SubscriptionRoot, Subsystem};
+use crate::validation::Validator;
 use kubos_telemetry_db::Database;
 
+// Address of a UDP port on the service's IP address, if the port is configured
+fn udp_url(config: &Config, key: &str) -> Option<String> {
+    config.get(key).and_then(|port| {
+        let port_num = port.as_integer()?;
+        let host = config.hosturl().unwrap_or_else(|| {
+            log::error!("Failed to load service URL");
+            std::process::exit(1);
+        });
+        let mut host_parts = host.split(':').map(|val| val.to_owned());
+        let host_ip = host_parts.next().unwrap_or_else(|| {
+            log::error!("Failed to parse service IP address");
+            std::process::exit(1);
+        });
+
+        Some(format!("{}:{}", host_ip, port_num))
+    })
+}
+
 fn main() {
     // Initialize logging
     env_logger::init();
@@ -229,32 +544,37 @@ fn main() {
         .map(|v| v.as_str().unwrap_or("telemetry.db").to_string())
         .unwrap_or_else(|| "telemetry.db".to_string());
     
-    let mut database = Database::new(&db_path);
-    database.setup();
+    let (database, integrity) = Database::open(&db_path);
 
-    // Determine if we should set up a UDP connection for passively receiving
+    // Determine if we should set up UDP connections for passively receiving
     // telemetry for insertion
-    let direct_udp = config.get("direct_port").and_then(|port| {
-        let port_num = port.as_integer()?;
-        let host = config.hosturl().unwrap_or_else(|| {
-            log::error!("Failed to load service URL");
-            std::process::exit(1);
-        });
-        let mut host_parts = host.split(':').map(|val| val.to_owned());
-        let host_ip = host_parts.next().unwrap_or_else(|| {
-            log::error!("Failed to parse service IP address");
-            std::process::exit(1);
-        });
+    let direct_udp = udp_url(&config, "direct_port");
+    let cbor_udp = udp_url(&config, "cbor_port");
 
-        Some(format!("{}:{}", host_ip, port_num))
+    let policy = Policy::from_config(&config).unwrap_or_else(|err| {
+        log::error!("Failed to load maintenance config: {}", err);
+        std::process::exit(1);
+    });
+
+    let validator = Validator::from_config(&config).unwrap_or_else(|err| {
+        log::error!("Failed to load parameter dictionary: {}", err);
+        std::process::exit(1);
+    });
+
+    let limits = Limits::from_config(&config, validator.dictionary()).unwrap_or_else(|err| {
+        log::error!("Failed to load telemetry limits: {}", err);
+        std::process::exit(1);
     });
 
     // Create and start the service
-    Service::new(
+    Service::new_with_subscription(
         config,
-        Subsystem::new(database, direct_udp),
+        Subsystem::new(
+            database, integrity, direct_udp, cbor_udp, policy, validator, limits,
+        ),
         QueryRoot,
         MutationRoot,
+        SubscriptionRoot,
     )
     .start();
 }
@@ -0,0 +1,292 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use diesel::RunQueryDsl;
+use kubos_service::Config;
+use kubos_telemetry_db::{Database, RESOLUTION_HOUR, RESOLUTION_MINUTE};
+use log::{error, info};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_INTERVAL: u64 = 60;
+
+// How long telemetry matching a subsystem and, optionally, a parameter is kept
+#[derive(Clone, Debug, Deserialize)]
+pub struct RetentionRule {
+    pub subsystem: Option<String>,
+    pub parameter: Option<String>,
+    // Seconds
+    pub max_age: u64,
+}
+
+impl RetentionRule {
+    // 0 for the catch-all rule, 1 for a subsystem, 2 for a single parameter
+    fn specificity(&self) -> usize {
+        self.subsystem.iter().count() + self.parameter.iter().count()
+    }
+
+    // Whether every entry matched by `other` is also matched by this rule
+    fn covers(&self, other: &RetentionRule) -> bool {
+        (self.subsystem.is_none() || self.subsystem == other.subsystem)
+            && (self.parameter.is_none() || self.parameter == other.parameter)
+    }
+
+    // SQL condition matching the entries this rule applies to
+    fn scope(&self) -> Option<String> {
+        let mut conditions = vec![];
+        if let Some(subsystem) = &self.subsystem {
+            conditions.push(format!("subsystem = '{}'", subsystem.replace('\'', "''")));
+        }
+        if let Some(parameter) = &self.parameter {
+            conditions.push(format!("parameter = '{}'", parameter.replace('\'', "''")));
+        }
+        if conditions.is_empty() {
+            None
+        } else {
+            Some(conditions.join(" AND "))
+        }
+    }
+}
+
+// Which rollups are kept, and for how long in seconds
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RollupConfig {
+    pub enabled: bool,
+    pub minute_max_age: u64,
+    pub hour_max_age: u64,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        RollupConfig {
+            enabled: true,
+            // One week
+            minute_max_age: 604_800,
+            // One year
+            hour_max_age: 31_536_000,
+        }
+    }
+}
+
+// Database upkeep settings from the service config
+#[derive(Clone, Debug)]
+pub struct Policy {
+    pub interval: Duration,
+    pub rollups: RollupConfig,
+    pub retention: Vec<RetentionRule>,
+    // Bytes of data the database may hold before its oldest telemetry is removed
+    pub max_size: Option<u64>,
+}
+
+impl Policy {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let interval = match config.get("maintenance_interval") {
+            Some(interval) => match interval.as_integer() {
+                Some(secs) if secs > 0 => secs as u64,
+                _ => {
+                    return Err(
+                        "maintenance_interval must be a positive number of seconds".to_owned()
+                    )
+                }
+            },
+            None => DEFAULT_INTERVAL,
+        };
+
+        let rollups = match config.get("rollups") {
+            Some(rollups) => rollups
+                .try_into::<RollupConfig>()
+                .map_err(|err| format!("Failed to parse rollups: {}", err))?,
+            None => RollupConfig::default(),
+        };
+        if rollups.minute_max_age == 0 || rollups.hour_max_age == 0 {
+            return Err("Rollup max ages must be greater than zero".to_owned());
+        }
+
+        let retention = match config.get("retention") {
+            Some(retention) => retention
+                .try_into::<Vec<RetentionRule>>()
+                .map_err(|err| format!("Failed to parse retention rules: {}", err))?,
+            None => vec![],
+        };
+        for (index, rule) in retention.iter().enumerate() {
+            if rule.subsystem.is_none() && rule.parameter.is_some() {
+                return Err("Retention rules for a parameter must name its subsystem".to_owned());
+            }
+            if rule.max_age == 0 {
+                return Err("Retention max_age must be greater than zero".to_owned());
+            }
+            if retention[..index]
+                .iter()
+                .any(|other| other.subsystem == rule.subsystem && other.parameter == rule.parameter)
+            {
+                return Err(format!(
+                    "Duplicate retention rule for {}",
+                    rule.scope().unwrap_or_else(|| "all telemetry".to_owned())
+                ));
+            }
+        }
+
+        let max_size = match config.get("max_size") {
+            Some(max_size) => match max_size.as_integer() {
+                Some(bytes) if bytes > 0 => Some(bytes as u64),
+                _ => return Err("max_size must be a positive number of bytes".to_owned()),
+            },
+            None => None,
+        };
+
+        Ok(Policy {
+            interval: Duration::from_secs(interval),
+            rollups,
+            retention,
+            max_size,
+        })
+    }
+}
+
+// Background worker which summarises telemetry and removes expired entries
+pub struct Maintenance {
+    db: Arc<Mutex<Database>>,
+    policy: Policy,
+}
+
+impl Maintenance {
+    pub fn new(db: Arc<Mutex<Database>>, policy: Policy) -> Self {
+        Maintenance { db, policy }
+    }
+
+    pub fn start(&self) {
+        info!(
+            "Maintaining telemetry database every {}s",
+            self.policy.interval.as_secs()
+        );
+
+        loop {
+            if let Err(err) = self.run() {
+                error!("Telemetry maintenance failed: {}", err);
+            }
+            thread::sleep(self.policy.interval);
+        }
+    }
+
+    fn run(&self) -> Result<(), String> {
+        let time = time::now_utc().to_timespec();
+        let now = time.sec as f64 + (f64::from(time.nsec) / 1_000_000_000.0);
+
+        // Raw telemetry is kept until it has been summarised at every resolution
+        let mut summarised = Some(now);
+        if self.policy.rollups.enabled {
+            for &(resolution, max_age) in &[
+                (RESOLUTION_MINUTE, self.policy.rollups.minute_max_age),
+                (RESOLUTION_HOUR, self.policy.rollups.hour_max_age),
+            ] {
+                // The lock is released between chunks so a backlog doesn't
+                // hold up requests
+                loop {
+                    let done = self
+                        .lock()?
+                        .rollup_chunk(resolution, now)
+                        .map_err(|err| format!("Failed to roll up telemetry: {}", err))?;
+                    if done {
+                        break;
+                    }
+                }
+
+                self.execute(format!(
+                    "DELETE FROM telemetry_rollup WHERE resolution = {} AND start < {}",
+                    resolution,
+                    now - max_age as f64
+                ))?;
+
+                let through = self
+                    .lock()?
+                    .rollup_through(resolution)
+                    .map_err(|err| format!("Failed to read rollup progress: {}", err))?;
+                summarised = match (summarised, through) {
+                    (Some(earlier), Some(through)) => Some(earlier.min(through)),
+                    _ => None,
+                };
+            }
+        }
+
+        for rule in &self.policy.retention {
+            let cutoff = match summarised {
+                Some(through) => through.min(now - rule.max_age as f64),
+                None => continue,
+            };
+
+            let mut conditions = vec![format!("timestamp < {}", cutoff)];
+            conditions.extend(rule.scope());
+            // Entries with a more specific rule of their own are left to it
+            for other in &self.policy.retention {
+                if other.specificity() > rule.specificity() && rule.covers(other) {
+                    if let Some(scope) = other.scope() {
+                        conditions.push(format!("NOT ({})", scope));
+                    }
+                }
+            }
+
+            let deleted = self.execute(format!(
+                "DELETE FROM telemetry WHERE {}",
+                conditions.join(" AND ")
+            ))?;
+            if deleted > 0 {
+                info!(
+                    "Removed {} entries older than {}s for {}",
+                    deleted,
+                    rule.max_age,
+                    rule.scope().unwrap_or_else(|| "all telemetry".to_owned())
+                );
+            }
+        }
+
+        if let Some(max_size) = self.policy.max_size {
+            let mut evicted = 0;
+            loop {
+                let removed = self
+                    .lock()?
+                    .evict_oldest(max_size)
+                    .map_err(|err| format!("Failed to remove the oldest telemetry: {}", err))?;
+                if removed == 0 {
+                    break;
+                }
+                evicted += removed;
+            }
+            if evicted > 0 {
+                info!(
+                    "Removed the {} oldest entries to keep the database under {} bytes",
+                    evicted, max_size
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, query: String) -> Result<usize, String> {
+        diesel::sql_query(query)
+            .execute(&mut self.lock()?.connection)
+            .map_err(|err| format!("Failed to remove expired telemetry: {}", err))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Database>, String> {
+        self.db
+            .lock()
+            .map_err(|err| format!("Failed to get lock on database: {}", err))
+    }
+}
@@ -14,13 +14,20 @@
 // limitations under the License.
 //
 
+use crate::cbor_udp::CborUdp;
+use crate::feed::Feed;
+use crate::ingest::IngestStats;
+use crate::limits::Limits;
+use crate::maintenance::{Maintenance, Policy};
 use crate::udp::*;
+use crate::validation::Validator;
 use diesel::prelude::*;
 use diesel::sql_types::*;
 use diesel::RunQueryDsl;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use async_graphql::{Context, Object, Result, InputObject, SimpleObject};
+use kubos_telemetry_db::{IntegrityCheck, TypedEntry, ValueType};
+use async_graphql::{Context, Enum, Object, Result, InputObject, SimpleObject, Subscription};
 use serde_derive::Serialize;
 use std::fs;
 use std::fs::File;
@@ -28,27 +35,77 @@ use std::io::prelude::*;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread::spawn;
+use tokio_stream::{Stream, StreamExt};
 
 #[derive(Clone)]
 pub struct Subsystem {
     pub database: Arc<Mutex<kubos_telemetry_db::Database>>,
+    pub validator: Arc<Validator>,
+    pub limits: Arc<Limits>,
+    pub stats: Arc<IngestStats>,
+    pub feed: Arc<Feed>,
+    pub integrity: Arc<Mutex<IntegrityCheck>>,
+    pub max_size: Option<u64>,
 }
 
 impl Subsystem {
-    pub fn new(database: kubos_telemetry_db::Database, direct_udp: Option<String>) -> Self {
+    pub fn new(
+        mut database: kubos_telemetry_db::Database,
+        integrity: IntegrityCheck,
+        direct_udp: Option<String>,
+        cbor_udp: Option<String>,
+        policy: Policy,
+        validator: Validator,
+        limits: Limits,
+    ) -> Self {
+        limits.restore(&mut database);
+
         let db = Arc::new(Mutex::new(database));
+        let validator = Arc::new(validator);
+        let limits = Arc::new(limits);
+        let stats = Arc::new(IngestStats::default());
+        let feed = Arc::new(Feed::default());
 
         if let Some(udp_url) = direct_udp {
-            let udp = DirectUdp::new(db.clone());
+            let udp = DirectUdp::new(
+                db.clone(),
+                validator.clone(),
+                limits.clone(),
+                stats.clone(),
+                feed.clone(),
+            );
             spawn(move || udp.start(udp_url.to_owned()));
         }
 
-        Subsystem { database: db }
+        if let Some(cbor_url) = cbor_udp {
+            let cbor = CborUdp::new(
+                db.clone(),
+                validator.clone(),
+                limits.clone(),
+                stats.clone(),
+                feed.clone(),
+            );
+            spawn(move || cbor.start(cbor_url));
+        }
+
+        let max_size = policy.max_size;
+        let maintenance = Maintenance::new(db.clone(), policy);
+        spawn(move || maintenance.start());
+
+        Subsystem {
+            database: db,
+            validator,
+            limits,
+            stats,
+            feed,
+            integrity: Arc::new(Mutex::new(integrity)),
+            max_size,
+        }
     }
 }
 
 // Define our own Entry struct to be compatible with diesel 2.0
-#[derive(Debug, Serialize, Queryable, QueryableByName, SimpleObject)]
+#[derive(Clone, Debug, Serialize, Queryable, QueryableByName, SimpleObject)]
 pub struct Entry {
     #[diesel(sql_type = Double)]
     /// Timestamp
@@ -62,17 +119,291 @@ pub struct Entry {
     #[diesel(sql_type = Text)]
     /// Telemetry value
     pub value: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Value of a parameter defined as an integer
+    pub int_value: Option<i64>,
+    #[diesel(sql_type = Nullable<Double>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Value of a parameter defined as a float
+    pub float_value: Option<f64>,
+    #[diesel(sql_type = Nullable<Bool>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Value of a parameter defined as a boolean
+    pub bool_value: Option<bool>,
 }
 
-fn query_db(
-    database: &Arc<Mutex<kubos_telemetry_db::Database>>,
+impl From<&TypedEntry> for Entry {
+    fn from(entry: &TypedEntry) -> Self {
+        Entry {
+            timestamp: entry.timestamp,
+            subsystem: entry.subsystem.clone(),
+            parameter: entry.parameter.clone(),
+            value: entry.value.clone(),
+            int_value: entry.int_value,
+            float_value: entry.float_value,
+            bool_value: entry.bool_value,
+        }
+    }
+}
+
+impl From<Entry> for telemetry_export::Point {
+    fn from(entry: Entry) -> Self {
+        telemetry_export::Point {
+            timestamp: entry.timestamp,
+            subsystem: entry.subsystem,
+            parameter: entry.parameter,
+            value: entry.value,
+            int_value: entry.int_value,
+            float_value: entry.float_value,
+            bool_value: entry.bool_value,
+        }
+    }
+}
+
+/// File format of routed telemetry
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum ExportFormat {
+    /// JSON array of entries
+    Json,
+    /// Compact binary export, decoded with the `telemetry-export` library
+    Binary,
+}
+
+/// Type of a parameter's values
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum ParameterType {
+    /// Signed 64-bit integer
+    Integer,
+    /// 64-bit float
+    Float,
+    /// `true` or `false`
+    Bool,
+    /// Any text
+    String,
+}
+
+/// Definition of a telemetry parameter from the dictionary
+#[derive(Debug, SimpleObject)]
+pub struct Parameter {
+    /// Subsystem name
+    pub subsystem: String,
+    /// Parameter name
+    pub parameter: String,
+    /// Number identifying the parameter on the CBOR port
+    pub id: Option<u32>,
+    /// Type of the parameter's values
+    #[graphql(name = "type")]
+    pub value_type: ParameterType,
+    /// Unit of the parameter's values
+    pub unit: Option<String>,
+    /// Smallest valid value
+    pub min: Option<f64>,
+    /// Largest valid value
+    pub max: Option<f64>,
+    /// Description of the parameter
+    pub description: Option<String>,
+}
+
+/// Telemetry points rejected for one parameter
+#[derive(Debug, SimpleObject)]
+pub struct RejectedPoints {
+    /// Subsystem name
+    pub subsystem: String,
+    /// Parameter name
+    pub parameter: String,
+    /// Number of points rejected since the service started
+    pub count: u64,
+    /// Reason the most recent point was rejected
+    pub last_error: String,
+}
+
+/// Telemetry received on the UDP ports from one source
+#[derive(Debug, SimpleObject)]
+pub struct IngestSource {
+    /// Source IP address
+    pub source: String,
+    /// Messages received, including malformed ones
+    pub messages: u64,
+    /// Points stored
+    pub accepted: u64,
+    /// Points which were not stored
+    pub rejected: u64,
+    /// Messages which could not be decoded
+    pub malformed: u64,
+    /// System time of the most recent message
+    pub last_seen: f64,
+    /// Reason the most recent rejected point or malformed message was refused
+    pub last_error: Option<String>,
+}
+
+/// How far outside its limits a parameter was
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum AlarmSeverity {
+    /// Outside the yellow limits
+    Yellow,
+    /// Outside the red limits
+    Red,
+}
+
+// Alarm as stored in the database
+#[derive(QueryableByName)]
+struct AlarmRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+    #[diesel(sql_type = Double)]
+    timestamp: f64,
+    #[diesel(sql_type = Text)]
+    subsystem: String,
+    #[diesel(sql_type = Text)]
+    parameter: String,
+    #[diesel(sql_type = Text)]
+    severity: String,
+    #[diesel(sql_type = Text)]
+    value: String,
+    #[diesel(sql_type = Text)]
+    message: String,
+    #[diesel(sql_type = Nullable<Double>)]
+    cleared: Option<f64>,
+    #[diesel(sql_type = Nullable<Double>)]
+    acknowledged: Option<f64>,
+}
+
+/// Limit violation of a telemetry parameter
+#[derive(Debug, SimpleObject)]
+pub struct Alarm {
+    /// Alarm ID, used to acknowledge it
+    pub id: i32,
+    /// Timestamp of the value which raised the alarm
+    pub timestamp: f64,
+    /// Subsystem name
+    pub subsystem: String,
+    /// Telemetry parameter
+    pub parameter: String,
+    /// Limits which were violated
+    pub severity: AlarmSeverity,
+    /// Value which raised the alarm
+    pub value: String,
+    /// Description of the violated limit
+    pub message: String,
+    /// Timestamp of the value which ended the alarm, if it has ended
+    pub cleared_at: Option<f64>,
+    /// System time at which the alarm was acknowledged, if it has been
+    pub acknowledged_at: Option<f64>,
+}
+
+impl From<AlarmRow> for Alarm {
+    fn from(row: AlarmRow) -> Self {
+        Alarm {
+            id: row.id,
+            timestamp: row.timestamp,
+            subsystem: row.subsystem,
+            parameter: row.parameter,
+            severity: match row.severity.as_str() {
+                "red" => AlarmSeverity::Red,
+                _ => AlarmSeverity::Yellow,
+            },
+            value: row.value,
+            message: row.message,
+            cleared_at: row.cleared,
+            acknowledged_at: row.acknowledged,
+        }
+    }
+}
+
+/// Bucket length of telemetry rollups
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum Resolution {
+    /// One minute
+    Minute,
+    /// One hour
+    Hour,
+}
+
+/// Summary of a parameter's numeric values over one bucket
+#[derive(Debug, Serialize, QueryableByName, SimpleObject)]
+pub struct Rollup {
+    #[diesel(sql_type = Double)]
+    /// Start of the bucket
+    pub timestamp: f64,
+    #[diesel(sql_type = Text)]
+    /// Subsystem name
+    pub subsystem: String,
+    #[diesel(sql_type = Text)]
+    /// Telemetry parameter
+    pub parameter: String,
+    #[diesel(sql_type = Double)]
+    /// Smallest value
+    pub min: f64,
+    #[diesel(sql_type = Double)]
+    /// Largest value
+    pub max: f64,
+    #[diesel(sql_type = Double)]
+    /// Mean value
+    pub mean: f64,
+    #[diesel(sql_type = BigInt)]
+    /// Number of values summarised
+    pub count: i64,
+}
+
+/// Result of a database integrity check
+#[derive(Debug, SimpleObject)]
+pub struct DatabaseCheck {
+    /// System time of the check
+    pub timestamp: f64,
+    /// Whether the database was intact
+    pub ok: bool,
+    /// Problems found
+    pub problems: Vec<String>,
+    /// Where the damaged database was moved when the service started
+    pub quarantined: Option<String>,
+    /// Rows copied from the damaged database into the fresh one
+    pub recovered_rows: u64,
+}
+
+impl From<&IntegrityCheck> for DatabaseCheck {
+    fn from(check: &IntegrityCheck) -> Self {
+        DatabaseCheck {
+            timestamp: check.timestamp,
+            ok: check.ok(),
+            problems: check.problems.clone(),
+            quarantined: check.quarantined.clone(),
+            recovered_rows: check.recovered_rows as u64,
+        }
+    }
+}
+
+/// Number of rows in a database table
+#[derive(Debug, SimpleObject)]
+pub struct TableRows {
+    /// Table name
+    pub table: String,
+    /// Number of rows
+    pub rows: i64,
+}
+
+/// Size and state of the telemetry database
+#[derive(Debug, SimpleObject)]
+pub struct DatabaseHealth {
+    /// Size of the database file, in bytes
+    pub size: u64,
+    /// Space within the file holding data, in bytes
+    pub used_size: u64,
+    /// Space the database may use before its oldest telemetry is removed, in bytes
+    pub max_size: Option<u64>,
+    /// Rows in each table
+    pub tables: Vec<TableRows>,
+    /// Most recent integrity check
+    pub last_check: DatabaseCheck,
+}
+
+// SQL conditions selecting rows by time, subsystem and parameter
+fn filter_conditions(
     timestamp_ge: Option<f64>,
     timestamp_le: Option<f64>,
     subsystem: Option<String>,
     parameters: Option<Vec<String>>,
-    limit: Option<i32>,
-) -> Result<Vec<Entry>> {
-    // Build query using raw SQL to avoid Diesel version conflicts
+) -> Vec<String> {
     let mut conditions = Vec::new();
 
     if let Some(sub) = subsystem {
@@ -97,12 +428,26 @@ fn query_db(
         conditions.push(format!("timestamp <= {}", time_le));
     }
 
+    conditions
+}
+
+fn query_db(
+    database: &Arc<Mutex<kubos_telemetry_db::Database>>,
+    timestamp_ge: Option<f64>,
+    timestamp_le: Option<f64>,
+    subsystem: Option<String>,
+    parameters: Option<Vec<String>>,
+    limit: Option<i32>,
+) -> Result<Vec<Entry>> {
+    // Build query using raw SQL to avoid Diesel version conflicts
+    let conditions = filter_conditions(timestamp_ge, timestamp_le, subsystem, parameters);
+
     let query = if conditions.is_empty() {
-        "SELECT timestamp, subsystem, parameter, value FROM telemetry ORDER BY timestamp DESC"
+        "SELECT timestamp, subsystem, parameter, value, int_value, float_value, bool_value FROM telemetry ORDER BY timestamp DESC"
             .to_string()
     } else {
         format!(
-            "SELECT timestamp, subsystem, parameter, value FROM telemetry WHERE {} ORDER BY timestamp DESC",
+            "SELECT timestamp, subsystem, parameter, value, int_value, float_value, bool_value FROM telemetry WHERE {} ORDER BY timestamp DESC",
             conditions.join(" AND ")
         )
     };
@@ -129,6 +474,92 @@ fn query_db(
     Ok(entries)
 }
 
+fn query_rollups(
+    database: &Arc<Mutex<kubos_telemetry_db::Database>>,
+    resolution: Resolution,
+    timestamp_ge: Option<f64>,
+    timestamp_le: Option<f64>,
+    subsystem: Option<String>,
+    parameters: Option<Vec<String>>,
+    limit: Option<i32>,
+) -> Result<Vec<Rollup>> {
+    let resolution = match resolution {
+        Resolution::Minute => kubos_telemetry_db::RESOLUTION_MINUTE,
+        Resolution::Hour => kubos_telemetry_db::RESOLUTION_HOUR,
+    };
+
+    let mut conditions = filter_conditions(timestamp_ge, timestamp_le, subsystem, parameters);
+    conditions.insert(0, format!("resolution = {}", resolution));
+
+    let mut query = format!(
+        "SELECT * FROM (SELECT resolution, start AS timestamp, subsystem, parameter, minimum AS min, maximum AS max, mean, samples AS count FROM telemetry_rollup) WHERE {} ORDER BY timestamp DESC",
+        conditions.join(" AND ")
+    );
+    if let Some(l) = limit {
+        query = format!("{} LIMIT {}", query, l);
+    }
+
+    let mut db_lock = database.lock().map_err(|err| {
+        log::error!("Failed to get lock on database: {:?}", err);
+        async_graphql::Error::new(format!("Database lock error: {}", err))
+    })?;
+
+    let rollups = diesel::sql_query(query)
+        .load::<Rollup>(&mut db_lock.connection)
+        .map_err(|err| {
+            log::error!("Failed to load telemetry rollups: {:?}", err);
+            async_graphql::Error::new(format!("Database query error: {}", err))
+        })?;
+
+    Ok(rollups)
+}
+
+fn query_alarms(
+    database: &Arc<Mutex<kubos_telemetry_db::Database>>,
+    subsystem: Option<String>,
+    parameter: Option<String>,
+    active: Option<bool>,
+    acknowledged: Option<bool>,
+    limit: Option<i32>,
+) -> Result<Vec<Alarm>> {
+    let mut conditions = filter_conditions(None, None, subsystem, parameter.map(|p| vec![p]));
+    if let Some(active) = active {
+        conditions.push(format!(
+            "cleared IS {}",
+            if active { "NULL" } else { "NOT NULL" }
+        ));
+    }
+    if let Some(acknowledged) = acknowledged {
+        conditions.push(format!(
+            "acknowledged IS {}",
+            if acknowledged { "NOT NULL" } else { "NULL" }
+        ));
+    }
+
+    let mut query = "SELECT * FROM telemetry_alarm".to_owned();
+    if !conditions.is_empty() {
+        query = format!("{} WHERE {}", query, conditions.join(" AND "));
+    }
+    query = format!("{} ORDER BY id DESC", query);
+    if let Some(l) = limit {
+        query = format!("{} LIMIT {}", query, l);
+    }
+
+    let mut db_lock = database.lock().map_err(|err| {
+        log::error!("Failed to get lock on database: {:?}", err);
+        async_graphql::Error::new(format!("Database lock error: {}", err))
+    })?;
+
+    let alarms = diesel::sql_query(query)
+        .load::<AlarmRow>(&mut db_lock.connection)
+        .map_err(|err| {
+            log::error!("Failed to load alarms: {:?}", err);
+            async_graphql::Error::new(format!("Database query error: {}", err))
+        })?;
+
+    Ok(alarms.into_iter().map(Alarm::from).collect())
+}
+
 pub struct QueryRoot;
 
 #[Object]
@@ -178,6 +609,172 @@ impl QueryRoot {
         }
     }
 
+    /// Parameters defined in the dictionary
+    async fn parameters(
+        &self,
+        ctx: &Context<'_>,
+        subsystem: Option<String>,
+    ) -> Result<Vec<Parameter>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        Ok(context
+            .subsystem()
+            .validator
+            .dictionary()
+            .definitions()
+            .filter(|definition| {
+                subsystem
+                    .as_ref()
+                    .is_none_or(|subsystem| *subsystem == definition.subsystem)
+            })
+            .map(|definition| Parameter {
+                subsystem: definition.subsystem.clone(),
+                parameter: definition.parameter.clone(),
+                id: definition.id,
+                value_type: match definition.value_type {
+                    ValueType::Integer => ParameterType::Integer,
+                    ValueType::Float => ParameterType::Float,
+                    ValueType::Bool => ParameterType::Bool,
+                    ValueType::String => ParameterType::String,
+                },
+                unit: definition.unit.clone(),
+                min: definition.min,
+                max: definition.max,
+                description: definition.description.clone(),
+            })
+            .collect())
+    }
+
+    /// Telemetry points which failed validation, by parameter
+    async fn rejected_points(&self, ctx: &Context<'_>) -> Result<Vec<RejectedPoints>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        Ok(context
+            .subsystem()
+            .validator
+            .rejections()
+            .into_iter()
+            .map(|(subsystem, parameter, rejections)| RejectedPoints {
+                subsystem,
+                parameter,
+                count: rejections.count,
+                last_error: rejections.last_error,
+            })
+            .collect())
+    }
+
+    /// Counts of telemetry received on the UDP ports, by source
+    async fn ingest_statistics(&self, ctx: &Context<'_>) -> Result<Vec<IngestSource>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        Ok(context
+            .subsystem()
+            .stats
+            .sources()
+            .into_iter()
+            .map(|(source, stats)| IngestSource {
+                source,
+                messages: stats.messages,
+                accepted: stats.accepted,
+                rejected: stats.rejected,
+                malformed: stats.malformed,
+                last_seen: stats.last_seen,
+                last_error: stats.last_error,
+            })
+            .collect())
+    }
+
+    /// Size, row counts and integrity of the telemetry database
+    async fn database_health(&self, ctx: &Context<'_>) -> Result<DatabaseHealth> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        let mut db_lock = context.subsystem().database.lock().map_err(|err| {
+            log::error!(
+                "database_health - Failed to get lock on database: {:?}",
+                err
+            );
+            async_graphql::Error::new(format!("Database lock error: {}", err))
+        })?;
+        let size = db_lock.size().map_err(|err| {
+            async_graphql::Error::new(format!("Failed to read database size: {}", err))
+        })?;
+        let tables = db_lock.row_counts().map_err(|err| {
+            async_graphql::Error::new(format!("Failed to count database rows: {}", err))
+        })?;
+        drop(db_lock);
+
+        let integrity = context.subsystem().integrity.lock().map_err(|err| {
+            async_graphql::Error::new(format!("Integrity check lock error: {}", err))
+        })?;
+
+        Ok(DatabaseHealth {
+            size: size.total,
+            used_size: size.used(),
+            max_size: context.subsystem().max_size,
+            tables: tables
+                .into_iter()
+                .map(|(table, rows)| TableRows {
+                    table: table.to_owned(),
+                    rows,
+                })
+                .collect(),
+            last_check: DatabaseCheck::from(&*integrity),
+        })
+    }
+
+    /// Alarms raised by parameters outside their limits, newest first
+    async fn alarms(
+        &self,
+        ctx: &Context<'_>,
+        subsystem: Option<String>,
+        parameter: Option<String>,
+        active: Option<bool>,
+        acknowledged: Option<bool>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Alarm>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        query_alarms(
+            &context.subsystem().database,
+            subsystem,
+            parameter,
+            active,
+            acknowledged,
+            limit,
+        )
+    }
+
+    /// Minimum, maximum and mean of numeric telemetry over each minute or hour
+    async fn rollups(
+        &self,
+        ctx: &Context<'_>,
+        resolution: Resolution,
+        timestamp_ge: Option<f64>,
+        timestamp_le: Option<f64>,
+        subsystem: Option<String>,
+        parameter: Option<String>,
+        parameters: Option<Vec<String>>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Rollup>> {
+        if parameter.is_some() && parameters.is_some() {
+            return Err(async_graphql::Error::new(
+                "The `parameter` and `parameters` input fields are mutually exclusive",
+            ));
+        }
+
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        query_rollups(
+            &context.subsystem().database,
+            resolution,
+            timestamp_ge,
+            timestamp_le,
+            subsystem,
+            parameter.map(|param| vec![param]).or(parameters),
+            limit,
+        )
+    }
+
     /// Telemetry entries in database
     async fn routed_telemetry(
         &self,
@@ -190,6 +787,7 @@ impl QueryRoot {
         limit: Option<i32>,
         output: String,
         compress: Option<bool>,
+        format: Option<ExportFormat>,
     ) -> Result<String> {
         let compress = compress.unwrap_or(true);
 
@@ -221,9 +819,17 @@ impl QueryRoot {
             )?
         };
 
-        let entries = serde_json::to_vec(&entries).map_err(|err| {
-            async_graphql::Error::new(format!("JSON serialization error: {}", err))
-        })?;
+        let entries = match format.unwrap_or(ExportFormat::Json) {
+            ExportFormat::Json => serde_json::to_vec(&entries).map_err(|err| {
+                async_graphql::Error::new(format!("JSON serialization error: {}", err))
+            })?,
+            ExportFormat::Binary => telemetry_export::encode(
+                &entries
+                    .into_iter()
+                    .map(telemetry_export::Point::from)
+                    .collect::<Vec<_>>(),
+            ),
+        };
 
         let output_str = output.clone();
         let output_path = Path::new(&output_str);
@@ -278,6 +884,43 @@ impl QueryRoot {
     }
 }
 
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Telemetry entries as they are stored
+    async fn telemetry(
+        &self,
+        ctx: &Context<'_>,
+        subsystem: Option<String>,
+        parameter: Option<String>,
+        parameters: Option<Vec<String>>,
+    ) -> Result<impl Stream<Item = Entry>> {
+        if parameter.is_some() && parameters.is_some() {
+            return Err(async_graphql::Error::new(
+                "The `parameter` and `parameters` input fields are mutually exclusive",
+            ));
+        }
+
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        // An empty parameter list matches every parameter, as in the telemetry query
+        let parameters = parameter
+            .map(|param| vec![param])
+            .or(parameters)
+            .filter(|params| !params.is_empty());
+
+        Ok(context.subsystem().feed.subscribe().filter(move |entry| {
+            subsystem
+                .as_ref()
+                .is_none_or(|subsystem| *subsystem == entry.subsystem)
+                && parameters
+                    .as_ref()
+                    .is_none_or(|params| params.contains(&entry.parameter))
+        }))
+    }
+}
+
 pub struct MutationRoot;
 
 #[derive(SimpleObject)]
@@ -286,6 +929,12 @@ struct InsertResponse {
     errors: String,
 }
 
+#[derive(SimpleObject)]
+struct AcknowledgeResponse {
+    success: bool,
+    errors: String,
+}
+
 #[derive(SimpleObject)]
 struct DeleteResponse {
     success: bool,
@@ -313,23 +962,31 @@ impl MutationRoot {
     ) -> Result<InsertResponse> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
 
-        let result = match timestamp {
-            Some(time) => {
-                let mut db_lock = context.subsystem().database.lock().map_err(|err| {
-                    log::error!("insert - Failed to get lock on database: {:?}", err);
-                    async_graphql::Error::new(format!("Database lock error: {}", err))
-                })?;
-                db_lock.insert(time, &subsystem, &parameter, &value)
-            }
-            None => {
-                let mut db_lock = context.subsystem().database.lock().map_err(|err| {
-                    log::error!("insert - Failed to get lock on database: {:?}", err);
-                    async_graphql::Error::new(format!("Database lock error: {}", err))
-                })?;
-                db_lock.insert_systime(&subsystem, &parameter, &value)
+        let timestamp = timestamp.unwrap_or_else(kubos_telemetry_db::systime);
+        let entry = match context
+            .subsystem()
+            .validator
+            .entry(timestamp, &subsystem, &parameter, &value)
+        {
+            Ok(entry) => entry,
+            Err(err) => {
+                return Ok(InsertResponse {
+                    success: false,
+                    errors: err,
+                })
             }
         };
 
+        let mut db_lock = context.subsystem().database.lock().map_err(|err| {
+            log::error!("insert - Failed to get lock on database: {:?}", err);
+            async_graphql::Error::new(format!("Database lock error: {}", err))
+        })?;
+        let result = db_lock.insert_typed(&entry);
+        if result.is_ok() {
+            context.subsystem().limits.check(&mut db_lock, &entry);
+            context.subsystem().feed.publish(&entry);
+        }
+
         Ok(InsertResponse {
             success: result.is_ok(),
             errors: match result {
@@ -345,29 +1002,45 @@ impl MutationRoot {
         timestamp: Option<f64>,
         entries: Vec<InsertEntry>,
     ) -> Result<InsertResponse> {
-        let time = time::now_utc().to_timespec();
-        let systime = time.sec as f64 + (f64::from(time.nsec) / 1_000_000_000.0);
+        let systime = kubos_telemetry_db::systime();
 
-        let mut new_entries: Vec<kubos_telemetry_db::Entry> = Vec::new();
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        // Nothing is inserted unless every entry is valid
+        let mut new_entries = Vec::new();
+        let mut errors = Vec::new();
         for entry in entries {
             let ts = entry.timestamp.or(timestamp).unwrap_or(systime);
 
-            new_entries.push(kubos_telemetry_db::Entry {
-                timestamp: ts,
-                subsystem: entry.subsystem,
-                parameter: entry.parameter,
-                value: entry.value,
+            match context.subsystem().validator.entry(
+                ts,
+                &entry.subsystem,
+                &entry.parameter,
+                &entry.value,
+            ) {
+                Ok(entry) => new_entries.push(entry),
+                Err(err) => errors.push(err),
+            }
+        }
+        if !errors.is_empty() {
+            return Ok(InsertResponse {
+                success: false,
+                errors: errors.join("; "),
             });
         }
 
-        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
-
         let mut db_lock = context.subsystem().database.lock().map_err(|err| {
             log::error!("insert_bulk - Failed to get lock on database: {:?}", err);
             async_graphql::Error::new(format!("Database lock error: {}", err))
         })?;
 
-        let result = db_lock.insert_bulk(new_entries);
+        let result = db_lock.insert_bulk_typed(&new_entries);
+        if result.is_ok() {
+            for entry in &new_entries {
+                context.subsystem().limits.check(&mut db_lock, entry);
+                context.subsystem().feed.publish(entry);
+            }
+        }
 
         Ok(InsertResponse {
             success: result.is_ok(),
@@ -378,6 +1051,62 @@ impl MutationRoot {
         })
     }
 
+    async fn acknowledge_alarm(&self, ctx: &Context<'_>, id: i32) -> Result<AcknowledgeResponse> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        let mut db_lock = context.subsystem().database.lock().map_err(|err| {
+            log::error!(
+                "acknowledge_alarm - Failed to get lock on database: {:?}",
+                err
+            );
+            async_graphql::Error::new(format!("Database lock error: {}", err))
+        })?;
+
+        Ok(
+            match db_lock.acknowledge_alarm(id, kubos_telemetry_db::systime()) {
+                Ok(true) => AcknowledgeResponse {
+                    success: true,
+                    errors: "".to_owned(),
+                },
+                Ok(false) => AcknowledgeResponse {
+                    success: false,
+                    errors: format!("No unacknowledged alarm with ID {}", id),
+                },
+                Err(err) => AcknowledgeResponse {
+                    success: false,
+                    errors: format!("{}", err),
+                },
+            },
+        )
+    }
+
+    /// Check the integrity of the database. A damaged database is replaced
+    /// when the service next starts.
+    async fn check_database(&self, ctx: &Context<'_>) -> Result<DatabaseCheck> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        let check = context
+            .subsystem()
+            .database
+            .lock()
+            .map_err(|err| {
+                log::error!("check_database - Failed to get lock on database: {:?}", err);
+                async_graphql::Error::new(format!("Database lock error: {}", err))
+            })?
+            .check_integrity();
+        if !check.ok() {
+            log::error!("Database is damaged: {}", check.problems.join("; "));
+        }
+
+        let result = DatabaseCheck::from(&check);
+        let mut integrity = context.subsystem().integrity.lock().map_err(|err| {
+            async_graphql::Error::new(format!("Integrity check lock error: {}", err))
+        })?;
+        *integrity = check;
+
+        Ok(result)
+    }
+
     async fn delete(
         &self,
         ctx: &Context<'_>,
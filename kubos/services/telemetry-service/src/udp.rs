@@ -14,6 +14,10 @@
 // limitations under the License.
 //
 
+use crate::feed::Feed;
+use crate::ingest::IngestStats;
+use crate::limits::Limits;
+use crate::validation::Validator;
 use kubos_telemetry_db::Database;
 use log::{error, info};
 use serde::Deserialize;
@@ -22,6 +26,10 @@ use std::sync::{Arc, Mutex};
 
 pub struct DirectUdp {
     db: Arc<Mutex<Database>>,
+    validator: Arc<Validator>,
+    limits: Arc<Limits>,
+    stats: Arc<IngestStats>,
+    feed: Arc<Feed>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,8 +41,20 @@ struct DataPoint {
 }
 
 impl DirectUdp {
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        DirectUdp { db }
+    pub fn new(
+        db: Arc<Mutex<Database>>,
+        validator: Arc<Validator>,
+        limits: Arc<Limits>,
+        stats: Arc<IngestStats>,
+        feed: Arc<Feed>,
+    ) -> Self {
+        DirectUdp {
+            db,
+            validator,
+            limits,
+            stats,
+            feed,
+        }
     }
 
     pub fn start(&self, url: String) {
@@ -58,56 +78,61 @@ impl DirectUdp {
         loop {
             // Wait for an incoming message
             let mut buf = [0; 4096];
-            let (size, _peer) = socket
+            let (size, peer) = socket
                 .recv_from(&mut buf)
                 .map_err(|err| format!("Failed to receive a message: {}", err))
                 .unwrap();
 
-            if let Ok(val) = serde_json::from_slice::<DataPoint>(&buf[0..(size)]) {
-                if let Err(err) = self.process(&val) {
-                    error!("Error {:?} storing message {:?}", err, val);
-                }
+            let points = if let Ok(val) = serde_json::from_slice::<DataPoint>(&buf[0..(size)]) {
+                vec![val]
             } else if let Ok(vec) = serde_json::from_slice::<Vec<DataPoint>>(&buf[0..(size)]) {
-                for val in vec.iter() {
-                    if let Err(err) = self.process(val) {
-                        error!("Error {:?} storing message {:?}", err, val);
-                    }
-                }
+                vec
             } else {
                 error!(
                     "Couldn't deserialize JSON object or object array from {:?}",
                     String::from_utf8_lossy(&buf[0..size])
                 );
+                self.stats
+                    .malformed(&peer, "Not a JSON object or object array".to_owned());
+                continue;
+            };
+
+            let mut accepted = 0;
+            let mut last_error = None;
+            for val in points.iter() {
+                match self.process(val) {
+                    Ok(()) => accepted += 1,
+                    Err(err) => {
+                        error!("Error {:?} storing message {:?}", err, val);
+                        last_error = Some(err);
+                    }
+                }
             }
+            self.stats
+                .message(&peer, accepted, points.len() as u64 - accepted, last_error);
         }
     }
 
     fn process(&self, message: &DataPoint) -> Result<(), String> {
-        if let Some(time) = message.timestamp {
-            self.db
-                .lock()
-                .map_err(|err| {
-                    error!("udp - Failed to get lock on database: {}", err);
-                    format!("{}", err)
-                })?
-                .insert(time, &message.subsystem, &message.parameter, &message.value)
-                .map_err(|err| {
-                    error!("udp - Failed to get lock on database: {}", err);
-                    format!("{}", err)
-                })?;
-        } else {
-            self.db
-                .lock()
-                .map_err(|err| {
-                    error!("udp - Failed to get lock on database: {}", err);
-                    format!("{}", err)
-                })?
-                .insert_systime(&message.subsystem, &message.parameter, &message.value)
-                .map_err(|err| {
-                    error!("udp - Failed to get lock on database: {}", err);
-                    format!("{}", err)
-                })?;
-        }
+        let entry = self.validator.entry(
+            message
+                .timestamp
+                .unwrap_or_else(kubos_telemetry_db::systime),
+            &message.subsystem,
+            &message.parameter,
+            &message.value,
+        )?;
+
+        let mut db = self.db.lock().map_err(|err| {
+            error!("udp - Failed to get lock on database: {}", err);
+            format!("{}", err)
+        })?;
+        db.insert_typed(&entry).map_err(|err| {
+            error!("udp - Failed to insert telemetry: {}", err);
+            format!("{}", err)
+        })?;
+        self.limits.check(&mut db, &entry);
+        self.feed.publish(&entry);
 
         Ok(())
     }
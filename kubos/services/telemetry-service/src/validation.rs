@@ -0,0 +1,110 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use kubos_service::Config;
+use kubos_telemetry_db::{Dictionary, ParameterDefinition, TypedEntry, Value};
+use log::warn;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+// Points rejected for one parameter
+#[derive(Clone, Debug)]
+pub struct Rejections {
+    pub count: u64,
+    pub last_error: String,
+}
+
+// Checks received telemetry against the parameter dictionary
+pub struct Validator {
+    dictionary: Dictionary,
+    // Whether parameters missing from the dictionary are rejected
+    require_definition: bool,
+    rejected: Mutex<BTreeMap<(String, String), Rejections>>,
+}
+
+impl Validator {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let definitions = match config.get("dictionary") {
+            Some(dictionary) => dictionary
+                .try_into::<Vec<ParameterDefinition>>()
+                .map_err(|err| format!("Failed to parse dictionary: {}", err))?,
+            None => vec![],
+        };
+
+        let require_definition = match config.get("require_dictionary") {
+            Some(require) => require
+                .as_bool()
+                .ok_or("require_dictionary must be true or false")?,
+            None => false,
+        };
+
+        Ok(Validator {
+            dictionary: Dictionary::new(definitions)?,
+            require_definition,
+            rejected: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    // Checks a received point, counting it against its parameter if it is rejected
+    pub fn entry(
+        &self,
+        timestamp: f64,
+        subsystem: &str,
+        parameter: &str,
+        value: &str,
+    ) -> Result<TypedEntry, String> {
+        let typed = match self.dictionary.get(subsystem, parameter) {
+            Some(definition) => definition.parse(value),
+            None if self.require_definition => Err("not in the parameter dictionary".to_owned()),
+            None => Ok(Value::String(value.to_owned())),
+        };
+
+        typed
+            .map(|typed| TypedEntry::new(timestamp, subsystem, parameter, value, &typed))
+            .map_err(|err| {
+                let err = format!("{}/{}: {}", subsystem, parameter, err);
+                warn!("Rejected telemetry point {}", err);
+                if let Ok(mut rejected) = self.rejected.lock() {
+                    let rejections = rejected
+                        .entry((subsystem.to_owned(), parameter.to_owned()))
+                        .or_insert(Rejections {
+                            count: 0,
+                            last_error: String::new(),
+                        });
+                    rejections.count += 1;
+                    rejections.last_error = err.clone();
+                }
+                err
+            })
+    }
+
+    // Rejection counts of each parameter which has had a point rejected
+    pub fn rejections(&self) -> Vec<(String, String, Rejections)> {
+        match self.rejected.lock() {
+            Ok(rejected) => rejected
+                .iter()
+                .map(|((subsystem, parameter), rejections)| {
+                    (subsystem.clone(), parameter.clone(), rejections.clone())
+                })
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use serde_json::{json, ser};
+use std::io::{Read, Write};
+use std::net::{TcpListener, UdpSocket};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+static LIMITS: &str = r#"
+            [[telemetry-service.limits]]
+            subsystem = "eps"
+            parameter = "voltage"
+            yellow_low = 3.5
+            yellow_high = 4.2
+            red_low = 3.3
+            red_high = 4.4
+            persistence = 2
+"#;
+
+#[test]
+fn test_alarms() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8335;
+    let udp = 9335;
+
+    let _fixture =
+        TelemetryServiceFixture::setup_with_config(db, Some(port), Some(udp), None, LIMITS);
+
+    // A single value outside the limits is not enough to raise an alarm
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            first: insert(timestamp: 1000, subsystem: "eps", parameter: "voltage", value: "4.3") {
+                success
+            }
+            second: insert(timestamp: 1001, subsystem: "eps", parameter: "voltage", value: "4.0") {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({"data": {"first": {"success": true}, "second": {"success": true}}})
+    );
+    let res = do_query(Some(port), "{alarms{id}}");
+    assert_eq!(res, json!({"data": {"alarms": []}}));
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insertBulk(entries: [
+                {timestamp: 1002, subsystem: "eps", parameter: "voltage", value: "4.3"},
+                {timestamp: 1003, subsystem: "eps", parameter: "voltage", value: "4.3"},
+                {timestamp: 1004, subsystem: "eps", parameter: "voltage", value: "4.5"},
+                {timestamp: 1005, subsystem: "eps", parameter: "voltage", value: "4.6"}
+            ]) {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(res, json!({"data": {"insertBulk": {"success": true}}}));
+
+    let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+    let points = json!([
+        {"timestamp": 1006, "subsystem": "eps", "parameter": "voltage", "value": "4.0"},
+        {"timestamp": 1007, "subsystem": "eps", "parameter": "voltage", "value": "4.1"}
+    ]);
+    socket
+        .send_to(&ser::to_vec(&points).unwrap(), format!("127.0.0.1:{}", udp))
+        .unwrap();
+    thread::sleep(Duration::from_secs(1));
+
+    let res = do_query(
+        Some(port),
+        "{alarms{id,timestamp,subsystem,parameter,severity,value,message,clearedAt,acknowledgedAt}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "alarms": [
+                    {
+                        "id": 2,
+                        "timestamp": 1005.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "severity": "RED",
+                        "value": "4.6",
+                        "message": "above the red high limit of 4.4",
+                        "clearedAt": 1007.0,
+                        "acknowledgedAt": null
+                    },
+                    {
+                        "id": 1,
+                        "timestamp": 1003.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "severity": "YELLOW",
+                        "value": "4.3",
+                        "message": "above the yellow high limit of 4.2",
+                        "clearedAt": 1005.0,
+                        "acknowledgedAt": null
+                    }
+                ]
+            }
+        })
+    );
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            first: acknowledgeAlarm(id: 1) {
+                success,
+                errors
+            }
+            again: acknowledgeAlarm(id: 1) {
+                success,
+                errors
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "first": {"success": true, "errors": ""},
+                "again": {
+                    "success": false,
+                    "errors": "No unacknowledged alarm with ID 1"
+                }
+            }
+        })
+    );
+
+    let res = do_query(Some(port), "{alarms(acknowledged: false){id}}");
+    assert_eq!(res, json!({"data": {"alarms": [{"id": 2}]}}));
+
+    let res = do_query(Some(port), "{alarms(active: true){id}}");
+    assert_eq!(res, json!({"data": {"alarms": []}}));
+}
+
+#[test]
+fn test_red_mode() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8336;
+    let udp = 9336;
+    let scheduler_port = 8337;
+
+    // Stand-in for scheduler-service, which answers one request
+    let listener = TcpListener::bind(("127.0.0.1", scheduler_port)).unwrap();
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        while let Ok(size) = stream.read(&mut buf) {
+            if size == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..size]);
+        }
+
+        let body = r#"{"data":{"safeMode":{"success":true,"errors":""}}}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        sender
+            .send(String::from_utf8_lossy(&request).into_owned())
+            .unwrap();
+    });
+
+    let config = format!(
+        r#"
+            [[telemetry-service.limits]]
+            subsystem = "eps"
+            parameter = "voltage"
+            red_low = 3.3
+            red_mode = "safe"
+
+            [scheduler-service.addr]
+            ip = "127.0.0.1"
+            port = {}
+"#,
+        scheduler_port
+    );
+    let _fixture =
+        TelemetryServiceFixture::setup_with_config(db, Some(port), Some(udp), None, &config);
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insert(timestamp: 1000, subsystem: "eps", parameter: "voltage", value: "3.1") {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(res, json!({"data": {"insert": {"success": true}}}));
+
+    let request = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(request.contains("safeMode"));
+
+    let res = do_query(Some(port), "{alarms(active: true){severity,message}}");
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "alarms": [
+                    {"severity": "RED", "message": "below the red low limit of 3.3"}
+                ]
+            }
+        })
+    );
+}
@@ -0,0 +1,179 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use cbor_protocol::Protocol;
+use serde_cbor::{ser, Value};
+use serde_json::json;
+use std::time::Duration;
+use tempfile::TempDir;
+
+static CONFIG: &str = r#"
+            cbor_port = 9339
+
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "voltage"
+            id = 1
+            type = "float"
+            min = 0.0
+            max = 20.0
+
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "count"
+            id = 2
+            type = "integer"
+
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "charging"
+            id = 3
+            type = "bool"
+"#;
+
+fn point(id: u64, offset: f64, value: Value) -> Value {
+    Value::Array(vec![Value::U64(id), Value::F64(offset), value])
+}
+
+#[test]
+fn test_cbor_ingest() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8338;
+    let udp = 9338;
+
+    let _fixture =
+        TelemetryServiceFixture::setup_with_config(db, Some(port), Some(udp), None, CONFIG);
+
+    let client = Protocol::new("127.0.0.1:0", 4096);
+    let service = "127.0.0.1:9339".parse().unwrap();
+
+    let message = Value::Array(vec![
+        Value::U64(7),
+        Value::Bool(true),
+        Value::F64(1000.0),
+        Value::Array(vec![
+            point(1, 0.0, Value::F64(4.5)),
+            point(2, 0.5, Value::U64(3)),
+            point(3, 1.0, Value::Bool(true)),
+            point(1, 1.5, Value::F64(25.0)),
+            point(9, 2.0, Value::U64(1)),
+        ]),
+    ]);
+    client
+        .send_message(&ser::to_vec_packed(&message).unwrap(), service)
+        .unwrap();
+
+    let ack = client.recv_message_timeout(Duration::from_secs(2)).unwrap();
+    assert_eq!(
+        ack,
+        Value::Array(vec![Value::U64(7), Value::U64(3), Value::U64(2)])
+    );
+
+    let res = do_query(
+        Some(port),
+        "{telemetry{timestamp,subsystem,parameter,value,intValue,floatValue,boolValue}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "telemetry": [
+                    {
+                        "timestamp": 1001.0,
+                        "subsystem": "eps",
+                        "parameter": "charging",
+                        "value": "true",
+                        "intValue": null,
+                        "floatValue": null,
+                        "boolValue": true
+                    },
+                    {
+                        "timestamp": 1000.5,
+                        "subsystem": "eps",
+                        "parameter": "count",
+                        "value": "3",
+                        "intValue": 3,
+                        "floatValue": null,
+                        "boolValue": null
+                    },
+                    {
+                        "timestamp": 1000.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "value": "4.5",
+                        "intValue": null,
+                        "floatValue": 4.5,
+                        "boolValue": null
+                    }
+                ]
+            }
+        })
+    );
+
+    // Malformed messages are counted but not acknowledged
+    let message = Value::Array(vec![Value::String("voltage".to_owned())]);
+    client
+        .send_message(&ser::to_vec_packed(&message).unwrap(), service)
+        .unwrap();
+    assert!(client
+        .recv_message_timeout(Duration::from_millis(500))
+        .is_err());
+
+    let res = do_query(
+        Some(port),
+        "{ingestStatistics{source,messages,accepted,rejected,malformed,lastError}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "ingestStatistics": [
+                    {
+                        "source": "127.0.0.1",
+                        "messages": 2,
+                        "accepted": 3,
+                        "rejected": 2,
+                        "malformed": 1,
+                        "lastError": "Sequence number must be an unsigned integer"
+                    }
+                ]
+            }
+        })
+    );
+
+    let res = do_query(
+        Some(port),
+        r#"{parameters(subsystem: "eps"){parameter,id}}"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "parameters": [
+                    {"parameter": "charging", "id": 3},
+                    {"parameter": "count", "id": 2},
+                    {"parameter": "voltage", "id": 1}
+                ]
+            }
+        })
+    );
+}
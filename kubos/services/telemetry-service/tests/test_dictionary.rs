@@ -0,0 +1,308 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use serde_json::{json, ser};
+use std::net::UdpSocket;
+use std::time::Duration;
+use tempfile::TempDir;
+
+static DICTIONARY: &str = r#"
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "voltage"
+            type = "float"
+            unit = "V"
+            min = 0.0
+            max = 20.0
+            description = "Battery bus voltage"
+
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "count"
+            type = "integer"
+
+            [[telemetry-service.dictionary]]
+            subsystem = "eps"
+            parameter = "charging"
+            type = "bool"
+"#;
+
+#[test]
+fn test_typed_inserts() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8332;
+    let udp = 9332;
+
+    let _fixture =
+        TelemetryServiceFixture::setup_with_config(db, Some(port), Some(udp), None, DICTIONARY);
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            valid: insert(timestamp: 1000, subsystem: "eps", parameter: "voltage", value: "4.5") {
+                success,
+                errors
+            }
+            high: insert(timestamp: 1001, subsystem: "eps", parameter: "voltage", value: "25") {
+                success,
+                errors
+            }
+            text: insert(timestamp: 1001, subsystem: "eps", parameter: "voltage", value: "abc") {
+                success,
+                errors
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "valid": {"success": true, "errors": ""},
+                "high": {
+                    "success": false,
+                    "errors": "eps/voltage: 25 is above the maximum of 20"
+                },
+                "text": {
+                    "success": false,
+                    "errors": "eps/voltage: 'abc' is not a number"
+                }
+            }
+        })
+    );
+
+    // A single bad entry stops the whole batch from being inserted
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insertBulk(entries: [
+                {timestamp: 1002, subsystem: "eps", parameter: "count", value: "7"},
+                {timestamp: 1003, subsystem: "eps", parameter: "count", value: "7.5"}
+            ]) {
+                success,
+                errors
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "insertBulk": {
+                    "success": false,
+                    "errors": "eps/count: '7.5' is not an integer"
+                }
+            }
+        })
+    );
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insertBulk(entries: [
+                {timestamp: 1002, subsystem: "eps", parameter: "count", value: "7"},
+                {timestamp: 1003, subsystem: "eps", parameter: "charging", value: "true"},
+                {timestamp: 1004, subsystem: "gps", parameter: "lock", value: "1"}
+            ]) {
+                success,
+                errors
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({"data": {"insertBulk": {"success": true, "errors": ""}}})
+    );
+
+    let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+    let point = json!({
+        "timestamp": 1005,
+        "subsystem": "eps",
+        "parameter": "voltage",
+        "value": "-1"
+    });
+    socket
+        .send_to(&ser::to_vec(&point).unwrap(), format!("127.0.0.1:{}", udp))
+        .unwrap();
+    ::std::thread::sleep(Duration::from_secs(1));
+
+    let res = do_query(
+        Some(port),
+        "{telemetry{timestamp,subsystem,parameter,value,intValue,floatValue,boolValue}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "telemetry": [
+                    {
+                        "timestamp": 1004.0,
+                        "subsystem": "gps",
+                        "parameter": "lock",
+                        "value": "1",
+                        "intValue": null,
+                        "floatValue": null,
+                        "boolValue": null
+                    },
+                    {
+                        "timestamp": 1003.0,
+                        "subsystem": "eps",
+                        "parameter": "charging",
+                        "value": "true",
+                        "intValue": null,
+                        "floatValue": null,
+                        "boolValue": true
+                    },
+                    {
+                        "timestamp": 1002.0,
+                        "subsystem": "eps",
+                        "parameter": "count",
+                        "value": "7",
+                        "intValue": 7,
+                        "floatValue": null,
+                        "boolValue": null
+                    },
+                    {
+                        "timestamp": 1000.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "value": "4.5",
+                        "intValue": null,
+                        "floatValue": 4.5,
+                        "boolValue": null
+                    }
+                ]
+            }
+        })
+    );
+
+    let res = do_query(
+        Some(port),
+        "{rejectedPoints{subsystem,parameter,count,lastError}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "rejectedPoints": [
+                    {
+                        "subsystem": "eps",
+                        "parameter": "count",
+                        "count": 1,
+                        "lastError": "eps/count: '7.5' is not an integer"
+                    },
+                    {
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "count": 3,
+                        "lastError": "eps/voltage: -1 is below the minimum of 0"
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn test_parameters_query() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8333;
+    let udp = 9333;
+
+    let config = format!("require_dictionary = true\n{}", DICTIONARY);
+    let _fixture =
+        TelemetryServiceFixture::setup_with_config(db, Some(port), Some(udp), None, &config);
+
+    let res = do_query(
+        Some(port),
+        r#"{
+            parameters(subsystem: "eps") {
+                parameter,
+                type,
+                unit,
+                min,
+                max,
+                description
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "parameters": [
+                    {
+                        "parameter": "charging",
+                        "type": "BOOL",
+                        "unit": null,
+                        "min": null,
+                        "max": null,
+                        "description": null
+                    },
+                    {
+                        "parameter": "count",
+                        "type": "INTEGER",
+                        "unit": null,
+                        "min": null,
+                        "max": null,
+                        "description": null
+                    },
+                    {
+                        "parameter": "voltage",
+                        "type": "FLOAT",
+                        "unit": "V",
+                        "min": 0.0,
+                        "max": 20.0,
+                        "description": "Battery bus voltage"
+                    }
+                ]
+            }
+        })
+    );
+
+    let res = do_query(Some(port), r#"{parameters(subsystem: "gps"){parameter}}"#);
+    assert_eq!(res, json!({"data": {"parameters": []}}));
+
+    // Parameters missing from the dictionary are rejected
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insert(timestamp: 1000, subsystem: "gps", parameter: "lock", value: "1") {
+                success,
+                errors
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "insert": {
+                    "success": false,
+                    "errors": "gps/lock: not in the parameter dictionary"
+                }
+            }
+        })
+    );
+}
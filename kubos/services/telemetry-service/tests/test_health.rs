@@ -0,0 +1,153 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use serde_json::json;
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+
+// 2000 entries of about 200 bytes each
+static SQL: &str = r"
+with recursive n(i) as (select 1 union all select i + 1 from n where i < 2000)
+insert into telemetry select i, 'eps', 'log', hex(zeroblob(100)) from n;
+";
+
+#[test]
+fn test_damaged_database() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+    fs::write(&db_path, "not a database".repeat(100)).unwrap();
+
+    let db = db_path.to_str().unwrap();
+    let port = 8340;
+    let udp = 9341;
+
+    let _fixture = TelemetryServiceFixture::setup(db, Some(port), Some(udp), None);
+
+    let res = do_query(
+        Some(port),
+        "{databaseHealth{maxSize,tables{table,rows},lastCheck{ok,quarantined,recoveredRows}}}",
+    );
+    let quarantined = res["data"]["databaseHealth"]["lastCheck"]["quarantined"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    assert!(quarantined.starts_with(&format!("{}.corrupt-", db)));
+    assert_eq!(
+        fs::read_to_string(&quarantined).unwrap(),
+        "not a database".repeat(100)
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "databaseHealth": {
+                    "maxSize": null,
+                    "tables": [
+                        {"table": "telemetry", "rows": 0},
+                        {"table": "telemetry_rollup", "rows": 0},
+                        {"table": "telemetry_rollup_state", "rows": 0},
+                        {"table": "telemetry_alarm", "rows": 0}
+                    ],
+                    "lastCheck": {
+                        "ok": false,
+                        "quarantined": quarantined,
+                        "recoveredRows": 0
+                    }
+                }
+            }
+        })
+    );
+
+    // The fresh database is usable and intact
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insert(timestamp: 1000, subsystem: "eps", parameter: "voltage", value: "4.0") {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(res, json!({"data": {"insert": {"success": true}}}));
+
+    let res = do_query(
+        Some(port),
+        "mutation {checkDatabase{ok,problems,quarantined}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "checkDatabase": {"ok": true, "problems": [], "quarantined": null}
+            }
+        })
+    );
+    let res = do_query(Some(port), "{databaseHealth{lastCheck{ok}}}");
+    assert_eq!(
+        res,
+        json!({"data": {"databaseHealth": {"lastCheck": {"ok": true}}}})
+    );
+}
+
+#[test]
+fn test_size_budget() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8341;
+    let udp = 9342;
+
+    let _fixture = TelemetryServiceFixture::setup_with_config(
+        db,
+        Some(port),
+        Some(udp),
+        Some(SQL),
+        r#"
+            maintenance_interval = 1
+            max_size = 100000
+        "#,
+    );
+
+    // Give the service time to finish a maintenance pass
+    ::std::thread::sleep(Duration::from_secs(2));
+
+    let res = do_query(
+        Some(port),
+        "{databaseHealth{usedSize,maxSize,tables{rows},lastCheck{ok}}}",
+    );
+    let health = &res["data"]["databaseHealth"];
+    assert_eq!(health["maxSize"], 100000);
+    assert_eq!(health["lastCheck"]["ok"], true);
+    assert!(health["usedSize"].as_u64().unwrap() <= 100000);
+    let remaining = health["tables"][0]["rows"].as_i64().unwrap();
+    assert!(remaining > 0 && remaining < 2000);
+
+    // The oldest entries were removed first
+    let res = do_query(Some(port), "{telemetry(timestampGe: 2000){timestamp}}");
+    assert_eq!(res, json!({"data": {"telemetry": [{"timestamp": 2000.0}]}}));
+    let res = do_query(
+        Some(port),
+        &format!(
+            "{{telemetry(timestampLe: {}){{timestamp}}}}",
+            2000 - remaining
+        ),
+    );
+    assert_eq!(res, json!({"data": {"telemetry": []}}));
+}
@@ -0,0 +1,182 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use serde_json::json;
+use std::time::Duration;
+use tempfile::TempDir;
+
+static SQL: &str = r"
+insert into telemetry values(3600, 'eps', 'voltage', '2.0');
+insert into telemetry values(3600, 'eps', 'current', '1.5');
+insert into telemetry values(3600, 'eps', 'mode', 'safe');
+insert into telemetry values(3610, 'eps', 'voltage', '4.0');
+insert into telemetry values(3620, 'gps', 'lock', '1');
+insert into telemetry values(3660, 'eps', 'voltage', '6.0');
+";
+
+#[test]
+fn test_rollup_and_retention() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8330;
+    let udp = 9330;
+
+    let _fixture = TelemetryServiceFixture::setup_with_config(
+        db,
+        Some(port),
+        Some(udp),
+        Some(SQL),
+        r#"
+            maintenance_interval = 1
+
+            [telemetry-service.rollups]
+            minute_max_age = 100000000000
+            hour_max_age = 100000000000
+
+            [[telemetry-service.retention]]
+            subsystem = "eps"
+            max_age = 60
+
+            [[telemetry-service.retention]]
+            subsystem = "eps"
+            parameter = "current"
+            max_age = 100000000000
+        "#,
+    );
+
+    // Give the service time to finish a maintenance pass
+    ::std::thread::sleep(Duration::from_secs(2));
+
+    let res = do_query(
+        Some(port),
+        r#"{
+            rollups(resolution: MINUTE, subsystem: "eps", parameter: "voltage") {
+                timestamp,
+                min,
+                max,
+                mean,
+                count
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "rollups": [
+                    {"timestamp": 3660.0, "min": 6.0, "max": 6.0, "mean": 6.0, "count": 1},
+                    {"timestamp": 3600.0, "min": 2.0, "max": 4.0, "mean": 3.0, "count": 2}
+                ]
+            }
+        })
+    );
+
+    let res = do_query(
+        Some(port),
+        r#"{
+            rollups(resolution: HOUR, subsystem: "eps", parameters: ["voltage", "mode"]) {
+                timestamp,
+                subsystem,
+                parameter,
+                min,
+                max,
+                mean,
+                count
+            }
+        }"#,
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "rollups": [
+                    {
+                        "timestamp": 3600.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "min": 2.0,
+                        "max": 6.0,
+                        "mean": 4.0,
+                        "count": 3
+                    }
+                ]
+            }
+        })
+    );
+
+    // Expired eps telemetry is removed, except for the parameter with its own
+    // rule. Telemetry without a rule is kept
+    let res = do_query(
+        Some(port),
+        "{telemetry{timestamp,subsystem,parameter,value}}",
+    );
+    assert_eq!(
+        res,
+        json!({
+            "data": {
+                "telemetry": [
+                    {"timestamp": 3620.0, "subsystem": "gps", "parameter": "lock", "value": "1"},
+                    {"timestamp": 3600.0, "subsystem": "eps", "parameter": "current", "value": "1.5"}
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn test_retention_without_rollups() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8331;
+    let udp = 9331;
+
+    let _fixture = TelemetryServiceFixture::setup_with_config(
+        db,
+        Some(port),
+        Some(udp),
+        Some(SQL),
+        r#"
+            maintenance_interval = 1
+
+            [telemetry-service.rollups]
+            enabled = false
+
+            [[telemetry-service.retention]]
+            max_age = 60
+        "#,
+    );
+
+    ::std::thread::sleep(Duration::from_secs(2));
+
+    let res = do_query(
+        Some(port),
+        "{rollups(resolution: MINUTE){timestamp,subsystem,parameter}}",
+    );
+    assert_eq!(res, json!({"data": {"rollups": []}}));
+
+    let res = do_query(
+        Some(port),
+        "{telemetry{timestamp,subsystem,parameter,value}}",
+    );
+    assert_eq!(res, json!({"data": {"telemetry": []}}));
+}
@@ -332,3 +332,96 @@ fn test_route_conflict() {
         })
     );
 }
+
+#[test]
+fn test_route_binary() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8334;
+    let udp = 9334;
+
+    let _fixture = TelemetryServiceFixture::setup_with_config(
+        db,
+        Some(port),
+        Some(udp),
+        Some(SQL),
+        r#"
+            [[telemetry-service.dictionary]]
+            subsystem = "obc"
+            parameter = "uptime"
+            type = "integer"
+
+            [[telemetry-service.dictionary]]
+            subsystem = "obc"
+            parameter = "temperature"
+            type = "float"
+
+            [[telemetry-service.dictionary]]
+            subsystem = "obc"
+            parameter = "safe"
+            type = "bool"
+        "#,
+    );
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insertBulk(entries: [
+                {timestamp: 1005.25, subsystem: "obc", parameter: "uptime", value: "-12"},
+                {timestamp: 1005.5, subsystem: "obc", parameter: "temperature", value: "21.0"},
+                {timestamp: 1005.75, subsystem: "obc", parameter: "safe", value: "false"}
+            ]) {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(res, json!({"data": {"insertBulk": {"success": true}}}));
+
+    let output_dir = TempDir::new().unwrap();
+    let json_path = output_dir.path().join("json");
+    let binary_path = output_dir.path().join("binary");
+
+    do_query(
+        Some(port),
+        &format!(
+            r#"{{ routedTelemetry(output: "{}", compress: false) }}"#,
+            json_path.to_str().unwrap()
+        ),
+    );
+    do_query(
+        Some(port),
+        &format!(
+            r#"{{ routedTelemetry(output: "{}", compress: false, format: BINARY) }}"#,
+            binary_path.to_str().unwrap()
+        ),
+    );
+
+    let binary = fs::read(&binary_path).unwrap();
+    assert!(binary.len() < fs::read(&json_path).unwrap().len());
+
+    // The decoded export matches the entries returned by the service
+    let points = telemetry_export::decode(&binary).unwrap();
+    let decoded: Vec<Value> = points
+        .iter()
+        .map(|point| {
+            json!({
+                "timestamp": point.timestamp,
+                "subsystem": point.subsystem,
+                "parameter": point.parameter,
+                "value": point.value,
+                "intValue": point.int_value,
+                "floatValue": point.float_value,
+                "boolValue": point.bool_value
+            })
+        })
+        .collect();
+
+    let res = do_query(
+        Some(port),
+        "{telemetry{timestamp,subsystem,parameter,value,intValue,floatValue,boolValue}}",
+    );
+    assert_eq!(decoded.len(), 13);
+    assert_eq!(json!(decoded), res["data"]["telemetry"]);
+}
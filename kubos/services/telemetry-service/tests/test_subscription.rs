@@ -0,0 +1,154 @@
+//
+// Copyright (C) 2018 Kubos Corporation
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+mod utils;
+
+use crate::utils::*;
+use serde_json::{json, Value};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn connect(port: u16) -> Socket {
+    let mut request = format!("ws://127.0.0.1:{}/ws", port)
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        "graphql-transport-ws".parse().unwrap(),
+    );
+
+    let (mut socket, _) = tungstenite::connect(request).unwrap();
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+    }
+
+    send(&mut socket, json!({"type": "connection_init"}));
+    assert_eq!(receive(&mut socket), json!({"type": "connection_ack"}));
+    socket
+}
+
+fn send(socket: &mut Socket, message: Value) {
+    socket.send(Message::text(message.to_string())).unwrap();
+}
+
+// Next GraphQL message, skipping pings
+fn receive(socket: &mut Socket) -> Value {
+    loop {
+        let message = socket.read().unwrap();
+        if message.is_text() {
+            let message: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+            if message["type"] != "ping" {
+                return message;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_subscription() {
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.db");
+
+    let db = db_path.to_str().unwrap();
+    let port = 8339;
+    let udp = 9340;
+
+    let _fixture = TelemetryServiceFixture::setup(db, Some(port), Some(udp), None);
+
+    let mut socket = connect(port);
+    send(
+        &mut socket,
+        json!({
+            "id": "1",
+            "type": "subscribe",
+            "payload": {
+                "query": r#"subscription {
+                    telemetry(subsystem: "eps", parameter: "voltage") {
+                        timestamp, subsystem, parameter, value
+                    }
+                }"#
+            }
+        }),
+    );
+
+    // Give the subscription time to be set up before anything is stored
+    thread::sleep(Duration::from_millis(500));
+
+    let res = do_query(
+        Some(port),
+        r#"mutation {
+            insertBulk(entries: [
+                {timestamp: 1000, subsystem: "gps", parameter: "voltage", value: "3.0"},
+                {timestamp: 1001, subsystem: "eps", parameter: "current", value: "0.5"},
+                {timestamp: 1002, subsystem: "eps", parameter: "voltage", value: "4.1"}
+            ]) {
+                success
+            }
+        }"#,
+    );
+    assert_eq!(res, json!({"data": {"insertBulk": {"success": true}}}));
+
+    assert_eq!(
+        receive(&mut socket),
+        json!({
+            "id": "1",
+            "type": "next",
+            "payload": {
+                "data": {
+                    "telemetry": {
+                        "timestamp": 1002.0,
+                        "subsystem": "eps",
+                        "parameter": "voltage",
+                        "value": "4.1"
+                    }
+                }
+            }
+        })
+    );
+
+    send(
+        &mut socket,
+        json!({
+            "id": "2",
+            "type": "subscribe",
+            "payload": {
+                "query": r#"subscription {
+                    telemetry(parameter: "voltage", parameters: ["current"]) { value }
+                }"#
+            }
+        }),
+    );
+    let res = receive(&mut socket);
+    assert_eq!(res["id"], "2");
+    // Depending on when it is found, an error is sent in either an `error` or a `next` message
+    let errors = match res["type"].as_str() {
+        Some("error") => &res["payload"],
+        _ => &res["payload"]["errors"],
+    };
+    assert_eq!(
+        errors[0]["message"],
+        "The `parameter` and `parameters` input fields are mutually exclusive"
+    );
+}
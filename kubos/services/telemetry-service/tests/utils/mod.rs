@@ -94,6 +94,18 @@ impl TelemetryServiceFixture {
         service_port: Option<u16>,
         udp_port: Option<u16>,
         sql: Option<&str>,
+    ) -> Self {
+        Self::setup_with_config(db, service_port, udp_port, sql, "")
+    }
+
+    // Starts the service with extra `[telemetry-service]` settings
+    #[allow(dead_code)]
+    pub fn setup_with_config(
+        db: &str,
+        service_port: Option<u16>,
+        udp_port: Option<u16>,
+        sql: Option<&str>,
+        extra_config: &str,
     ) -> Self {
         // Use port 0 for OS-assigned ports when None is provided
         // This prevents port conflicts between parallel tests
@@ -113,12 +125,13 @@ impl TelemetryServiceFixture {
             [telemetry-service]
             database = "{}"
             direct_port = {}
+            {}
             
             [telemetry-service.addr]
             ip = "127.0.0.1"
             port = {}
             "#,
-            db, udp_port, service_port
+            db, udp_port, extra_config, service_port
         );
 
         let mut config_file = File::create(config_path.clone()).unwrap();
@@ -143,7 +156,7 @@ impl Drop for TelemetryServiceFixture {
 pub fn do_query(service_port: Option<u16>, query: &str) -> serde_json::Value {
     let port = service_port.unwrap_or(8111); // Must match default in TelemetryServiceFixture::setup
 
-    let client = reqwest::Client::new();
+    let client = reqwest::blocking::Client::new();
 
     let uri = format!("http://127.0.0.1:{}/graphql", port);
 
@@ -156,7 +169,7 @@ pub fn do_query(service_port: Option<u16>, query: &str) -> serde_json::Value {
 
     for attempt in 0..max_retries {
         match client.post(&uri).json(&map).send() {
-            Ok(mut response) => {
+            Ok(response) => {
                 return response.json().expect("Couldn't deserialize response");
             }
             Err(e) => {
@@ -0,0 +1,43 @@
+//! Runs the CubeSpace ADCS simulator on a CAN interface.
+//!
+//! ```text
+//! sudo ip link add dev vcan0 type vcan && sudo ip link set vcan0 up
+//! cube-adcs-sim vcan0 4
+//! ```
+//!
+//! Then point cube-adcs-service at the same interface with `can_interface = "vcan0"`.
+
+use std::sync::Mutex;
+
+use cube_adcs_service::sim::{self, Simulator};
+use rust_can::Connection;
+
+const DEFAULT_INTERFACE: &str = "vcan0";
+const DEFAULT_ADDRESS: u8 = 4;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let interface = args.next().unwrap_or_else(|| DEFAULT_INTERFACE.to_string());
+    let address = match args.next().map(|arg| arg.parse::<u8>()) {
+        None => DEFAULT_ADDRESS,
+        Some(Ok(address)) => address,
+        Some(Err(err)) => {
+            eprintln!("invalid ADCS address: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let connection = match Connection::from_interface(&interface) {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("failed to open {interface}: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    println!("simulating ADCS at address {address} on {interface}");
+    if let Err(err) = sim::serve(&connection, &Mutex::new(Simulator::new(address)), None) {
+        eprintln!("simulator stopped: {err}");
+        std::process::exit(3);
+    }
+}
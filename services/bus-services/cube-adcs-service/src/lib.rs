@@ -1,8 +1,12 @@
 //! KubOS service for CubeSpace ADCS CAN telecommands and telemetry.
 
+pub mod polling;
 pub mod schema;
 mod schema_generated;
+pub mod sim;
 pub mod subsystem;
+pub mod transfer;
+pub mod unsolicited;
 
 #[cfg(test)]
 mod tests;
@@ -1,5 +1,7 @@
+use cube_adcs_service::polling::{self, PollingPlan};
 use cube_adcs_service::schema::{MutationRoot, QueryRoot};
 use cube_adcs_service::subsystem::Subsystem;
+use cube_adcs_service::unsolicited;
 use kubos_service::{Config, Logger, Service};
 
 fn main() {
@@ -26,6 +28,17 @@ fn main() {
         }
     };
 
+    match PollingPlan::from_config(&config) {
+        Ok(plan) => polling::start(subsystem.clone(), plan),
+        Err(err) => {
+            log::error!("invalid telemetry polling plan: {err}");
+            eprintln!("invalid telemetry polling plan: {err}");
+            std::process::exit(4);
+        }
+    }
+
+    unsolicited::start(subsystem.clone());
+
     log::info!("cube-adcs-service started");
     Service::new(
         config,
@@ -0,0 +1,420 @@
+//! Background ADCS telemetry polling.
+//!
+//! The polling plan is read from `[[cube-adcs-service.poll]]` entries:
+//!
+//! ```toml
+//! [[cube-adcs-service.poll]]
+//! id = 185
+//! period_ms = 1000
+//! forward = ["control_mode", "control_timeout"]
+//! ```
+//!
+//! Every telemetry response, polled or requested on demand, updates the
+//! [`TelemetryCache`]. The fields listed in `forward` are also sent to
+//! telemetry-service's direct UDP port with `subsystem = "adcs"`.
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_graphql::SimpleObject;
+use cubespace_adcs_api::{FieldValue, TelemetrySpec, telemetry_spec};
+use serde_json::json;
+
+use crate::subsystem::{CubeAdcsError, Subsystem};
+
+/// Subsystem name used for forwarded telemetry.
+pub const TELEMETRY_SUBSYSTEM: &str = "adcs";
+
+/// Latest response received for one telemetry ID.
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    payload: Option<Vec<u8>>,
+    received: Option<Instant>,
+    timestamp: f64,
+    error: Option<String>,
+}
+
+/// Latest telemetry payload per telemetry ID.
+#[derive(Clone, Default)]
+pub struct TelemetryCache {
+    entries: Arc<Mutex<HashMap<u8, CacheEntry>>>,
+}
+
+impl TelemetryCache {
+    /// Stores a telemetry response.
+    pub fn store(&self, id: u8, payload: &[u8]) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                id,
+                CacheEntry {
+                    payload: Some(payload.to_vec()),
+                    received: Some(Instant::now()),
+                    timestamp: unix_time(),
+                    error: None,
+                },
+            );
+        }
+    }
+
+    /// Records a failed poll, keeping the last good payload.
+    pub fn store_error(&self, id: u8, error: &CubeAdcsError) {
+        if let Ok(mut entries) = self.entries.lock() {
+            let entry = entries.entry(id).or_insert(CacheEntry {
+                payload: None,
+                received: None,
+                timestamp: 0.0,
+                error: None,
+            });
+            entry.error = Some(error.to_string());
+        }
+    }
+
+    /// Returns the cached payload if it was received within `max_age`.
+    pub fn fresh(&self, id: u8, max_age: Duration) -> Result<Option<Vec<u8>>, CubeAdcsError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|_| CubeAdcsError::LockPoisoned)?;
+        Ok(entries.get(&id).and_then(|entry| match entry.received {
+            Some(received) if received.elapsed() <= max_age => entry.payload.clone(),
+            _ => None,
+        }))
+    }
+
+    /// Returns the decoded cache contents, ordered by telemetry ID.
+    pub fn snapshot(&self) -> Result<Vec<CachedTelemetry>, CubeAdcsError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|_| CubeAdcsError::LockPoisoned)?;
+        let mut ids: Vec<u8> = entries.keys().copied().collect();
+        ids.sort_unstable();
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| Some(CachedTelemetry::new(telemetry_spec(id)?, &entries[&id])))
+            .collect())
+    }
+}
+
+/// Cached telemetry, decoded from its field metadata.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct CachedTelemetry {
+    /// Telemetry ID.
+    pub id: i32,
+    /// Telemetry name.
+    pub name: String,
+    /// Time the payload was received, in seconds since the UNIX epoch.
+    pub timestamp: Option<f64>,
+    /// Age of the payload in milliseconds.
+    pub age_ms: Option<i64>,
+    /// Numeric field values.
+    pub values: Vec<FieldValue>,
+    /// Error from the most recent failed poll, if it failed.
+    pub errors: String,
+}
+
+impl CachedTelemetry {
+    fn new(spec: &TelemetrySpec, entry: &CacheEntry) -> Self {
+        let mut errors = entry.error.clone().unwrap_or_default();
+        let values = match &entry.payload {
+            Some(payload) => spec.decode_values(payload).unwrap_or_else(|err| {
+                errors = err.to_string();
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        Self {
+            id: i32::from(spec.id),
+            name: spec.name.to_owned(),
+            timestamp: entry.received.map(|_| entry.timestamp),
+            age_ms: entry
+                .received
+                .map(|received| received.elapsed().as_millis() as i64),
+            values,
+            errors,
+        }
+    }
+}
+
+/// One telemetry item in the polling plan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollEntry {
+    /// Telemetry definition.
+    pub spec: &'static TelemetrySpec,
+    /// Time between requests.
+    pub period: Duration,
+    /// Field keys forwarded to telemetry-service.
+    pub forward: Vec<String>,
+}
+
+/// Background polling settings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PollingPlan {
+    /// Telemetry to poll.
+    pub entries: Vec<PollEntry>,
+    /// `ip:port` of telemetry-service's direct UDP port.
+    pub telemetry_url: Option<String>,
+}
+
+impl PollingPlan {
+    /// Reads the polling plan from the service configuration.
+    pub fn from_config(config: &kubos_service::Config) -> Result<Self, CubeAdcsError> {
+        let entries = match config.get("poll") {
+            Some(value) => value
+                .as_array()
+                .ok_or_else(|| CubeAdcsError::Api("poll must be an array of tables".to_owned()))?
+                .iter()
+                .map(poll_entry)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        let telemetry_url = if entries.iter().any(|entry| !entry.forward.is_empty()) {
+            kubos_service::telemetry_url()
+        } else {
+            None
+        };
+
+        Ok(Self {
+            entries,
+            telemetry_url,
+        })
+    }
+}
+
+fn poll_entry(value: &toml::Value) -> Result<PollEntry, CubeAdcsError> {
+    let invalid = |description: &str| CubeAdcsError::Api(format!("poll entry: {description}"));
+
+    let id = value
+        .get("id")
+        .and_then(|id| id.as_integer())
+        .and_then(|id| u8::try_from(id).ok())
+        .ok_or_else(|| invalid("id must be 0..=255"))?;
+    let spec = telemetry_spec(id).ok_or_else(|| invalid(&format!("unknown telemetry ID {id}")))?;
+    let period = value
+        .get("period_ms")
+        .and_then(|period| period.as_integer())
+        .and_then(|period| u64::try_from(period).ok())
+        .filter(|period| *period > 0)
+        .map(Duration::from_millis)
+        .ok_or_else(|| invalid(&format!("telemetry {id} needs a positive period_ms")))?;
+
+    let numeric: Vec<String> = spec
+        .decode_values(&vec![0; spec.length_bytes])
+        .map(|values| values.into_iter().map(|value| value.name).collect())
+        .unwrap_or_default();
+    let mut forward = Vec::new();
+    for name in value
+        .get("forward")
+        .and_then(|forward| forward.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str())
+    {
+        if numeric.iter().any(|key| key == name) {
+            forward.push(name.to_owned());
+        } else {
+            log::warn!("telemetry {id} has no numeric field '{name}' to forward");
+        }
+    }
+
+    Ok(PollEntry {
+        spec,
+        period,
+        forward,
+    })
+}
+
+/// Spawns the background polling thread.
+pub fn start(subsystem: Subsystem, plan: PollingPlan) {
+    if plan.entries.is_empty() {
+        log::info!("no ADCS telemetry polling configured");
+        return;
+    }
+
+    let forwarder = plan.telemetry_url.as_deref().and_then(|url| {
+        TelemetryForwarder::new(url)
+            .map_err(|err| log::error!("failed to set up telemetry forwarding to {url}: {err}"))
+            .ok()
+    });
+
+    log::info!("polling {} ADCS telemetry items", plan.entries.len());
+
+    thread::spawn(move || {
+        let mut due = vec![Instant::now(); plan.entries.len()];
+        loop {
+            for (entry, next) in plan.entries.iter().zip(due.iter_mut()) {
+                if *next > Instant::now() {
+                    continue;
+                }
+                *next = Instant::now() + entry.period;
+                poll(&subsystem, entry, forwarder.as_ref());
+            }
+
+            if let Some(wake) = due.iter().min() {
+                thread::sleep(wake.saturating_duration_since(Instant::now()));
+            }
+        }
+    });
+}
+
+fn poll(subsystem: &Subsystem, entry: &PollEntry, forwarder: Option<&TelemetryForwarder>) {
+    let id = entry.spec.id;
+    let payload = match subsystem.request_telemetry_payload(id, entry.spec.length_bytes) {
+        Ok(payload) => payload,
+        Err(err) => {
+            log::warn!("failed to poll ADCS telemetry {id}: {err}");
+            subsystem.telemetry_cache().store_error(id, &err);
+            return;
+        }
+    };
+
+    let Some(forwarder) = forwarder.filter(|_| !entry.forward.is_empty()) else {
+        return;
+    };
+    match entry.spec.decode_values(&payload) {
+        Ok(values) => {
+            let values: Vec<FieldValue> = values
+                .into_iter()
+                .filter(|value| entry.forward.contains(&value.name))
+                .collect();
+            if let Err(err) = forwarder.send(unix_time(), &values) {
+                log::warn!("failed to forward ADCS telemetry {id}: {err}");
+            }
+        }
+        Err(err) => log::warn!("failed to decode ADCS telemetry {id}: {err}"),
+    }
+}
+
+/// Sends telemetry values to telemetry-service's direct UDP port.
+pub struct TelemetryForwarder {
+    socket: UdpSocket,
+}
+
+impl TelemetryForwarder {
+    /// Creates a forwarder sending to `url`.
+    pub fn new(url: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(url)?;
+        Ok(Self { socket })
+    }
+
+    /// Sends one datagram holding all of `values`.
+    pub fn send(&self, timestamp: f64, values: &[FieldValue]) -> std::io::Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.socket
+            .send(data_points(timestamp, values).to_string().as_bytes())?;
+        Ok(())
+    }
+}
+
+fn data_points(timestamp: f64, values: &[FieldValue]) -> serde_json::Value {
+    values
+        .iter()
+        .map(|value| {
+            json!({
+                "timestamp": timestamp,
+                "subsystem": TELEMETRY_SUBSYSTEM,
+                "parameter": value.name,
+                "value": value.value.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn unix_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cubespace_adcs_api::{ControlModeTelemetry, Telemetry};
+
+    const CONFIG: &str = r#"
+        [[cube-adcs-service.poll]]
+        id = 185
+        period_ms = 1000
+        forward = ["control_mode", "no_such_field"]
+
+        [[cube-adcs-service.poll]]
+        id = 133
+        period_ms = 5000
+    "#;
+
+    #[test]
+    fn reads_polling_plan() {
+        let config = kubos_service::Config::new_from_str("cube-adcs-service", CONFIG).unwrap();
+        let plan = PollingPlan::from_config(&config).unwrap();
+
+        assert_eq!(plan.entries.len(), 2);
+        assert_eq!(plan.entries[0].spec.id, ControlModeTelemetry::ID);
+        assert_eq!(plan.entries[0].period, Duration::from_millis(1000));
+        assert_eq!(plan.entries[0].forward, vec!["control_mode".to_owned()]);
+        assert!(plan.entries[1].forward.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_telemetry() {
+        let config = kubos_service::Config::new_from_str(
+            "cube-adcs-service",
+            "[[cube-adcs-service.poll]]\nid = 1\nperiod_ms = 1000\n",
+        )
+        .unwrap();
+
+        assert!(PollingPlan::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn cache_respects_max_age() {
+        let cache = TelemetryCache::default();
+        cache.store(ControlModeTelemetry::ID, &[12, 0x58, 0x02]);
+
+        assert_eq!(
+            cache
+                .fresh(ControlModeTelemetry::ID, Duration::from_secs(60))
+                .unwrap(),
+            Some(vec![12, 0x58, 0x02])
+        );
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(
+            cache
+                .fresh(ControlModeTelemetry::ID, Duration::from_millis(1))
+                .unwrap(),
+            None
+        );
+
+        cache.store_error(
+            ControlModeTelemetry::ID,
+            &CubeAdcsError::Can("timeout".to_owned()),
+        );
+        let snapshot = cache.snapshot().unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].values[0].value, 12.0);
+        assert!(snapshot[0].errors.contains("timeout"));
+    }
+
+    #[test]
+    fn forwards_as_adcs_subsystem() {
+        let values = vec![FieldValue {
+            name: "control_mode".to_owned(),
+            value: 12.0,
+            unit: None,
+        }];
+        let points = data_points(1.5, &values);
+
+        assert_eq!(points[0]["subsystem"], "adcs");
+        assert_eq!(points[0]["parameter"], "control_mode");
+        assert_eq!(points[0]["value"], "12");
+    }
+}
@@ -1,11 +1,20 @@
 use async_graphql::{Context, MergedObject, Object, Result};
 use cubespace_adcs_api::{
-    COMMAND_SPECS, CommandInfo, CommandResponse, HealthInfo, MutationResponse, RawFrameResponse,
-    TELEMETRY_SPECS, TelemetryInfo, command_spec, telemetry_spec,
+    COMMAND_SPECS, CommandInfo, CommandResponse, HealthInfo,
+    InitiateFilteredEventLogTransferCommand, MutationResponse, RawFrameResponse,
+    RequestTelemetryLogTransferSetupCommand, TELEMETRY_SPECS, TelemetryInfo, command_spec,
+    telemetry_spec,
 };
+use std::path::Path;
+use std::time::Duration;
 
+use crate::polling::CachedTelemetry;
 use crate::schema_generated::{GeneratedMutationRoot, GeneratedQueryRoot};
 use crate::subsystem::{CommandAck, RawFrame, Subsystem};
+use crate::transfer::{SetupCommand, TransferProgress, TransferRequest, TransferResponse};
+use crate::unsolicited::{UnsolicitedKind, UnsolicitedMessage};
+
+const DEFAULT_UNSOLICITED_LIMIT: i32 = 100;
 
 /// GraphQL query root.
 #[derive(MergedObject, Default)]
@@ -30,18 +39,21 @@ impl BaseQueryRoot {
         "pong"
     }
 
-    /// Returns service configuration.
+    /// Returns service configuration and CAN bus error statistics.
     async fn health(&self, ctx: &Context<'_>) -> Result<HealthInfo> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         let config = context.subsystem().config();
 
-        Ok(HealthInfo::new(
+        let mut health = HealthInfo::new(
             config.interface.clone(),
             i64::from(config.bitrate),
             i32::from(config.source_address),
             i32::from(config.destination_address),
             config.timeout.as_millis() as i64,
-        ))
+        );
+        health.bus = context.subsystem().bus_health();
+
+        Ok(health)
     }
 
     /// Lists telecommands generated from the ADCS command matrix.
@@ -73,6 +85,39 @@ impl BaseQueryRoot {
             .ok_or_else(|| async_graphql::Error::new(format!("unknown telemetry ID: {id}")))
     }
 
+    /// Lists running and recently finished block transfers.
+    async fn transfers(&self, ctx: &Context<'_>) -> Result<Vec<TransferProgress>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        Ok(context.subsystem().transfers().list()?)
+    }
+
+    /// Returns the progress of one block transfer.
+    async fn transfer(&self, ctx: &Context<'_>, id: i32) -> Result<Option<TransferProgress>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        Ok(context.subsystem().transfers().get(id)?)
+    }
+
+    /// Returns the latest cached response for every telemetry ID received so far.
+    async fn telemetry_cache(&self, ctx: &Context<'_>) -> Result<Vec<CachedTelemetry>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        Ok(context.subsystem().telemetry_cache().snapshot()?)
+    }
+
+    /// Returns buffered unsolicited telemetry and events, oldest first.
+    ///
+    /// Pass the last `sequence` seen as `after` to fetch only newer messages.
+    async fn unsolicited(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<UnsolicitedKind>,
+        after: Option<i64>,
+        limit: Option<i32>,
+    ) -> Result<Vec<UnsolicitedMessage>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        let limit = usize::try_from(limit.unwrap_or(DEFAULT_UNSOLICITED_LIMIT)).unwrap_or(0);
+        Ok(context.subsystem().unsolicited().page(kind, after, limit)?)
+    }
+
     /// Reads one raw CAN frame.
     async fn read_raw_frame(&self, ctx: &Context<'_>) -> Result<RawFrameResponse> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
@@ -131,6 +176,92 @@ impl BaseMutationRoot {
         let payload = decode_hex(&payload_hex)?;
         map_empty_result(context.subsystem().send_raw_frame(can_id, &payload))
     }
+
+    /// Downloads the event log entries selected by `filter` to `path` in the OBC transfer
+    /// directory.
+    async fn download_event_log(
+        &self,
+        ctx: &Context<'_>,
+        filter: InitiateFilteredEventLogTransferCommand,
+        path: String,
+    ) -> Result<TransferResponse> {
+        start_transfer(ctx, TransferRequest::EventLog(filter), path)
+    }
+
+    /// Downloads the telemetry log entries selected by `filter` to `path` in the OBC transfer
+    /// directory.
+    async fn download_telemetry_log(
+        &self,
+        ctx: &Context<'_>,
+        filter: RequestTelemetryLogTransferSetupCommand,
+        path: String,
+    ) -> Result<TransferResponse> {
+        start_transfer(ctx, TransferRequest::TelemetryLog(filter), path)
+    }
+
+    /// Downloads a camera image to `path` in the OBC transfer directory.
+    ///
+    /// The optional setup telecommand is sent first to select the image.
+    async fn download_image(
+        &self,
+        ctx: &Context<'_>,
+        path: String,
+        setup_id: Option<i32>,
+        setup_payload_hex: Option<String>,
+    ) -> Result<TransferResponse> {
+        let setup = setup_command(setup_id, setup_payload_hex)?;
+        start_transfer(ctx, TransferRequest::Image(setup), path)
+    }
+
+    /// Uploads the file at `path` in the OBC transfer directory to the ADCS.
+    ///
+    /// The optional setup telecommand is sent first to describe the file.
+    async fn upload_file(
+        &self,
+        ctx: &Context<'_>,
+        path: String,
+        setup_id: Option<i32>,
+        setup_payload_hex: Option<String>,
+    ) -> Result<TransferResponse> {
+        let setup = setup_command(setup_id, setup_payload_hex)?;
+        start_transfer(ctx, TransferRequest::Upload(setup), path)
+    }
+
+    /// Cancels a running block transfer.
+    async fn cancel_transfer(&self, ctx: &Context<'_>, id: i32) -> Result<TransferResponse> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        Ok(TransferResponse::from_result(
+            context.subsystem().transfers().cancel(id),
+        ))
+    }
+}
+
+fn start_transfer(
+    ctx: &Context<'_>,
+    request: TransferRequest,
+    path: String,
+) -> Result<TransferResponse> {
+    let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+    let subsystem = context.subsystem();
+    Ok(TransferResponse::from_result(subsystem.transfers().start(
+        subsystem.clone(),
+        subsystem.config().transfer.clone(),
+        request,
+        Path::new(&path),
+    )))
+}
+
+fn setup_command(id: Option<i32>, payload_hex: Option<String>) -> Result<Option<SetupCommand>> {
+    match (id, payload_hex) {
+        (Some(id), payload_hex) => Ok(Some(SetupCommand {
+            id: checked_u8(id, "setupId")?,
+            payload: decode_hex(payload_hex.as_deref().unwrap_or_default())?,
+        })),
+        (None, Some(_)) => Err(async_graphql::Error::new(
+            "setupPayloadHex requires setupId",
+        )),
+        (None, None) => Ok(None),
+    }
 }
 
 fn map_empty_result(
@@ -161,6 +292,10 @@ fn map_raw_frame(frame: RawFrame) -> RawFrameResponse {
     RawFrameResponse::success_response(frame.id, frame.extended, encode_hex(&frame.data))
 }
 
+pub(crate) fn max_age(max_age_ms: Option<i64>) -> Option<Duration> {
+    max_age_ms.map(|ms| Duration::from_millis(u64::try_from(ms).unwrap_or(0)))
+}
+
 fn checked_u8(value: i32, name: &str) -> Result<u8> {
     u8::try_from(value).map_err(|_| async_graphql::Error::new(format!("{name} must be 0..=255")))
 }
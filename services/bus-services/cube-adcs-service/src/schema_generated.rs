@@ -3,7 +3,7 @@
 use async_graphql::{Context, Object, Result};
 use cubespace_adcs_api::{self as api};
 
-use crate::schema::map_command_ack;
+use crate::schema::{map_command_ack, max_age};
 use crate::subsystem::Subsystem;
 
 /// Generated GraphQL queries, one per telemetry ID.
@@ -13,1106 +13,1548 @@ pub struct GeneratedQueryRoot;
 #[Object]
 impl GeneratedQueryRoot {
     /// Requests and decodes telemetry ID 131: Error Log Entry.
-    async fn error_log_entry(&self, ctx: &Context<'_>) -> Result<api::ErrorLogEntryTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn error_log_entry(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ErrorLogEntryTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ErrorLogEntryTelemetry>()
+            .cached_typed_telemetry::<api::ErrorLogEntryTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 133: Current Unix Time.
-    async fn current_unix_time(&self, ctx: &Context<'_>) -> Result<api::CurrentUnixTimeTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn current_unix_time(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::CurrentUnixTimeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CurrentUnixTimeTelemetry>()
+            .cached_typed_telemetry::<api::CurrentUnixTimeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 134: Persist Config Diagnostics.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn persist_config_diagnostics(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::PersistConfigDiagnosticsTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::PersistConfigDiagnosticsTelemetry>()
+            .cached_typed_telemetry::<api::PersistConfigDiagnosticsTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 136: Version.
-    async fn version(&self, ctx: &Context<'_>) -> Result<api::VersionTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn version(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::VersionTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::VersionTelemetry>()
+            .cached_typed_telemetry::<api::VersionTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 139: Common Error Codes.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn common_error_codes(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CommonErrorCodesTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CommonErrorCodesTelemetry>()
+            .cached_typed_telemetry::<api::CommonErrorCodesTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 140: Identification2.
-    async fn identification2(&self, ctx: &Context<'_>) -> Result<api::Identification2Telemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn identification2(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::Identification2Telemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::Identification2Telemetry>()
+            .cached_typed_telemetry::<api::Identification2Telemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 161: File Transfer Status.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn file_transfer_status(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::FileTransferStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::FileTransferStatusTelemetry>()
+            .cached_typed_telemetry::<api::FileTransferStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 167: CubeMag Health.
-    async fn cubemag_health(&self, ctx: &Context<'_>) -> Result<api::CubemagHealthTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn cubemag_health(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::CubemagHealthTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CubemagHealthTelemetry>()
+            .cached_typed_telemetry::<api::CubemagHealthTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 168: CubeSense Sun Health.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn cubesense_sun_health(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CubesenseSunHealthTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CubesenseSunHealthTelemetry>()
+            .cached_typed_telemetry::<api::CubesenseSunHealthTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 169: Torquer Current.
-    async fn torquer_current(&self, ctx: &Context<'_>) -> Result<api::TorquerCurrentTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn torquer_current(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::TorquerCurrentTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::TorquerCurrentTelemetry>()
+            .cached_typed_telemetry::<api::TorquerCurrentTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 170: FSS CubeSense Sun Raw.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn fss_cubesense_sun_raw(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::FssCubesenseSunRawTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::FssCubesenseSunRawTelemetry>()
+            .cached_typed_telemetry::<api::FssCubesenseSunRawTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 172: Controller.
-    async fn controller(&self, ctx: &Context<'_>) -> Result<api::ControllerTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn controller(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ControllerTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ControllerTelemetry>()
+            .cached_typed_telemetry::<api::ControllerTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 173: Estimator Backup.
-    async fn estimator_backup(&self, ctx: &Context<'_>) -> Result<api::EstimatorBackupTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn estimator_backup(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::EstimatorBackupTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::EstimatorBackupTelemetry>()
+            .cached_typed_telemetry::<api::EstimatorBackupTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 174: Models.
-    async fn models(&self, ctx: &Context<'_>) -> Result<api::ModelsTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn models(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ModelsTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ModelsTelemetry>()
+            .cached_typed_telemetry::<api::ModelsTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 175: Sensor Cal GNSS.
-    async fn sensor_cal_gnss(&self, ctx: &Context<'_>) -> Result<api::SensorCalGnssTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_gnss(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalGnssTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalGnssTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalGnssTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 176: Sensor Cal HSS.
-    async fn sensor_cal_hss(&self, ctx: &Context<'_>) -> Result<api::SensorCalHssTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_hss(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalHssTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalHssTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalHssTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 177: Sensor Cal Mag.
-    async fn sensor_cal_mag(&self, ctx: &Context<'_>) -> Result<api::SensorCalMagTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_mag(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalMagTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalMagTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalMagTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 178: Sensor Cal FSS.
-    async fn sensor_cal_fss(&self, ctx: &Context<'_>) -> Result<api::SensorCalFssTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_fss(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalFssTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalFssTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalFssTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 179: HSS CubeSense Earth Raw.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn hss_cubesense_earth_raw(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HssCubesenseEarthRawTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HssCubesenseEarthRawTelemetry>()
+            .cached_typed_telemetry::<api::HssCubesenseEarthRawTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 180: Sensor Raw Mag.
-    async fn sensor_raw_mag(&self, ctx: &Context<'_>) -> Result<api::SensorRawMagTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_raw_mag(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorRawMagTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorRawMagTelemetry>()
+            .cached_typed_telemetry::<api::SensorRawMagTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 181: Reference RPY Values.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn reference_rpy_values(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ReferenceRpyValuesTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ReferenceRpyValuesTelemetry>()
+            .cached_typed_telemetry::<api::ReferenceRpyValuesTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 182: OpenLoopCommandMtq.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn openloopcommandmtq(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::OpenloopcommandmtqTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::OpenloopcommandmtqTelemetry>()
+            .cached_typed_telemetry::<api::OpenloopcommandmtqTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 183: PowerState.
-    async fn powerstate(&self, ctx: &Context<'_>) -> Result<api::PowerstateTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn powerstate(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::PowerstateTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::PowerstateTelemetry>()
+            .cached_typed_telemetry::<api::PowerstateTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 184: ADCS Run Mode.
-    async fn adcs_run_mode(&self, ctx: &Context<'_>) -> Result<api::AdcsRunModeTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn adcs_run_mode(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::AdcsRunModeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AdcsRunModeTelemetry>()
+            .cached_typed_telemetry::<api::AdcsRunModeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 185: Control Mode.
-    async fn control_mode(&self, ctx: &Context<'_>) -> Result<api::ControlModeTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn control_mode(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ControlModeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ControlModeTelemetry>()
+            .cached_typed_telemetry::<api::ControlModeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 186: Wheel Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn wheel_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::WheelConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::WheelConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::WheelConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 189: ADCS Satellite Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn adcs_satellite_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AdcsSatelliteConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AdcsSatelliteConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::AdcsSatelliteConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 190: ADCS Controller Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn adcs_controller_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AdcsControllerConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AdcsControllerConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::AdcsControllerConfigurationTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 191: Mag0 Orbit Calibration Config.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn mag0_orbit_calibration_config(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::Mag0OrbitCalibrationConfigTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::Mag0OrbitCalibrationConfigTelemetry>()
+            .cached_typed_telemetry::<api::Mag0OrbitCalibrationConfigTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 192: Default Mode Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn default_mode_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::DefaultModeConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::DefaultModeConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::DefaultModeConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 193: Mounting Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn mounting_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::MountingConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::MountingConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::MountingConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 194: Mag1 Orbit Calibration Config.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn mag1_orbit_calibration_config(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::Mag1OrbitCalibrationConfigTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::Mag1OrbitCalibrationConfigTelemetry>()
+            .cached_typed_telemetry::<api::Mag1OrbitCalibrationConfigTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 195: ADCS Estimator Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn adcs_estimator_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AdcsEstimatorConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AdcsEstimatorConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::AdcsEstimatorConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 196: Satellite Orbit Parameters.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn satellite_orbit_parameters(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::SatelliteOrbitParametersTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SatelliteOrbitParametersTelemetry>()
+            .cached_typed_telemetry::<api::SatelliteOrbitParametersTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 197: Node Selection Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn node_selection_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::NodeSelectionConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::NodeSelectionConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::NodeSelectionConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 198: Magnetorquer Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn magnetorquer_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::MagnetorquerConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::MagnetorquerConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::MagnetorquerConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 199: Estimation Mode.
-    async fn estimation_mode(&self, ctx: &Context<'_>) -> Result<api::EstimationModeTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn estimation_mode(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::EstimationModeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::EstimationModeTelemetry>()
+            .cached_typed_telemetry::<api::EstimationModeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 202: OpenLoopCommandRwl.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn openloopcommandrwl(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::OpenloopcommandrwlTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::OpenloopcommandrwlTelemetry>()
+            .cached_typed_telemetry::<api::OpenloopcommandrwlTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 203: Raw CSS Sensor Telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_css_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawCssSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawCssSensorTelemetry>()
+            .cached_typed_telemetry::<api::RawCssSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 204: Sensor Raw Gyro.
-    async fn sensor_raw_gyro(&self, ctx: &Context<'_>) -> Result<api::SensorRawGyroTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_raw_gyro(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorRawGyroTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorRawGyroTelemetry>()
+            .cached_typed_telemetry::<api::SensorRawGyroTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 205: Sensor Raw RWL.
-    async fn sensor_raw_rwl(&self, ctx: &Context<'_>) -> Result<api::SensorRawRwlTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_raw_rwl(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorRawRwlTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorRawRwlTelemetry>()
+            .cached_typed_telemetry::<api::SensorRawRwlTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 206: Calibrated CSS Sensor Telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn calibrated_css_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CalibratedCssSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CalibratedCssSensorTelemetry>()
+            .cached_typed_telemetry::<api::CalibratedCssSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 207: Sensor Cal Gyro.
-    async fn sensor_cal_gyro(&self, ctx: &Context<'_>) -> Result<api::SensorCalGyroTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_gyro(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalGyroTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalGyroTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalGyroTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 209: Sensor Cal RWL.
-    async fn sensor_cal_rwl(&self, ctx: &Context<'_>) -> Result<api::SensorCalRwlTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_cal_rwl(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorCalRwlTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorCalRwlTelemetry>()
+            .cached_typed_telemetry::<api::SensorCalRwlTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 210: Main Estimator.
-    async fn main_estimator(&self, ctx: &Context<'_>) -> Result<api::MainEstimatorTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn main_estimator(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::MainEstimatorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::MainEstimatorTelemetry>()
+            .cached_typed_telemetry::<api::MainEstimatorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 211: Estimator Main High Res.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn estimator_main_high_res(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::EstimatorMainHighResTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::EstimatorMainHighResTelemetry>()
+            .cached_typed_telemetry::<api::EstimatorMainHighResTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 212: Sensor Raw GNSS.
-    async fn sensor_raw_gnss(&self, ctx: &Context<'_>) -> Result<api::SensorRawGnssTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn sensor_raw_gnss(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SensorRawGnssTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SensorRawGnssTelemetry>()
+            .cached_typed_telemetry::<api::SensorRawGnssTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 215: OpenLoopCommandHxyzRW.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn openloopcommandhxyzrw(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::OpenloopcommandhxyzrwTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::OpenloopcommandhxyzrwTelemetry>()
+            .cached_typed_telemetry::<api::OpenloopcommandhxyzrwTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 216: CubeComputer Health.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn cubecomputer_health(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CubecomputerHealthTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CubecomputerHealthTelemetry>()
+            .cached_typed_telemetry::<api::CubecomputerHealthTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 217: HSS Health.
-    async fn hss_health(&self, ctx: &Context<'_>) -> Result<api::HssHealthTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn hss_health(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::HssHealthTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HssHealthTelemetry>()
+            .cached_typed_telemetry::<api::HssHealthTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 218: RWL Health.
-    async fn rwl_health(&self, ctx: &Context<'_>) -> Result<api::RwlHealthTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn rwl_health(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::RwlHealthTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RwlHealthTelemetry>()
+            .cached_typed_telemetry::<api::RwlHealthTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 219: Data Frame.
-    async fn data_frame(&self, ctx: &Context<'_>) -> Result<api::DataFrameTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn data_frame(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::DataFrameTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::DataFrameTelemetry>()
+            .cached_typed_telemetry::<api::DataFrameTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 220: Image Frame Information.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn image_frame_information(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ImageFrameInformationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ImageFrameInformationTelemetry>()
+            .cached_typed_telemetry::<api::ImageFrameInformationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 221: Mag Sensing Element Configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn mag_sensing_element_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::MagSensingElementConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::MagSensingElementConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::MagSensingElementConfigurationTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 227: Telemetry Log Masks.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn telemetry_log_masks(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::TelemetryLogMasksTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::TelemetryLogMasksTelemetry>()
+            .cached_typed_telemetry::<api::TelemetryLogMasksTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 228: Unsolicited Telemetry Setup.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn unsolicited_telemetry_setup(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::UnsolicitedTelemetrySetupTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::UnsolicitedTelemetrySetupTelemetry>()
+            .cached_typed_telemetry::<api::UnsolicitedTelemetrySetupTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 229: Pass Through TCTLM.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn pass_through_tctlm(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::PassThroughTctlmTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::PassThroughTctlmTelemetry>()
+            .cached_typed_telemetry::<api::PassThroughTctlmTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 230: Component Error Codes.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn component_error_codes(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ComponentErrorCodesTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ComponentErrorCodesTelemetry>()
+            .cached_typed_telemetry::<api::ComponentErrorCodesTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 231: Image File Info.
-    async fn image_file_info(&self, ctx: &Context<'_>) -> Result<api::ImageFileInfoTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn image_file_info(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ImageFileInfoTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ImageFileInfoTelemetry>()
+            .cached_typed_telemetry::<api::ImageFileInfoTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 232: Image Transfer Status.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn image_transfer_status(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ImageTransferStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ImageTransferStatusTelemetry>()
+            .cached_typed_telemetry::<api::ImageTransferStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 233: Unsolicited Event Setup.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn unsolicited_event_setup(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::UnsolicitedEventSetupTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::UnsolicitedEventSetupTelemetry>()
+            .cached_typed_telemetry::<api::UnsolicitedEventSetupTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 234: Telemetry Log Status.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn telemetry_log_status(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::TelemetryLogStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::TelemetryLogStatusTelemetry>()
+            .cached_typed_telemetry::<api::TelemetryLogStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 235: Event Log Status.
-    async fn event_log_status(&self, ctx: &Context<'_>) -> Result<api::EventLogStatusTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn event_log_status(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::EventLogStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::EventLogStatusTelemetry>()
+            .cached_typed_telemetry::<api::EventLogStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 237: Node Initialization States.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn node_initialization_states(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::NodeInitializationStatesTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::NodeInitializationStatesTelemetry>()
+            .cached_typed_telemetry::<api::NodeInitializationStatesTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 238: Expected Nodes.
-    async fn expected_nodes(&self, ctx: &Context<'_>) -> Result<api::ExpectedNodesTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn expected_nodes(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::ExpectedNodesTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ExpectedNodesTelemetry>()
+            .cached_typed_telemetry::<api::ExpectedNodesTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 239: Port Map.
-    async fn port_map(&self, ctx: &Context<'_>) -> Result<api::PortMapTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn port_map(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::PortMapTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::PortMapTelemetry>()
+            .cached_typed_telemetry::<api::PortMapTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 240: Port Diagnostics.
-    async fn port_diagnostics(&self, ctx: &Context<'_>) -> Result<api::PortDiagnosticsTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn port_diagnostics(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::PortDiagnosticsTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::PortDiagnosticsTelemetry>()
+            .cached_typed_telemetry::<api::PortDiagnosticsTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 241: File Transfer Setup.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn file_transfer_setup(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::FileTransferSetupTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::FileTransferSetupTelemetry>()
+            .cached_typed_telemetry::<api::FileTransferSetupTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 128: Identification.
-    async fn identification(&self, ctx: &Context<'_>) -> Result<api::IdentificationTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn identification(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::IdentificationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::IdentificationTelemetry>()
+            .cached_typed_telemetry::<api::IdentificationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 129: Serial Number.
-    async fn serial_number(&self, ctx: &Context<'_>) -> Result<api::SerialNumberTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn serial_number(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::SerialNumberTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SerialNumberTelemetry>()
+            .cached_typed_telemetry::<api::SerialNumberTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 135: Communication Status.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn communication_status(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CommunicationStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CommunicationStatusTelemetry>()
+            .cached_typed_telemetry::<api::CommunicationStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 137: Boot Status.
-    async fn boot_status(&self, ctx: &Context<'_>) -> Result<api::BootStatusTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn boot_status(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::BootStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::BootStatusTelemetry>()
+            .cached_typed_telemetry::<api::BootStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 138: Telecommand Acknowledge.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn telecommand_acknowledge(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::TelecommandAcknowledgeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::TelecommandAcknowledgeTelemetry>()
+            .cached_typed_telemetry::<api::TelecommandAcknowledgeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 149: Reference Rotation Angle.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn reference_rotation_angle(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ReferenceRotationAngleTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ReferenceRotationAngleTelemetry>()
+            .cached_typed_telemetry::<api::ReferenceRotationAngleTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 150: Control and estimation mode.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn control_and_estimation_mode(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ControlAndEstimationModeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ControlAndEstimationModeTelemetry>()
+            .cached_typed_telemetry::<api::ControlAndEstimationModeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 153: Health telemetry for CubeAuriga.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn health_telemetry_for_cubeauriga(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HealthTelemetryForCubeaurigaTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HealthTelemetryForCubeaurigaTelemetry>()
+            .cached_typed_telemetry::<api::HealthTelemetryForCubeaurigaTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 154: Raw CubeAuriga telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_cubeauriga_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawCubeaurigaTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawCubeaurigaTelemetry>()
+            .cached_typed_telemetry::<api::RawCubeaurigaTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 155: Reference parameters for FMC scan.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn reference_parameters_for_fmc_scan(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ReferenceParametersForFmcScanTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ReferenceParametersForFmcScanTelemetry>()
+            .cached_typed_telemetry::<api::ReferenceParametersForFmcScanTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 156: Reference IRC vector.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn reference_irc_vector(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ReferenceIrcVectorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ReferenceIrcVectorTelemetry>()
+            .cached_typed_telemetry::<api::ReferenceIrcVectorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 157: Reference LLH target command.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn reference_llh_target_command(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::ReferenceLlhTargetCommandTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::ReferenceLlhTargetCommandTelemetry>()
+            .cached_typed_telemetry::<api::ReferenceLlhTargetCommandTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 158: GNSS UART Status.
-    async fn gnss_uart_status(&self, ctx: &Context<'_>) -> Result<api::GnssUartStatusTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn gnss_uart_status(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::GnssUartStatusTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::GnssUartStatusTelemetry>()
+            .cached_typed_telemetry::<api::GnssUartStatusTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 159: CubeNode-Quad PortMap.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn cubenode_quad_portmap(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CubenodeQuadPortmapTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CubenodeQuadPortmapTelemetry>()
+            .cached_typed_telemetry::<api::CubenodeQuadPortmapTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 160: Raw CubeStar telemetry.
-    async fn raw_cubestar_telemetry(&self, ctx: &Context<'_>) -> Result<api::RawCubestarTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn raw_cubestar_telemetry(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::RawCubestarTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawCubestarTelemetry>()
+            .cached_typed_telemetry::<api::RawCubestarTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 162: Orbit mode.
-    async fn orbit_mode(&self, ctx: &Context<'_>) -> Result<api::OrbitModeTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn orbit_mode(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::OrbitModeTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::OrbitModeTelemetry>()
+            .cached_typed_telemetry::<api::OrbitModeTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 163: Current execution point.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn current_execution_point(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CurrentExecutionPointTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CurrentExecutionPointTelemetry>()
+            .cached_typed_telemetry::<api::CurrentExecutionPointTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 164: HIL telemetry.
-    async fn hil_telemetry(&self, ctx: &Context<'_>) -> Result<api::HilTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn hil_telemetry(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::HilTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HilTelemetry>()
+            .cached_typed_telemetry::<api::HilTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 165: Health telemetry for CubeStar.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn health_telemetry_for_cubestar(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HealthTelemetryForCubestarTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HealthTelemetryForCubestarTelemetry>()
+            .cached_typed_telemetry::<api::HealthTelemetryForCubestarTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 166: Health telemetry for CubeNode PST3S.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn health_telemetry_for_cubenode_pst3s(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HealthTelemetryForCubenodePst3sTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HealthTelemetryForCubenodePst3sTelemetry>()
+            .cached_typed_telemetry::<api::HealthTelemetryForCubenodePst3sTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 171: Raw external sensor telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_external_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawExternalSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawExternalSensorTelemetry>()
+            .cached_typed_telemetry::<api::RawExternalSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 187: Target satellite orbit parameter configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn target_satellite_orbit_parameter_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::TargetSatelliteOrbitParameterConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::TargetSatelliteOrbitParameterConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::TargetSatelliteOrbitParameterConfigurationTelemetry>(
+                max_age(max_age_ms),
+            )
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 188: Augmented SGP4 configuration.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn augmented_sgp4_configuration(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AugmentedSgp4ConfigurationTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AugmentedSgp4ConfigurationTelemetry>()
+            .cached_typed_telemetry::<api::AugmentedSgp4ConfigurationTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 200: ADCS operational state.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn adcs_operational_state(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AdcsOperationalStateTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AdcsOperationalStateTelemetry>()
+            .cached_typed_telemetry::<api::AdcsOperationalStateTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 201: Simulation raw sensor telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn simulation_raw_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::SimulationRawSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::SimulationRawSensorTelemetry>()
+            .cached_typed_telemetry::<api::SimulationRawSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 208: Calibrated STR sensor telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn calibrated_str_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::CalibratedStrSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::CalibratedStrSensorTelemetry>()
+            .cached_typed_telemetry::<api::CalibratedStrSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 213: Raw PST3S star tracker telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_pst3s_star_tracker_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawPst3sStarTrackerTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawPst3sStarTrackerTelemetry>()
+            .cached_typed_telemetry::<api::RawPst3sStarTrackerTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 214: ACP execution telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn acp_execution_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::AcpExecutionTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::AcpExecutionTelemetry>()
+            .cached_typed_telemetry::<api::AcpExecutionTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 225: Health telemetry for CubeNode NSSRWL.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn health_telemetry_for_cubenode_nssrwl(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HealthTelemetryForCubenodeNssrwlTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HealthTelemetryForCubenodeNssrwlTelemetry>()
+            .cached_typed_telemetry::<api::HealthTelemetryForCubenodeNssrwlTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 226: Raw NSSRWL sensor telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_nssrwl_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawNssrwlSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawNssrwlSensorTelemetry>()
+            .cached_typed_telemetry::<api::RawNssrwlSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 236: RAW LITEF uFORS sensor telemetry.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn raw_litef_ufors_sensor_telemetry(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::RawLitefUforsSensorTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::RawLitefUforsSensorTelemetry>()
+            .cached_typed_telemetry::<api::RawLitefUforsSensorTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 242: File Info.
-    async fn file_info(&self, ctx: &Context<'_>) -> Result<api::FileInfoTelemetry> {
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
+    async fn file_info(
+        &self,
+        ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
+    ) -> Result<api::FileInfoTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::FileInfoTelemetry>()
+            .cached_typed_telemetry::<api::FileInfoTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 243: Health telemetry for CubeNode LITEFUFORS.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn health_telemetry_for_cubenode_litefufors(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::HealthTelemetryForCubenodeLitefuforsTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::HealthTelemetryForCubenodeLitefuforsTelemetry>()
+            .cached_typed_telemetry::<api::HealthTelemetryForCubenodeLitefuforsTelemetry>(max_age(
+                max_age_ms,
+            ))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 
     /// Requests and decodes telemetry ID 244: ASGP4 Orbital Parameters.
+    ///
+    /// A cached response no older than `max_age_ms` is returned without a bus request.
     async fn asgp4_orbital_parameters(
         &self,
         ctx: &Context<'_>,
+        max_age_ms: Option<i64>,
     ) -> Result<api::Asgp4OrbitalParametersTelemetry> {
         let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
         context
             .subsystem()
-            .request_typed_telemetry::<api::Asgp4OrbitalParametersTelemetry>()
+            .cached_typed_telemetry::<api::Asgp4OrbitalParametersTelemetry>(max_age(max_age_ms))
             .map_err(|err| async_graphql::Error::new(err.to_string()))
     }
 }
@@ -0,0 +1,434 @@
+//! Stateful CubeSpace ADCS simulator for hardware-in-the-loop testing.
+//!
+//! The simulator answers telecommands and telemetry requests using the
+//! generated command and telemetry database:
+//!
+//! - Telecommands are ACKed if the ID is known, the payload length matches the
+//!   database and every enum field holds a value from its table, otherwise they
+//!   are NACKed with one of the `NACK_*` error codes.
+//! - Every numeric field of an ACKed telecommand is remembered by its
+//!   [`field_key`], and telemetry fields with the same key and length report
+//!   that value. Setting the control mode with telecommand 58 is therefore
+//!   reflected in telemetry 185 and in every other telemetry with a
+//!   `Control mode` field.
+//! - Remaining telemetry fields are filled with small, deterministic values
+//!   which change between requests.
+//!
+//! Responses longer than 8 bytes are split over several `MSG_TYPE_TLM_RESP_EXT`
+//! frames. Longer telecommands are reassembled the same way: full 8-byte frames
+//! are buffered until the database length is reached, and a short frame ends
+//! the telecommand.
+//!
+//! Use [`SimStream`] to plug the simulator into a [`Connection`], or [`serve`]
+//! to answer frames on a Linux CAN interface such as `vcan0` (see the
+//! `cube-adcs-sim` binary).
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cubespace_adcs_api::{
+    DataType, FieldSpec, MSG_TYPE_TC, MSG_TYPE_TC_ACK, MSG_TYPE_TC_NACK, MSG_TYPE_TLM_REQ,
+    MSG_TYPE_TLM_RESP_EXT, build_can_id, codec, command_spec, decode_can_id, enum_value_valid,
+    field_key, telemetry_spec,
+};
+use rust_can::{CanError, CanFrame, CanResult, Connection, FrameFilter, Stream};
+
+/// NACK error code for a telecommand ID which is not in the database.
+pub const NACK_INVALID_ID: u8 = 1;
+/// NACK error code for a telecommand with the wrong payload length.
+pub const NACK_INVALID_LENGTH: u8 = 2;
+/// NACK error code for a telecommand with an out-of-range parameter.
+pub const NACK_INVALID_PARAMETER: u8 = 3;
+
+const CAN_FRAME_BYTES: usize = 8;
+const SERVE_POLL: Duration = Duration::from_millis(500);
+
+/// Simulated ADCS state.
+pub struct Simulator {
+    address: u8,
+    values: HashMap<String, (u32, u128)>,
+    partial: HashMap<u8, Vec<u8>>,
+    requests: u64,
+}
+
+impl Simulator {
+    /// Creates a simulator which answers frames addressed to `address`.
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            values: HashMap::new(),
+            partial: HashMap::new(),
+            requests: 0,
+        }
+    }
+
+    /// Sets the raw value reported for every telemetry field with this key.
+    pub fn set_value(&mut self, key: &str, length_bits: u32, raw: u128) {
+        self.values.insert(key.to_string(), (length_bits, raw));
+    }
+
+    /// Returns the raw value last set for a field key.
+    pub fn value(&self, key: &str) -> Option<u128> {
+        self.values.get(key).map(|(_, raw)| *raw)
+    }
+
+    /// Handles one received frame and returns the response frames, if any.
+    pub fn handle(&mut self, frame: &CanFrame) -> Vec<CanFrame> {
+        if !frame.extended {
+            return Vec::new();
+        }
+
+        let fields = decode_can_id(frame.id);
+        if fields.dst_addr != self.address {
+            return Vec::new();
+        }
+
+        match fields.msg_type {
+            MSG_TYPE_TC => {
+                let payload = {
+                    let partial = self.partial.entry(fields.tctlm_id).or_default();
+                    partial.extend_from_slice(&frame.data);
+                    let expected =
+                        command_spec(fields.tctlm_id).map_or(0, |spec| spec.length_bytes);
+                    if frame.data.len() == CAN_FRAME_BYTES && partial.len() < expected {
+                        return Vec::new();
+                    }
+                    self.partial.remove(&fields.tctlm_id).unwrap_or_default()
+                };
+                let (msg_type, data) = match self.telecommand(fields.tctlm_id, &payload) {
+                    Ok(()) => (MSG_TYPE_TC_ACK, Vec::new()),
+                    Err(code) => (MSG_TYPE_TC_NACK, vec![code]),
+                };
+                let id = build_can_id(msg_type, fields.tctlm_id, self.address, fields.src_addr);
+                vec![CanFrame::extended(id, &data)]
+            }
+            MSG_TYPE_TLM_REQ => match self.telemetry(fields.tctlm_id) {
+                Some(payload) => {
+                    let id = build_can_id(
+                        MSG_TYPE_TLM_RESP_EXT,
+                        fields.tctlm_id,
+                        self.address,
+                        fields.src_addr,
+                    );
+                    payload
+                        .chunks(CAN_FRAME_BYTES)
+                        .map(|chunk| CanFrame::extended(id, chunk))
+                        .collect()
+                }
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Encodes the current response for a telemetry ID.
+    pub fn telemetry(&mut self, telemetry_id: u8) -> Option<Vec<u8>> {
+        let spec = telemetry_spec(telemetry_id)?;
+        self.requests += 1;
+
+        let mut payload = vec![0; spec.length_bytes];
+        for (index, field) in spec.fields.iter().enumerate() {
+            let stored = self
+                .values
+                .get(&field_key(field.name))
+                .filter(|(length_bits, _)| *length_bits == field.length_bits)
+                .map(|(_, raw)| *raw);
+            let seed = u64::from(telemetry_id) * 31 + index as u64 * 7 + self.requests;
+
+            if let Err(err) = write_field(&mut payload, field, stored, seed) {
+                log::warn!("simulator could not fill telemetry {telemetry_id} field: {err}");
+            }
+        }
+
+        Some(payload)
+    }
+
+    fn telecommand(&mut self, command_id: u8, payload: &[u8]) -> Result<(), u8> {
+        let spec = command_spec(command_id).ok_or(NACK_INVALID_ID)?;
+        if payload.len() != spec.length_bytes {
+            return Err(NACK_INVALID_LENGTH);
+        }
+
+        let mut values = Vec::new();
+        for field in spec.fields {
+            if !is_numeric(field) {
+                continue;
+            }
+            let raw =
+                codec::read_unsigned(payload, field.offset_bits, field.length_bits, field.name)
+                    .map_err(|_| NACK_INVALID_LENGTH)?;
+            if let Some(table) = field.enum_table
+                && !enum_value_valid(table, raw as u8)
+            {
+                return Err(NACK_INVALID_PARAMETER);
+            }
+            values.push((field_key(field.name), (field.length_bits, raw)));
+        }
+
+        self.values.extend(values);
+        Ok(())
+    }
+}
+
+fn is_numeric(field: &FieldSpec) -> bool {
+    matches!(
+        field.data_type,
+        DataType::Uint
+            | DataType::Int
+            | DataType::Float
+            | DataType::Double
+            | DataType::Enum
+            | DataType::Bool
+    ) && field.length_bits <= 128
+}
+
+fn write_field(
+    payload: &mut [u8],
+    field: &FieldSpec,
+    stored: Option<u128>,
+    seed: u64,
+) -> cubespace_adcs_api::AdcsResult<()> {
+    if !is_numeric(field) {
+        return Ok(());
+    }
+
+    let (offset, length, name) = (field.offset_bits, field.length_bits, field.name);
+    if let Some(raw) = stored {
+        return codec::write_unsigned(payload, offset, length, name, raw);
+    }
+
+    let raw = match (field.data_type, length) {
+        // Enum tables start at 0, which is also a sane "off" state for flags
+        (DataType::Enum | DataType::Bool, _) => 0,
+        (DataType::Float, 32) => u128::from(((seed % 1000) as f32 / 10.0).to_bits()),
+        (DataType::Double, 64) => u128::from(((seed % 1000) as f64 / 10.0).to_bits()),
+        (DataType::Int, 2..=127) => {
+            let limit = (1_i128 << (length - 1)).min(100);
+            return codec::write_signed(
+                payload,
+                offset,
+                length,
+                name,
+                i128::from(seed) % (2 * limit) - limit,
+            );
+        }
+        _ => {
+            let limit = if length >= 10 { 1000 } else { 1_u128 << length };
+            u128::from(seed) % limit
+        }
+    };
+    codec::write_unsigned(payload, offset, length, name, raw)
+}
+
+/// A [`Stream`] which delivers every written frame to a [`Simulator`] and
+/// reads back its responses.
+pub struct SimStream {
+    simulator: Arc<Mutex<Simulator>>,
+    responses: RefCell<VecDeque<CanFrame>>,
+}
+
+impl SimStream {
+    /// Creates a stream backed by a shared simulator, so its state can be
+    /// inspected while a [`Connection`] owns the stream.
+    pub fn new(simulator: Arc<Mutex<Simulator>>) -> Self {
+        Self {
+            simulator,
+            responses: RefCell::new(VecDeque::new()),
+        }
+    }
+}
+
+impl Stream for SimStream {
+    fn write(&self, frame: CanFrame) -> CanResult<()> {
+        let responses = self
+            .simulator
+            .lock()
+            .map_err(|_| CanError::PortBusy)?
+            .handle(&frame);
+        self.responses.borrow_mut().extend(responses);
+        Ok(())
+    }
+
+    fn read(&self, _timeout: Duration) -> CanResult<CanFrame> {
+        self.responses
+            .borrow_mut()
+            .pop_front()
+            .ok_or(CanError::Timeout)
+    }
+
+    fn read_frames(&self, count: usize, timeout: Duration) -> CanResult<Vec<CanFrame>> {
+        (0..count).map(|_| self.read(timeout)).collect()
+    }
+
+    fn read_payload(
+        &self,
+        expected_len: usize,
+        timeout: Duration,
+        filter: Option<FrameFilter>,
+    ) -> CanResult<Vec<u8>> {
+        let mut payload = Vec::with_capacity(expected_len);
+
+        while payload.len() < expected_len {
+            let frame = self.read(timeout)?;
+            if filter
+                .as_ref()
+                .is_none_or(|filter| filter.id == frame.id && filter.extended == frame.extended)
+            {
+                payload.extend_from_slice(&frame.data);
+            }
+        }
+
+        payload.truncate(expected_len);
+        Ok(payload)
+    }
+}
+
+/// Answers frames received on `connection` until `run_for` elapses, or forever
+/// when it is `None`.
+///
+/// Used with a virtual CAN interface, e.g. `vcan0`, to run the service
+/// against the simulator without hardware.
+pub fn serve(
+    connection: &Connection,
+    simulator: &Mutex<Simulator>,
+    run_for: Option<Duration>,
+) -> CanResult<()> {
+    let start = Instant::now();
+
+    while run_for.is_none_or(|run_for| start.elapsed() < run_for) {
+        let frame = match connection.read(SERVE_POLL) {
+            Ok(frame) => frame,
+            Err(CanError::Timeout) => continue,
+            Err(err) => return Err(err),
+        };
+
+        let responses = simulator
+            .lock()
+            .map_err(|_| CanError::PortBusy)?
+            .handle(&frame);
+        for response in responses {
+            connection.write(response)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subsystem::{AdcsServiceConfig, Subsystem};
+    use cubespace_adcs_api::{
+        COMMAND_SPECS, ControlMode, ControlModeCommand, ControlModeTelemetry, TELEMETRY_SPECS,
+    };
+
+    const ADCS: u8 = 4;
+    const OBC: u8 = 1;
+
+    fn subsystem() -> (Subsystem, Arc<Mutex<Simulator>>) {
+        let simulator = Arc::new(Mutex::new(Simulator::new(ADCS)));
+        let connection = Connection::new(Box::new(SimStream::new(simulator.clone())));
+        (
+            Subsystem::with_connection(AdcsServiceConfig::default(), connection),
+            simulator,
+        )
+    }
+
+    #[test]
+    fn control_mode_is_reflected_in_telemetry() {
+        let (subsystem, simulator) = subsystem();
+
+        let ack = subsystem
+            .send_typed_command(&ControlModeCommand {
+                control_mode: ControlMode::ConSunTrack,
+                control_timeout: 600,
+            })
+            .unwrap();
+        assert!(ack.acknowledged);
+
+        let telemetry = subsystem
+            .request_typed_telemetry::<ControlModeTelemetry>()
+            .unwrap();
+        assert_eq!(telemetry.control_mode, Some(ControlMode::ConSunTrack));
+        assert_eq!(telemetry.control_timeout, 600);
+        assert_eq!(
+            simulator.lock().unwrap().value("control_timeout"),
+            Some(600)
+        );
+    }
+
+    #[test]
+    fn acks_multi_frame_telecommands() {
+        let (subsystem, _) = subsystem();
+        let spec = COMMAND_SPECS
+            .iter()
+            .find(|spec| spec.length_bytes > 2 * CAN_FRAME_BYTES)
+            .unwrap();
+
+        let ack = subsystem
+            .send_command_payload(spec.id, &vec![0; spec.length_bytes])
+            .unwrap();
+        assert!(ack.acknowledged);
+    }
+
+    #[test]
+    fn nacks_invalid_telecommands() {
+        let mut simulator = Simulator::new(ADCS);
+        let nack = |frames: Vec<CanFrame>| {
+            assert_eq!(decode_can_id(frames[0].id).msg_type, MSG_TYPE_TC_NACK);
+            frames[0].data[0]
+        };
+
+        let short = CanFrame::extended(build_can_id(MSG_TYPE_TC, 58, OBC, ADCS), &[13]);
+        assert_eq!(nack(simulator.handle(&short)), NACK_INVALID_LENGTH);
+
+        let bad_mode = CanFrame::extended(build_can_id(MSG_TYPE_TC, 58, OBC, ADCS), &[2, 0, 0]);
+        assert_eq!(nack(simulator.handle(&bad_mode)), NACK_INVALID_PARAMETER);
+        assert_eq!(simulator.value("control_mode"), None);
+
+        let unknown_id = (0..=u8::MAX)
+            .find(|id| command_spec(*id).is_none())
+            .unwrap();
+        let unknown = CanFrame::extended(build_can_id(MSG_TYPE_TC, unknown_id, OBC, ADCS), &[]);
+        assert_eq!(nack(simulator.handle(&unknown)), NACK_INVALID_ID);
+    }
+
+    #[test]
+    fn splits_long_telemetry_into_frames() {
+        let mut simulator = Simulator::new(ADCS);
+        let spec = TELEMETRY_SPECS
+            .iter()
+            .find(|spec| spec.length_bytes > CAN_FRAME_BYTES)
+            .unwrap();
+
+        let request = CanFrame::extended(build_can_id(MSG_TYPE_TLM_REQ, spec.id, OBC, ADCS), &[]);
+        let frames = simulator.handle(&request);
+
+        assert_eq!(frames.len(), spec.length_bytes.div_ceil(CAN_FRAME_BYTES));
+        assert!(frames.iter().all(|frame| {
+            let fields = decode_can_id(frame.id);
+            fields.msg_type == MSG_TYPE_TLM_RESP_EXT && fields.dst_addr == OBC
+        }));
+        let payload: Vec<u8> = frames.into_iter().flat_map(|frame| frame.data).collect();
+        assert_eq!(payload.len(), spec.length_bytes);
+    }
+
+    #[test]
+    fn answers_every_telemetry_id() {
+        let mut simulator = Simulator::new(ADCS);
+        for spec in TELEMETRY_SPECS {
+            let payload = simulator.telemetry(spec.id).unwrap();
+            assert_eq!(payload.len(), spec.length_bytes);
+            spec.decode_values(&payload).unwrap();
+        }
+    }
+
+    #[test]
+    fn ignores_frames_for_other_nodes() {
+        let mut simulator = Simulator::new(ADCS);
+        let request = CanFrame::extended(build_can_id(MSG_TYPE_TLM_REQ, 185, OBC, 9), &[]);
+        assert!(simulator.handle(&request).is_empty());
+    }
+}
@@ -1,18 +1,35 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use cubespace_adcs_api::{
-    MSG_TYPE_TC, MSG_TYPE_TC_ACK, MSG_TYPE_TC_NACK, MSG_TYPE_TLM_REQ, MSG_TYPE_TLM_RESP_EXT,
-    Telecommand, Telemetry, build_can_id, command_spec, decode_can_id, telemetry_spec,
+    BusHealth, MSG_TYPE_TC, MSG_TYPE_TC_ACK, MSG_TYPE_TC_NACK, MSG_TYPE_TLM_REQ,
+    MSG_TYPE_TLM_RESP_EXT, MSG_TYPE_UNSOLICITED_EVENT, MSG_TYPE_UNSOLICITED_TLM, Telecommand,
+    Telemetry, build_can_id, command_spec, decode_can_id, telemetry_spec,
+};
+use rust_can::{
+    BusMonitor, BusState, CanError, CanFrame, Connection, FrameFilter, Framing, MonitorOptions,
+    Transport,
 };
-use rust_can::{CanFrame, Connection, FrameFilter};
 use thiserror::Error;
 
+use crate::polling::TelemetryCache;
+use crate::transfer::{TransferManager, TransferSettings};
+use crate::unsolicited::{UnsolicitedLog, UnsolicitedSettings};
+
 const DEFAULT_INTERFACE: &str = "can0";
 const DEFAULT_BITRATE: u32 = 1_000_000;
 const DEFAULT_SRC_ADDRESS: u8 = 1;
 const DEFAULT_DST_ADDRESS: u8 = 4;
 const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+const DEFAULT_TRANSFER_DIR: &str = "adcs-transfers";
+const DEFAULT_TRANSFER_RETRIES: u32 = 3;
+const DEFAULT_TRANSFER_POLL_MS: u64 = 500;
+const DEFAULT_TRANSFER_IDLE_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_UNSOLICITED_POLL_MS: u64 = 50;
+const DEFAULT_UNSOLICITED_BUFFER_SIZE: u32 = 256;
+const DEFAULT_BUS_OFF_BACKOFF_MS: u64 = 100;
+const DEFAULT_BUS_OFF_MAX_BACKOFF_MS: u64 = 10_000;
 
 /// Service configuration values for CubeSpace ADCS CAN communication.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -29,6 +46,20 @@ pub struct AdcsServiceConfig {
     pub timeout: Duration,
     /// Whether to run `ip link set <interface> up type can bitrate <bitrate>` at startup.
     pub bring_interface_up: bool,
+    /// Retry, timing and storage settings for block transfers.
+    pub transfer: TransferSettings,
+    /// Unsolicited telemetry and event reception settings.
+    pub unsolicited: UnsolicitedSettings,
+    /// Whether to receive CAN error frames and keep bus error statistics
+    /// (`can_error_frames`, off by default).
+    pub monitor_errors: bool,
+    /// Whether to restart the interface automatically after bus-off. Only takes
+    /// effect when `monitor_errors` is set.
+    pub bus_off_recovery: bool,
+    /// Wait before the first restart after bus-off, doubled on every attempt.
+    pub bus_off_backoff: Duration,
+    /// Longest wait between restart attempts.
+    pub bus_off_max_backoff: Duration,
 }
 
 impl AdcsServiceConfig {
@@ -40,6 +71,79 @@ impl AdcsServiceConfig {
             destination_address: config_u8(config, "destination_address", DEFAULT_DST_ADDRESS),
             timeout: Duration::from_millis(config_u64(config, "timeout_ms", DEFAULT_TIMEOUT_MS)),
             bring_interface_up: config_bool(config, "bring_interface_up", false),
+            transfer: TransferSettings {
+                directory: PathBuf::from(config_string(
+                    config,
+                    "transfer_dir",
+                    DEFAULT_TRANSFER_DIR,
+                )),
+                retries: config_u32(config, "transfer_retries", DEFAULT_TRANSFER_RETRIES),
+                poll_interval: Duration::from_millis(config_u64(
+                    config,
+                    "transfer_poll_ms",
+                    DEFAULT_TRANSFER_POLL_MS,
+                )),
+                idle_timeout: Duration::from_millis(config_u64(
+                    config,
+                    "transfer_idle_timeout_ms",
+                    DEFAULT_TRANSFER_IDLE_TIMEOUT_MS,
+                )),
+            },
+            unsolicited: UnsolicitedSettings {
+                receive: config_bool(config, "receive_unsolicited", false),
+                poll_interval: Duration::from_millis(config_u64(
+                    config,
+                    "unsolicited_poll_ms",
+                    DEFAULT_UNSOLICITED_POLL_MS,
+                )),
+                buffer_size: config_u32(
+                    config,
+                    "unsolicited_buffer_size",
+                    DEFAULT_UNSOLICITED_BUFFER_SIZE,
+                ) as usize,
+                forward: config_bool(config, "forward_unsolicited", false),
+            },
+            monitor_errors: config_bool(config, "can_error_frames", false),
+            bus_off_recovery: config_bool(config, "bus_off_recovery", false),
+            bus_off_backoff: Duration::from_millis(config_u64(
+                config,
+                "bus_off_backoff_ms",
+                DEFAULT_BUS_OFF_BACKOFF_MS,
+            )),
+            bus_off_max_backoff: Duration::from_millis(config_u64(
+                config,
+                "bus_off_max_backoff_ms",
+                DEFAULT_BUS_OFF_MAX_BACKOFF_MS,
+            )),
+        }
+    }
+}
+
+impl Default for AdcsServiceConfig {
+    fn default() -> Self {
+        Self {
+            interface: DEFAULT_INTERFACE.to_string(),
+            bitrate: DEFAULT_BITRATE,
+            source_address: DEFAULT_SRC_ADDRESS,
+            destination_address: DEFAULT_DST_ADDRESS,
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            bring_interface_up: false,
+            transfer: TransferSettings {
+                directory: PathBuf::from(DEFAULT_TRANSFER_DIR),
+                retries: DEFAULT_TRANSFER_RETRIES,
+                poll_interval: Duration::from_millis(DEFAULT_TRANSFER_POLL_MS),
+                idle_timeout: Duration::from_millis(DEFAULT_TRANSFER_IDLE_TIMEOUT_MS),
+            },
+            unsolicited: UnsolicitedSettings {
+                receive: false,
+                poll_interval: Duration::from_millis(DEFAULT_UNSOLICITED_POLL_MS),
+                buffer_size: DEFAULT_UNSOLICITED_BUFFER_SIZE as usize,
+                forward: false,
+            },
+            monitor_errors: false,
+            bus_off_recovery: false,
+            bus_off_backoff: Duration::from_millis(DEFAULT_BUS_OFF_BACKOFF_MS),
+            bus_off_max_backoff: Duration::from_millis(DEFAULT_BUS_OFF_MAX_BACKOFF_MS),
         }
     }
 }
@@ -69,6 +173,9 @@ pub enum CubeAdcsError {
         /// Details about the unexpected frame.
         details: String,
     },
+    /// A block transfer failed.
+    #[error("transfer error: {0}")]
+    Transfer(String),
     /// Internal lock was poisoned.
     #[error("subsystem lock poisoned")]
     LockPoisoned,
@@ -103,6 +210,10 @@ pub struct CommandAck {
 pub struct Subsystem {
     config: AdcsServiceConfig,
     connection: Arc<Mutex<Connection>>,
+    transfers: TransferManager,
+    cache: TelemetryCache,
+    unsolicited: UnsolicitedLog,
+    bus: Option<Arc<BusMonitor>>,
 }
 
 impl Subsystem {
@@ -115,13 +226,34 @@ impl Subsystem {
                 .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
         }
 
-        let connection = Connection::from_interface(&config.interface)
-            .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
+        let connection = if config.monitor_errors {
+            Connection::from_interface_monitored(
+                &config.interface,
+                MonitorOptions {
+                    recovery_bitrate: Some(config.bitrate).filter(|_| config.bus_off_recovery),
+                    initial_backoff: config.bus_off_backoff,
+                    max_backoff: config.bus_off_max_backoff,
+                },
+            )
+        } else {
+            Connection::from_interface(&config.interface)
+        }
+        .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
+
+        Ok(Self::with_connection(config, connection))
+    }
 
-        Ok(Self {
+    /// Creates a subsystem using an existing CAN connection, e.g. one backed by
+    /// [`crate::sim::SimStream`].
+    pub fn with_connection(config: AdcsServiceConfig, connection: Connection) -> Self {
+        Self {
+            unsolicited: UnsolicitedLog::new(config.unsolicited.buffer_size),
+            bus: connection.stream.monitor(),
             config,
             connection: Arc::new(Mutex::new(connection)),
-        })
+            transfers: TransferManager::default(),
+            cache: TelemetryCache::default(),
+        }
     }
 
     /// Returns service configuration.
@@ -129,6 +261,48 @@ impl Subsystem {
         &self.config
     }
 
+    /// Returns the block transfer manager.
+    pub fn transfers(&self) -> &TransferManager {
+        &self.transfers
+    }
+
+    /// Returns the latest telemetry received per telemetry ID.
+    pub fn telemetry_cache(&self) -> &TelemetryCache {
+        &self.cache
+    }
+
+    /// Returns the unsolicited telemetry and event log.
+    pub fn unsolicited(&self) -> &UnsolicitedLog {
+        &self.unsolicited
+    }
+
+    /// Returns CAN bus error statistics, if error frames are monitored.
+    pub fn bus_health(&self) -> Option<BusHealth> {
+        let stats = self.bus.as_ref()?.stats();
+        let state = match stats.state {
+            BusState::ErrorActive => "ERROR_ACTIVE",
+            BusState::ErrorWarning => "ERROR_WARNING",
+            BusState::ErrorPassive => "ERROR_PASSIVE",
+            BusState::BusOff => "BUS_OFF",
+        };
+
+        Some(BusHealth {
+            state: state.to_owned(),
+            error_frames: stats.error_frames as i64,
+            bus_off: stats.bus_off as i64,
+            error_passive: stats.error_passive as i64,
+            error_warning: stats.error_warning as i64,
+            no_ack: stats.no_ack as i64,
+            protocol: stats.protocol as i64,
+            overflow: stats.overflow as i64,
+            recovery_attempts: stats.recovery_attempts as i64,
+            recovery_failures: stats.recovery_failures as i64,
+            tx_errors: stats.tx_errors.map(i32::from),
+            rx_errors: stats.rx_errors.map(i32::from),
+            last_error: stats.last_error,
+        })
+    }
+
     /// Sets the CAN interface up.
     pub fn set_interface_up(&self) -> Result<(), CubeAdcsError> {
         Connection::set_interface_up(&self.config.interface, self.config.bitrate)
@@ -163,20 +337,47 @@ impl Subsystem {
             )));
         }
 
+        self.exchange_command(command_id, payload)
+    }
+
+    /// Sends a telecommand payload which is not part of the generated command database
+    /// and waits for ACK/NACK.
+    ///
+    /// Used for setup telecommands, such as file transfer setup, that the ADCS
+    /// database export does not describe.
+    pub fn send_unlisted_command_payload(
+        &self,
+        command_id: u8,
+        payload: &[u8],
+    ) -> Result<CommandAck, CubeAdcsError> {
+        if command_spec(command_id).is_some() {
+            return Err(CubeAdcsError::Api(format!(
+                "telecommand {command_id} is in the command database; send it as a typed command"
+            )));
+        }
+
+        self.exchange_command(command_id, payload)
+    }
+
+    fn exchange_command(
+        &self,
+        command_id: u8,
+        payload: &[u8],
+    ) -> Result<CommandAck, CubeAdcsError> {
         let can_id = build_can_id(
             MSG_TYPE_TC,
             command_id,
             self.config.source_address,
             self.config.destination_address,
         );
-        let frame = CanFrame::extended(can_id, payload);
         let connection = self
             .connection
             .lock()
             .map_err(|_| CubeAdcsError::LockPoisoned)?;
 
-        connection
-            .write(frame)
+        // Telecommands longer than one frame are split into full frames sharing the ID
+        self.transport(&connection, can_id, can_id)
+            .send(payload)
             .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
 
         loop {
@@ -189,6 +390,7 @@ impl Subsystem {
                 || fields.src_addr != self.config.destination_address
                 || fields.dst_addr != self.config.source_address
             {
+                self.route_unsolicited(response);
                 continue;
             }
 
@@ -210,6 +412,9 @@ impl Subsystem {
                         payload: response.data,
                     });
                 }
+                MSG_TYPE_TLM_RESP_EXT | MSG_TYPE_UNSOLICITED_TLM | MSG_TYPE_UNSOLICITED_EVENT => {
+                    self.route_unsolicited(response);
+                }
                 _ => {
                     return Err(CubeAdcsError::UnexpectedResponse {
                         expected_id: command_id,
@@ -266,23 +471,108 @@ impl Subsystem {
             .write(CanFrame::extended(request_id, &[]))
             .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
 
-        let payload = connection
-            .read_payload(
-                length_bytes,
-                self.config.timeout,
-                Some(FrameFilter::extended(response_id)),
-            )
+        let payload = self
+            .transport(&connection, request_id, response_id)
+            .receive_with(Some(length_bytes), |frame| self.route_unsolicited(frame))
             .map_err(|err| CubeAdcsError::Can(err.to_string()))?;
 
+        self.cache.store(telemetry_id, &payload);
         Ok(payload)
     }
 
+    fn transport<'a>(&self, connection: &'a Connection, tx_id: u32, rx_id: u32) -> Transport<'a> {
+        Transport::new(
+            connection,
+            FrameFilter::extended(tx_id),
+            FrameFilter::extended(rx_id),
+            Framing::Extended,
+            self.config.timeout,
+        )
+    }
+
+    /// Passes up to `limit` waiting frames to the unsolicited log, and returns how many
+    /// were read.
+    ///
+    /// Used by the background receive thread between requests. Each read waits at most
+    /// `timeout`, so the bus is only held briefly.
+    pub fn receive_unsolicited(
+        &self,
+        timeout: Duration,
+        limit: usize,
+    ) -> Result<usize, CubeAdcsError> {
+        let connection = self
+            .connection
+            .lock()
+            .map_err(|_| CubeAdcsError::LockPoisoned)?;
+
+        for count in 0..limit {
+            match connection.read(timeout) {
+                Ok(frame) => self.route_unsolicited(frame),
+                Err(CanError::Timeout) => return Ok(count),
+                Err(err) => return Err(CubeAdcsError::Can(err.to_string())),
+            }
+        }
+        Ok(limit)
+    }
+
+    // Handles a frame which is not the response currently being waited for
+    fn route_unsolicited(&self, frame: CanFrame) {
+        let fields = decode_can_id(frame.id);
+        if !frame.extended
+            || fields.src_addr != self.config.destination_address
+            || fields.dst_addr != self.config.source_address
+        {
+            log::debug!(
+                "ignoring CAN frame 0x{:X} not addressed to this node",
+                frame.id
+            );
+            return;
+        }
+
+        match fields.msg_type {
+            MSG_TYPE_UNSOLICITED_TLM => {
+                if let Some(payload) = self
+                    .unsolicited
+                    .telemetry_frame(fields.tctlm_id, &frame.data)
+                {
+                    self.cache.store(fields.tctlm_id, &payload);
+                }
+            }
+            MSG_TYPE_UNSOLICITED_EVENT => self.unsolicited.event(fields.tctlm_id, &frame.data),
+            // Late responses to timed out requests would be reassembled together with
+            // unsolicited frames of the same ID, so they are dropped
+            MSG_TYPE_TLM_RESP_EXT => log::debug!(
+                "dropping late telemetry response for ID {}",
+                fields.tctlm_id
+            ),
+            msg_type => log::debug!(
+                "ignoring unexpected ADCS message type {msg_type} for ID {}",
+                fields.tctlm_id
+            ),
+        }
+    }
+
     /// Sends a telemetry request and decodes the response into a typed telemetry struct.
     pub fn request_typed_telemetry<T: Telemetry>(&self) -> Result<T, CubeAdcsError> {
         let payload = self.request_telemetry_payload(T::ID, T::LENGTH_BYTES)?;
         T::decode(&payload).map_err(|err| CubeAdcsError::Api(err.to_string()))
     }
 
+    /// Returns cached telemetry received within `max_age`, otherwise requests it
+    /// from the ADCS. `None` always requests.
+    pub fn cached_typed_telemetry<T: Telemetry>(
+        &self,
+        max_age: Option<Duration>,
+    ) -> Result<T, CubeAdcsError> {
+        if let Some(max_age) = max_age
+            && let Some(payload) = self.cache.fresh(T::ID, max_age)?
+        {
+            return T::decode(&payload).map_err(|err| CubeAdcsError::Api(err.to_string()));
+        }
+
+        self.request_typed_telemetry()
+    }
+
     /// Sends a raw extended CAN frame.
     pub fn send_raw_frame(&self, can_id: u32, payload: &[u8]) -> Result<(), CubeAdcsError> {
         let connection = self
@@ -33,3 +33,81 @@ fn schema_exposes_service_control_fields() {
     assert!(sdl.contains("resetInterface"));
     assert!(sdl.contains("sendCommandRaw"));
 }
+
+#[test]
+fn schema_exposes_transfer_fields() {
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .finish();
+    let sdl = schema.sdl();
+
+    assert!(sdl.contains("transfers: [TransferProgress!]!"));
+    assert!(sdl.contains("downloadEventLog"));
+    assert!(sdl.contains("downloadTelemetryLog"));
+    assert!(sdl.contains("downloadImage"));
+    assert!(sdl.contains("uploadFile"));
+    assert!(sdl.contains("cancelTransfer"));
+}
+
+#[test]
+fn schema_exposes_adcs_enums() {
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .finish();
+    let sdl = schema.sdl();
+
+    assert!(sdl.contains("enum ControlMode"));
+    assert!(sdl.contains("CON_SUN_TRACK"));
+    assert!(sdl.contains("controlMode: ControlMode"));
+    assert!(sdl.contains("enum OperationalState"));
+}
+
+#[test]
+fn schema_exposes_telemetry_cache() {
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .finish();
+    let sdl = schema.sdl();
+
+    assert!(sdl.contains("telemetryCache: [CachedTelemetry!]!"));
+    assert!(sdl.contains("maxAgeMs: Int"));
+}
+
+#[test]
+fn schema_exposes_unsolicited_messages() {
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .finish();
+    let sdl = schema.sdl();
+
+    assert!(sdl.contains("enum UnsolicitedKind"));
+    assert!(sdl.contains(
+        "unsolicited(kind: UnsolicitedKind, after: Int, limit: Int): [UnsolicitedMessage!]!"
+    ));
+}
+
+#[test]
+fn schema_exposes_bus_health() {
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .finish();
+    let sdl = schema.sdl();
+
+    assert!(sdl.contains("bus: BusHealth"));
+    assert!(sdl.contains("recoveryAttempts: Int!"));
+}
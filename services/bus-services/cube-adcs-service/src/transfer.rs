@@ -0,0 +1,941 @@
+//! Block-by-block ADCS log, image and file transfers.
+//!
+//! Each transfer runs on its own thread and moves data one data frame at a
+//! time, retrying every exchange up to the configured number of times. The ADCS
+//! has a single data frame buffer, so only one transfer may run at once.
+//!
+//! Transfer paths are resolved within the configured transfer directory.
+//! Downloads are written to `<path>.part` and renamed to `<path>` once complete.
+//! A failed or cancelled download leaves the partial file in place.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_graphql::{Enum, SimpleObject};
+use cubespace_adcs_api::{
+    DataFrameCommand, DataFrameTelemetry, EventLogStatusTelemetry, FileTransferStatusTelemetry,
+    ImageFrameInformationTelemetry, ImageTransferStatusTelemetry,
+    InitiateFilteredEventLogTransferCommand, RequestTelemetryLogTransferSetupCommand, Telecommand,
+    Telemetry, TelemetryLogStatusTelemetry,
+};
+
+use crate::subsystem::{CommandAck, CubeAdcsError, Subsystem};
+
+/// Payload bytes carried by one ADCS data frame.
+pub const DATA_FRAME_BYTES: usize = 256;
+
+/// Number of finished transfers kept for progress queries.
+const MAX_FINISHED_TRANSFERS: usize = 32;
+
+/// ADCS operations used by the transfer engine.
+pub trait AdcsLink {
+    /// Encodes and sends a typed telecommand.
+    fn send_command<C: Telecommand>(&self, command: &C) -> Result<CommandAck, CubeAdcsError>;
+
+    /// Sends a setup telecommand which is not part of the command database.
+    fn send_setup_command(&self, setup: &SetupCommand) -> Result<CommandAck, CubeAdcsError>;
+
+    /// Requests and decodes one telemetry item.
+    fn request_telemetry<T: Telemetry>(&self) -> Result<T, CubeAdcsError>;
+}
+
+impl AdcsLink for Subsystem {
+    fn send_command<C: Telecommand>(&self, command: &C) -> Result<CommandAck, CubeAdcsError> {
+        self.send_typed_command(command)
+    }
+
+    fn send_setup_command(&self, setup: &SetupCommand) -> Result<CommandAck, CubeAdcsError> {
+        self.send_unlisted_command_payload(setup.id, &setup.payload)
+    }
+
+    fn request_telemetry<T: Telemetry>(&self) -> Result<T, CubeAdcsError> {
+        self.request_typed_telemetry()
+    }
+}
+
+/// Retry, timing and storage settings for block transfers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransferSettings {
+    /// OBC directory which downloads are written to and uploads are read from.
+    pub directory: PathBuf,
+    /// Number of times a failed exchange is retried before the transfer fails.
+    pub retries: u32,
+    /// Delay between status polls while waiting on the ADCS.
+    pub poll_interval: Duration,
+    /// How long to wait for the ADCS to produce or consume data before giving up.
+    pub idle_timeout: Duration,
+}
+
+/// Kind of block transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum TransferKind {
+    /// Filtered event log download.
+    EventLog,
+    /// Telemetry log download.
+    TelemetryLog,
+    /// Camera image download.
+    Image,
+    /// File upload to the ADCS, such as firmware or configuration.
+    Upload,
+}
+
+/// Transfer state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum TransferState {
+    /// The transfer is in progress.
+    Running,
+    /// The transfer was cancelled and stops before its next exchange.
+    Cancelling,
+    /// All data was transferred.
+    Completed,
+    /// The transfer stopped on an error.
+    Failed,
+    /// The transfer was cancelled by an operator.
+    Cancelled,
+}
+
+/// Raw telecommand that prepares the ADCS for an image download or file upload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetupCommand {
+    /// Telecommand ID.
+    pub id: u8,
+    /// Telecommand payload.
+    pub payload: Vec<u8>,
+}
+
+/// What a transfer should move.
+#[derive(Clone, Debug)]
+pub enum TransferRequest {
+    /// Download the event log entries selected by the filter.
+    EventLog(InitiateFilteredEventLogTransferCommand),
+    /// Download the telemetry log entries selected by the filter.
+    TelemetryLog(RequestTelemetryLogTransferSetupCommand),
+    /// Download an image, optionally sending a setup telecommand first.
+    Image(Option<SetupCommand>),
+    /// Upload the file at the transfer path, optionally sending a setup telecommand first.
+    Upload(Option<SetupCommand>),
+}
+
+impl TransferRequest {
+    fn kind(&self) -> TransferKind {
+        match self {
+            TransferRequest::EventLog(_) => TransferKind::EventLog,
+            TransferRequest::TelemetryLog(_) => TransferKind::TelemetryLog,
+            TransferRequest::Image(_) => TransferKind::Image,
+            TransferRequest::Upload(_) => TransferKind::Upload,
+        }
+    }
+}
+
+/// Progress of one transfer.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct TransferProgress {
+    /// Transfer ID.
+    pub id: i32,
+    /// Kind of transfer.
+    pub kind: TransferKind,
+    /// Current state.
+    pub state: TransferState,
+    /// OBC file written by a download or read by an upload.
+    pub path: String,
+    /// Data frames transferred so far.
+    pub blocks: i32,
+    /// Payload bytes transferred so far.
+    pub bytes: i64,
+    /// Total payload bytes, when known.
+    pub total_bytes: Option<i64>,
+    /// Number of exchanges which had to be retried.
+    pub retries: i32,
+    /// Error text if the transfer failed.
+    pub errors: String,
+    /// Start time in seconds since the UNIX epoch.
+    pub started: f64,
+    /// Time of the last progress update in seconds since the UNIX epoch.
+    pub updated: f64,
+}
+
+/// Response to a transfer mutation.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct TransferResponse {
+    /// Whether the mutation succeeded.
+    pub success: bool,
+    /// Error text if the mutation failed.
+    pub errors: String,
+    /// The affected transfer.
+    pub transfer: Option<TransferProgress>,
+}
+
+impl TransferResponse {
+    /// Builds a transfer mutation response from a result.
+    pub fn from_result(result: Result<TransferProgress, CubeAdcsError>) -> Self {
+        match result {
+            Ok(transfer) => Self {
+                success: true,
+                errors: String::new(),
+                transfer: Some(transfer),
+            },
+            Err(err) => Self {
+                success: false,
+                errors: err.to_string(),
+                transfer: None,
+            },
+        }
+    }
+}
+
+/// Tracks running and recently finished transfers.
+#[derive(Clone, Default)]
+pub struct TransferManager {
+    transfers: Arc<Mutex<BTreeMap<i32, TransferProgress>>>,
+}
+
+impl TransferManager {
+    /// Starts a transfer on a background thread. `path` is resolved within the
+    /// transfer directory.
+    pub fn start<L: AdcsLink + Send + 'static>(
+        &self,
+        link: L,
+        settings: TransferSettings,
+        request: TransferRequest,
+        path: &Path,
+    ) -> Result<TransferProgress, CubeAdcsError> {
+        let path = resolve_path(&settings.directory, path)?;
+        let progress = {
+            let mut transfers = self.lock()?;
+            // A cancelled transfer holds the ADCS until its thread stops
+            if let Some(running) = transfers.values().find(|transfer| transfer.is_active()) {
+                return Err(CubeAdcsError::Transfer(format!(
+                    "transfer {} is already running",
+                    running.id
+                )));
+            }
+
+            let id = transfers.keys().next_back().map_or(1, |id| id + 1);
+            let now = unix_time();
+            let progress = TransferProgress {
+                id,
+                kind: request.kind(),
+                state: TransferState::Running,
+                path: path.display().to_string(),
+                blocks: 0,
+                bytes: 0,
+                total_bytes: None,
+                retries: 0,
+                errors: String::new(),
+                started: now,
+                updated: now,
+            };
+            transfers.insert(id, progress.clone());
+            prune(&mut transfers);
+            progress
+        };
+
+        let job = Job {
+            id: progress.id,
+            manager: self.clone(),
+            settings,
+        };
+        thread::spawn(move || {
+            let result = run(&link, &job, &request, &path);
+            job.finish(result);
+        });
+
+        Ok(progress)
+    }
+
+    /// Returns all known transfers, oldest first.
+    pub fn list(&self) -> Result<Vec<TransferProgress>, CubeAdcsError> {
+        Ok(self.lock()?.values().cloned().collect())
+    }
+
+    /// Returns one transfer.
+    pub fn get(&self, id: i32) -> Result<Option<TransferProgress>, CubeAdcsError> {
+        Ok(self.lock()?.get(&id).cloned())
+    }
+
+    /// Cancels a running transfer. It is `Cancelling` until it stops before its
+    /// next exchange, and then `Cancelled`.
+    pub fn cancel(&self, id: i32) -> Result<TransferProgress, CubeAdcsError> {
+        let mut transfers = self.lock()?;
+        let transfer = transfers
+            .get_mut(&id)
+            .ok_or_else(|| CubeAdcsError::Transfer(format!("unknown transfer {id}")))?;
+        if transfer.state != TransferState::Running {
+            return Err(CubeAdcsError::Transfer(format!(
+                "transfer {id} is not running"
+            )));
+        }
+
+        transfer.state = TransferState::Cancelling;
+        transfer.updated = unix_time();
+        Ok(transfer.clone())
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, BTreeMap<i32, TransferProgress>>, CubeAdcsError> {
+        self.transfers
+            .lock()
+            .map_err(|_| CubeAdcsError::LockPoisoned)
+    }
+}
+
+impl TransferProgress {
+    /// Whether the transfer's thread may still be exchanging data with the ADCS.
+    fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            TransferState::Running | TransferState::Cancelling
+        )
+    }
+}
+
+/// Resolves a transfer path within `directory`. Relative paths are taken from the
+/// directory, and neither `..` nor symbolic links may lead out of it.
+fn resolve_path(directory: &Path, path: &Path) -> Result<PathBuf, CubeAdcsError> {
+    let outside = || {
+        CubeAdcsError::Transfer(format!(
+            "{} is not within the transfer directory {}",
+            path.display(),
+            directory.display()
+        ))
+    };
+
+    if path.components().any(|part| part == Component::ParentDir) {
+        return Err(outside());
+    }
+    let resolved = directory.join(path);
+    if !resolved.starts_with(directory) || resolved == directory {
+        return Err(outside());
+    }
+
+    fs::create_dir_all(directory).map_err(|err| file_error(directory, err))?;
+    let root = fs::canonicalize(directory).map_err(|err| file_error(directory, err))?;
+    let existing = resolved
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+        .unwrap_or(directory);
+    let real = fs::canonicalize(existing).map_err(|err| file_error(existing, err))?;
+    if !real.starts_with(root) {
+        return Err(outside());
+    }
+
+    Ok(resolved)
+}
+
+fn prune(transfers: &mut BTreeMap<i32, TransferProgress>) {
+    let finished: Vec<i32> = transfers
+        .values()
+        .filter(|transfer| !transfer.is_active())
+        .map(|transfer| transfer.id)
+        .collect();
+
+    for id in finished
+        .iter()
+        .take(finished.len().saturating_sub(MAX_FINISHED_TRANSFERS))
+    {
+        transfers.remove(id);
+    }
+}
+
+/// Handle used by a transfer thread to report progress.
+struct Job {
+    id: i32,
+    manager: TransferManager,
+    settings: TransferSettings,
+}
+
+impl Job {
+    fn update(&self, update: impl FnOnce(&mut TransferProgress)) {
+        if let Ok(mut transfers) = self.manager.transfers.lock()
+            && let Some(progress) = transfers.get_mut(&self.id)
+        {
+            update(progress);
+            progress.updated = unix_time();
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<(), CubeAdcsError> {
+        let cancelled = self
+            .manager
+            .get(self.id)?
+            .is_some_and(|progress| progress.state == TransferState::Cancelling);
+
+        if cancelled {
+            Err(CubeAdcsError::Transfer("cancelled".to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn retry<T>(
+        &self,
+        mut exchange: impl FnMut() -> Result<T, CubeAdcsError>,
+    ) -> Result<T, CubeAdcsError> {
+        let mut attempt = 0;
+        loop {
+            self.check_cancelled()?;
+            match exchange() {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < self.settings.retries => {
+                    attempt += 1;
+                    log::warn!("transfer {}: retrying after error: {err}", self.id);
+                    self.update(|progress| progress.retries += 1);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn command<L: AdcsLink, C: Telecommand>(
+        &self,
+        link: &L,
+        command: &C,
+    ) -> Result<(), CubeAdcsError> {
+        self.retry(|| link.send_command(command).and_then(acknowledged))
+    }
+
+    fn record_block(&self, len: usize) {
+        self.update(|progress| {
+            progress.blocks += 1;
+            progress.bytes += len as i64;
+        });
+    }
+
+    fn finish(&self, result: Result<(), CubeAdcsError>) {
+        self.update(|progress| {
+            if progress.state == TransferState::Cancelling {
+                progress.state = TransferState::Cancelled;
+                return;
+            }
+            match result {
+                Ok(()) => progress.state = TransferState::Completed,
+                Err(err) => {
+                    log::error!("transfer {} failed: {err}", progress.id);
+                    progress.state = TransferState::Failed;
+                    progress.errors = err.to_string();
+                }
+            }
+        });
+    }
+}
+
+fn acknowledged(ack: CommandAck) -> Result<(), CubeAdcsError> {
+    if ack.acknowledged {
+        Ok(())
+    } else {
+        Err(CubeAdcsError::Nack {
+            command_id: ack.command_id,
+            error_code: ack.error_code,
+        })
+    }
+}
+
+fn run<L: AdcsLink>(
+    link: &L,
+    job: &Job,
+    request: &TransferRequest,
+    path: &Path,
+) -> Result<(), CubeAdcsError> {
+    match request {
+        TransferRequest::EventLog(filter) => {
+            job.command(link, filter)?;
+            download_log(link, job, path, |link| {
+                link.request_telemetry::<EventLogStatusTelemetry>()
+                    .map(|status| u32::from(status.number_of_queued_entries))
+            })
+        }
+        TransferRequest::TelemetryLog(filter) => {
+            job.command(link, filter)?;
+            download_log(link, job, path, |link| {
+                link.request_telemetry::<TelemetryLogStatusTelemetry>()
+                    .map(|status| u32::from(status.number_of_queued_entries))
+            })
+        }
+        TransferRequest::Image(setup) => {
+            if let Some(setup) = setup {
+                job.retry(|| link.send_setup_command(setup).and_then(acknowledged))?;
+            }
+            download_image(link, job, path)
+        }
+        TransferRequest::Upload(setup) => {
+            let data = fs::read(path).map_err(|err| {
+                CubeAdcsError::Transfer(format!("failed to read {}: {err}", path.display()))
+            })?;
+            job.update(|progress| progress.total_bytes = Some(data.len() as i64));
+            if let Some(setup) = setup {
+                job.retry(|| link.send_setup_command(setup).and_then(acknowledged))?;
+            }
+            upload(link, job, &data)
+        }
+    }
+}
+
+/// Reads data frames until the ADCS reports an empty read queue.
+fn download_log<L, F>(link: &L, job: &Job, path: &Path, queued: F) -> Result<(), CubeAdcsError>
+where
+    L: AdcsLink,
+    F: Fn(&L) -> Result<u32, CubeAdcsError>,
+{
+    let mut output = PartFile::create(path)?;
+    let mut idle_since: Option<Instant> = None;
+
+    loop {
+        let frame = job.retry(|| link.request_telemetry::<DataFrameTelemetry>())?;
+        let data = frame_data(&frame)?;
+        if !data.is_empty() {
+            output.write(data)?;
+            job.record_block(data.len());
+            idle_since = None;
+            continue;
+        }
+
+        if job.retry(|| queued(link))? == 0 {
+            return output.commit();
+        }
+
+        // Entries are queued but not yet loaded into the data frame buffer
+        if idle_since.get_or_insert_with(Instant::now).elapsed() > job.settings.idle_timeout {
+            return Err(CubeAdcsError::Transfer(
+                "timed out waiting for ADCS log data".to_owned(),
+            ));
+        }
+        thread::sleep(job.settings.poll_interval);
+    }
+}
+
+/// Reads numbered, checksummed image frames until the ADCS flags the last one.
+fn download_image<L: AdcsLink>(link: &L, job: &Job, path: &Path) -> Result<(), CubeAdcsError> {
+    let status = job.retry(|| link.request_telemetry::<ImageTransferStatusTelemetry>())?;
+    if status.error_code != 0 {
+        return Err(CubeAdcsError::Transfer(format!(
+            "ADCS image transfer error code {}",
+            status.error_code
+        )));
+    }
+    job.update(|progress| progress.total_bytes = Some(i64::from(status.transfer_size)));
+
+    let mut output = PartFile::create(path)?;
+    let mut expected: u16 = 0;
+
+    loop {
+        let (info, frame) = job.retry(|| {
+            let info = link.request_telemetry::<ImageFrameInformationTelemetry>()?;
+            if info.frame_error {
+                return Err(CubeAdcsError::Transfer(format!(
+                    "ADCS reported an error in image frame {}",
+                    info.image_frame_number
+                )));
+            }
+            if info.image_frame_number != expected {
+                return Err(CubeAdcsError::Transfer(format!(
+                    "expected image frame {expected}, ADCS has frame {}",
+                    info.image_frame_number
+                )));
+            }
+
+            let frame = link.request_telemetry::<DataFrameTelemetry>()?;
+            let checksum = xor_checksum(frame_data(&frame)?);
+            if checksum != info.checksum {
+                return Err(CubeAdcsError::Transfer(format!(
+                    "image frame {expected} checksum 0x{checksum:02x} does not match 0x{:02x}",
+                    info.checksum
+                )));
+            }
+            Ok((info, frame))
+        })?;
+
+        let data = frame_data(&frame)?;
+        output.write(data)?;
+        job.record_block(data.len());
+
+        if info.last_frame {
+            return output.commit();
+        }
+        expected = expected.wrapping_add(1);
+    }
+}
+
+/// Sends the file as data frames, then waits for the ADCS to finish storing it.
+fn upload<L: AdcsLink>(link: &L, job: &Job, data: &[u8]) -> Result<(), CubeAdcsError> {
+    for chunk in data.chunks(DATA_FRAME_BYTES) {
+        let command = DataFrameCommand {
+            frame_size: chunk.len() as u16,
+            frame_bytes: chunk.to_vec(),
+        };
+        job.command(link, &command)?;
+        job.record_block(chunk.len());
+    }
+
+    let start = Instant::now();
+    loop {
+        let status = job.retry(|| link.request_telemetry::<FileTransferStatusTelemetry>())?;
+        if status.error_code != 0 {
+            return Err(CubeAdcsError::Transfer(format!(
+                "ADCS file transfer error code {}",
+                status.error_code
+            )));
+        }
+        if status.data_remain == 0 {
+            return Ok(());
+        }
+        if start.elapsed() > job.settings.idle_timeout {
+            return Err(CubeAdcsError::Transfer(format!(
+                "timed out with {} bytes still to be stored by the ADCS",
+                status.data_remain
+            )));
+        }
+        thread::sleep(job.settings.poll_interval);
+    }
+}
+
+fn frame_data(frame: &DataFrameTelemetry) -> Result<&[u8], CubeAdcsError> {
+    let size = usize::from(frame.frame_size);
+    frame.frame_bytes.get(..size).ok_or_else(|| {
+        CubeAdcsError::Transfer(format!(
+            "data frame size {size} exceeds the {} byte frame",
+            frame.frame_bytes.len()
+        ))
+    })
+}
+
+fn xor_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |checksum, byte| checksum ^ byte)
+}
+
+/// Download output, written next to its final path until complete.
+struct PartFile {
+    file: File,
+    part: PathBuf,
+    path: PathBuf,
+}
+
+impl PartFile {
+    fn create(path: &Path) -> Result<Self, CubeAdcsError> {
+        let mut part = path.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|err| file_error(parent, err))?;
+        }
+        let file = File::create(&part).map_err(|err| file_error(&part, err))?;
+
+        Ok(Self {
+            file,
+            part,
+            path: path.to_owned(),
+        })
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), CubeAdcsError> {
+        self.file
+            .write_all(data)
+            .map_err(|err| file_error(&self.part, err))
+    }
+
+    fn commit(self) -> Result<(), CubeAdcsError> {
+        self.file
+            .sync_all()
+            .map_err(|err| file_error(&self.part, err))?;
+        fs::rename(&self.part, &self.path).map_err(|err| file_error(&self.path, err))
+    }
+}
+
+fn file_error(path: &Path, err: std::io::Error) -> CubeAdcsError {
+    CubeAdcsError::Transfer(format!("{}: {err}", path.display()))
+}
+
+fn unix_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// Replays telemetry payloads in order and records telecommands.
+    #[derive(Default)]
+    struct ScriptedLink {
+        telemetry: RefCell<VecDeque<(u8, Result<Vec<u8>, CubeAdcsError>)>>,
+        commands: RefCell<Vec<(u8, Vec<u8>)>>,
+    }
+
+    impl ScriptedLink {
+        fn respond(&self, id: u8, payload: Vec<u8>) {
+            self.telemetry.borrow_mut().push_back((id, Ok(payload)));
+        }
+
+        fn fail(&self, id: u8) {
+            self.telemetry
+                .borrow_mut()
+                .push_back((id, Err(CubeAdcsError::Can("timeout".to_owned()))));
+        }
+    }
+
+    impl AdcsLink for ScriptedLink {
+        fn send_command<C: Telecommand>(&self, command: &C) -> Result<CommandAck, CubeAdcsError> {
+            self.commands
+                .borrow_mut()
+                .push((C::ID, command.encode().unwrap()));
+            Ok(CommandAck {
+                acknowledged: true,
+                command_id: C::ID,
+                error_code: None,
+                payload: Vec::new(),
+            })
+        }
+
+        fn send_setup_command(&self, setup: &SetupCommand) -> Result<CommandAck, CubeAdcsError> {
+            self.commands
+                .borrow_mut()
+                .push((setup.id, setup.payload.clone()));
+            Ok(CommandAck {
+                acknowledged: true,
+                command_id: setup.id,
+                error_code: None,
+                payload: Vec::new(),
+            })
+        }
+
+        fn request_telemetry<T: Telemetry>(&self) -> Result<T, CubeAdcsError> {
+            let (id, payload) = self
+                .telemetry
+                .borrow_mut()
+                .pop_front()
+                .expect("unexpected telemetry request");
+            assert_eq!(id, T::ID);
+            T::decode(&payload?).map_err(|err| CubeAdcsError::Api(err.to_string()))
+        }
+    }
+
+    fn data_frame(data: &[u8]) -> Vec<u8> {
+        let mut payload = vec![0; DataFrameTelemetry::LENGTH_BYTES];
+        payload[0..2].copy_from_slice(&(data.len() as u16).to_le_bytes());
+        payload[2..2 + data.len()].copy_from_slice(data);
+        payload
+    }
+
+    fn job(manager: &TransferManager, kind: TransferKind) -> Job {
+        let id = 1;
+        manager.transfers.lock().unwrap().insert(
+            id,
+            TransferProgress {
+                id,
+                kind,
+                state: TransferState::Running,
+                path: String::new(),
+                blocks: 0,
+                bytes: 0,
+                total_bytes: None,
+                retries: 0,
+                errors: String::new(),
+                started: 0.0,
+                updated: 0.0,
+            },
+        );
+
+        Job {
+            id,
+            manager: manager.clone(),
+            settings: TransferSettings {
+                directory: temp_path("transfers"),
+                retries: 2,
+                poll_interval: Duration::from_millis(1),
+                idle_timeout: Duration::from_millis(100),
+            },
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cube-adcs-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn downloads_event_log_until_queue_empty() {
+        let link = ScriptedLink::default();
+        link.respond(DataFrameTelemetry::ID, data_frame(&[1, 2, 3]));
+        link.fail(DataFrameTelemetry::ID);
+        link.respond(DataFrameTelemetry::ID, data_frame(&[4, 5]));
+        link.respond(DataFrameTelemetry::ID, data_frame(&[]));
+        link.respond(
+            EventLogStatusTelemetry::ID,
+            vec![0; EventLogStatusTelemetry::LENGTH_BYTES],
+        );
+
+        let manager = TransferManager::default();
+        let job = job(&manager, TransferKind::EventLog);
+        let path = temp_path("event.log");
+        let request = TransferRequest::EventLog(InitiateFilteredEventLogTransferCommand::default());
+
+        run(&link, &job, &request, &path).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            link.commands.borrow()[0].0,
+            InitiateFilteredEventLogTransferCommand::ID
+        );
+        let progress = manager.get(1).unwrap().unwrap();
+        assert_eq!(progress.blocks, 2);
+        assert_eq!(progress.bytes, 5);
+        assert_eq!(progress.retries, 1);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn image_download_verifies_checksums() {
+        let link = ScriptedLink::default();
+        let mut status = vec![0; ImageTransferStatusTelemetry::LENGTH_BYTES];
+        status[5..9].copy_from_slice(&4_u32.to_le_bytes());
+        link.respond(ImageTransferStatusTelemetry::ID, status);
+        // Frame 0 arrives corrupted once, then cleanly
+        link.respond(ImageFrameInformationTelemetry::ID, vec![0, 0, 0x03, 0]);
+        link.respond(DataFrameTelemetry::ID, data_frame(&[0x01, 0x03]));
+        link.respond(ImageFrameInformationTelemetry::ID, vec![0, 0, 0x03, 0]);
+        link.respond(DataFrameTelemetry::ID, data_frame(&[0x01, 0x02]));
+        link.respond(ImageFrameInformationTelemetry::ID, vec![1, 0, 0x0c, 0b01]);
+        link.respond(DataFrameTelemetry::ID, data_frame(&[0x04, 0x08]));
+
+        let manager = TransferManager::default();
+        let job = job(&manager, TransferKind::Image);
+        let path = temp_path("image.bin");
+        let setup = SetupCommand {
+            id: 113,
+            payload: vec![1],
+        };
+
+        run(&link, &job, &TransferRequest::Image(Some(setup)), &path).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), vec![0x01, 0x02, 0x04, 0x08]);
+        assert_eq!(link.commands.borrow()[0], (113, vec![1]));
+        let progress = manager.get(1).unwrap().unwrap();
+        assert_eq!(progress.total_bytes, Some(4));
+        assert_eq!(progress.retries, 1);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn upload_sends_data_frames() {
+        let link = ScriptedLink::default();
+        let mut busy = vec![0; FileTransferStatusTelemetry::LENGTH_BYTES];
+        busy[2..6].copy_from_slice(&10_u32.to_le_bytes());
+        link.respond(FileTransferStatusTelemetry::ID, busy);
+        link.respond(
+            FileTransferStatusTelemetry::ID,
+            vec![0; FileTransferStatusTelemetry::LENGTH_BYTES],
+        );
+
+        let path = temp_path("upload.bin");
+        fs::write(&path, vec![7; DATA_FRAME_BYTES + 10]).unwrap();
+        let manager = TransferManager::default();
+        let job = job(&manager, TransferKind::Upload);
+
+        run(&link, &job, &TransferRequest::Upload(None), &path).unwrap();
+
+        let commands = link.commands.borrow();
+        assert_eq!(commands.len(), 2);
+        assert!(commands.iter().all(|(id, _)| *id == DataFrameCommand::ID));
+        assert_eq!(&commands[1].1[0..2], &10_u16.to_le_bytes());
+        assert_eq!(manager.get(1).unwrap().unwrap().blocks, 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn cancelled_transfer_stops() {
+        let link = ScriptedLink::default();
+        let manager = TransferManager::default();
+        let job = job(&manager, TransferKind::TelemetryLog);
+        manager.cancel(1).unwrap();
+
+        // No other transfer may start until the cancelled one has stopped
+        let second = manager.start(
+            NeverLink,
+            job.settings.clone(),
+            TransferRequest::Image(None),
+            Path::new("second.bin"),
+        );
+        assert!(matches!(second, Err(CubeAdcsError::Transfer(_))));
+        assert_eq!(
+            manager.get(1).unwrap().unwrap().state,
+            TransferState::Cancelling
+        );
+
+        let request =
+            TransferRequest::TelemetryLog(RequestTelemetryLogTransferSetupCommand::default());
+        let result = run(&link, &job, &request, &temp_path("cancelled.log"));
+        job.finish(result);
+
+        assert!(link.commands.borrow().is_empty());
+        assert_eq!(
+            manager.get(1).unwrap().unwrap().state,
+            TransferState::Cancelled
+        );
+    }
+
+    #[test]
+    fn only_one_transfer_runs() {
+        let manager = TransferManager::default();
+        let running = job(&manager, TransferKind::Image);
+
+        let result = manager.start(
+            NeverLink,
+            running.settings.clone(),
+            TransferRequest::Image(None),
+            Path::new("second.bin"),
+        );
+
+        assert!(matches!(result, Err(CubeAdcsError::Transfer(_))));
+    }
+
+    #[test]
+    fn paths_stay_within_transfer_directory() {
+        let directory = temp_path("resolve");
+        fs::create_dir_all(directory.join("logs")).unwrap();
+
+        assert_eq!(
+            resolve_path(&directory, Path::new("logs/event.log")).unwrap(),
+            directory.join("logs/event.log")
+        );
+        assert_eq!(
+            resolve_path(&directory, &directory.join("image.bin")).unwrap(),
+            directory.join("image.bin")
+        );
+        assert!(resolve_path(&directory, Path::new("../event.log")).is_err());
+        assert!(resolve_path(&directory, Path::new("/etc/passwd")).is_err());
+
+        std::os::unix::fs::symlink("/etc", directory.join("etc")).unwrap();
+        assert!(resolve_path(&directory, Path::new("etc/passwd")).is_err());
+        std::os::unix::fs::symlink("/nonexistent", directory.join("dangling")).unwrap();
+        assert!(resolve_path(&directory, Path::new("dangling")).is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    struct NeverLink;
+
+    impl AdcsLink for NeverLink {
+        fn send_command<C: Telecommand>(&self, _command: &C) -> Result<CommandAck, CubeAdcsError> {
+            unreachable!()
+        }
+
+        fn send_setup_command(&self, _setup: &SetupCommand) -> Result<CommandAck, CubeAdcsError> {
+            unreachable!()
+        }
+
+        fn request_telemetry<T: Telemetry>(&self) -> Result<T, CubeAdcsError> {
+            unreachable!()
+        }
+    }
+}
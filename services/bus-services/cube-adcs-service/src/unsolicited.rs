@@ -0,0 +1,398 @@
+//! Unsolicited ADCS telemetry and events.
+//!
+//! Once the ADCS is set up with the Unsolicited Telemetry Setup or Unsolicited
+//! Event Setup telecommands it transmits frames nobody asked for. Every
+//! unsolicited frame the subsystem receives while waiting for a response or
+//! between requests is passed to the [`UnsolicitedLog`], which reassembles multi-frame telemetry, decodes
+//! it with the telemetry database and keeps the most recent messages.
+//!
+//! With `receive_unsolicited = true` a background thread also reads the bus
+//! between requests, so messages are picked up while the service is idle.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use async_graphql::{Enum, SimpleObject};
+use cubespace_adcs_api::{FieldValue, Telemetry, telemetry_spec};
+
+use crate::polling::{self, TelemetryForwarder};
+use crate::subsystem::{CubeAdcsError, Subsystem};
+
+/// Frames of one telemetry message arriving further apart than this start a
+/// new message.
+const PARTIAL_TIMEOUT: Duration = Duration::from_secs(1);
+const ERROR_BACKOFF: Duration = Duration::from_secs(1);
+/// How long the background thread waits for each frame while it holds the bus.
+const READ_TIMEOUT: Duration = Duration::from_millis(1);
+/// Most frames the background thread reads before letting requests use the bus.
+const READ_LIMIT: usize = 32;
+
+/// Settings for unsolicited message reception.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsolicitedSettings {
+    /// Whether to read the bus in the background between requests.
+    pub receive: bool,
+    /// How long the background thread leaves the bus to requests between
+    /// reads. Frames arriving meanwhile wait in the receive queue.
+    pub poll_interval: Duration,
+    /// Number of messages kept in memory.
+    pub buffer_size: usize,
+    /// Whether to forward unsolicited telemetry to telemetry-service.
+    pub forward: bool,
+}
+
+/// Type of an unsolicited message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum UnsolicitedKind {
+    /// Telemetry pushed by the ADCS.
+    Telemetry,
+    /// Event pushed by the ADCS.
+    Event,
+}
+
+/// One unsolicited message received from the ADCS.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct UnsolicitedMessage {
+    /// Sequence number, increasing with every message received.
+    pub sequence: i64,
+    /// Message type.
+    pub kind: UnsolicitedKind,
+    /// Telemetry or event ID.
+    pub id: i32,
+    /// Telemetry name, if the ID is in the telemetry database.
+    pub name: Option<String>,
+    /// Time the message was completed, in seconds since the UNIX epoch.
+    pub timestamp: f64,
+    /// Raw payload as hexadecimal.
+    pub payload_hex: String,
+    /// Numeric telemetry field values.
+    pub values: Vec<FieldValue>,
+    /// Decoding error, if any.
+    pub errors: String,
+    #[graphql(skip)]
+    payload: Vec<u8>,
+}
+
+struct Partial {
+    payload: Vec<u8>,
+    started: Instant,
+}
+
+struct Inner {
+    messages: VecDeque<UnsolicitedMessage>,
+    partial: HashMap<u8, Partial>,
+    capacity: usize,
+    next_sequence: i64,
+    forwarder: Option<TelemetryForwarder>,
+}
+
+/// Ring buffer of unsolicited messages.
+#[derive(Clone)]
+pub struct UnsolicitedLog {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl UnsolicitedLog {
+    /// Creates a log keeping the `capacity` most recent messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                messages: VecDeque::new(),
+                partial: HashMap::new(),
+                capacity,
+                next_sequence: 0,
+                forwarder: None,
+            })),
+        }
+    }
+
+    /// Forwards completed telemetry messages to telemetry-service.
+    pub fn set_forwarder(&self, forwarder: TelemetryForwarder) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.forwarder = Some(forwarder);
+        }
+    }
+
+    /// Adds one telemetry frame, returning the payload once all frames of the
+    /// message have arrived.
+    pub fn telemetry_frame(&self, id: u8, data: &[u8]) -> Option<Vec<u8>> {
+        let mut inner = self.inner.lock().ok()?;
+        let length = telemetry_spec(id).map_or(data.len(), |spec| spec.length_bytes);
+
+        let partial = inner.partial.entry(id).or_insert_with(|| Partial {
+            payload: Vec::with_capacity(length),
+            started: Instant::now(),
+        });
+        if partial.started.elapsed() > PARTIAL_TIMEOUT {
+            log::warn!(
+                "dropping {} bytes of incomplete unsolicited telemetry {id}",
+                partial.payload.len()
+            );
+            partial.payload.clear();
+            partial.started = Instant::now();
+        }
+        partial.payload.extend_from_slice(data);
+        if partial.payload.len() < length {
+            return None;
+        }
+
+        let mut payload = inner.partial.remove(&id)?.payload;
+        payload.truncate(length);
+        inner.push(UnsolicitedKind::Telemetry, id, &payload);
+        Some(payload)
+    }
+
+    /// Adds one event frame.
+    pub fn event(&self, id: u8, data: &[u8]) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.push(UnsolicitedKind::Event, id, data);
+        }
+    }
+
+    /// Up to `limit` messages with a sequence number greater than `after`,
+    /// oldest first.
+    pub fn page(
+        &self,
+        kind: Option<UnsolicitedKind>,
+        after: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<UnsolicitedMessage>, CubeAdcsError> {
+        let inner = self.inner.lock().map_err(|_| CubeAdcsError::LockPoisoned)?;
+        Ok(inner
+            .messages
+            .iter()
+            .filter(|message| after.is_none_or(|after| message.sequence > after))
+            .filter(|message| kind.is_none_or(|kind| message.kind == kind))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Decodes the most recent unsolicited message for a telemetry type.
+    pub fn latest<T: Telemetry>(&self) -> Result<Option<T>, CubeAdcsError> {
+        let inner = self.inner.lock().map_err(|_| CubeAdcsError::LockPoisoned)?;
+        inner
+            .messages
+            .iter()
+            .rev()
+            .find(|message| {
+                message.kind == UnsolicitedKind::Telemetry && message.id == i32::from(T::ID)
+            })
+            .map(|message| T::decode(&message.payload))
+            .transpose()
+            .map_err(|err| CubeAdcsError::Api(err.to_string()))
+    }
+}
+
+impl Inner {
+    fn push(&mut self, kind: UnsolicitedKind, id: u8, payload: &[u8]) {
+        let spec = telemetry_spec(id).filter(|_| kind == UnsolicitedKind::Telemetry);
+        let timestamp = polling::unix_time();
+        let (values, errors) = match spec.map(|spec| spec.decode_values(payload)) {
+            Some(Ok(values)) => (values, String::new()),
+            Some(Err(err)) => (Vec::new(), err.to_string()),
+            None => (Vec::new(), String::new()),
+        };
+
+        if let Some(forwarder) = &self.forwarder
+            && let Err(err) = forwarder.send(timestamp, &values)
+        {
+            log::warn!("failed to forward unsolicited ADCS telemetry {id}: {err}");
+        }
+
+        self.messages.push_back(UnsolicitedMessage {
+            sequence: self.next_sequence,
+            kind,
+            id: i32::from(id),
+            name: spec.map(|spec| spec.name.to_owned()),
+            timestamp,
+            payload_hex: payload.iter().map(|byte| format!("{byte:02x}")).collect(),
+            values,
+            errors,
+            payload: payload.to_vec(),
+        });
+        self.next_sequence += 1;
+
+        while self.messages.len() > self.capacity {
+            self.messages.pop_front();
+        }
+    }
+}
+
+/// Spawns the background receive thread if it is enabled.
+pub fn start(subsystem: Subsystem) {
+    let settings = subsystem.config().unsolicited.clone();
+
+    if settings.forward {
+        match kubos_service::telemetry_url().map(|url| TelemetryForwarder::new(&url)) {
+            Some(Ok(forwarder)) => subsystem.unsolicited().set_forwarder(forwarder),
+            Some(Err(err)) => log::error!("failed to set up unsolicited forwarding: {err}"),
+            None => {}
+        }
+    }
+
+    if !settings.receive {
+        log::info!("background reception of unsolicited ADCS messages is disabled");
+        return;
+    }
+
+    thread::spawn(move || {
+        loop {
+            // The bus is released between reads so waiting requests can take it
+            match subsystem.receive_unsolicited(READ_TIMEOUT, READ_LIMIT) {
+                // More frames are probably waiting
+                Ok(READ_LIMIT) => thread::yield_now(),
+                Ok(_) => thread::sleep(settings.poll_interval),
+                Err(err) => {
+                    log::warn!("failed to receive unsolicited ADCS messages: {err}");
+                    thread::sleep(ERROR_BACKOFF);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subsystem::AdcsServiceConfig;
+    use cubespace_adcs_api::{
+        ControlModeTelemetry, MSG_TYPE_TLM_RESP_EXT, MSG_TYPE_UNSOLICITED_EVENT,
+        MSG_TYPE_UNSOLICITED_TLM, TELEMETRY_SPECS, build_can_id,
+    };
+    use rust_can::mock::MockStream;
+    use rust_can::{CanFrame, Connection};
+
+    #[test]
+    fn reassembles_multi_frame_telemetry() {
+        let log = UnsolicitedLog::new(8);
+        let spec = TELEMETRY_SPECS
+            .iter()
+            .find(|spec| spec.length_bytes > 8 && spec.length_bytes <= 16)
+            .unwrap();
+        let payload = vec![0; spec.length_bytes];
+
+        assert_eq!(log.telemetry_frame(spec.id, &payload[..8]), None);
+        assert_eq!(
+            log.telemetry_frame(spec.id, &payload[8..]),
+            Some(payload.clone())
+        );
+
+        let messages = log.page(None, None, 10).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].name.as_deref(), Some(spec.name));
+        assert!(messages[0].errors.is_empty());
+    }
+
+    #[test]
+    fn keeps_most_recent_messages() {
+        let log = UnsolicitedLog::new(2);
+        for id in 0..3 {
+            log.event(id, &[id]);
+        }
+
+        let messages = log.page(Some(UnsolicitedKind::Event), None, 10).unwrap();
+        assert_eq!(
+            messages.iter().map(|m| m.sequence).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(log.page(None, Some(1), 10).unwrap().len(), 1);
+        assert!(
+            log.page(Some(UnsolicitedKind::Telemetry), None, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn background_reads_release_bus_after_limit() {
+        let config = AdcsServiceConfig::default();
+        let (obc, adcs) = (config.source_address, config.destination_address);
+        let mut mock = MockStream::default();
+        mock.read.set_output(
+            (0..3)
+                .map(|id| {
+                    CanFrame::extended(
+                        build_can_id(MSG_TYPE_UNSOLICITED_EVENT, id, adcs, obc),
+                        &[id],
+                    )
+                })
+                .collect(),
+        );
+        let subsystem = Subsystem::with_connection(config, Connection::new(Box::new(mock)));
+
+        assert_eq!(subsystem.receive_unsolicited(READ_TIMEOUT, 2).unwrap(), 2);
+        assert_eq!(subsystem.receive_unsolicited(READ_TIMEOUT, 2).unwrap(), 1);
+        assert_eq!(
+            subsystem.unsolicited().page(None, None, 10).unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn drops_late_telemetry_responses() {
+        let config = AdcsServiceConfig::default();
+        let (obc, adcs) = (config.source_address, config.destination_address);
+        let mut mock = MockStream::default();
+        mock.read.set_output(vec![CanFrame::extended(
+            build_can_id(MSG_TYPE_TLM_RESP_EXT, ControlModeTelemetry::ID, adcs, obc),
+            &[12, 0x58, 0x02],
+        )]);
+        let subsystem = Subsystem::with_connection(config, Connection::new(Box::new(mock)));
+
+        assert_eq!(subsystem.receive_unsolicited(READ_TIMEOUT, 2).unwrap(), 1);
+        assert!(
+            subsystem
+                .unsolicited()
+                .page(None, None, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn separates_unsolicited_frames_from_responses() {
+        let config = AdcsServiceConfig::default();
+        let (obc, adcs) = (config.source_address, config.destination_address);
+        let mut mock = MockStream::default();
+        mock.write.set_result(Ok(()));
+        mock.read.set_output(vec![
+            CanFrame::extended(
+                build_can_id(MSG_TYPE_UNSOLICITED_EVENT, 7, adcs, obc),
+                &[1, 2],
+            ),
+            CanFrame::extended(
+                build_can_id(
+                    MSG_TYPE_UNSOLICITED_TLM,
+                    ControlModeTelemetry::ID,
+                    adcs,
+                    obc,
+                ),
+                &[13, 0x2c, 0x01],
+            ),
+            CanFrame::extended(
+                build_can_id(MSG_TYPE_TLM_RESP_EXT, ControlModeTelemetry::ID, adcs, obc),
+                &[12, 0x58, 0x02],
+            ),
+        ]);
+        let subsystem = Subsystem::with_connection(config, Connection::new(Box::new(mock)));
+
+        let telemetry = subsystem
+            .request_typed_telemetry::<ControlModeTelemetry>()
+            .unwrap();
+        assert_eq!(telemetry.control_timeout, 600);
+
+        let messages = subsystem.unsolicited().page(None, None, 10).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].kind, UnsolicitedKind::Event);
+        assert_eq!(messages[0].payload_hex, "0102");
+        let latest = subsystem
+            .unsolicited()
+            .latest::<ControlModeTelemetry>()
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.control_timeout, 300);
+    }
+}
@@ -0,0 +1,201 @@
+use kubos_service::Config;
+use std::collections::HashMap;
+
+use crate::i2c_reader::{TEMP_OFFSET, TEMP_SLOPE};
+
+/// Names of the RADFET channels, in the order they are sampled
+pub const RADFET_CHANNELS: [&str; 7] = ["u1", "u2", "u3", "u4", "u5", "u6", "u7"];
+/// Name the `readSensor` query uses for the sixth RADFET (`u6`), accepted as an
+/// alias for it in the calibration sections
+pub const U6_ALIAS: &str = "radfet";
+/// Name of the board temperature channel
+pub const TEMP_CHANNEL: &str = "temp";
+
+/// Linear calibration for a single channel.
+///
+/// The calibrated value is `(mv - offset_mv) * gain`. For the RADFET channels
+/// `offset_mv` is the zero-dose output voltage and `gain` converts the threshold
+/// voltage shift into accumulated dose (Gy/mV). For the temperature channel the
+/// result is degrees Celsius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelCalibration {
+    pub offset_mv: f64,
+    pub gain: f64,
+}
+
+impl ChannelCalibration {
+    pub fn apply(&self, mv: f64) -> f64 {
+        (mv - self.offset_mv) * self.gain
+    }
+}
+
+impl Default for ChannelCalibration {
+    fn default() -> Self {
+        ChannelCalibration {
+            offset_mv: 0.0,
+            gain: 1.0,
+        }
+    }
+}
+
+/// The calibration used for the temperature channel when none is configured.
+/// Equivalent to `mv_to_temp_c`.
+pub fn default_temp_calibration() -> ChannelCalibration {
+    ChannelCalibration {
+        offset_mv: -TEMP_OFFSET / TEMP_SLOPE,
+        gain: TEMP_SLOPE,
+    }
+}
+
+/// Per-channel calibration loaded from the `[dosimeter.calibration.<channel>]`
+/// config sections
+#[derive(Clone, Debug)]
+pub struct CalibrationTable {
+    channels: HashMap<String, ChannelCalibration>,
+}
+
+impl CalibrationTable {
+    pub fn from_config(config: &Config) -> Self {
+        let mut table = CalibrationTable::default();
+
+        let sections = match config.get("calibration") {
+            Some(value) => value,
+            None => return table,
+        };
+        let sections = match sections.as_table() {
+            Some(sections) => sections,
+            None => {
+                log::warn!("Ignoring dosimeter calibration: expected a table");
+                return table;
+            }
+        };
+
+        for (name, section) in sections.iter() {
+            let name = if name == U6_ALIAS {
+                "u6"
+            } else {
+                name.as_str()
+            };
+            let current = match table.channels.get(name) {
+                Some(current) => *current,
+                None => {
+                    log::warn!("Ignoring calibration for unknown channel '{}'", name);
+                    continue;
+                }
+            };
+
+            let get_float = |key: &str| {
+                section.get(key).and_then(|value| {
+                    value
+                        .as_float()
+                        .or_else(|| value.as_integer().map(|int| int as f64))
+                })
+            };
+
+            table.channels.insert(
+                name.to_string(),
+                ChannelCalibration {
+                    offset_mv: get_float("offset_mv").unwrap_or(current.offset_mv),
+                    gain: get_float("gain").unwrap_or(current.gain),
+                },
+            );
+        }
+
+        table
+    }
+
+    pub fn get(&self, channel: &str) -> Option<ChannelCalibration> {
+        self.channels.get(channel).copied()
+    }
+
+    /// All channels and their calibration, RADFETs first
+    pub fn entries(&self) -> Vec<(String, ChannelCalibration)> {
+        RADFET_CHANNELS
+            .iter()
+            .chain(std::iter::once(&TEMP_CHANNEL))
+            .filter_map(|name| self.get(name).map(|cal| (name.to_string(), cal)))
+            .collect()
+    }
+}
+
+impl Default for CalibrationTable {
+    fn default() -> Self {
+        let mut channels: HashMap<String, ChannelCalibration> = RADFET_CHANNELS
+            .iter()
+            .map(|name| (name.to_string(), ChannelCalibration::default()))
+            .collect();
+        channels.insert(TEMP_CHANNEL.to_string(), default_temp_calibration());
+
+        CalibrationTable { channels }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_reader::mv_to_temp_c;
+
+    const CONFIG: &str = r#"
+        [dosimeter.calibration.u1]
+        offset_mv = 1200.0
+        gain = 0.5
+
+        [dosimeter.calibration.radfet]
+        offset_mv = 900.0
+
+        [dosimeter.calibration.u7]
+        gain = 2
+
+        [dosimeter.calibration.u9]
+        gain = 3.0
+    "#;
+
+    #[test]
+    fn default_temp_matches_fixed_conversion() {
+        let cal = default_temp_calibration();
+        for mv in [0.0, 1650.0, 3300.0] {
+            assert!((cal.apply(mv) - mv_to_temp_c(mv)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn loads_channel_overrides() {
+        let config = Config::new_from_str("dosimeter", CONFIG).unwrap();
+        let table = CalibrationTable::from_config(&config);
+
+        assert_eq!(
+            table.get("u1"),
+            Some(ChannelCalibration {
+                offset_mv: 1200.0,
+                gain: 0.5
+            })
+        );
+        assert_eq!(
+            table.get("u7"),
+            Some(ChannelCalibration {
+                offset_mv: 0.0,
+                gain: 2.0
+            })
+        );
+        assert_eq!(
+            table.get("u6"),
+            Some(ChannelCalibration {
+                offset_mv: 900.0,
+                gain: 1.0
+            })
+        );
+        assert_eq!(table.get("radfet"), None);
+        assert_eq!(table.get("u2"), Some(ChannelCalibration::default()));
+        assert_eq!(table.get("u9"), None);
+        assert_eq!(table.entries().len(), 8);
+    }
+
+    #[test]
+    fn apply_offset_and_gain() {
+        let cal = ChannelCalibration {
+            offset_mv: 1000.0,
+            gain: 0.25,
+        };
+        assert_eq!(cal.apply(1400.0), 100.0);
+    }
+}
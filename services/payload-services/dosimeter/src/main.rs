@@ -4,9 +4,13 @@ use kubos_service::Service;
 use log;
 use rust_i2c::Connection;
 
+mod calibration;
 mod i2c_reader;
+mod sampler;
 mod schema;
 
+use crate::calibration::CalibrationTable;
+use crate::sampler::SamplerConfig;
 use crate::schema::{MutationRoot, QueryRoot, Subsystem};
 
 fn main() {
@@ -36,10 +40,18 @@ fn main() {
     // Set up I2C connection
     let connection = Connection::from_path(&i2c_bus, device_addr);
 
+    let calibration = CalibrationTable::from_config(&config);
+    let sampler_config = SamplerConfig::from_config(&config);
+
+    let subsystem = Subsystem::new(connection, calibration, sampler_config.history_size);
+
+    // Start sampling in the background
+    sampler::start(subsystem.clone(), sampler_config);
+
     // Create and start the service
     Service::new(
         config,
-        Subsystem::new(connection),
+        subsystem,
         QueryRoot,
         MutationRoot,
     )
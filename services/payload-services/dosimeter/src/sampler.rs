@@ -0,0 +1,404 @@
+use kubos_service::{Config, telemetry_url};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::net::UdpSocket;
+use std::thread;
+use std::time::Duration;
+
+use crate::calibration::{CalibrationTable, RADFET_CHANNELS, TEMP_CHANNEL};
+use crate::i2c_reader::{read_sensor_adc, to_mv};
+use crate::schema::{DOSIMETER_LIST, Subsystem, TEMP_SENSOR, TIMER_DELAY_MS};
+
+pub const DEFAULT_SAMPLE_INTERVAL_MS: u64 = 60_000;
+/// Samples kept per channel: one day at the default interval
+pub const DEFAULT_HISTORY_SIZE: usize = 1440;
+pub const TELEMETRY_SUBSYSTEM: &str = "dosimeter";
+
+/// A single calibrated reading of one channel
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// Seconds since the UNIX epoch
+    pub timestamp: f64,
+    pub channel: String,
+    pub adc: u16,
+    pub mv: f64,
+    /// Accumulated dose (Gy) for RADFET channels, degrees Celsius for the temperature channel
+    pub value: f64,
+    /// Dose rate (Gy/h) since the previous sample of the same RADFET channel
+    pub dose_rate: Option<f64>,
+}
+
+/// Min/max/mean of a set of values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl WindowStats {
+    pub fn from_values<I: IntoIterator<Item = f64>>(values: I) -> Option<Self> {
+        let mut count = 0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+
+        for value in values {
+            count += 1;
+            min = min.min(value);
+            max = max.max(value);
+            sum += value;
+        }
+
+        if count == 0 {
+            return None;
+        }
+
+        Some(WindowStats {
+            count,
+            min,
+            max,
+            mean: sum / count as f64,
+        })
+    }
+}
+
+/// Bounded, time-ordered store of the most recent samples of each channel.
+/// `capacity` applies per channel, so a busy channel can't evict another's history.
+#[derive(Debug)]
+pub struct SampleHistory {
+    channels: HashMap<String, VecDeque<Sample>>,
+    capacity: usize,
+}
+
+impl SampleHistory {
+    pub fn new(capacity: usize) -> Self {
+        SampleHistory {
+            channels: HashMap::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: Sample) {
+        let samples = self.channels.entry(sample.channel.clone()).or_default();
+        samples.push_back(sample);
+
+        while samples.len() > self.capacity {
+            samples.pop_front();
+        }
+    }
+
+    pub fn latest(&self, channel: &str) -> Option<&Sample> {
+        self.channels
+            .get(channel)
+            .and_then(|samples| samples.back())
+    }
+
+    /// Samples of `channel` with `from <= timestamp <= to`, oldest first
+    pub fn window(&self, channel: &str, from: Option<f64>, to: Option<f64>) -> Vec<&Sample> {
+        self.channels
+            .get(channel)
+            .into_iter()
+            .flatten()
+            .filter(|sample| from.is_none_or(|from| sample.timestamp >= from))
+            .filter(|sample| to.is_none_or(|to| sample.timestamp <= to))
+            .collect()
+    }
+}
+
+/// Background sampling settings from the `[dosimeter]` config section
+#[derive(Clone, Debug)]
+pub struct SamplerConfig {
+    /// Time between sweeps. `None` disables background sampling.
+    pub interval: Option<Duration>,
+    /// Samples kept per channel
+    pub history_size: usize,
+    /// `ip:port` of the telemetry service's direct UDP port
+    pub telemetry_url: Option<String>,
+}
+
+impl SamplerConfig {
+    pub fn from_config(config: &Config) -> Self {
+        let interval_ms = config
+            .get("sample_interval_ms")
+            .and_then(|v| v.as_integer())
+            .map(|v| v.max(0) as u64)
+            .unwrap_or(DEFAULT_SAMPLE_INTERVAL_MS);
+
+        let history_size = config
+            .get("history_size")
+            .and_then(|v| v.as_integer())
+            .map(|v| v.max(1) as usize)
+            .unwrap_or(DEFAULT_HISTORY_SIZE);
+
+        let forward = config
+            .get("forward_telemetry")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        SamplerConfig {
+            interval: (interval_ms > 0).then(|| Duration::from_millis(interval_ms)),
+            history_size,
+            telemetry_url: if forward { telemetry_url() } else { None },
+        }
+    }
+}
+
+/// Read and calibrate every channel once.
+///
+/// Channels which fail to read are logged and skipped.
+pub fn sweep(subsystem: &Subsystem) -> Vec<Sample> {
+    let codes = DOSIMETER_LIST
+        .into_iter()
+        .zip(RADFET_CHANNELS)
+        .chain(std::iter::once((TEMP_SENSOR, TEMP_CHANNEL)));
+
+    let mut samples = Vec::new();
+
+    for (code, channel) in codes {
+        let adc = {
+            let conn = match subsystem.i2c_connection.lock() {
+                Ok(conn) => conn,
+                Err(err) => {
+                    log::error!("Failed to get lock on I2C connection: {:?}", err);
+                    return samples;
+                }
+            };
+            read_sensor_adc(&conn, code)
+        };
+
+        match adc {
+            Ok(adc) => samples.push(calibrate(&subsystem.calibration, channel, adc, unix_time())),
+            Err(err) => log::warn!("Failed to sample {} (0x{:02X}): {}", channel, code, err),
+        }
+
+        thread::sleep(Duration::from_millis(TIMER_DELAY_MS));
+    }
+
+    samples
+}
+
+pub fn calibrate(
+    calibration: &CalibrationTable,
+    channel: &str,
+    adc: u16,
+    timestamp: f64,
+) -> Sample {
+    let mv = to_mv(adc);
+    let value = calibration
+        .get(channel)
+        .map(|cal| cal.apply(mv))
+        .unwrap_or(mv);
+
+    Sample {
+        timestamp,
+        channel: channel.to_owned(),
+        adc,
+        mv,
+        value,
+        dose_rate: None,
+    }
+}
+
+/// Fill in dose rates against the previous samples, then store the sweep
+pub fn record(history: &mut SampleHistory, samples: &mut [Sample]) {
+    for sample in samples.iter_mut() {
+        if sample.channel != TEMP_CHANNEL {
+            sample.dose_rate = history.latest(&sample.channel).and_then(|prev| {
+                let elapsed = sample.timestamp - prev.timestamp;
+                (elapsed > 0.0).then(|| (sample.value - prev.value) / elapsed * 3600.0)
+            });
+        }
+    }
+
+    for sample in samples.iter() {
+        history.push(sample.clone());
+    }
+}
+
+/// Take a sweep, add it to the subsystem's history and return it
+pub fn sample_now(subsystem: &Subsystem) -> Vec<Sample> {
+    let mut samples = sweep(subsystem);
+
+    match subsystem.history.lock() {
+        Ok(mut history) => record(&mut history, &mut samples),
+        Err(err) => log::error!("Failed to get lock on sample history: {:?}", err),
+    }
+
+    samples
+}
+
+/// Spawn the background sampling thread
+pub fn start(subsystem: Subsystem, config: SamplerConfig) {
+    let interval = match config.interval {
+        Some(interval) => interval,
+        None => {
+            log::info!("Background sampling disabled");
+            return;
+        }
+    };
+
+    let forwarder = config.telemetry_url.and_then(|url| {
+        TelemetryForwarder::new(&url)
+            .map_err(|err| log::error!("Failed to set up telemetry forwarding to {}: {}", url, err))
+            .ok()
+    });
+
+    log::info!("Sampling dosimeter every {:?}", interval);
+
+    thread::spawn(move || {
+        loop {
+            let samples = sample_now(&subsystem);
+
+            if let Some(forwarder) = &forwarder {
+                if let Err(err) = forwarder.send(&samples) {
+                    log::warn!("Failed to forward dosimeter samples: {}", err);
+                }
+            }
+
+            thread::sleep(interval);
+        }
+    });
+}
+
+/// Sends samples to the telemetry service's direct UDP port
+pub struct TelemetryForwarder {
+    socket: UdpSocket,
+}
+
+impl TelemetryForwarder {
+    pub fn new(url: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(url)?;
+        Ok(TelemetryForwarder { socket })
+    }
+
+    pub fn send(&self, samples: &[Sample]) -> std::io::Result<()> {
+        for sample in samples {
+            let points = data_points(sample);
+            if points.is_empty() {
+                continue;
+            }
+            self.socket
+                .send(serde_json::Value::Array(points).to_string().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn data_points(sample: &Sample) -> Vec<serde_json::Value> {
+    let point = |parameter: String, value: f64| {
+        json!({
+            "timestamp": sample.timestamp,
+            "subsystem": TELEMETRY_SUBSYSTEM,
+            "parameter": parameter,
+            "value": value.to_string(),
+        })
+    };
+
+    if sample.channel == TEMP_CHANNEL {
+        return vec![point(TEMP_CHANNEL.to_owned(), sample.value)];
+    }
+
+    let mut points = vec![
+        point(format!("{}_mv", sample.channel), sample.mv),
+        point(format!("{}_dose", sample.channel), sample.value),
+    ];
+    if let Some(rate) = sample.dose_rate {
+        points.push(point(format!("{}_dose_rate", sample.channel), rate));
+    }
+    points
+}
+
+pub fn unix_time() -> f64 {
+    chrono::Utc::now().timestamp_micros() as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(channel: &str, timestamp: f64, value: f64) -> Sample {
+        Sample {
+            timestamp,
+            channel: channel.to_owned(),
+            adc: 0,
+            mv: 0.0,
+            value,
+            dose_rate: None,
+        }
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = SampleHistory::new(2);
+        for i in 0..3 {
+            history.push(sample("u1", i as f64, i as f64));
+        }
+
+        let window = history.window("u1", None, None);
+        assert_eq!(window.len(), 2);
+        assert_eq!(window[0].timestamp, 1.0);
+    }
+
+    #[test]
+    fn history_is_bounded_per_channel() {
+        let mut history = SampleHistory::new(2);
+        history.push(sample("u1", 0.0, 0.0));
+        for i in 0..4 {
+            history.push(sample("temp", i as f64, 20.0));
+        }
+
+        assert_eq!(history.window("u1", None, None).len(), 1);
+        assert_eq!(history.window("temp", None, None).len(), 2);
+        assert_eq!(history.latest("temp").unwrap().timestamp, 3.0);
+        assert!(history.window("u2", None, None).is_empty());
+    }
+
+    #[test]
+    fn record_computes_dose_rate() {
+        let mut history = SampleHistory::new(10);
+        record(
+            &mut history,
+            &mut [sample("u1", 0.0, 1.0), sample("temp", 0.0, 20.0)],
+        );
+
+        let mut next = [sample("u1", 1800.0, 1.5), sample("temp", 1800.0, 21.0)];
+        record(&mut history, &mut next);
+
+        assert!((next[0].dose_rate.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(next[1].dose_rate, None);
+    }
+
+    #[test]
+    fn window_stats() {
+        let mut history = SampleHistory::new(10);
+        for (t, v) in [(0.0, 4.0), (10.0, 2.0), (20.0, 6.0), (30.0, 100.0)] {
+            history.push(sample("u2", t, v));
+        }
+
+        let values = history
+            .window("u2", Some(5.0), Some(25.0))
+            .into_iter()
+            .map(|s| s.value);
+        let stats = WindowStats::from_values(values).unwrap();
+
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 6.0);
+        assert_eq!(stats.mean, 4.0);
+        assert_eq!(WindowStats::from_values(Vec::new()), None);
+    }
+
+    #[test]
+    fn temperature_forwarded_as_single_point() {
+        let points = data_points(&sample("temp", 1.0, 25.0));
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0]["parameter"], "temp");
+
+        let mut radfet = sample("u3", 1.0, 0.5);
+        radfet.dose_rate = Some(0.1);
+        assert_eq!(data_points(&radfet).len(), 3);
+    }
+}
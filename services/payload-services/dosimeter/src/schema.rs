@@ -1,23 +1,29 @@
-use async_graphql::{Context, Object, Result, SimpleObject};
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use crate::calibration::CalibrationTable;
 use crate::i2c_reader::{read_sensor_adc, chip_name_map, to_mv, mv_to_temp_c};
+use crate::sampler::{self, Sample, SampleHistory, WindowStats};
 use rust_i2c::Connection;
 
-const DOSIMETER_LIST: [u8; 7] = [0x8C, 0xCC, 0x9C, 0xDC, 0xAC, 0xEC, 0xBC];
-const TEMP_SENSOR: u8 = 0xFC;
-const TIMER_DELAY_MS: u64 = 100;
+pub const DOSIMETER_LIST: [u8; 7] = [0x8C, 0xCC, 0x9C, 0xDC, 0xAC, 0xEC, 0xBC];
+pub const TEMP_SENSOR: u8 = 0xFC;
+pub const TIMER_DELAY_MS: u64 = 100;
 
 #[derive(Clone)]
 pub struct Subsystem {
     pub i2c_connection: Arc<Mutex<Connection>>,
+    pub calibration: Arc<CalibrationTable>,
+    pub history: Arc<Mutex<SampleHistory>>,
 }
 
 impl Subsystem {
-    pub fn new(connection: Connection) -> Self {
+    pub fn new(connection: Connection, calibration: CalibrationTable, history_size: usize) -> Self {
         Subsystem {
             i2c_connection: Arc::new(Mutex::new(connection)),
+            calibration: Arc::new(calibration),
+            history: Arc::new(Mutex::new(SampleHistory::new(history_size))),
         }
     }
 }
@@ -36,6 +42,77 @@ pub struct SensorReading {
     pub error: Option<String>,
 }
 
+#[derive(Debug, SimpleObject)]
+pub struct ChannelCalibration {
+    /// Channel name (u1, u2, etc.)
+    pub channel: String,
+    /// Output voltage subtracted before the gain is applied (mV)
+    pub offset_mv: f64,
+    /// Conversion from millivolts to the calibrated unit
+    pub gain: f64,
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct DoseSample {
+    /// Seconds since the UNIX epoch
+    pub timestamp: f64,
+    /// Channel name (u1, u2, etc.)
+    pub channel: String,
+    /// Raw ADC value
+    pub adc: i32,
+    /// ADC value converted to millivolts
+    pub mv: f64,
+    /// Accumulated dose (Gy), or degrees Celsius for the temperature channel
+    pub value: f64,
+    /// Dose rate (Gy/h) since the previous sample of this channel
+    pub dose_rate: Option<f64>,
+}
+
+impl From<&Sample> for DoseSample {
+    fn from(sample: &Sample) -> Self {
+        DoseSample {
+            timestamp: sample.timestamp,
+            channel: sample.channel.clone(),
+            adc: sample.adc as i32,
+            mv: sample.mv,
+            value: sample.value,
+            dose_rate: sample.dose_rate,
+        }
+    }
+}
+
+/// Which part of a sample to compute statistics over
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum SampleQuantity {
+    /// Calibrated value (accumulated dose or temperature)
+    Value,
+    /// Dose rate
+    DoseRate,
+    /// Voltage before calibration
+    Millivolts,
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct SampleStatistics {
+    /// Channel name (u1, u2, etc.)
+    pub channel: String,
+    /// Number of samples in the window
+    pub count: i32,
+    /// Smallest value in the window
+    pub min: Option<f64>,
+    /// Largest value in the window
+    pub max: Option<f64>,
+    /// Mean of the values in the window
+    pub mean: Option<f64>,
+}
+
+fn lock_history(subsystem: &Subsystem) -> Result<std::sync::MutexGuard<'_, SampleHistory>> {
+    subsystem.history.lock().map_err(|err| {
+        log::error!("Failed to get lock on sample history: {:?}", err);
+        async_graphql::Error::new(format!("Sample history lock error: {}", err))
+    })
+}
+
 pub struct QueryRoot;
 
 #[Object]
@@ -167,6 +244,87 @@ impl QueryRoot {
     async fn temp_convert(&self, voltage_mv: f64) -> f64 {
         mv_to_temp_c(voltage_mv)
     }
+
+    /// Calibration applied to each channel by the sampler
+    async fn calibration(&self, ctx: &Context<'_>) -> Result<Vec<ChannelCalibration>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        Ok(context
+            .subsystem()
+            .calibration
+            .entries()
+            .into_iter()
+            .map(|(channel, cal)| ChannelCalibration {
+                channel,
+                offset_mv: cal.offset_mv,
+                gain: cal.gain,
+            })
+            .collect())
+    }
+
+    /// Most recent sample of every channel
+    async fn latest_samples(&self, ctx: &Context<'_>) -> Result<Vec<DoseSample>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        let history = lock_history(context.subsystem())?;
+
+        Ok(context
+            .subsystem()
+            .calibration
+            .entries()
+            .iter()
+            .filter_map(|(channel, _)| history.latest(channel))
+            .map(DoseSample::from)
+            .collect())
+    }
+
+    /// Stored samples of a channel, optionally limited to a time window
+    async fn samples(
+        &self,
+        ctx: &Context<'_>,
+        channel: String,
+        from: Option<f64>,
+        to: Option<f64>,
+    ) -> Result<Vec<DoseSample>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        let history = lock_history(context.subsystem())?;
+
+        Ok(history
+            .window(&channel, from, to)
+            .into_iter()
+            .map(DoseSample::from)
+            .collect())
+    }
+
+    /// Min/max/mean of a channel's stored samples over a time window
+    async fn statistics(
+        &self,
+        ctx: &Context<'_>,
+        channel: String,
+        from: Option<f64>,
+        to: Option<f64>,
+        #[graphql(default_with = "SampleQuantity::Value")] quantity: SampleQuantity,
+    ) -> Result<SampleStatistics> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+        let history = lock_history(context.subsystem())?;
+
+        let values = history
+            .window(&channel, from, to)
+            .into_iter()
+            .filter_map(|sample| match quantity {
+                SampleQuantity::Value => Some(sample.value),
+                SampleQuantity::DoseRate => sample.dose_rate,
+                SampleQuantity::Millivolts => Some(sample.mv),
+            });
+        let stats = WindowStats::from_values(values);
+
+        Ok(SampleStatistics {
+            channel,
+            count: stats.map(|s| s.count as i32).unwrap_or(0),
+            min: stats.map(|s| s.min),
+            max: stats.map(|s| s.max),
+            mean: stats.map(|s| s.mean),
+        })
+    }
 }
 
 pub struct MutationRoot;
@@ -177,4 +335,14 @@ impl MutationRoot {
     async fn noop(&self) -> bool {
         true
     }
+
+    /// Sample every channel immediately and store the results
+    async fn sample_now(&self, ctx: &Context<'_>) -> Result<Vec<DoseSample>> {
+        let context = ctx.data::<kubos_service::Context<Subsystem>>()?;
+
+        Ok(sampler::sample_now(context.subsystem())
+            .iter()
+            .map(DoseSample::from)
+            .collect())
+    }
 }
\ No newline at end of file
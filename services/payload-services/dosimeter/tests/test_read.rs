@@ -102,6 +102,11 @@ impl DosimeterServiceFixture {
             [dosimeter]
             i2c_bus = "/dev/i2c-1"
             device_addr = 74
+            sample_interval_ms = 0
+
+            [dosimeter.calibration.u1]
+            offset_mv = 1200.0
+            gain = 0.5
 
             [dosimeter.addr]
             ip = "127.0.0.1"
@@ -363,3 +368,91 @@ fn test_multiple_queries_in_one_request() {
     assert!(result["data"]["u1"]["adc"].is_number());
     assert!(result["data"]["voltage"].is_number());
 }
+
+#[test]
+fn test_calibration_from_config() {
+    let port = next_test_port();
+
+    let _fixture = DosimeterServiceFixture::setup(Some(port));
+
+    let query = r#"{
+        calibration {
+            channel
+            offsetMv
+            gain
+        }
+    }"#;
+
+    let result = do_query(Some(port), query);
+
+    let channels = result["data"]["calibration"].as_array().unwrap();
+    assert_eq!(channels.len(), 8);
+    assert_eq!(
+        channels[0],
+        json!({ "channel": "u1", "offsetMv": 1200.0, "gain": 0.5 })
+    );
+    assert_eq!(
+        channels[1],
+        json!({ "channel": "u2", "offsetMv": 0.0, "gain": 1.0 })
+    );
+    assert_eq!(channels[7]["channel"], "temp");
+}
+
+#[test]
+fn test_statistics_empty_window() {
+    let port = next_test_port();
+
+    let _fixture = DosimeterServiceFixture::setup(Some(port));
+
+    let query = r#"{
+        statistics(channel: "u1", from: 0.0, quantity: DOSE_RATE) {
+            channel
+            count
+            min
+            max
+            mean
+        }
+        samples(channel: "u1") {
+            timestamp
+        }
+    }"#;
+
+    let expected = json!({
+        "data": {
+            "statistics": {
+                "channel": "u1",
+                "count": 0,
+                "min": null,
+                "max": null,
+                "mean": null
+            },
+            "samples": []
+        }
+    });
+
+    let result = do_query(Some(port), query);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_sample_now() {
+    let port = next_test_port();
+
+    let _fixture = DosimeterServiceFixture::setup(Some(port));
+
+    let mutation = r#"mutation {
+        sampleNow {
+            channel
+            value
+        }
+    }"#;
+
+    let result = do_query(Some(port), mutation);
+    let sampled = result["data"]["sampleNow"].as_array().unwrap().len();
+
+    let result = do_query(Some(port), "{ latestSamples { channel } }");
+    assert_eq!(
+        result["data"]["latestSamples"].as_array().unwrap().len(),
+        sampled
+    );
+}
@@ -0,0 +1,425 @@
+use kubos_service::Config;
+
+/// Error detected while decoding the UART byte stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// Bytes were discarded while searching for the sync word.
+    SyncLost { discarded: usize },
+    /// Length field exceeded the configured maximum payload size.
+    BadLength { length: usize },
+    /// Frame CRC did not match the computed value.
+    CrcMismatch { expected: u32, actual: u32 },
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::SyncLost { discarded } => {
+                write!(f, "sync lost, discarded {} bytes", discarded)
+            }
+            FrameError::BadLength { length } => write!(f, "invalid frame length {}", length),
+            FrameError::CrcMismatch { expected, actual } => write!(
+                f,
+                "crc mismatch: expected {:04X}, got {:04X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Output of a decoder for a chunk of input bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeEvent {
+    /// A complete, validated frame payload.
+    Frame(Vec<u8>),
+    Error(FrameError),
+}
+
+/// Turns a raw UART byte stream into frames.
+///
+/// Implementations keep any partial frame between calls, so `push` can be
+/// fed whatever chunk sizes the UART returns.
+pub trait FrameDecoder: Send {
+    fn push(&mut self, bytes: &[u8]) -> Vec<DecodeEvent>;
+
+    /// Wrap a payload so the other end's decoder will accept it.
+    fn encode(&self, payload: &[u8]) -> Vec<u8>;
+}
+
+/// Checksum appended after the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcKind {
+    None,
+    /// CRC-16/CCITT-FALSE over the length field and payload, big-endian.
+    Crc16Ccitt,
+}
+
+impl CrcKind {
+    fn len(self) -> usize {
+        match self {
+            CrcKind::None => 0,
+            CrcKind::Crc16Ccitt => 2,
+        }
+    }
+
+    fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            CrcKind::None => 0,
+            CrcKind::Crc16Ccitt => u32::from(crc16_ccitt(data)),
+        }
+    }
+}
+
+/// Layout of a `sync | length | payload | crc` frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramingConfig {
+    pub sync: Vec<u8>,
+    /// Width of the length field in bytes (1, 2 or 4).
+    pub length_bytes: usize,
+    pub big_endian: bool,
+    pub crc: CrcKind,
+    pub max_payload: usize,
+}
+
+impl Default for FramingConfig {
+    fn default() -> Self {
+        FramingConfig {
+            sync: vec![0xEB, 0x90],
+            length_bytes: 2,
+            big_endian: true,
+            crc: CrcKind::Crc16Ccitt,
+            max_payload: 1024,
+        }
+    }
+}
+
+impl FramingConfig {
+    /// Read the optional `[star-risc.framing]` section.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut framing = FramingConfig::default();
+
+        let section = match config.get("framing") {
+            Some(section) => section,
+            None => return Ok(framing),
+        };
+
+        if let Some(sync) = section.get("sync").and_then(|v| v.as_array()) {
+            framing.sync = sync
+                .iter()
+                .map(|byte| {
+                    byte.as_integer()
+                        .and_then(|b| u8::try_from(b).ok())
+                        .ok_or_else(|| format!("invalid sync byte {}", byte))
+                })
+                .collect::<Result<_, _>>()?;
+            if framing.sync.is_empty() {
+                return Err("sync must not be empty".to_owned());
+            }
+        }
+
+        if let Some(length_bytes) = section.get("length_bytes").and_then(|v| v.as_integer()) {
+            framing.length_bytes = match length_bytes {
+                1 | 2 | 4 => length_bytes as usize,
+                other => return Err(format!("unsupported length_bytes {}", other)),
+            };
+        }
+
+        if let Some(big_endian) = section.get("big_endian").and_then(|v| v.as_bool()) {
+            framing.big_endian = big_endian;
+        }
+
+        if let Some(crc) = section.get("crc").and_then(|v| v.as_str()) {
+            framing.crc = match crc {
+                "none" => CrcKind::None,
+                "crc16-ccitt" => CrcKind::Crc16Ccitt,
+                other => return Err(format!("unsupported crc '{}'", other)),
+            };
+        }
+
+        if let Some(max_payload) = section.get("max_payload").and_then(|v| v.as_integer()) {
+            framing.max_payload = max_payload.max(0) as usize;
+        }
+
+        Ok(framing)
+    }
+}
+
+/// Decoder for frames delimited by a sync word and a length field.
+pub struct SyncFrameDecoder {
+    config: FramingConfig,
+    buffer: Vec<u8>,
+}
+
+impl SyncFrameDecoder {
+    pub fn new(config: FramingConfig) -> Self {
+        SyncFrameDecoder {
+            config,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn read_length(&self, bytes: &[u8]) -> usize {
+        let mut length = 0usize;
+        if self.config.big_endian {
+            for &byte in bytes {
+                length = (length << 8) | byte as usize;
+            }
+        } else {
+            for &byte in bytes.iter().rev() {
+                length = (length << 8) | byte as usize;
+            }
+        }
+        length
+    }
+
+    fn write_length(&self, length: usize) -> Vec<u8> {
+        let width = self.config.length_bytes;
+        let mut bytes: Vec<u8> = (0..width).map(|i| (length >> (8 * i)) as u8).collect();
+        if self.config.big_endian {
+            bytes.reverse();
+        }
+        bytes
+    }
+
+    // Drop everything before the next sync word. Returns how many bytes were skipped.
+    fn resync(&mut self) -> usize {
+        let sync = &self.config.sync;
+        let start = self
+            .buffer
+            .windows(sync.len())
+            .position(|window| window == sync.as_slice())
+            .unwrap_or_else(|| {
+                // Keep a possible partial sync word at the end of the buffer
+                self.buffer.len().saturating_sub(sync.len() - 1)
+            });
+        self.buffer.drain(..start);
+        start
+    }
+}
+
+impl FrameDecoder for SyncFrameDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Vec<DecodeEvent> {
+        self.buffer.extend_from_slice(bytes);
+
+        let sync_len = self.config.sync.len();
+        let header_len = sync_len + self.config.length_bytes;
+        let crc_len = self.config.crc.len();
+        let mut events = Vec::new();
+
+        loop {
+            let discarded = self.resync();
+            if discarded > 0 {
+                events.push(DecodeEvent::Error(FrameError::SyncLost { discarded }));
+            }
+
+            if self.buffer.len() < header_len {
+                break;
+            }
+
+            let length = self.read_length(&self.buffer[sync_len..header_len]);
+            if length > self.config.max_payload {
+                events.push(DecodeEvent::Error(FrameError::BadLength { length }));
+                // Skip this sync word and look for the next one
+                self.buffer.drain(..1);
+                continue;
+            }
+
+            let frame_len = header_len + length + crc_len;
+            if self.buffer.len() < frame_len {
+                break;
+            }
+
+            let payload_end = header_len + length;
+            if crc_len > 0 {
+                let expected = self.buffer[payload_end..frame_len]
+                    .iter()
+                    .fold(0u32, |acc, &byte| (acc << 8) | u32::from(byte));
+                let actual = self.config.crc.compute(&self.buffer[sync_len..payload_end]);
+                if expected != actual {
+                    events.push(DecodeEvent::Error(FrameError::CrcMismatch {
+                        expected,
+                        actual,
+                    }));
+                    self.buffer.drain(..1);
+                    continue;
+                }
+            }
+
+            let payload = self.buffer[header_len..payload_end].to_vec();
+            self.buffer.drain(..frame_len);
+            events.push(DecodeEvent::Frame(payload));
+        }
+
+        events
+    }
+
+    fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        let mut frame = self.config.sync.clone();
+        let mut body = self.write_length(payload.len());
+        body.extend_from_slice(payload);
+
+        let crc = self.config.crc.compute(&body);
+        frame.extend_from_slice(&body);
+        for i in (0..self.config.crc.len()).rev() {
+            frame.push((crc >> (8 * i)) as u8);
+        }
+        frame
+    }
+}
+
+/// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF).
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoder() -> SyncFrameDecoder {
+        SyncFrameDecoder::new(FramingConfig::default())
+    }
+
+    #[test]
+    fn crc16_known_vector() {
+        // CRC-16/CCITT-FALSE of "123456789" == 0x29B1
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut dec = decoder();
+        let frame = dec.encode(&[1, 2, 3]);
+        assert_eq!(frame[..4], [0xEB, 0x90, 0x00, 0x03]);
+        assert_eq!(dec.push(&frame), vec![DecodeEvent::Frame(vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn split_across_reads() {
+        let mut dec = decoder();
+        let frame = dec.encode(b"hello");
+        let (first, second) = frame.split_at(3);
+
+        assert!(dec.push(first).is_empty());
+        assert_eq!(
+            dec.push(second),
+            vec![DecodeEvent::Frame(b"hello".to_vec())]
+        );
+    }
+
+    #[test]
+    fn skips_garbage_before_sync() {
+        let mut dec = decoder();
+        let mut input = vec![0x00, 0x11, 0x22];
+        input.extend(dec.encode(&[9]));
+
+        assert_eq!(
+            dec.push(&input),
+            vec![
+                DecodeEvent::Error(FrameError::SyncLost { discarded: 3 }),
+                DecodeEvent::Frame(vec![9]),
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_sync_is_kept() {
+        let mut dec = decoder();
+        let frame = dec.encode(&[7]);
+
+        assert_eq!(
+            dec.push(&[0x55, 0xEB]),
+            vec![DecodeEvent::Error(FrameError::SyncLost { discarded: 1 })]
+        );
+        assert_eq!(dec.push(&frame[1..]), vec![DecodeEvent::Frame(vec![7])]);
+    }
+
+    #[test]
+    fn crc_mismatch_then_recovers() {
+        let mut dec = decoder();
+        let mut bad = dec.encode(&[1, 2]);
+        let last = bad.len() - 1;
+        bad[last] ^= 0xFF;
+        let good = dec.encode(&[3]);
+
+        let mut input = bad;
+        input.extend(&good);
+        let events = dec.push(&input);
+
+        assert!(matches!(
+            events[0],
+            DecodeEvent::Error(FrameError::CrcMismatch { .. })
+        ));
+        assert_eq!(events.last(), Some(&DecodeEvent::Frame(vec![3])));
+    }
+
+    #[test]
+    fn rejects_oversized_length() {
+        let mut dec = SyncFrameDecoder::new(FramingConfig {
+            max_payload: 4,
+            ..FramingConfig::default()
+        });
+
+        let events = dec.push(&[0xEB, 0x90, 0x00, 0x05]);
+        assert_eq!(
+            events[0],
+            DecodeEvent::Error(FrameError::BadLength { length: 5 })
+        );
+    }
+
+    #[test]
+    fn little_endian_one_byte_length_without_crc() {
+        let config = FramingConfig {
+            sync: vec![0xAA],
+            length_bytes: 1,
+            big_endian: false,
+            crc: CrcKind::None,
+            max_payload: 255,
+        };
+        let mut dec = SyncFrameDecoder::new(config);
+
+        assert_eq!(dec.encode(&[5, 6]), vec![0xAA, 0x02, 5, 6]);
+        assert_eq!(
+            dec.push(&[0xAA, 0x02, 5, 6]),
+            vec![DecodeEvent::Frame(vec![5, 6])]
+        );
+    }
+
+    #[test]
+    fn framing_from_config() {
+        let config = Config::new_from_str(
+            "star-risc",
+            r#"
+            [star-risc.framing]
+            sync = [0x1A, 0xCF, 0xFC, 0x1D]
+            length_bytes = 4
+            big_endian = false
+            crc = "none"
+            max_payload = 64
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            FramingConfig::from_config(&config).unwrap(),
+            FramingConfig {
+                sync: vec![0x1A, 0xCF, 0xFC, 0x1D],
+                length_bytes: 4,
+                big_endian: false,
+                crc: CrcKind::None,
+                max_payload: 64,
+            }
+        );
+    }
+}
@@ -1,17 +1,34 @@
-use async_graphql::{Context, EmptyMutation, Object};
+use async_graphql::{Context, Object, SimpleObject};
 use kubos_service::{Config, Service};
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::AsyncReadExt;
-use tokio::sync::RwLock;
-use tokio_serial::SerialPortBuilderExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, WriteHalf};
+use tokio::sync::{Mutex, RwLock};
+use tokio_serial::{SerialPortBuilderExt, SerialStream};
+
+mod frame;
+mod store;
+
+use crate::frame::{DecodeEvent, FrameDecoder, FramingConfig, SyncFrameDecoder};
+use crate::store::{FrameStore, StoredFrame};
+
+const DEFAULT_FRAME_CACHE: usize = 1000;
+const DEFAULT_MAX_LOG_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_PAGE_SIZE: usize = 500;
 
 struct UartConfig {
     bus: String,
     baud: u32,
 }
 
+struct StoreConfig {
+    log_path: Option<PathBuf>,
+    cache_size: usize,
+    max_log_bytes: u64,
+}
+
 // Define our data structure for storing UART readings
 #[derive(Clone)]
 struct UartReading {
@@ -51,18 +68,70 @@ impl AppState {
 #[derive(Clone)]
 pub struct StarRiscSubsystem {
     state: Arc<RwLock<AppState>>,
+    frames: Arc<RwLock<FrameStore>>,
+    decoder: Arc<Mutex<Box<dyn FrameDecoder>>>,
+    writer: Arc<Mutex<Option<WriteHalf<SerialStream>>>>,
 }
 
 impl StarRiscSubsystem {
-    pub fn new() -> Self {
+    fn new(decoder: Box<dyn FrameDecoder>, store: FrameStore) -> Self {
         Self {
             state: Arc::new(RwLock::new(AppState::new(1000))),
+            frames: Arc::new(RwLock::new(store)),
+            decoder: Arc::new(Mutex::new(decoder)),
+            writer: Arc::new(Mutex::new(None)),
         }
     }
 
     pub async fn add_reading(&self, data: Vec<u8>) {
-        let mut state = self.state.write().await;
-        state.add_reading(data);
+        let events = self.decoder.lock().await.push(&data);
+
+        {
+            let mut state = self.state.write().await;
+            state.add_reading(data);
+        }
+
+        if events.is_empty() {
+            return;
+        }
+
+        let timestamp = chrono::Utc::now().timestamp_micros() as f64 / 1_000_000.0;
+        let mut frames = self.frames.write().await;
+        for event in events {
+            match event {
+                DecodeEvent::Frame(payload) => {
+                    frames.add(timestamp, payload);
+                }
+                DecodeEvent::Error(err) => {
+                    log::warn!("STAR-RISC frame error: {}", err);
+                    frames.counters.record_error(&err);
+                }
+            }
+        }
+    }
+
+    /// Write bytes to the payload, wrapped in a frame if `framed` is set.
+    ///
+    /// Returns the number of bytes written to the UART.
+    pub async fn send_command(&self, data: &[u8], framed: bool) -> Result<usize, String> {
+        let bytes = if framed {
+            self.decoder.lock().await.encode(data)
+        } else {
+            data.to_vec()
+        };
+
+        let mut writer = self.writer.lock().await;
+        let uart = writer
+            .as_mut()
+            .ok_or_else(|| "UART is not open".to_string())?;
+        uart.write_all(&bytes)
+            .await
+            .map_err(|err| format!("UART write failed: {}", err))?;
+        uart.flush()
+            .await
+            .map_err(|err| format!("UART flush failed: {}", err))?;
+
+        Ok(bytes.len())
     }
 
     pub async fn get_uart_readings(&self) -> Vec<u8> {
@@ -75,6 +144,52 @@ impl StarRiscSubsystem {
     }
 }
 
+/// A decoded STAR-RISC frame
+#[derive(SimpleObject)]
+pub struct Frame {
+    /// Frame number, increasing across service restarts
+    sequence: u64,
+    /// Seconds since the UNIX epoch at which the frame was decoded
+    timestamp: f64,
+    /// Frame payload, without sync word, length or CRC
+    data: Vec<u8>,
+}
+
+impl From<StoredFrame> for Frame {
+    fn from(frame: StoredFrame) -> Self {
+        Frame {
+            sequence: frame.sequence,
+            timestamp: frame.timestamp,
+            data: frame.data,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct FramePage {
+    frames: Vec<Frame>,
+    /// Pass as `after` to fetch the next page. Null when there are no frames in this page.
+    next_cursor: Option<u64>,
+    /// Total number of frames decoded
+    total: u64,
+}
+
+#[derive(SimpleObject)]
+pub struct FrameStatistics {
+    frames: u64,
+    crc_errors: u64,
+    length_errors: u64,
+    sync_losses: u64,
+    bytes_discarded: u64,
+}
+
+#[derive(SimpleObject)]
+pub struct SendCommandResponse {
+    success: bool,
+    errors: String,
+    bytes_written: i32,
+}
+
 // Define our Query type using async-graphql
 #[derive(Default)]
 pub struct QueryRoot;
@@ -89,6 +204,85 @@ impl QueryRoot {
         let subsystem_ctx = ctx.data::<kubos_service::Context<StarRiscSubsystem>>()?;
         Ok(subsystem_ctx.subsystem().get_uart_readings().await)
     }
+
+    /// Decoded frames with a sequence number after `after`, oldest first
+    async fn frames(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<u64>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> async_graphql::Result<FramePage> {
+        let subsystem_ctx = ctx.data::<kubos_service::Context<StarRiscSubsystem>>()?;
+        let store = subsystem_ctx.subsystem().frames.read().await;
+
+        let frames = store.page(after, limit.min(MAX_PAGE_SIZE));
+        Ok(FramePage {
+            next_cursor: frames.last().map(|frame| frame.sequence),
+            frames: frames.into_iter().map(Frame::from).collect(),
+            total: store.total(),
+        })
+    }
+
+    /// The most recently decoded frames, oldest first
+    async fn latest_frames(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] count: usize,
+    ) -> async_graphql::Result<Vec<Frame>> {
+        let subsystem_ctx = ctx.data::<kubos_service::Context<StarRiscSubsystem>>()?;
+        let store = subsystem_ctx.subsystem().frames.read().await;
+
+        Ok(store
+            .latest(count.min(MAX_PAGE_SIZE))
+            .into_iter()
+            .map(Frame::from)
+            .collect())
+    }
+
+    /// Frame decoder counters since the service started
+    async fn frame_statistics(&self, ctx: &Context<'_>) -> async_graphql::Result<FrameStatistics> {
+        let subsystem_ctx = ctx.data::<kubos_service::Context<StarRiscSubsystem>>()?;
+        let counters = subsystem_ctx.subsystem().frames.read().await.counters;
+
+        Ok(FrameStatistics {
+            frames: counters.frames,
+            crc_errors: counters.crc_errors,
+            length_errors: counters.length_errors,
+            sync_losses: counters.sync_losses,
+            bytes_discarded: counters.bytes_discarded,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Send a command to the payload over the UART
+    async fn send_command(
+        &self,
+        ctx: &Context<'_>,
+        data: Vec<u8>,
+        #[graphql(default = true)] framed: bool,
+    ) -> async_graphql::Result<SendCommandResponse> {
+        let subsystem_ctx = ctx.data::<kubos_service::Context<StarRiscSubsystem>>()?;
+
+        Ok(
+            match subsystem_ctx.subsystem().send_command(&data, framed).await {
+                Ok(written) => SendCommandResponse {
+                    success: true,
+                    errors: String::new(),
+                    bytes_written: written as i32,
+                },
+                Err(err) => SendCommandResponse {
+                    success: false,
+                    errors: err,
+                    bytes_written: 0,
+                },
+            },
+        )
+    }
 }
 
 // UART reading task
@@ -107,10 +301,13 @@ async fn uart_reading_task(subsystem: StarRiscSubsystem, uart_config: UartConfig
         .timeout(Duration::from_millis(100))
         .open_native_async();
 
-    if let Ok(mut uart) = uart_result {
+    if let Ok(uart) = uart_result {
+        let (mut reader, writer) = tokio::io::split(uart);
+        *subsystem.writer.lock().await = Some(writer);
+
         let mut buffer = [0u8; 1024];
         loop {
-            match uart.read(&mut buffer).await {
+            match reader.read(&mut buffer).await {
                 Ok(bytes_read) if bytes_read > 0 => {
                     let data = buffer[..bytes_read].to_vec();
                     subsystem.add_reading(data).await;
@@ -126,10 +323,11 @@ async fn uart_reading_task(subsystem: StarRiscSubsystem, uart_config: UartConfig
         }
     } else {
         println!("Could not open UART device, running in simulation mode");
-        // Generate some simulated data
+        // Generate some simulated frames
         let mut counter = 0u8;
         loop {
-            let data = vec![counter, counter.wrapping_add(1), counter.wrapping_add(2)];
+            let payload = vec![counter, counter.wrapping_add(1), counter.wrapping_add(2)];
+            let data = subsystem.decoder.lock().await.encode(&payload);
             subsystem.add_reading(data).await;
             counter = counter.wrapping_add(1);
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -151,6 +349,28 @@ fn load_uart_config(config: &Config) -> UartConfig {
     UartConfig { bus, baud }
 }
 
+fn load_store_config(config: &Config) -> StoreConfig {
+    let log_path = config
+        .get("frame_log")
+        .and_then(|v| v.as_str().map(PathBuf::from));
+    let cache_size = config
+        .get("frame_cache")
+        .and_then(|v| v.as_integer())
+        .map(|v| v.max(1) as usize)
+        .unwrap_or(DEFAULT_FRAME_CACHE);
+    let max_log_bytes = config
+        .get("max_log_bytes")
+        .and_then(|v| v.as_integer())
+        .map(|v| v.max(0) as u64)
+        .unwrap_or(DEFAULT_MAX_LOG_BYTES);
+
+    StoreConfig {
+        log_path,
+        cache_size,
+        max_log_bytes,
+    }
+}
+
 #[tokio::main]
 async fn main() {
     // Initialize the logger
@@ -165,9 +385,21 @@ async fn main() {
         .unwrap();
 
     let uart_config = load_uart_config(&config);
+    let store_config = load_store_config(&config);
+    let framing = FramingConfig::from_config(&config)
+        .map_err(|err| {
+            eprintln!("Invalid framing config: {}", err);
+            err
+        })
+        .unwrap();
 
     // Create our subsystem
-    let subsystem = StarRiscSubsystem::new();
+    let store = FrameStore::new(
+        store_config.cache_size,
+        store_config.log_path,
+        store_config.max_log_bytes,
+    );
+    let subsystem = StarRiscSubsystem::new(Box::new(SyncFrameDecoder::new(framing)), store);
 
     // Spawn the UART reading task
     let subsystem_clone = subsystem.clone();
@@ -176,7 +408,12 @@ async fn main() {
     });
 
     // Create and start the service using kubos-service
-    let service = Service::new(config, subsystem, QueryRoot::default(), EmptyMutation);
+    let service = Service::new(
+        config,
+        subsystem,
+        QueryRoot::default(),
+        MutationRoot::default(),
+    );
 
     println!("Star RISC service starting...");
     service.start_async().await;
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::frame::FrameError;
+
+/// Every this many frames of a log file, the offset of the frame is indexed.
+const INDEX_INTERVAL: u64 = 64;
+
+/// A decoded frame as stored on disk, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredFrame {
+    /// Monotonic frame number, continued across restarts
+    pub sequence: u64,
+    /// Seconds since the UNIX epoch at which the frame was decoded
+    pub timestamp: f64,
+    pub data: Vec<u8>,
+}
+
+/// Running totals of decoder results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameCounters {
+    pub frames: u64,
+    pub crc_errors: u64,
+    pub length_errors: u64,
+    pub sync_losses: u64,
+    pub bytes_discarded: u64,
+}
+
+impl FrameCounters {
+    pub fn record_error(&mut self, error: &FrameError) {
+        match error {
+            FrameError::SyncLost { discarded } => {
+                self.sync_losses += 1;
+                self.bytes_discarded += *discarded as u64;
+            }
+            FrameError::BadLength { .. } => self.length_errors += 1,
+            FrameError::CrcMismatch { .. } => self.crc_errors += 1,
+        }
+    }
+}
+
+/// Byte offsets of some of the frames in a log file, so a page can be read
+/// from near its first frame rather than from the start of the file.
+#[derive(Debug, Default)]
+struct LogIndex {
+    /// Sequence number and offset of every `INDEX_INTERVAL`th frame
+    offsets: Vec<(u64, u64)>,
+    frames: u64,
+    last_sequence: Option<u64>,
+}
+
+impl LogIndex {
+    fn record(&mut self, sequence: u64, offset: u64) {
+        if self.frames % INDEX_INTERVAL == 0 {
+            self.offsets.push((sequence, offset));
+        }
+        self.frames += 1;
+        self.last_sequence = Some(sequence);
+    }
+
+    /// Whether the file may hold frames numbered `start` or above.
+    fn reaches(&self, start: u64) -> bool {
+        self.last_sequence.is_some_and(|last| last >= start)
+    }
+
+    /// Offset to read from to find the frame numbered `start`.
+    fn seek(&self, start: u64) -> u64 {
+        match self
+            .offsets
+            .partition_point(|(sequence, _)| *sequence <= start)
+        {
+            0 => 0,
+            found => self.offsets[found - 1].1,
+        }
+    }
+}
+
+/// Decoded frames, with the most recent ones held in memory and all of them
+/// appended to a log file when one is configured.
+///
+/// Once the log grows past `max_log_bytes` it is moved to `<path>.1`
+/// (replacing any previous one) and a new log is started. Frames are read
+/// back from both files.
+pub struct FrameStore {
+    cache: VecDeque<StoredFrame>,
+    cache_size: usize,
+    log_path: Option<PathBuf>,
+    max_log_bytes: u64,
+    log_index: LogIndex,
+    rotated_index: LogIndex,
+    next_sequence: u64,
+    pub counters: FrameCounters,
+}
+
+impl FrameStore {
+    pub fn new(cache_size: usize, log_path: Option<PathBuf>, max_log_bytes: u64) -> Self {
+        let mut store = FrameStore {
+            cache: VecDeque::new(),
+            cache_size,
+            log_path,
+            max_log_bytes,
+            log_index: LogIndex::default(),
+            rotated_index: LogIndex::default(),
+            next_sequence: 0,
+            counters: FrameCounters::default(),
+        };
+
+        // Pick up where the previous run left off
+        if let Some(path) = store.log_path.clone() {
+            store.rotated_index = store.load_log(&rotated_path(&path));
+            store.log_index = store.load_log(&path);
+        }
+
+        store
+    }
+
+    pub fn add(&mut self, timestamp: f64, data: Vec<u8>) -> StoredFrame {
+        let frame = StoredFrame {
+            sequence: self.next_sequence,
+            timestamp,
+            data,
+        };
+        self.next_sequence += 1;
+        self.counters.frames += 1;
+
+        if let Err(err) = self.append_log(&frame) {
+            log::error!("Failed to write frame {} to log: {}", frame.sequence, err);
+        }
+        self.cache_frame(frame.clone());
+
+        frame
+    }
+
+    /// Up to `limit` frames with a sequence number greater than `after`, oldest first.
+    ///
+    /// Frames which have aged out of memory are read back from the log files.
+    pub fn page(&self, after: Option<u64>, limit: usize) -> Vec<StoredFrame> {
+        let start = after.map(|seq| seq + 1).unwrap_or(0);
+        let cached_from = self.cache.front().map(|frame| frame.sequence);
+
+        let path = match &self.log_path {
+            Some(path) if !cached_from.is_some_and(|first| start >= first) => path,
+            _ => {
+                return self
+                    .cache
+                    .iter()
+                    .filter(|frame| frame.sequence >= start)
+                    .take(limit)
+                    .cloned()
+                    .collect();
+            }
+        };
+
+        let mut frames = Vec::new();
+        for (path, index) in [
+            (rotated_path(path), &self.rotated_index),
+            (path.clone(), &self.log_index),
+        ] {
+            if frames.len() >= limit || !index.reaches(start) {
+                continue;
+            }
+
+            let remaining = limit - frames.len();
+            let result = read_log(&path, index.seek(start)).and_then(|entries| {
+                entries
+                    .skip_while(|entry| matches!(entry, Ok((_, frame)) if frame.sequence < start))
+                    .take(remaining)
+                    .map(|entry| entry.map(|(_, frame)| frame))
+                    .collect::<io::Result<Vec<_>>>()
+            });
+            match result {
+                Ok(found) => frames.extend(found),
+                Err(err) => {
+                    log::error!("Failed to read frame log {}: {}", path.display(), err);
+                    break;
+                }
+            }
+        }
+        frames
+    }
+
+    /// The most recent `count` frames, oldest first.
+    pub fn latest(&self, count: usize) -> Vec<StoredFrame> {
+        let skip = self.cache.len().saturating_sub(count);
+        self.cache.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.next_sequence
+    }
+
+    fn cache_frame(&mut self, frame: StoredFrame) {
+        self.cache.push_back(frame);
+        while self.cache.len() > self.cache_size {
+            self.cache.pop_front();
+        }
+    }
+
+    fn append_log(&mut self, frame: &StoredFrame) -> io::Result<()> {
+        let path = match &self.log_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut offset = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        if offset >= self.max_log_bytes {
+            fs::rename(path, rotated_path(path))?;
+            self.rotated_index = mem::take(&mut self.log_index);
+            offset = 0;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(frame)?;
+        writeln!(file, "{}", line)?;
+        self.log_index.record(frame.sequence, offset);
+        Ok(())
+    }
+
+    /// Caches the frames of a log file and indexes it.
+    fn load_log(&mut self, path: &Path) -> LogIndex {
+        let mut index = LogIndex::default();
+        if !path.exists() {
+            return index;
+        }
+
+        let result = read_log(path, 0).and_then(|entries| {
+            for entry in entries {
+                let (offset, frame) = entry?;
+                index.record(frame.sequence, offset);
+                self.next_sequence = frame.sequence + 1;
+                self.cache_frame(frame);
+            }
+            Ok(())
+        });
+        if let Err(err) = result {
+            log::warn!("Failed to read frame log {}: {}", path.display(), err);
+        }
+        index
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+/// Frames of a log file from byte `offset` on, along with the offset of each.
+fn read_log(
+    path: &Path,
+    offset: u64,
+) -> io::Result<impl Iterator<Item = io::Result<(u64, StoredFrame)>>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut position = offset;
+
+    Ok(std::iter::from_fn(move || {
+        loop {
+            let mut line = Vec::new();
+            let start = position;
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => return None,
+                Ok(len) => position += len as u64,
+                Err(err) => return Some(Err(err)),
+            }
+
+            // A partially written line from a power cut shouldn't lose the rest of the log
+            match serde_json::from_slice(&line) {
+                Ok(frame) => return Some(Ok((start, frame))),
+                Err(err) => log::warn!("Skipping corrupt frame log entry: {}", err),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn pages_from_cache() {
+        let mut store = FrameStore::new(10, None, 0);
+        for i in 0..5u8 {
+            store.add(f64::from(i), vec![i]);
+        }
+
+        let page = store.page(Some(1), 2);
+        assert_eq!(
+            page.iter().map(|f| f.sequence).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(store.latest(1)[0].data, vec![4]);
+    }
+
+    #[test]
+    fn pages_from_log_after_cache_evicts() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frames.log");
+        let mut store = FrameStore::new(2, Some(path), u64::MAX);
+        for i in 0..5u8 {
+            store.add(f64::from(i), vec![i]);
+        }
+
+        let page = store.page(None, 3);
+        assert_eq!(
+            page.iter().map(|f| f.sequence).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn pages_from_middle_of_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frames.log");
+        let mut store = FrameStore::new(2, Some(path), u64::MAX);
+        for i in 0..200u8 {
+            store.add(f64::from(i), vec![i]);
+        }
+
+        let page = store.page(Some(150), 3);
+        assert_eq!(
+            page.iter().map(|f| f.sequence).collect::<Vec<_>>(),
+            vec![151, 152, 153]
+        );
+    }
+
+    #[test]
+    fn pages_across_rotated_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frames.log");
+        let mut store = FrameStore::new(1, Some(path.clone()), 100);
+        for i in 0..5u8 {
+            store.add(f64::from(i), vec![i]);
+        }
+        assert!(dir.path().join("frames.log.1").exists());
+
+        let sequences =
+            |page: Vec<StoredFrame>| page.iter().map(|f| f.sequence).collect::<Vec<_>>();
+        assert_eq!(sequences(store.page(None, 4)), vec![0, 1, 2, 3]);
+        assert_eq!(sequences(store.page(Some(0), 10)), vec![1, 2, 3, 4]);
+
+        // Both files are read back after a restart
+        let store = FrameStore::new(1, Some(path), 100);
+        assert_eq!(store.total(), 5);
+        assert_eq!(sequences(store.page(Some(1), 2)), vec![2, 3]);
+    }
+
+    #[test]
+    fn sequence_continues_after_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frames.log");
+        {
+            let mut store = FrameStore::new(10, Some(path.clone()), u64::MAX);
+            store.add(0.0, vec![1]);
+            store.add(1.0, vec![2]);
+        }
+
+        let mut store = FrameStore::new(10, Some(path), u64::MAX);
+        assert_eq!(store.total(), 2);
+        assert_eq!(store.add(2.0, vec![3]).sequence, 2);
+        assert_eq!(store.latest(10).len(), 3);
+    }
+
+    #[test]
+    fn rotates_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frames.log");
+        let mut store = FrameStore::new(10, Some(path.clone()), 1);
+        store.add(0.0, vec![1]);
+        store.add(1.0, vec![2]);
+
+        assert!(dir.path().join("frames.log.1").exists());
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn counts_errors() {
+        let mut counters = FrameCounters::default();
+        counters.record_error(&FrameError::SyncLost { discarded: 3 });
+        counters.record_error(&FrameError::CrcMismatch {
+            expected: 1,
+            actual: 2,
+        });
+
+        assert_eq!(counters.sync_losses, 1);
+        assert_eq!(counters.bytes_discarded, 3);
+        assert_eq!(counters.crc_errors, 1);
+    }
+}